@@ -4,43 +4,45 @@ use sdl2::{
     mouse::MouseButton,
 };
 
+#[cfg(not(target_os = "emscripten"))]
+use stagehand::app::gameloop;
 use stagehand::{
-    app::gameloop,
     example::{ui::UIScene, ExampleScene},
     input::{ActionState, ActionType, InputMap},
 };
 
+#[cfg(target_os = "emscripten")]
+use stagehand_sdl2::step::{emscripten, FrameStepper};
 use stagehand_sdl2::{
     initialize_sdl2,
-    input::{SDLCommand, SDLGamepadFeature},
+    input::{Chord, SDLCommand, SDLGamepadFeature},
     loading::SDLStorage,
     SDLApp,
 };
 
+// The browser main loop never returns.
+#[cfg_attr(target_os = "emscripten", allow(unreachable_code))]
 fn main() -> Result<(), String> {
     let (context, canvas, texture_loader, font_loader) = initialize_sdl2()?;
 
     let mut storage = SDLStorage::new(&texture_loader, &font_loader);
     storage
-        .textures
-        .load("Logo.png".to_string(), "example-assets/Logo.png")
+        .load_texture("Logo.png".to_string(), "example-assets/Logo.png")
         .unwrap();
 
     storage
-        .music
-        .load("Music.wav".to_string(), "example-assets/Music.wav")
+        .load_music("Music.wav".to_string(), "example-assets/Music.wav")
         .unwrap();
 
     storage
-        .sounds
-        .load("OoB.wav".to_string(), "example-assets/OoB.wav")
+        .load_sound("OoB.wav".to_string(), "example-assets/OoB.wav")
         .unwrap();
 
     storage
-        .fonts
-        .load(
+        .load_font(
             "Napalm.ttf".to_string(),
-            &("example-assets/OperationNapalm.ttf", 32),
+            "example-assets/OperationNapalm.ttf",
+            32,
         )
         .unwrap();
     storage.lock();
@@ -52,10 +54,13 @@ fn main() -> Result<(), String> {
             player,
             "Forward".to_string(),
             vec![
-                SDLCommand::Key(vec![Scancode::W]),
-                SDLCommand::Key(vec![Scancode::Up, Scancode::LShift]),
-                SDLCommand::MouseButton(vec![MouseButton::Left]),
-                SDLCommand::Gamepad(SDLGamepadFeature::Button(vec![Button::DPadUp]), None),
+                SDLCommand::Key(Chord::All(vec![Scancode::W])),
+                SDLCommand::Key(Chord::All(vec![Scancode::Up, Scancode::LShift])),
+                SDLCommand::MouseButton(Chord::All(vec![MouseButton::Left])),
+                SDLCommand::Gamepad(
+                    SDLGamepadFeature::Button(Chord::All(vec![Button::DPadUp])),
+                    None,
+                ),
             ],
             ActionType::Digital(ActionState::Up),
         )
@@ -65,10 +70,13 @@ fn main() -> Result<(), String> {
             player,
             "Backward".to_string(),
             vec![
-                SDLCommand::Key(vec![Scancode::S]),
-                SDLCommand::Key(vec![Scancode::Down, Scancode::LShift]),
-                SDLCommand::MouseButton(vec![MouseButton::Right]),
-                SDLCommand::Gamepad(SDLGamepadFeature::Button(vec![Button::DPadDown]), None),
+                SDLCommand::Key(Chord::All(vec![Scancode::S])),
+                SDLCommand::Key(Chord::All(vec![Scancode::Down, Scancode::LShift])),
+                SDLCommand::MouseButton(Chord::All(vec![MouseButton::Right])),
+                SDLCommand::Gamepad(
+                    SDLGamepadFeature::Button(Chord::All(vec![Button::DPadDown])),
+                    None,
+                ),
             ],
             ActionType::Digital(ActionState::Up),
         )
@@ -89,9 +97,9 @@ fn main() -> Result<(), String> {
             player,
             "Pause".to_string(),
             vec![
-                SDLCommand::Key(vec![Scancode::Escape]),
-                SDLCommand::MouseButton(vec![MouseButton::Middle]),
-                SDLCommand::Gamepad(SDLGamepadFeature::Button(vec![Button::A]), None),
+                SDLCommand::Key(Chord::All(vec![Scancode::Escape])),
+                SDLCommand::MouseButton(Chord::All(vec![MouseButton::Middle])),
+                SDLCommand::Gamepad(SDLGamepadFeature::Button(Chord::All(vec![Button::A])), None),
             ],
             ActionType::Digital(ActionState::Up),
         )
@@ -105,6 +113,20 @@ fn main() -> Result<(), String> {
     app.add_scene("Example".to_string(), Box::new(scene), true, true);
     app.add_scene("UI".to_string(), Box::new(ui), true, true);
 
+    // Browsers own the loop, so the page calls back into a step per animation frame.
+    #[cfg(target_os = "emscripten")]
+    {
+        let mut stepper = FrameStepper::new(60);
+        emscripten::set_main_loop(move || match app.step(&mut stepper) {
+            Ok(running) => running,
+            Err(e) => {
+                eprintln!("{}", e);
+                false
+            }
+        });
+    }
+
+    #[cfg(not(target_os = "emscripten"))]
     gameloop(&mut app, 60)?;
 
     Ok(())
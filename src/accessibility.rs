@@ -0,0 +1,146 @@
+use std::collections::{HashMap, HashSet};
+
+use sdl2::{controller::Button, event::Event, keyboard::Scancode, mouse::MouseButton};
+
+use crate::input::Chord;
+
+pub struct InputAccessibility {
+    // Milliseconds a key stays part of a chord after it was pressed, so the keys of an
+    // All chord don't have to be held at the same time. Zero keeps chords strict.
+    pub chord_window: u64,
+    // Milliseconds between an input happening and the player perceiving it, so timing
+    // judgements can account for slow displays or audio. Subtracted from `input_time` and
+    // the input timeline's timestamps, and taken into account by `BeatClock`'s offsets.
+    pub latency: u64,
+    toggles: HashSet<(usize, usize)>,
+    latched: HashMap<(usize, usize), (bool, bool)>,
+    keys: HashMap<Scancode, u64>,
+    mouse: HashMap<MouseButton, u64>,
+    buttons: HashMap<(u32, Button), u64>,
+    now: u64,
+}
+
+impl InputAccessibility {
+    pub fn new() -> Self {
+        InputAccessibility {
+            chord_window: 0,
+            latency: 0,
+            toggles: HashSet::new(),
+            latched: HashMap::new(),
+            keys: HashMap::new(),
+            mouse: HashMap::new(),
+            buttons: HashMap::new(),
+            now: 0,
+        }
+    }
+
+    // A toggled action stays down from one press until the next, instead of while held.
+    pub fn set_toggle(&mut self, user_index: usize, action_index: usize, toggle: bool) {
+        match toggle {
+            true => self.toggles.insert((user_index, action_index)),
+            false => {
+                self.latched.remove(&(user_index, action_index));
+                self.toggles.remove(&(user_index, action_index))
+            }
+        };
+    }
+
+    pub fn is_toggle(&self, user_index: usize, action_index: usize) -> bool {
+        self.toggles.contains(&(user_index, action_index))
+    }
+
+    // Releases every latched toggle, for example when a menu opens.
+    pub fn release_toggles(&mut self) {
+        self.latched.clear();
+    }
+
+    pub fn input_time(&self) -> u64 {
+        self.now.saturating_sub(self.latency)
+    }
+
+    pub(crate) fn begin(&mut self, now: u64) {
+        self.now = now;
+        let (window, now) = (self.chord_window, self.now);
+        let recent = |t: &mut u64| now.saturating_sub(*t) <= window;
+        self.keys.retain(|_, t| recent(t));
+        self.mouse.retain(|_, t| recent(t));
+        self.buttons.retain(|_, t| recent(t));
+    }
+
+    pub(crate) fn record(&mut self, event: &Event) {
+        if self.chord_window == 0 {
+            return;
+        }
+
+        match event {
+            Event::KeyDown {
+                scancode: Some(s),
+                repeat: false,
+                ..
+            } => {
+                self.keys.insert(*s, self.now);
+            }
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                self.mouse.insert(*mouse_btn, self.now);
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.buttons.insert((*which, *button), self.now);
+            }
+            _ => (),
+        }
+    }
+
+    pub(crate) fn key_recent(&self, key: Scancode) -> bool {
+        self.keys.contains_key(&key)
+    }
+
+    pub(crate) fn mouse_recent(&self, button: MouseButton) -> bool {
+        self.mouse.contains_key(&button)
+    }
+
+    pub(crate) fn button_recent(&self, which: u32, button: Button) -> bool {
+        self.buttons.contains_key(&(which, button))
+    }
+
+    // An All chord with at least one member still held, and the others either held or
+    // pressed within the window.
+    pub(crate) fn chord_within<T, P, R>(&self, chord: &Chord<T>, pressed: P, recent: R) -> bool
+    where
+        P: Fn(&T) -> bool,
+        R: Fn(&T) -> bool,
+    {
+        match chord {
+            Chord::All(v) if self.chord_window > 0 && v.len() > 1 => {
+                v.iter().any(&pressed) && v.iter().all(|k| pressed(k) || recent(k))
+            }
+            _ => chord.is_active(pressed),
+        }
+    }
+
+    pub(crate) fn apply_toggle(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        down: bool,
+    ) -> bool {
+        if !self.is_toggle(user_index, action_index) {
+            return down;
+        }
+
+        let (was_down, latched) = self
+            .latched
+            .entry((user_index, action_index))
+            .or_insert((false, false));
+        if down && !*was_down {
+            *latched = !*latched;
+        }
+        *was_down = down;
+        *latched
+    }
+}
+
+impl Default for InputAccessibility {
+    fn default() -> Self {
+        Self::new()
+    }
+}
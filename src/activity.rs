@@ -0,0 +1,74 @@
+use sdl2::event::Event;
+
+use crate::input::translate_axis;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeviceActivity {
+    pub keyboard: bool,
+    pub mouse: bool,
+    pub controller: bool,
+    pub touch: bool,
+}
+
+impl DeviceActivity {
+    pub fn any(&self) -> bool {
+        self.keyboard || self.mouse || self.controller || self.touch
+    }
+}
+
+pub struct InputActivity {
+    // Stick noise below this doesn't count as activity.
+    pub axis_threshold: f32,
+    frame: DeviceActivity,
+    last_input: u64,
+}
+
+impl InputActivity {
+    pub fn new() -> Self {
+        InputActivity {
+            axis_threshold: 0.3,
+            frame: DeviceActivity::default(),
+            last_input: 0,
+        }
+    }
+
+    // Devices that produced input since the previous frame.
+    pub fn this_frame(&self) -> DeviceActivity {
+        self.frame
+    }
+
+    pub fn idle_for(&self, now: u64) -> u64 {
+        now.saturating_sub(self.last_input)
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.frame = DeviceActivity::default();
+    }
+
+    pub(crate) fn record(&mut self, event: &Event, now: u64) {
+        match event {
+            Event::KeyDown { .. } | Event::TextInput { .. } => self.frame.keyboard = true,
+            Event::MouseButtonDown { .. }
+            | Event::MouseMotion { .. }
+            | Event::MouseWheel { .. } => self.frame.mouse = true,
+            Event::ControllerButtonDown { .. } => self.frame.controller = true,
+            Event::ControllerAxisMotion { value, .. } => {
+                if translate_axis(*value).abs() >= self.axis_threshold {
+                    self.frame.controller = true;
+                }
+            }
+            Event::FingerDown { .. } | Event::FingerMotion { .. } => self.frame.touch = true,
+            _ => return,
+        }
+
+        if self.frame.any() {
+            self.last_input = now;
+        }
+    }
+}
+
+impl Default for InputActivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
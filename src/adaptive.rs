@@ -0,0 +1,68 @@
+pub struct AdaptiveResolution {
+    pub enabled: bool,
+    // Frame time in milliseconds the scaling tries to stay under.
+    pub budget: f64,
+    pub min_scale: f32,
+    pub step: f32,
+    // Frames over budget before stepping down, and comfortably under before stepping up.
+    pub patience: u32,
+    pub recovery: u32,
+    // Fraction of the budget a frame must stay under to count towards recovery.
+    pub headroom: f64,
+    scale: f32,
+    over: u32,
+    under: u32,
+}
+
+impl AdaptiveResolution {
+    pub fn new() -> Self {
+        AdaptiveResolution {
+            enabled: false,
+            budget: 1000.0 / 60.0,
+            min_scale: 0.5,
+            step: 0.1,
+            patience: 10,
+            recovery: 120,
+            headroom: 0.7,
+            scale: 1.0,
+            over: 0,
+            under: 0,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        match self.enabled {
+            true => self.scale,
+            false => 1.0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, frame_time: f64) {
+        if !self.enabled {
+            self.scale = 1.0;
+            return;
+        }
+
+        if frame_time > self.budget {
+            self.over += 1;
+            self.under = 0;
+        } else if frame_time < self.budget * self.headroom {
+            self.under += 1;
+            self.over = 0;
+        }
+
+        if self.over >= self.patience {
+            self.scale = (self.scale - self.step).max(self.min_scale.clamp(0.1, 1.0));
+            self.over = 0;
+        } else if self.under >= self.recovery {
+            self.scale = (self.scale + self.step).min(1.0);
+            self.under = 0;
+        }
+    }
+}
+
+impl Default for AdaptiveResolution {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use sdl2::rect::FRect;
+
+use crate::overlay::Anchor;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScreenPoint {
+    Corner(Anchor),
+    // Fractions of the logical screen, (0.5, 0.5) being the center.
+    Percent(f32, f32),
+}
+
+impl ScreenPoint {
+    fn fraction(&self) -> (f32, f32) {
+        match self {
+            ScreenPoint::Corner(Anchor::TopLeft) => (0.0, 0.0),
+            ScreenPoint::Corner(Anchor::TopRight) => (1.0, 0.0),
+            ScreenPoint::Corner(Anchor::BottomLeft) => (0.0, 1.0),
+            ScreenPoint::Corner(Anchor::BottomRight) => (1.0, 1.0),
+            ScreenPoint::Corner(Anchor::Center) => (0.5, 0.5),
+            ScreenPoint::Percent(x, y) => (*x, *y),
+        }
+    }
+}
+
+// Destinations of anchored draws are offsets from a point on the logical screen, and
+// `pivot` is the point of the drawn rect placed there, normalized to its size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenAnchor {
+    pub point: ScreenPoint,
+    pub pivot: (f32, f32),
+}
+
+impl ScreenAnchor {
+    pub fn new(point: ScreenPoint) -> Self {
+        let pivot = point.fraction();
+        ScreenAnchor { point, pivot }
+    }
+
+    pub fn resolve(&self, screen: (u32, u32), dest: FRect) -> FRect {
+        let (fx, fy) = self.point.fraction();
+        let x = screen.0 as f32 * fx + dest.x() - self.pivot.0 * dest.width();
+        let y = screen.1 as f32 * fy + dest.y() - self.pivot.1 * dest.height();
+        FRect::new(x, y, dest.width(), dest.height())
+    }
+}
+
+// Anchors for draws by batch slot, the instruction's index within its batch in the order
+// the stage returned them, or for whole batches.
+pub struct ScreenAnchors {
+    draws: HashMap<(usize, usize), ScreenAnchor>,
+    batches: HashMap<usize, ScreenAnchor>,
+}
+
+impl ScreenAnchors {
+    pub fn new() -> Self {
+        ScreenAnchors {
+            draws: HashMap::new(),
+            batches: HashMap::new(),
+        }
+    }
+
+    pub fn set(&mut self, batch_index: usize, index: usize, anchor: Option<ScreenAnchor>) {
+        match anchor {
+            Some(a) => self.draws.insert((batch_index, index), a),
+            None => self.draws.remove(&(batch_index, index)),
+        };
+    }
+
+    // Batches are indexed in the order the stage returns them for a frame.
+    pub fn set_batch(&mut self, batch_index: usize, anchor: Option<ScreenAnchor>) {
+        match anchor {
+            Some(a) => self.batches.insert(batch_index, a),
+            None => self.batches.remove(&batch_index),
+        };
+    }
+
+    // A draw's own anchor wins over its batch's.
+    pub fn get(&self, batch_index: usize, index: usize) -> Option<ScreenAnchor> {
+        self.draws
+            .get(&(batch_index, index))
+            .or_else(|| self.batches.get(&batch_index))
+            .copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.draws.clear();
+        self.batches.clear();
+    }
+}
+
+impl Default for ScreenAnchors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+use crate::captions::CaptionStyle;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Announcement {
+    pub text: String,
+    // Cuts off whatever is being read out, for focus changes in menus.
+    pub interrupt: bool,
+}
+
+// Forwards announcements to an accessibility backend of the game's choosing.
+pub type AnnouncementHandler = Box<dyn FnMut(&Announcement)>;
+
+// Text for screen readers: menus announce the focused item, games announce events. Each
+// announcement goes to the handler, to text to speech when built with the `tts` feature,
+// and optionally to a toast on screen.
+pub struct Announcements {
+    // Sends announcements to the handler and text to speech.
+    pub speak: bool,
+    // Toasts are drawn by the backend when set, otherwise scenes read `toasts`.
+    pub toast_style: Option<CaptionStyle>,
+    // Milliseconds a toast stays on screen.
+    pub toast_duration: u64,
+    pub max_toasts: usize,
+    pending: Vec<Announcement>,
+    posted: Vec<Announcement>,
+    toasts: VecDeque<(String, u64)>,
+    handler: Option<AnnouncementHandler>,
+    #[cfg(feature = "tts")]
+    tts: Option<tts::Tts>,
+    #[cfg(feature = "tts")]
+    tts_failed: bool,
+}
+
+impl Announcements {
+    pub fn new() -> Self {
+        Announcements {
+            speak: true,
+            toast_style: None,
+            toast_duration: 3000,
+            max_toasts: 3,
+            pending: Vec::new(),
+            posted: Vec::new(),
+            toasts: VecDeque::new(),
+            handler: None,
+            #[cfg(feature = "tts")]
+            tts: None,
+            #[cfg(feature = "tts")]
+            tts_failed: false,
+        }
+    }
+
+    pub fn announce(&mut self, text: &str) {
+        self.pending.push(Announcement {
+            text: text.to_string(),
+            interrupt: false,
+        });
+    }
+
+    pub fn announce_now(&mut self, text: &str) {
+        self.pending.push(Announcement {
+            text: text.to_string(),
+            interrupt: true,
+        });
+    }
+
+    pub fn set_handler(&mut self, handler: Option<AnnouncementHandler>) {
+        self.handler = handler;
+    }
+
+    // Announcements delivered on the latest update.
+    pub fn this_frame(&self) -> &[Announcement] {
+        &self.posted
+    }
+
+    // Oldest first.
+    pub fn toasts(&self) -> impl Iterator<Item = &str> {
+        self.toasts.iter().map(|(t, _)| t.as_str())
+    }
+
+    pub fn clear_toasts(&mut self) {
+        self.toasts.clear();
+    }
+
+    pub(crate) fn deliver(&mut self, now: u64) {
+        self.toasts.retain(|(_, expires)| *expires > now);
+        self.posted = std::mem::take(&mut self.pending);
+
+        for announcement in &self.posted {
+            if self.speak {
+                if let Some(handler) = &mut self.handler {
+                    handler(announcement);
+                }
+                #[cfg(feature = "tts")]
+                self.speak_text(announcement);
+            }
+
+            if self.toast_style.is_some() {
+                self.toasts
+                    .push_back((announcement.text.clone(), now + self.toast_duration));
+            }
+        }
+
+        while self.toasts.len() > self.max_toasts {
+            self.toasts.pop_front();
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    fn speak_text(&mut self, announcement: &Announcement) {
+        if self.tts.is_none() && !self.tts_failed {
+            match tts::Tts::default() {
+                Ok(t) => self.tts = Some(t),
+                Err(e) => {
+                    log::warn!("Text to speech is unavailable: {}", e);
+                    self.tts_failed = true;
+                }
+            }
+        }
+
+        if let Some(tts) = &mut self.tts {
+            if let Err(e) = tts.speak(announcement.text.as_str(), announcement.interrupt) {
+                log::warn!("Could not speak announcement: {}", e);
+            }
+        }
+    }
+}
+
+impl Default for Announcements {
+    fn default() -> Self {
+        Self::new()
+    }
+}
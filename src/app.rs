@@ -1,19 +1,36 @@
-use std::{cell::RefCell, f32::EPSILON, rc::Rc};
+use std::f32::EPSILON;
 
 use log::{error, warn};
-use sdl2::{event::Event, pixels::Color};
+use sdl2::{
+    controller::{Button, GameController},
+    event::{Event, WindowEvent},
+    keyboard::{KeyboardState, Keycode, Mod, Scancode},
+    mouse::{MouseButton, MouseState, MouseWheelDirection},
+};
 
 use stagehand::{
     app::App,
-    draw::DrawType,
     input::{ActionState, ActionType, InputError},
-    loading::ResourceError,
-    utility::{Update, UpdateInstruction},
+    utility::Update,
     StageError,
 };
 
 use crate::{
-    input::{translate_axis, SDLCommand, SDLGamepadFeature},
+    accessibility::InputAccessibility,
+    controllers::{ControllerCalibration, ControllerRegistry},
+    coords::YAxis,
+    debug::{describe_command, DebugAction, DebugActionState},
+    input::{
+        modifiers_held, radial_deadzone, read_sensor, read_touchpad, translate_axis, Chord,
+        SDLCommand, SDLGamepadFeature, SDLSensor,
+    },
+    keyboard::OnScreenKeyboard,
+    keysplit::KeyboardSplit,
+    liveresize::EventWatch,
+    presses::PressQueue,
+    prompts::PromptFamily,
+    resolution::{ControllerPolicy, ControllerResolution, MissingController},
+    work::WorkQueue,
     SDLApp,
 };
 
@@ -27,122 +44,427 @@ impl<'a, 'b, 'c, IContent, UContent, Message> App
     }
 
     fn processed_events(&mut self) -> Result<bool, String> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("processed_events").entered();
         let mut events = self.sdl.event_pump()?;
+        self.input_activity.borrow_mut().begin();
+        self.axis_motion.borrow_mut().begin();
+        self.controller_events.borrow_mut().begin();
+        self.cursor.borrow_mut().begin();
+        self.lifecycle.borrow_mut().begin();
+        self.accessibility.borrow_mut().begin(self.timer.ticks64());
+        self.clicks.borrow_mut().begin();
+        self.presses.borrow_mut().begin();
+        let latency = self.accessibility.borrow().latency;
+        self.timeline
+            .borrow_mut()
+            .begin(self.timer.ticks64(), latency);
+        self.combos.borrow_mut().begin();
+        self.wheel = (0.0, 0.0);
+
+        // Everything is pumped before any event is handled, so the watch only ever presents
+        // while SDL is inside the pump. It ignores events pushed from other threads.
+        let mut pending = std::mem::take(&mut self.pending_events);
+        let live_resize = self.live_resize.clone();
+        if live_resize.borrow().enabled {
+            let main = std::thread::current().id();
+            let watched = live_resize.clone();
+            let _watch = EventWatch::add(move |event| {
+                if std::thread::current().id() != main {
+                    return;
+                }
+                if let Ok(mut l) = watched.try_borrow_mut() {
+                    if l.observe(event) {
+                        l.redraw();
+                    }
+                }
+            });
+            pending.extend(events.poll_iter());
+        } else {
+            pending.extend(events.poll_iter());
+        }
+        live_resize.borrow_mut().end_pump();
 
-        for event in events.poll_iter() {
+        for event in pending.drain(..) {
             match event {
                 Event::Quit { .. } => {
                     return Ok(false);
                 }
-                _ => (),
+                _ => {
+                    self.input_activity
+                        .borrow_mut()
+                        .record(&event, self.timer.ticks64());
+                    self.accessibility.borrow_mut().record(&event);
+                    self.presses.borrow_mut().record(&event);
+                    self.handle_lifecycle(&event);
+                    self.handle_background(&event);
+                    self.cursor
+                        .borrow_mut()
+                        .record(&event, self.timer.ticks64());
+                    self.handle_device_reset(&event);
+                    self.handle_controller_device(&event);
+                    if let Event::ControllerAxisMotion { which, .. } = event {
+                        let index = self.controller_slot(which);
+                        self.axis_motion.borrow_mut().record(&event, index);
+                    }
+                    if let Event::ControllerButtonDown { which, .. }
+                    | Event::ControllerButtonUp { which, .. } = event
+                    {
+                        let index = self.controller_slot(which);
+                        self.timeline
+                            .borrow_mut()
+                            .record(&event, index, &self.input.borrow());
+                    } else {
+                        self.timeline
+                            .borrow_mut()
+                            .record(&event, None, &self.input.borrow());
+                    }
+                    if let Event::MouseButtonDown {
+                        mouse_btn, x, y, ..
+                    } = event
+                    {
+                        let position = self.pointer_position(x, y);
+                        let now = self.timer.ticks64();
+                        self.clicks
+                            .borrow_mut()
+                            .press(mouse_btn, position, (x, y), now);
+                    }
+
+                    let used = match &event {
+                        Event::ControllerButtonDown { which, .. } => Some(*which),
+                        Event::ControllerAxisMotion { which, value, .. }
+                            if translate_axis(*value).abs() >= 0.5 =>
+                        {
+                            Some(*which)
+                        }
+                        _ => None,
+                    };
+                    if let Some(which) = used {
+                        if let Some(i) = self.controller_slot(which) {
+                            self.controller_resolution.borrow_mut().used(i);
+                            if let Some(c) = &self.controllers[i] {
+                                self.prompt_family = PromptFamily::from_controller(c);
+                            }
+                        }
+                    }
+                    if let Event::KeyDown { .. }
+                    | Event::MouseButtonDown { .. }
+                    | Event::MouseWheel { .. } = event
+                    {
+                        self.prompt_family = PromptFamily::KeyboardMouse;
+                    }
+
+                    match event {
+                        Event::FingerDown {
+                            finger_id, x, y, ..
+                        } => {
+                            let (x, y) = self.touch_to_canvas(x, y);
+                            self.virtual_gamepad
+                                .borrow_mut()
+                                .touch_down(finger_id, x, y);
+                        }
+                        Event::FingerMotion {
+                            finger_id, x, y, ..
+                        } => {
+                            let (x, y) = self.touch_to_canvas(x, y);
+                            self.virtual_gamepad
+                                .borrow_mut()
+                                .touch_move(finger_id, x, y);
+                        }
+                        Event::LocaleChanged { .. } => self.system.borrow_mut().locale_event(),
+                        Event::FingerUp { finger_id, .. } => {
+                            self.virtual_gamepad.borrow_mut().touch_up(finger_id);
+                        }
+                        // Trackpads report fractions of a notch, which the integer deltas
+                        // round away.
+                        Event::MouseWheel {
+                            direction,
+                            precise_x,
+                            precise_y,
+                            ..
+                        } => {
+                            let sign = match direction {
+                                MouseWheelDirection::Flipped => -1.0,
+                                _ => 1.0,
+                            };
+                            self.wheel.0 += precise_x * sign;
+                            self.wheel.1 += precise_y * sign;
+                        }
+                        Event::Window {
+                            win_event: WindowEvent::FocusGained,
+                            ..
+                        } => self.window_focused = true,
+                        Event::Window {
+                            win_event: WindowEvent::FocusLost,
+                            ..
+                        } => {
+                            self.window_focused = false;
+                            self.cursor.borrow_mut().focus_changed(false);
+                        }
+                        _ => {}
+                    }
+
+                    let window_id = self.canvas.window().id();
+                    let typed = self.keyboard.borrow_mut().handle(&event, window_id);
+
+                    self.dispatch_event(&event);
+                    for event in typed.iter() {
+                        self.dispatch_event(event);
+                    }
+                }
             }
         }
+        // Handed back so its capacity carries over.
+        self.pending_events = pending;
+
+        self.apply_cursor();
+        self.sample_calibration();
 
         let keys = events.keyboard_state();
+        let key_mods = self.sdl.keyboard().mod_state();
         let mouse = events.mouse_state();
 
+        self.mouse = (mouse.x(), mouse.y(), mouse.left());
+        self.update_coordinates();
+        self.update_metrics();
+
+        let pointer = self.pointer_position(mouse.x(), mouse.y());
+        let mut snapshot = self.snapshot.borrow_mut();
+        snapshot.capture(&keys, key_mods, &mouse, &self.controllers);
+        snapshot.pointer = pointer;
+        snapshot.wheel = self.wheel;
+        drop(snapshot);
+
+        let mut debug = self.input_debug.borrow_mut();
+        debug.actions.clear();
+        debug.mouse = (mouse.x(), mouse.y());
+
+        let contexts = self.input_contexts.borrow();
+        let mut holds = self.action_holds.borrow_mut();
+        let mut combos = self.combos.borrow_mut();
+        let mut filters = self.analog_filters.borrow_mut();
+        let mut resolution = self.controller_resolution.borrow_mut();
+        resolution.begin(&self.controllers);
+        let registry = self.controller_registry.borrow();
+        let touch = self.virtual_gamepad.borrow();
+        let mut access = self.accessibility.borrow_mut();
+        let mut debounce = self.debounce.borrow_mut();
+        let mut clicks = self.clicks.borrow_mut();
+        let presses = self.presses.borrow();
+        let keyboard = self.keyboard.borrow();
+        let split = self.keyboard_split.borrow();
+        let now = self.timer.ticks64();
+        // An egui window under the pointer or a focused egui text field takes that input
+        // from the game.
+        #[cfg(feature = "egui")]
+        let ui = self.egui.as_ref().map_or((false, false), |layer| {
+            (layer.wants_pointer_input(), layer.wants_keyboard_input())
+        });
+        #[cfg(not(feature = "egui"))]
+        let ui = (false, false);
+        // Mouse positions are window pixels, measured from the bottom when the Y axis is up,
+        // or world units when the coordinate space has a unit size.
+        let mouse_position = {
+            let coordinates = self.coordinates.borrow();
+            match (coordinates.pixels_per_unit, coordinates.y_axis) {
+                (Some(_), _) => coordinates.window_to_world(mouse.x() as f32, mouse.y() as f32),
+                (None, YAxis::Down) => (mouse.x() as f32, mouse.y() as f32),
+                (None, YAxis::Up) => (
+                    mouse.x() as f32,
+                    (self.canvas.window().size().1 as i32 - mouse.y()) as f32,
+                ),
+            }
+        };
+
         let mut input = self.input.borrow_mut();
         for command_options in 0..input.commands.len() {
             let mut active = ActionType::Digital(ActionState::Up);
+            let mut source = None;
+            let mut source_controller = None;
+
+            let polled = contexts.is_polled(
+                input.commands[command_options].user_index,
+                input.commands[command_options].action_index,
+            );
+            let commands = match polled && !self.scripted_only {
+                true => &input.commands[command_options].commands[..],
+                false => &[],
+            };
+            let digital = DigitalInput {
+                keys: &keys,
+                mouse: &mouse,
+                key_mods,
+                controllers: &self.controllers,
+                access: &access,
+                presses: &presses,
+                split: &split,
+                resolution: &resolution,
+                registry: &registry,
+                user_index: input.commands[command_options].user_index,
+            };
+
+            'commands: for (index, command) in commands.iter().enumerate() {
+                let live = !self.focus_gated_input
+                    || match command {
+                        SDLCommand::Gamepad(..) => self.window_focused || self.background_input,
+                        SDLCommand::Touch(_) => true,
+                        _ => self.window_focused,
+                    };
+                if !live || captured_by_keyboard(command, &keyboard) || claimed_by_ui(command, ui) {
+                    continue;
+                }
 
-            'commands: for command in input.commands[command_options].commands.iter() {
                 match command {
-                    SDLCommand::Key(c) => 'key: {
-                        for key in c.iter() {
-                            if !keys.is_scancode_pressed(*key) {
-                                break 'key;
-                            }
+                    SDLCommand::Key(_) | SDLCommand::KeyCode(..) | SDLCommand::MouseButton(_) => {
+                        if digital_down(command, &digital) {
+                            source = Some(index);
+                            active = ActionType::Digital(ActionState::Down);
+                            break 'commands;
                         }
-                        active = ActionType::Digital(ActionState::Down);
-                        break 'commands;
                     }
-                    SDLCommand::MouseButton(b) => 'button: {
-                        for button in b.iter() {
-                            if !mouse.is_mouse_button_pressed(*button) {
-                                break 'button;
-                            }
+                    SDLCommand::DigitalAxis { negative, positive } => {
+                        let pressed = |c: &SDLCommand| digital_down(c, &digital);
+                        let value = pressed(positive) as i32 - pressed(negative) as i32;
+                        if value != 0 {
+                            source = Some(index);
+                            active = ActionType::Axis(value as f32);
+                            break 'commands;
+                        }
+                    }
+                    SDLCommand::DigitalStick {
+                        up,
+                        down,
+                        left,
+                        right,
+                        normalize,
+                    } => {
+                        let pressed = |c: &SDLCommand| digital_down(c, &digital);
+                        let x = (pressed(right) as i32 - pressed(left) as i32) as f32;
+                        let y = (pressed(down) as i32 - pressed(up) as i32) as f32;
+                        if x != 0.0 || y != 0.0 {
+                            let scale = match *normalize && x != 0.0 && y != 0.0 {
+                                true => std::f32::consts::FRAC_1_SQRT_2,
+                                false => 1.0,
+                            };
+                            source = Some(index);
+                            active = ActionType::Analog {
+                                x: x * scale,
+                                y: y * scale,
+                            };
+                            break 'commands;
+                        }
+                    }
+                    SDLCommand::MultiClick(button, count) => {
+                        let down = mouse.is_mouse_button_pressed(*button)
+                            || clicks.pressed_this_frame(*button);
+                        if down && clicks.click_count(*button) >= *count as u32 {
+                            source = Some(index);
+                            active = ActionType::Digital(ActionState::Down);
+                            break 'commands;
+                        }
+                    }
+                    SDLCommand::MouseWheel => {
+                        if self.wheel != (0.0, 0.0) {
+                            source = Some(index);
+                            active = ActionType::Analog {
+                                x: self.wheel.0,
+                                y: self.wheel.1,
+                            };
+                            break 'commands;
                         }
-                        active = ActionType::Digital(ActionState::Down);
-                        break 'commands;
                     }
                     SDLCommand::MousePosition => {
+                        source = Some(index);
                         active = ActionType::Analog {
-                            x: mouse.x() as f32,
-                            y: mouse.y() as f32,
+                            x: mouse_position.0,
+                            y: mouse_position.1,
                         };
                     }
-                    SDLCommand::Gamepad(feature, controller) => match controller {
-                        Some(index) => {
-                            let controller = &self.controllers[*index];
-
-                            match feature {
-                                SDLGamepadFeature::Button(buttons) => {
-                                    for button in buttons.iter() {
-                                        if !controller.button(*button) {
-                                            continue;
-                                        }
-                                    }
-                                    active = ActionType::Digital(ActionState::Down);
-                                    break 'commands;
-                                }
-                                SDLGamepadFeature::Axis(axis) => {
-                                    let value = translate_axis(controller.axis(*axis));
-                                    if value.abs() >= EPSILON {
-                                        active = ActionType::Axis(value);
-                                        break 'commands;
-                                    }
-                                }
-                                SDLGamepadFeature::Stick(x, y) => {
-                                    let (x, y) = (
-                                        translate_axis(controller.axis(*x)),
-                                        translate_axis(controller.axis(*y)),
-                                    );
-                                    if x.abs() >= EPSILON || y.abs() >= EPSILON {
-                                        active = ActionType::Analog { x, y };
-                                        break 'commands;
-                                    }
-                                }
-                            };
+                    SDLCommand::Touch(control) => {
+                        if let Some(state) = touch.state(*control) {
+                            source = Some(index);
+                            active = state;
+                            break 'commands;
                         }
-                        None => {
-                            'controller: for controller in self.controllers.iter() {
-                                match feature {
-                                    SDLGamepadFeature::Button(buttons) => {
-                                        for button in buttons.iter() {
-                                            if !controller.button(*button) {
-                                                continue 'controller;
-                                            }
-                                        }
-                                        active = ActionType::Digital(ActionState::Down);
-                                        break 'commands;
-                                    }
-                                    SDLGamepadFeature::Axis(axis) => {
-                                        let value = translate_axis(controller.axis(*axis));
-                                        if value.abs() >= 0.1 {
-                                            active = ActionType::Axis(value);
-                                            break 'commands;
-                                        }
-                                    }
-                                    SDLGamepadFeature::Stick(x, y) => {
-                                        let (a_x, a_y) = (
-                                            translate_axis(controller.axis(*x)),
-                                            translate_axis(controller.axis(*y)),
-                                        );
-                                        if a_x.abs() >= 0.1 || a_y.abs() >= 0.1 {
-                                            active = ActionType::Analog { x: a_x, y: a_y };
-                                            break 'commands;
-                                        }
-                                    }
-                                };
-                            }
+                    }
+                    SDLCommand::Gamepad(feature, controller) => {
+                        let state = resolve_gamepad(feature, *controller, &digital);
+                        if let Some((controller_index, state)) = state {
+                            source = Some(index);
+                            source_controller = Some(controller_index);
+                            active = state;
+                            break 'commands;
                         }
-                    },
+                    }
                 };
             }
 
             let user_index = input.commands[command_options].user_index;
             let action_index = input.commands[command_options].action_index;
 
+            resolution.record(user_index, action_index, source_controller);
+            let click_button = match source.map(|i| &commands[i]) {
+                Some(SDLCommand::MouseButton(chord)) => chord
+                    .items()
+                    .iter()
+                    .copied()
+                    .find(|b| mouse.is_mouse_button_pressed(*b)),
+                Some(SDLCommand::MultiClick(button, _)) => Some(*button),
+                _ => None,
+            };
+            clicks.record(user_index, action_index, click_button);
+
+            let from_gamepad =
+                matches!(source.map(|i| &commands[i]), Some(SDLCommand::Gamepad(..)));
+            if let ActionType::Analog { x, y } = active {
+                if from_gamepad {
+                    if let Some((x, y)) = filters.apply(user_index, action_index, Some((x, y))) {
+                        active = ActionType::Analog { x, y };
+                    }
+                }
+            } else {
+                filters.apply(user_index, action_index, None);
+            }
+
+            if let Some(state) = self.scripted.get(&(user_index, action_index)) {
+                active = state.clone();
+            }
+
+            if debounce.config(user_index, action_index).is_some() {
+                let held = matches!(active, ActionType::Digital(ActionState::Down));
+                if !debounce.filter(user_index, action_index, held, now) && held {
+                    active = ActionType::Digital(ActionState::Up);
+                }
+            }
+
+            if access.is_toggle(user_index, action_index) {
+                let held = matches!(active, ActionType::Digital(ActionState::Down));
+                active = match access.apply_toggle(user_index, action_index, held) {
+                    true => ActionType::Digital(ActionState::Down),
+                    false => ActionType::Digital(ActionState::Up),
+                };
+            }
+
+            let down = matches!(active, ActionType::Digital(ActionState::Down));
+            holds.record(user_index, action_index, down, now);
+            combos.record(user_index, action_index, down);
+            if down
+                && holds.is_navigation(user_index, action_index)
+                && !holds.pulsed(user_index, action_index)
+            {
+                active = ActionType::Digital(ActionState::Up);
+            }
+
+            if debug.enabled {
+                debug.actions.push(DebugAction {
+                    user_index,
+                    action_index,
+                    state: DebugActionState::from(&active),
+                    source: source
+                        .map(|i| describe_command(&input.commands[command_options].commands[i])),
+                });
+            }
+
             match input.users[user_index].update_action(action_index, active) {
                 Err(e) => match e {
                     InputError::ActionIndexOutOfBounds => {
@@ -154,13 +476,41 @@ impl<'a, 'b, 'c, IContent, UContent, Message> App
             };
         }
 
+        combos.evaluate(now);
         input.set();
 
         Ok(true)
     }
 
     fn update(&mut self, delta: f64) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("update", delta).entered();
+        let delta = match self
+            .background
+            .borrow_mut()
+            .gate(self.timer.ticks64(), delta)
+        {
+            Some(d) => d,
+            None => return,
+        };
+        self.frame_skip.borrow_mut().record_update();
         self.prepare_info();
+        self.poll_system();
+        self.check_mode_confirmation();
+        self.apply_taskbar();
+        self.tweens.borrow_mut().advance(self.timer.ticks64());
+        self.audio_buses.borrow_mut().advance(self.timer.ticks64());
+        self.post_mix.borrow_mut().collect();
+        self.apply_rumble();
+        if self.visualizer.borrow().is_enabled() {
+            let mut post_mix = self.post_mix.borrow_mut();
+            self.visualizer
+                .borrow_mut()
+                .update(post_mix.levels(), delta);
+        }
+        self.advance_reveals(delta);
+        self.inbox.borrow_mut().pump();
+        self.add_ready_scenes();
 
         {
             let update = Update::new(
@@ -169,17 +519,14 @@ impl<'a, 'b, 'c, IContent, UContent, Message> App
                 self.u_content.clone(),
             );
 
-            match self.stage.update(&update, delta) {
+            self.profiler.borrow_mut().begin("stage update");
+            let result = self.stage.update(&update, delta);
+            self.profiler.borrow_mut().end("stage update");
+
+            match result {
                 Ok(v) => {
                     for instruction in v.iter() {
-                        match instruction {
-                            UpdateInstruction::PlayMusic(ticket, loops, volume) => {
-                                self.play_music(*ticket, *loops, *volume)
-                            }
-                            UpdateInstruction::PlaySound(ticket, volume) => {
-                                self.play_sound(*ticket, *volume)
-                            }
-                        }
+                        self.run_instruction(instruction);
                     }
                 }
                 Err(e) => match e {
@@ -188,15 +535,80 @@ impl<'a, 'b, 'c, IContent, UContent, Message> App
                 },
             }
         }
+        self.apply_scene_requests();
+        self.run_keyed_sounds();
+        self.apply_soundscape();
+        self.apply_storage_requests();
+        self.stream_regions();
+        self.announcements
+            .borrow_mut()
+            .deliver(self.timer.ticks64());
+
+        // A press is tested where it happened, not where the mouse ended the frame.
+        let (x, y) = match self.clicks.borrow().pressed_this_frame(MouseButton::Left) {
+            true => self.clicks.borrow().press_position(MouseButton::Left),
+            false => None,
+        }
+        .unwrap_or_else(|| self.pointer_position(self.mouse.0, self.mouse.1));
+        self.hit_regions.borrow_mut().resolve(x, y, self.mouse.2);
+        let typing = self.keyboard.borrow().is_open();
+        self.focus
+            .borrow_mut()
+            .resolve(&self.controllers, self.timer.ticks64(), typing);
+
+        self.refresh_music_state();
+        self.recorder.borrow_mut().poll();
 
+        let scheduler = self.scheduler.clone();
+        let inbox = self.inbox.clone();
+        scheduler.borrow_mut().run_due(
+            self.timer.ticks64(),
+            |i| self.run_instruction(i),
+            |m| inbox.borrow_mut().deliver(m),
+        );
+
+        self.profiler.borrow_mut().begin("work queue");
+        let work = self.work.clone();
+        WorkQueue::run(&work, || self.precise_ticks());
+        self.profiler.borrow_mut().end("work queue");
+
+        self.presses.borrow_mut().consume();
+        self.timeline.borrow_mut().consume();
+        self.combos.borrow_mut().consume();
         self.input.borrow_mut().updated();
     }
 
     fn draw(&mut self, interp: f64, _total_time: u64) {
-        self.canvas.set_draw_color(Color::RGB(55, 55, 55));
-        self.canvas.clear();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("draw", interp).entered();
+        if self.lifecycle.borrow().is_background() {
+            return;
+        }
+        let minimized = self.background.borrow().is_minimized();
+        if minimized {
+            let sleep = self.background.borrow().idle_sleep;
+            std::thread::sleep(std::time::Duration::from_millis(sleep));
+            return;
+        }
+        if !self.frame_skip.borrow_mut().should_draw() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("frame skipped");
+            return;
+        }
+
+        self.measure_frame();
+        self.begin_frame();
 
-        let batches = match self.stage.draw(&(), interp) {
+        if let Err(e) = self.clear.apply(&mut self.canvas) {
+            warn!("Could not clear the canvas: {}", e);
+        }
+
+        let interp = self.interpolation.borrow_mut().set(interp);
+        self.profiler.borrow_mut().begin("stage draw");
+        let batches = self.stage.draw(&(), interp);
+        self.profiler.borrow_mut().end("stage draw");
+
+        let batches = match batches {
             Ok(b) => b,
             Err(e) => {
                 match e {
@@ -208,59 +620,337 @@ impl<'a, 'b, 'c, IContent, UContent, Message> App
             }
         };
 
-        for batch in batches.iter() {
-            for draw in batch.instructions.iter() {
-                let texture = match &draw.draw_type {
-                    DrawType::Texture => {
-                        match self.storage.borrow().textures.get_by_ticket(draw.ticket) {
-                            Ok(t) => t,
-                            Err(e) => {
-                                ResourceError::log_failure(e);
-                                return;
-                            }
-                        }
-                    }
-                    DrawType::Text(s, c) => {
-                        match self.storage.borrow().fonts.get_by_ticket(draw.ticket) {
-                            Ok(f) => {
-                                let surface = match f
-                                    .borrow()
-                                    .render(&s)
-                                    .blended(super::to_color(&c))
-                                    .map_err(|e| e.to_string())
-                                {
-                                    Ok(s) => s,
-                                    Err(e) => {
-                                        error!("Error rendering font: {}", e);
-                                        return;
-                                    }
-                                };
-                                let texture = match self
-                                    .texture_creator
-                                    .create_texture_from_surface(&surface)
-                                    .map_err(|e| e.to_string())
-                                {
-                                    Ok(t) => t,
-                                    Err(e) => {
-                                        error!("Error transferring text surface to texture: {}", e);
-                                        return;
-                                    }
-                                };
-
-                                Rc::new(RefCell::new(texture))
-                            }
-                            Err(e) => {
-                                ResourceError::log_failure(e);
-                                return;
-                            }
-                        }
-                    }
-                };
+        self.profiler.borrow_mut().begin("draw prepare");
+        let mut commands = std::mem::take(&mut self.commands);
+        self.prepare_frame(&batches, &mut commands);
+        self.draw_count = commands.draw_count();
+        self.profiler.borrow_mut().end("draw prepare");
 
-                self.render_texture(texture, &draw.data);
-            }
+        self.profiler.borrow_mut().begin("draw submit");
+        self.submit_frame(&batches, &commands);
+        self.profiler.borrow_mut().end("draw submit");
+        self.frame_dump.borrow_mut().end_frame();
+        self.summarize_frame(&batches);
+
+        commands.clear();
+        self.commands = commands;
+
+        self.draw_lighting();
+        self.draw_global_tint();
+        self.draw_flash();
+        self.end_frame();
+        self.draw_cinematic_bars();
+        #[cfg(feature = "egui")]
+        if let Some(layer) = &mut self.egui {
+            let seconds = self.timer.ticks64() as f64 / 1000.0;
+            layer.render(&mut self.canvas, self.texture_creator, seconds);
         }
 
+        self.draw_wireframe();
+        self.draw_overlays();
+        self.draw_captions();
+        self.draw_announcements();
+        self.draw_keyboard();
+        self.draw_virtual_gamepad();
+        self.draw_input_debug();
+        self.draw_profiler();
+        self.capture_frame();
+        let budget = self.pacing.borrow().frame_budget();
+        let delay = self
+            .frame_skip
+            .borrow()
+            .cap_delay(self.precise_ticks(), budget);
+        if let Some(ms) = delay {
+            std::thread::sleep(std::time::Duration::from_secs_f64(ms / 1000.0));
+        }
         self.canvas.present();
+        let now = self.precise_ticks();
+        self.frame_skip.borrow_mut().presented(now);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            draws = self.draw_count,
+            culled = self.culled,
+            missing_textures = self.missing.textures,
+            skipped = self.frame_skip.borrow().stats().skipped,
+            "frame presented"
+        );
+    }
+}
+
+// Everything digital bindings read from for one user during an input poll.
+struct DigitalInput<'s> {
+    keys: &'s KeyboardState<'s>,
+    mouse: &'s MouseState,
+    key_mods: Mod,
+    controllers: &'s [Option<GameController>],
+    access: &'s InputAccessibility,
+    presses: &'s PressQueue,
+    split: &'s KeyboardSplit,
+    resolution: &'s ControllerResolution,
+    registry: &'s ControllerRegistry,
+    user_index: usize,
+}
+
+impl<'s> DigitalInput<'s> {
+    fn key_down(&self, key: Scancode) -> bool {
+        self.split.allows(self.user_index, key)
+            && (self.keys.is_scancode_pressed(key) || self.presses.key(key))
+    }
+
+    fn keycode_down(&self, key: Keycode) -> bool {
+        match Scancode::from_keycode(key) {
+            Some(code) => {
+                self.split.allows(self.user_index, code)
+                    && (self.keys.is_scancode_pressed(code) || self.presses.keycode(key))
+            }
+            None => self.presses.keycode(key),
+        }
+    }
+}
+
+// Composite commands only accept digital parts; anything else never counts as pressed.
+fn captured_by_keyboard(command: &SDLCommand, keyboard: &OnScreenKeyboard) -> bool {
+    match command {
+        SDLCommand::Gamepad(SDLGamepadFeature::Button(chord), _) => {
+            chord.items().iter().any(|b| keyboard.captures(*b))
+        }
+        SDLCommand::DigitalAxis { negative, positive } => {
+            captured_by_keyboard(negative, keyboard) || captured_by_keyboard(positive, keyboard)
+        }
+        SDLCommand::DigitalStick {
+            up,
+            down,
+            left,
+            right,
+            ..
+        } => [up, down, left, right]
+            .iter()
+            .any(|c| captured_by_keyboard(c, keyboard)),
+        _ => false,
+    }
+}
+
+// `ui` is whether the UI layer wants the pointer and the keyboard.
+fn claimed_by_ui(command: &SDLCommand, ui: (bool, bool)) -> bool {
+    match command {
+        SDLCommand::MouseButton(_)
+        | SDLCommand::MultiClick(..)
+        | SDLCommand::MousePosition
+        | SDLCommand::MouseWheel => ui.0,
+        SDLCommand::Key(_) | SDLCommand::KeyCode(..) => ui.1,
+        SDLCommand::DigitalAxis { negative, positive } => {
+            claimed_by_ui(negative, ui) || claimed_by_ui(positive, ui)
+        }
+        SDLCommand::DigitalStick {
+            up,
+            down,
+            left,
+            right,
+            ..
+        } => [up, down, left, right].iter().any(|c| claimed_by_ui(c, ui)),
+        SDLCommand::Gamepad(..) | SDLCommand::Touch(_) => false,
+    }
+}
+
+fn digital_down(command: &SDLCommand, input: &DigitalInput) -> bool {
+    let DigitalInput {
+        mouse,
+        key_mods,
+        access,
+        presses,
+        ..
+    } = *input;
+    match command {
+        SDLCommand::Key(chord) => access.chord_within(
+            chord,
+            |key| input.key_down(*key),
+            |key| access.key_recent(*key),
+        ),
+        SDLCommand::KeyCode(chord, modifiers) => {
+            let held = access.chord_within(
+                chord,
+                |key| input.keycode_down(*key),
+                |key| Scancode::from_keycode(*key).map_or(false, |code| access.key_recent(code)),
+            );
+            held && modifiers_held(*modifiers, key_mods)
+        }
+        SDLCommand::MouseButton(chord) => access.chord_within(
+            chord,
+            |button| mouse.is_mouse_button_pressed(*button) || presses.mouse(*button),
+            |button| access.mouse_recent(*button),
+        ),
+        SDLCommand::Gamepad(feature @ SDLGamepadFeature::Button(_), controller) => {
+            resolve_gamepad(feature, *controller, input).is_some()
+        }
+        _ => false,
+    }
+}
+
+// Shared by gamepad bindings and the gamepad buttons inside digital axes and sticks, so a
+// binding to a missing controller warns and falls back the same way wherever it is.
+fn resolve_gamepad(
+    feature: &SDLGamepadFeature,
+    controller: Option<usize>,
+    input: &DigitalInput,
+) -> Option<(usize, ActionType)> {
+    let DigitalInput {
+        controllers,
+        access,
+        presses,
+        resolution,
+        registry,
+        ..
+    } = *input;
+
+    // None reads whichever controller the policy picks.
+    let target = match controller {
+        Some(i) if controllers.get(i).map_or(true, Option::is_none) => {
+            resolution.missing_index(i);
+            match resolution.missing {
+                MissingController::Inactive => return None,
+                MissingController::AnyController => None,
+            }
+        }
+        target => target,
+    };
+
+    let state = match target {
+        Some(i) => controllers[i].as_ref().and_then(|c| {
+            gamepad_state(
+                c,
+                feature,
+                EPSILON,
+                registry.calibration_for(c.instance_id()),
+            )
+            .map(|s| (i, s))
+        }),
+        None => resolve_any(
+            controllers,
+            feature,
+            resolution.policy,
+            resolution.last_used(),
+            registry,
+        ),
+    };
+
+    // Chords only count as held together when none of the controllers has every button
+    // down, so check the window last.
+    match (state, feature) {
+        (None, SDLGamepadFeature::Button(chord)) => controllers
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| target.map_or(true, |t| t == *i))
+            .filter_map(|(i, c)| Some((i, c.as_ref()?)))
+            .find(|(_, c)| button_chord_down(c, chord, access, presses))
+            .map(|(i, _)| (i, ActionType::Digital(ActionState::Down))),
+        (state, _) => state,
+    }
+}
+
+fn button_chord_down(
+    controller: &GameController,
+    chord: &Chord<Button>,
+    access: &InputAccessibility,
+    presses: &PressQueue,
+) -> bool {
+    let which = controller.instance_id();
+    access.chord_within(
+        chord,
+        |button| controller.button(*button) || presses.button(which, *button),
+        |button| access.button_recent(which, *button),
+    )
+}
+
+fn gamepad_state(
+    controller: &GameController,
+    feature: &SDLGamepadFeature,
+    threshold: f32,
+    calibration: ControllerCalibration,
+) -> Option<ActionType> {
+    let deadzone = threshold.max(calibration.deadzone);
+    match feature {
+        SDLGamepadFeature::Button(chord) => {
+            if chord.is_active(|button| controller.button(*button)) {
+                return Some(ActionType::Digital(ActionState::Down));
+            }
+        }
+        SDLGamepadFeature::Axis(axis) => {
+            let value = calibration.axis(*axis, controller.axis(*axis));
+            if value.abs() >= deadzone {
+                return Some(ActionType::Axis(value));
+            }
+        }
+        SDLGamepadFeature::Stick(x, y) => {
+            let (x, y) = (
+                calibration.axis(*x, controller.axis(*x)),
+                calibration.axis(*y, controller.axis(*y)),
+            );
+            if let Some((x, y)) = radial_deadzone(x, y, deadzone) {
+                return Some(ActionType::Analog { x, y });
+            }
+        }
+        SDLGamepadFeature::Gyro { scale } => {
+            if let Some([pitch, yaw, _roll]) = read_sensor(controller, SDLSensor::Gyro) {
+                let (x, y) = (yaw * scale, pitch * scale);
+                if x.abs() >= threshold || y.abs() >= threshold {
+                    return Some(ActionType::Analog { x, y });
+                }
+            }
+        }
+        SDLGamepadFeature::Accelerometer { scale } => {
+            if let Some([x, _y, z]) = read_sensor(controller, SDLSensor::Accelerometer) {
+                let (x, y) = (x * scale, z * scale);
+                if x.abs() >= threshold || y.abs() >= threshold {
+                    return Some(ActionType::Analog { x, y });
+                }
+            }
+        }
+        SDLGamepadFeature::Touchpad(finger) => {
+            if let Some((x, y)) = read_touchpad(controller, *finger) {
+                return Some(ActionType::Analog { x, y });
+            }
+        }
+    };
+
+    None
+}
+
+fn resolve_any(
+    controllers: &[Option<GameController>],
+    feature: &SDLGamepadFeature,
+    policy: ControllerPolicy,
+    last_used: Option<usize>,
+    registry: &ControllerRegistry,
+) -> Option<(usize, ActionType)> {
+    let mut states = controllers.iter().enumerate().filter_map(|(i, c)| {
+        let c = c.as_ref()?;
+        let calibration = registry.calibration_for(c.instance_id());
+        gamepad_state(c, feature, 0.1, calibration).map(|s| (i, s))
+    });
+
+    match policy {
+        ControllerPolicy::First => states.next(),
+        ControllerPolicy::Strongest => states.max_by(|(_, a), (_, b)| {
+            magnitude(a)
+                .partial_cmp(&magnitude(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+        ControllerPolicy::MostRecent => {
+            let all: Vec<_> = states.collect();
+            let recent = all.iter().position(|(i, _)| Some(*i) == last_used);
+            match recent {
+                Some(p) => all.into_iter().nth(p),
+                None => all.into_iter().next(),
+            }
+        }
+    }
+}
+
+fn magnitude(action: &ActionType) -> f32 {
+    match action {
+        ActionType::Digital(_) => 1.0,
+        ActionType::Axis(v) => v.abs(),
+        ActionType::Analog { x, y } => (x * x + y * y).sqrt(),
     }
 }
@@ -0,0 +1,141 @@
+use sdl2::rect::Rect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasConfig {
+    pub page_size: u32,
+    // Textures with either side above this are left on their own.
+    pub max_size: u32,
+    // Transparent gap around each entry so filtering doesn't bleed neighbours in.
+    pub padding: u32,
+}
+
+impl Default for AtlasConfig {
+    fn default() -> Self {
+        AtlasConfig {
+            page_size: 2048,
+            max_size: 256,
+            padding: 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasRegion {
+    pub page: usize,
+    pub rect: Rect,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AtlasStats {
+    pub pages: usize,
+    pub packed: usize,
+    // Share of all page area covered by packed textures, 0.0 to 1.0.
+    pub occupancy: f32,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+}
+
+struct Page {
+    shelves: Vec<Shelf>,
+    used: u64,
+}
+
+// Shelf packing: entries fill rows left to right, and a new row opens under the last one
+// when nothing fits. Simple and good enough for sprites of similar heights.
+pub struct AtlasPacker {
+    pub config: AtlasConfig,
+    pages: Vec<Page>,
+    packed: usize,
+}
+
+impl AtlasPacker {
+    pub fn new(config: AtlasConfig) -> Self {
+        AtlasPacker {
+            config,
+            pages: Vec::new(),
+            packed: 0,
+        }
+    }
+
+    pub fn fits(&self, width: u32, height: u32) -> bool {
+        let limit = self.config.max_size.min(
+            self.config
+                .page_size
+                .saturating_sub(self.config.padding * 2),
+        );
+        width > 0 && height > 0 && width <= limit && height <= limit
+    }
+
+    // The second value is true when the region opened a new page.
+    pub fn insert(&mut self, width: u32, height: u32) -> Option<(AtlasRegion, bool)> {
+        if !self.fits(width, height) {
+            return None;
+        }
+
+        let padding = self.config.padding;
+        let (w, h) = (width + padding * 2, height + padding * 2);
+        let size = self.config.page_size;
+
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some((x, y)) = Self::place(page, size, w, h) {
+                self.packed += 1;
+                let rect = Rect::new((x + padding) as i32, (y + padding) as i32, width, height);
+                return Some((AtlasRegion { page: index, rect }, false));
+            }
+        }
+
+        let mut page = Page {
+            shelves: Vec::new(),
+            used: 0,
+        };
+        let (x, y) = Self::place(&mut page, size, w, h)?;
+        self.pages.push(page);
+        self.packed += 1;
+
+        let rect = Rect::new((x + padding) as i32, (y + padding) as i32, width, height);
+        Some((
+            AtlasRegion {
+                page: self.pages.len() - 1,
+                rect,
+            },
+            true,
+        ))
+    }
+
+    pub fn stats(&self) -> AtlasStats {
+        let area = self.config.page_size as u64 * self.config.page_size as u64;
+        let used: u64 = self.pages.iter().map(|p| p.used).sum();
+        AtlasStats {
+            pages: self.pages.len(),
+            packed: self.packed,
+            occupancy: match self.pages.is_empty() {
+                true => 0.0,
+                false => used as f32 / (area * self.pages.len() as u64) as f32,
+            },
+        }
+    }
+
+    fn place(page: &mut Page, size: u32, w: u32, h: u32) -> Option<(u32, u32)> {
+        for shelf in page.shelves.iter_mut() {
+            if h <= shelf.height && shelf.x + w <= size {
+                let x = shelf.x;
+                shelf.x += w;
+                page.used += w as u64 * h as u64;
+                return Some((x, shelf.y));
+            }
+        }
+
+        let y = page.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+        if y + h > size {
+            return None;
+        }
+
+        page.shelves.push(Shelf { y, height: h, x: w });
+        page.used += w as u64 * h as u64;
+        Some((0, y))
+    }
+}
@@ -0,0 +1,858 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+    os::raw::c_void,
+    slice,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use sdl2::{
+    mixer::{Channel, Chunk, Music, AUDIO_S16LSB, DEFAULT_CHANNELS},
+    sys::mixer::{
+        Mix_GetMusicPosition, Mix_MusicDuration, Mix_RegisterEffect, Mix_UnregisterAllEffects,
+    },
+};
+use stagehand::loading::Ticket;
+
+pub const SAMPLE_RATE: i32 = 44100;
+pub const POST_MIX: i32 = -2;
+
+static AVAILABLE: AtomicBool = AtomicBool::new(true);
+static WARNED: AtomicBool = AtomicBool::new(false);
+static POST_MIX_EFFECTS: AtomicUsize = AtomicUsize::new(0);
+
+// False when no audio device could be opened at startup. The app runs without sound then:
+// music and sound assets are skipped when loading and instructions to play them do nothing.
+pub fn is_available() -> bool {
+    AVAILABLE.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_unavailable() {
+    AVAILABLE.store(false, Ordering::Relaxed);
+}
+
+// Logs only the first time, a game playing sounds every frame would flood the log.
+pub(crate) fn warn_unavailable(what: &str) {
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        log::warn!("No audio device, ignoring {} and all audio after it", what);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolyphonyPolicy {
+    Ignore,
+    Retrigger,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolyphonyLimit {
+    pub max: usize,
+    pub policy: PolyphonyPolicy,
+}
+
+struct Voice {
+    ticket: Ticket,
+    started: u64,
+}
+
+pub struct SoundLimits {
+    limits: HashMap<Ticket, PolyphonyLimit>,
+    voices: HashMap<i32, Voice>,
+    counter: u64,
+}
+
+impl SoundLimits {
+    pub fn new() -> Self {
+        SoundLimits {
+            limits: HashMap::new(),
+            voices: HashMap::new(),
+            counter: 0,
+        }
+    }
+
+    // A `max` of 0 means unlimited, the same as no limit.
+    pub fn set_limit(&mut self, ticket: Ticket, limit: Option<PolyphonyLimit>) {
+        match limit {
+            Some(l) if l.max > 0 => self.limits.insert(ticket, l),
+            _ => self.limits.remove(&ticket),
+        };
+    }
+
+    pub fn playing(&self, ticket: Ticket) -> usize {
+        self.voices
+            .iter()
+            .filter(|(c, v)| v.ticket == ticket && Channel(**c).is_playing())
+            .count()
+    }
+
+    // Returns false when the sound should not be played at all.
+    pub(crate) fn admit(&mut self, ticket: Ticket) -> bool {
+        self.voices.retain(|c, _| Channel(*c).is_playing());
+
+        let limit = match self.limits.get(&ticket) {
+            Some(l) => *l,
+            None => return true,
+        };
+
+        if self.playing(ticket) < limit.max {
+            return true;
+        }
+
+        match limit.policy {
+            PolyphonyPolicy::Ignore => false,
+            PolyphonyPolicy::Retrigger => {
+                let oldest = self
+                    .voices
+                    .iter()
+                    .filter(|(_, v)| v.ticket == ticket)
+                    .min_by_key(|(_, v)| v.started)
+                    .map(|(c, _)| *c);
+
+                if let Some(channel) = oldest {
+                    Channel(channel).halt();
+                    self.voices.remove(&channel);
+                }
+                true
+            }
+        }
+    }
+
+    // The channel among `channels` whose current sound started first.
+    pub(crate) fn oldest(&self, channels: std::ops::Range<i32>) -> Option<Channel> {
+        self.voices
+            .iter()
+            .filter(|(c, _)| channels.contains(c))
+            .min_by_key(|(_, v)| v.started)
+            .map(|(c, _)| Channel(*c))
+    }
+
+    pub(crate) fn started(&mut self, ticket: Ticket, channel: Channel) {
+        self.counter += 1;
+        self.voices.insert(
+            channel.0,
+            Voice {
+                ticket,
+                started: self.counter,
+            },
+        );
+    }
+}
+
+impl Default for SoundLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchVariation {
+    pub min: f32,
+    pub max: f32,
+    pub variants: usize,
+}
+
+pub struct SoundVariations {
+    pitches: HashMap<Ticket, PitchVariation>,
+    variants: HashMap<Ticket, Vec<Chunk>>,
+    seed: u32,
+}
+
+impl SoundVariations {
+    pub fn new() -> Self {
+        SoundVariations {
+            pitches: HashMap::new(),
+            variants: HashMap::new(),
+            seed: 0x9E37_79B9,
+        }
+    }
+
+    pub fn set_pitch(&mut self, ticket: Ticket, pitch: Option<PitchVariation>) {
+        self.variants.remove(&ticket);
+        match pitch {
+            Some(p) => self.pitches.insert(ticket, p),
+            None => self.pitches.remove(&ticket),
+        };
+    }
+
+    pub fn has_variation(&self, ticket: Ticket) -> bool {
+        self.pitches.contains_key(&ticket)
+    }
+
+    pub(crate) fn clear_variants(&mut self) {
+        self.variants.clear();
+    }
+
+    // Variants are resampled copies built the first time the ticket plays.
+    pub(crate) fn pick(&mut self, ticket: Ticket, source: &Chunk) -> Option<&mut Chunk> {
+        let pitch = *self.pitches.get(&ticket)?;
+
+        if !self.variants.contains_key(&ticket) {
+            let count = pitch.variants.max(1);
+            let mut chunks = Vec::with_capacity(count);
+            for i in 0..count {
+                let t = match count {
+                    1 => 0.5,
+                    _ => i as f32 / (count - 1) as f32,
+                };
+                match resample(source, pitch.min + (pitch.max - pitch.min) * t) {
+                    Ok(c) => chunks.push(c),
+                    Err(e) => log::warn!("Could not build pitch variant: {}", e),
+                }
+            }
+            self.variants.insert(ticket, chunks);
+        }
+
+        // xorshift, only needs to avoid obvious repetition
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+
+        let chunks = self.variants.get_mut(&ticket)?;
+        if chunks.is_empty() {
+            return None;
+        }
+        let index = self.seed as usize % chunks.len();
+        chunks.get_mut(index)
+    }
+}
+
+impl Default for SoundVariations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Chunks are stored in the device format, so this only handles 16 bit samples, with any
+// channel count.
+pub fn resample(chunk: &Chunk, pitch: f32) -> Result<Chunk, String> {
+    if pitch <= 0.0 {
+        return Err("Pitch must be positive".to_string());
+    }
+
+    let channels = match sdl2::mixer::query_spec()? {
+        (_, format, _) if format != AUDIO_S16LSB => {
+            return Err(format!(
+                "Resampling needs 16 bit samples, the device has {:#x}",
+                format
+            ))
+        }
+        (_, _, channels) => channels.max(1) as usize,
+    };
+
+    let samples = unsafe {
+        let raw = &*chunk.raw;
+        std::slice::from_raw_parts(raw.abuf as *const i16, raw.alen as usize / 2)
+    };
+
+    let frames = samples.len() / channels;
+    let out_frames = (frames as f32 / pitch) as usize;
+
+    let mut out = vec![0i16; out_frames * channels];
+    for frame in 0..out_frames {
+        let position = frame as f32 * pitch;
+        let index = position as usize;
+        let fraction = position - index as f32;
+        let next = (index + 1).min(frames.saturating_sub(1));
+
+        for channel in 0..channels {
+            let a = samples[index * channels + channel] as f32;
+            let b = samples[next * channels + channel] as f32;
+            out[frame * channels + channel] = (a + (b - a) * fraction) as i16;
+        }
+    }
+
+    Chunk::from_raw_buffer(out.into_boxed_slice())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChannelEffect {
+    LowPass { cutoff: f32 },
+    Echo { delay: u32, feedback: f32 },
+    Reverb { room: f32, damping: f32, wet: f32 },
+}
+
+struct DelayLine {
+    buffer: Vec<f32>,
+    position: usize,
+    filter: f32,
+}
+
+impl DelayLine {
+    fn new(frames: usize) -> Self {
+        DelayLine {
+            buffer: vec![0.0; frames.max(1)],
+            position: 0,
+            filter: 0.0,
+        }
+    }
+
+    fn comb(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.position];
+        self.filter = output * (1.0 - damping) + self.filter * damping;
+        self.buffer[self.position] = input + self.filter * feedback;
+        self.position = (self.position + 1) % self.buffer.len();
+        output
+    }
+}
+
+struct EffectState {
+    effect: ChannelEffect,
+    // One set of lines per output channel.
+    lines: Vec<Vec<DelayLine>>,
+    last: Vec<f32>,
+}
+
+impl EffectState {
+    fn new(effect: ChannelEffect) -> Self {
+        let channels = DEFAULT_CHANNELS as usize;
+        let delays: Vec<usize> = match effect {
+            ChannelEffect::LowPass { .. } => Vec::new(),
+            ChannelEffect::Echo { delay, .. } => vec![(delay * SAMPLE_RATE as u32 / 1000) as usize],
+            ChannelEffect::Reverb { .. } => vec![1116, 1188, 1277, 1356],
+        };
+
+        EffectState {
+            effect,
+            lines: (0..channels)
+                .map(|c| delays.iter().map(|d| DelayLine::new(d + c * 23)).collect())
+                .collect(),
+            last: vec![0.0; channels],
+        }
+    }
+
+    fn process(&mut self, samples: &mut [i16]) {
+        let channels = self.last.len();
+
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let channel = i % channels;
+            let input = *sample as f32;
+
+            let output = match self.effect {
+                ChannelEffect::LowPass { cutoff } => {
+                    let rc = 1.0 / (2.0 * PI * cutoff.max(1.0));
+                    let dt = 1.0 / SAMPLE_RATE as f32;
+                    let alpha = dt / (rc + dt);
+                    self.last[channel] += alpha * (input - self.last[channel]);
+                    self.last[channel]
+                }
+                ChannelEffect::Echo { feedback, .. } => {
+                    let line = &mut self.lines[channel][0];
+                    input + line.comb(input, feedback.clamp(0.0, 0.95), 0.0) * feedback
+                }
+                ChannelEffect::Reverb { room, damping, wet } => {
+                    let feedback = 0.7 + room.clamp(0.0, 1.0) * 0.28;
+                    let lines = &mut self.lines[channel];
+                    let count = lines.len() as f32;
+                    let sum: f32 = lines
+                        .iter_mut()
+                        .map(|l| l.comb(input * 0.1, feedback, damping.clamp(0.0, 1.0)))
+                        .sum();
+                    input * (1.0 - wet) + sum / count * wet * 10.0
+                }
+            };
+
+            *sample = output.clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+}
+
+unsafe extern "C" fn effect_callback(
+    _channel: i32,
+    stream: *mut c_void,
+    length: i32,
+    data: *mut c_void,
+) {
+    let state = &mut *(data as *mut EffectState);
+    let samples = slice::from_raw_parts_mut(stream as *mut i16, length as usize / 2);
+    state.process(samples);
+}
+
+unsafe extern "C" fn effect_done(channel: i32, data: *mut c_void) {
+    drop(Box::from_raw(data as *mut EffectState));
+    if channel == POST_MIX {
+        POST_MIX_EFFECTS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// Effects on a regular channel end when it halts, use POST_MIX for a persistent effect.
+pub fn register_effect(channel: i32, effect: ChannelEffect) -> Result<(), String> {
+    let state = Box::into_raw(Box::new(EffectState::new(effect)));
+    let result = unsafe {
+        Mix_RegisterEffect(
+            channel,
+            Some(effect_callback),
+            Some(effect_done),
+            state as *mut c_void,
+        )
+    };
+
+    if result == 0 {
+        unsafe { drop(Box::from_raw(state)) };
+        return Err(sdl2::get_error());
+    }
+
+    if channel == POST_MIX {
+        POST_MIX_EFFECTS.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+pub(crate) fn post_mix_effects() -> usize {
+    POST_MIX_EFFECTS.load(Ordering::Relaxed)
+}
+
+pub fn clear_effects(channel: i32) -> Result<(), String> {
+    match unsafe { Mix_UnregisterAllEffects(channel) } {
+        0 => Err(sdl2::get_error()),
+        _ => Ok(()),
+    }
+}
+
+// Reserved channels are the first ones and are skipped when a sound picks any free channel,
+// so critical sounds like UI confirms or dialogue always have somewhere to play. Critical
+// sounds only ever take reserved channels, stealing the oldest when all are busy.
+pub struct ChannelReservation {
+    reserved: i32,
+    critical: HashSet<Ticket>,
+}
+
+impl ChannelReservation {
+    pub fn new() -> Self {
+        ChannelReservation {
+            reserved: 0,
+            critical: HashSet::new(),
+        }
+    }
+
+    // Grows or shrinks the mixer, sounds on removed channels stop. Returns the new count.
+    pub fn allocate(&mut self, channels: i32) -> i32 {
+        let allocated = sdl2::mixer::allocate_channels(channels.max(0));
+        if self.reserved > allocated {
+            self.reserved = sdl2::mixer::reserve_channels(allocated);
+        }
+        allocated
+    }
+
+    pub fn channels(&self) -> i32 {
+        sdl2::mixer::allocate_channels(-1)
+    }
+
+    // Returns how many channels were actually reserved, which is capped by the allocation.
+    pub fn reserve(&mut self, channels: i32) -> i32 {
+        self.reserved = sdl2::mixer::reserve_channels(channels.max(0));
+        self.reserved
+    }
+
+    pub fn reserved(&self) -> i32 {
+        self.reserved
+    }
+
+    // A reopened device starts without reservations.
+    pub(crate) fn reapply(&mut self) {
+        self.reserve(self.reserved);
+    }
+
+    pub fn set_critical(&mut self, ticket: Ticket, critical: bool) {
+        match critical {
+            true => self.critical.insert(ticket),
+            false => self.critical.remove(&ticket),
+        };
+    }
+
+    pub fn is_critical(&self, ticket: Ticket) -> bool {
+        self.critical.contains(&ticket)
+    }
+
+    pub(crate) fn channel(&self, ticket: Ticket, limits: &SoundLimits) -> Channel {
+        if self.reserved == 0 || !self.is_critical(ticket) {
+            return Channel::all();
+        }
+
+        (0..self.reserved)
+            .map(Channel)
+            .find(|c| !c.is_playing())
+            .or_else(|| limits.oldest(0..self.reserved))
+            .unwrap_or(Channel(0))
+    }
+}
+
+impl Default for ChannelReservation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelUsage {
+    pub total: usize,
+    pub playing: usize,
+}
+
+impl ChannelUsage {
+    pub fn free(&self) -> usize {
+        self.total.saturating_sub(self.playing)
+    }
+}
+
+// Channel occupancy for tuning `allocate_channels`. Groups are tagged channel ranges as in
+// SDL_mixer, and plays dropped because every channel was busy are counted per frame.
+pub struct MixerStats {
+    groups: HashMap<i32, (i32, i32)>,
+    dropped: u64,
+    dropped_frame: u64,
+}
+
+impl MixerStats {
+    pub fn new() -> Self {
+        MixerStats {
+            groups: HashMap::new(),
+            dropped: 0,
+            dropped_frame: 0,
+        }
+    }
+
+    pub fn usage(&self) -> ChannelUsage {
+        let total = sdl2::mixer::allocate_channels(-1).max(0);
+        ChannelUsage {
+            total: total as usize,
+            playing: (0..total).filter(|c| Channel(*c).is_playing()).count(),
+        }
+    }
+
+    // Tags channels `from` to `to` inclusive, replacing the range the tag had before.
+    pub fn set_group(&mut self, tag: i32, from: i32, to: i32) -> Result<(), String> {
+        if let Some((f, t)) = self.groups.remove(&tag) {
+            sdl2::mixer::Group(-1).add_channels_range(f, t);
+        }
+        match sdl2::mixer::Group(tag).add_channels_range(from, to) {
+            n if n == to - from + 1 => {
+                self.groups.insert(tag, (from, to));
+                Ok(())
+            }
+            _ => Err(format!("Could not group channels {} to {}", from, to)),
+        }
+    }
+
+    pub fn group_usage(&self, tag: i32) -> Option<ChannelUsage> {
+        let (from, to) = *self.groups.get(&tag)?;
+        Some(ChannelUsage {
+            total: (to - from + 1).max(0) as usize,
+            playing: (from..=to).filter(|c| Channel(*c).is_playing()).count(),
+        })
+    }
+
+    pub fn groups(&self) -> Vec<i32> {
+        self.groups.keys().copied().collect()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn dropped_this_frame(&self) -> u64 {
+        self.dropped_frame
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.dropped_frame = 0;
+    }
+
+    // A reopened device starts with every channel untagged.
+    pub(crate) fn reapply(&mut self) {
+        for (tag, (from, to)) in self.groups.iter() {
+            sdl2::mixer::Group(*tag).add_channels_range(*from, *to);
+        }
+    }
+
+    pub(crate) fn record_drop(&mut self) {
+        self.dropped += 1;
+        self.dropped_frame += 1;
+    }
+}
+
+impl Default for MixerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Bus {
+    Music,
+    Sounds,
+}
+
+struct Fade {
+    from: f32,
+    to: f32,
+    start: u64,
+    duration: u64,
+}
+
+// Bus levels scale on top of the volumes given with PlayMusic and PlaySound, so ducking
+// for a pause menu or a line of dialogue doesn't lose what the scene asked for. Each bus
+// has a persistent volume, the player's setting, and a duck level that fades for dialogue
+// and menus; the two multiply, so ducking never changes the setting.
+pub struct AudioBuses {
+    volumes: HashMap<Bus, f32>,
+    levels: HashMap<Bus, f32>,
+    fades: HashMap<Bus, Fade>,
+    music_volume: f32,
+    now: u64,
+    dirty: bool,
+}
+
+impl AudioBuses {
+    pub fn new() -> Self {
+        AudioBuses {
+            volumes: HashMap::new(),
+            levels: HashMap::new(),
+            fades: HashMap::new(),
+            music_volume: 1.0,
+            now: 0,
+            dirty: false,
+        }
+    }
+
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.volumes.get(&bus).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.volumes.insert(bus, volume.clamp(0.0, 1.0));
+        self.dirty = true;
+    }
+
+    // The duck level, before the bus volume.
+    pub fn level(&self, bus: Bus) -> f32 {
+        self.levels.get(&bus).copied().unwrap_or(1.0)
+    }
+
+    // What the mixer plays the bus at.
+    pub fn effective(&self, bus: Bus) -> f32 {
+        self.volume(bus) * self.level(bus)
+    }
+
+    pub fn is_fading(&self, bus: Bus) -> bool {
+        self.fades.contains_key(&bus)
+    }
+
+    // A reopened device starts at full volume, so the levels go out again next update.
+    pub(crate) fn reapply(&mut self) {
+        self.dirty = true;
+    }
+
+    // Moves the bus from its current level to `to_volume`, restore with a duck back to 1.0.
+    pub fn duck(&mut self, bus: Bus, to_volume: f32, over_ms: u64) {
+        let to = to_volume.clamp(0.0, 1.0);
+        if over_ms == 0 {
+            self.fades.remove(&bus);
+            self.levels.insert(bus, to);
+            self.dirty = true;
+            return;
+        }
+
+        let fade = Fade {
+            from: self.level(bus),
+            to,
+            start: self.now,
+            duration: over_ms,
+        };
+        self.fades.insert(bus, fade);
+    }
+
+    pub(crate) fn set_music_volume(&mut self, volume: f32) {
+        self.music_volume = volume;
+        self.dirty = true;
+    }
+
+    pub(crate) fn advance(&mut self, now: u64) {
+        self.now = now;
+
+        let mut finished = Vec::new();
+        for (bus, fade) in self.fades.iter() {
+            let t = (now.saturating_sub(fade.start) as f32 / fade.duration as f32).min(1.0);
+            self.levels
+                .insert(*bus, fade.from + (fade.to - fade.from) * t);
+            if t >= 1.0 {
+                finished.push(*bus);
+            }
+        }
+        for bus in finished {
+            self.fades.remove(&bus);
+        }
+
+        if !self.dirty && self.fades.is_empty() {
+            return;
+        }
+        self.dirty = !self.fades.is_empty();
+
+        let max = sdl2::mixer::MAX_VOLUME as f32;
+        Music::set_volume((self.music_volume * self.effective(Bus::Music) * max) as i32);
+        Channel::all().set_volume((self.effective(Bus::Sounds) * max) as i32);
+    }
+}
+
+impl Default for AudioBuses {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PauseReason {
+    Background,
+    Lifecycle,
+}
+
+// Pauses audio for the backend's own policies and resumes only what it paused, so music or
+// channels the game paused itself stay paused. Audio resumes when every reason is gone.
+pub(crate) struct PolicyPause {
+    reasons: Vec<PauseReason>,
+    music: bool,
+    channels: Vec<i32>,
+}
+
+impl PolicyPause {
+    pub fn new() -> Self {
+        PolicyPause {
+            reasons: Vec::new(),
+            music: false,
+            channels: Vec::new(),
+        }
+    }
+
+    pub fn pause(&mut self, reason: PauseReason) {
+        if self.reasons.contains(&reason) {
+            return;
+        }
+        self.reasons.push(reason);
+        if self.reasons.len() > 1 {
+            return;
+        }
+
+        self.music = Music::is_playing() && !Music::is_paused();
+        if self.music {
+            Music::pause();
+        }
+
+        self.channels = (0..sdl2::mixer::allocate_channels(-1))
+            .map(Channel)
+            .filter(|c| c.is_playing() && !c.is_paused())
+            .map(|c| c.0)
+            .collect();
+        for channel in self.channels.iter() {
+            Channel(*channel).pause();
+        }
+    }
+
+    pub fn resume(&mut self, reason: PauseReason) {
+        let before = self.reasons.len();
+        self.reasons.retain(|r| *r != reason);
+        if before == self.reasons.len() || !self.reasons.is_empty() {
+            return;
+        }
+
+        if std::mem::take(&mut self.music) {
+            Music::resume();
+        }
+        for channel in self.channels.drain(..) {
+            Channel(channel).resume();
+        }
+    }
+}
+
+pub struct MusicState {
+    ticket: Option<Ticket>,
+    loops: i32,
+    volume: f32,
+    position: Option<f64>,
+    duration: Option<f64>,
+    seek: Option<f64>,
+}
+
+impl MusicState {
+    pub fn new() -> Self {
+        MusicState {
+            ticket: None,
+            loops: 0,
+            volume: 1.0,
+            position: None,
+            duration: None,
+            seek: None,
+        }
+    }
+
+    pub fn ticket(&self) -> Option<Ticket> {
+        self.ticket
+    }
+
+    pub fn position(&self) -> Option<f64> {
+        self.position
+    }
+
+    // As given when the music started.
+    pub fn loops(&self) -> i32 {
+        self.loops
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    pub fn duration(&self) -> Option<f64> {
+        self.duration
+    }
+
+    // Applied by the backend on the next update.
+    pub fn seek(&mut self, seconds: f64) {
+        self.seek = Some(seconds.max(0.0));
+    }
+
+    pub(crate) fn started(&mut self, ticket: Ticket, loops: i32, volume: f32) {
+        self.ticket = Some(ticket);
+        self.loops = loops;
+        self.volume = volume;
+        self.seek = None;
+    }
+
+    pub(crate) fn refresh(&mut self, music: Option<&Music>) {
+        let music = match (music, Music::is_playing()) {
+            (Some(m), true) => m,
+            _ => {
+                self.position = None;
+                self.duration = None;
+                return;
+            }
+        };
+
+        if let Some(seconds) = self.seek.take() {
+            if let Err(e) = Music::set_pos(seconds) {
+                log::warn!("Could not seek music: {}", e);
+            }
+        }
+
+        let (position, duration) = unsafe {
+            (
+                Mix_GetMusicPosition(music.raw),
+                Mix_MusicDuration(music.raw),
+            )
+        };
+        self.position = if position >= 0.0 {
+            Some(position)
+        } else {
+            None
+        };
+        self.duration = if duration >= 0.0 {
+            Some(duration)
+        } else {
+            None
+        };
+    }
+}
+
+impl Default for MusicState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
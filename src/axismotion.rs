@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use sdl2::{controller::Axis, event::Event};
+
+use crate::input::translate_axis;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisMotion {
+    pub value: f32,
+    pub previous: f32,
+    // SDL ticks when the controller reported it, which is earlier than the frame that
+    // handled it. The gap is the input latency on the event path.
+    pub timestamp: u64,
+    pub previous_timestamp: u64,
+}
+
+impl AxisMotion {
+    // Change per second between the last two reports, large for a flick.
+    pub fn velocity(&self) -> f32 {
+        let elapsed = self.timestamp.saturating_sub(self.previous_timestamp);
+        if elapsed == 0 {
+            return 0.0;
+        }
+        (self.value - self.previous) * 1000.0 / elapsed as f32
+    }
+}
+
+// Axis motion as it arrived, with timestamps, rather than polled once a frame. Controllers
+// are by index in the connected list, like the rest of the input bindings.
+pub struct AxisMotionLog {
+    latest: HashMap<(usize, Axis), AxisMotion>,
+    frame: Vec<(usize, Axis, AxisMotion)>,
+}
+
+impl AxisMotionLog {
+    pub fn new() -> Self {
+        AxisMotionLog {
+            latest: HashMap::new(),
+            frame: Vec::new(),
+        }
+    }
+
+    pub fn last_motion(&self, controller: usize, axis: Axis) -> Option<AxisMotion> {
+        self.latest.get(&(controller, axis)).copied()
+    }
+
+    // Time of the most recent report for the axis.
+    pub fn last_motion_time(&self, controller: usize, axis: Axis) -> Option<u64> {
+        self.last_motion(controller, axis).map(|m| m.timestamp)
+    }
+
+    pub fn moved_this_frame(&self, controller: usize, axis: Axis) -> bool {
+        self.frame
+            .iter()
+            .any(|(c, a, _)| *c == controller && *a == axis)
+    }
+
+    // Every report since the previous frame, oldest first.
+    pub fn this_frame(&self, controller: usize, axis: Axis) -> impl Iterator<Item = &AxisMotion> {
+        self.frame
+            .iter()
+            .filter(move |(c, a, _)| *c == controller && *a == axis)
+            .map(|(_, _, m)| m)
+    }
+
+    // Fastest change seen this frame, so a flick that starts and settles within one frame
+    // still shows up.
+    pub fn peak_velocity(&self, controller: usize, axis: Axis) -> f32 {
+        self.this_frame(controller, axis)
+            .map(|m| m.velocity())
+            .fold(0.0, |a, b| match b.abs() > a.abs() {
+                true => b,
+                false => a,
+            })
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.frame.clear();
+    }
+
+    pub(crate) fn record(&mut self, event: &Event, controller: Option<usize>) {
+        let (timestamp, axis, value) = match event {
+            Event::ControllerAxisMotion {
+                timestamp,
+                axis,
+                value,
+                ..
+            } => (*timestamp as u64, *axis, translate_axis(*value)),
+            _ => return,
+        };
+        let controller = match controller {
+            Some(c) => c,
+            None => return,
+        };
+
+        let (previous, previous_timestamp) = match self.latest.get(&(controller, axis)) {
+            Some(m) => (m.value, m.timestamp),
+            None => (value, timestamp),
+        };
+        let motion = AxisMotion {
+            value,
+            previous,
+            timestamp,
+            previous_timestamp,
+        };
+        self.latest.insert((controller, axis), motion);
+        self.frame.push((controller, axis, motion));
+    }
+
+    // Slots are reused on reconnect, so the old controller's motion must not carry over.
+    pub(crate) fn forget(&mut self, controller: usize) {
+        self.latest.retain(|(c, _), _| *c != controller);
+        self.frame.retain(|(c, _, _)| *c != controller);
+    }
+}
+
+impl Default for AxisMotionLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
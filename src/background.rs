@@ -0,0 +1,110 @@
+use sdl2::event::{Event, WindowEvent};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackgroundUpdates {
+    Stop,
+    // Updates per second, each given the time since the previous one as its delta.
+    Reduced(u32),
+    // Keeps simulating at full speed, for simulation and idle games.
+    Full,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackgroundAudio {
+    Pause,
+    Continue,
+}
+
+// What the app does while its window is minimized, or also while it's unfocused with
+// `when_unfocused`. Drawing always stops while minimized since nothing is visible.
+pub struct BackgroundPolicy {
+    pub updates: BackgroundUpdates,
+    pub audio: BackgroundAudio,
+    pub when_unfocused: bool,
+    // Milliseconds to sleep instead of drawing, so a minimized window doesn't spin.
+    pub idle_sleep: u64,
+    minimized: bool,
+    unfocused: bool,
+    audio_paused: bool,
+    pending: f64,
+    last_update: Option<u64>,
+}
+
+impl BackgroundPolicy {
+    pub fn new() -> Self {
+        BackgroundPolicy {
+            updates: BackgroundUpdates::Full,
+            audio: BackgroundAudio::Continue,
+            when_unfocused: false,
+            idle_sleep: 10,
+            minimized: false,
+            unfocused: false,
+            audio_paused: false,
+            pending: 0.0,
+            last_update: None,
+        }
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    // True while the policy applies.
+    pub fn is_active(&self) -> bool {
+        self.minimized || (self.when_unfocused && self.unfocused)
+    }
+
+    pub(crate) fn record(&mut self, event: &Event) {
+        if let Event::Window { win_event, .. } = event {
+            match win_event {
+                WindowEvent::Minimized => self.minimized = true,
+                WindowEvent::Restored | WindowEvent::Maximized | WindowEvent::Shown => {
+                    self.minimized = false
+                }
+                WindowEvent::FocusLost => self.unfocused = true,
+                WindowEvent::FocusGained => self.unfocused = false,
+                _ => (),
+            }
+        }
+    }
+
+    // Some(true) to pause audio, Some(false) to resume, when that changed.
+    pub(crate) fn audio_change(&mut self) -> Option<bool> {
+        let pause = self.is_active() && self.audio == BackgroundAudio::Pause;
+        if pause == self.audio_paused {
+            return None;
+        }
+        self.audio_paused = pause;
+        Some(pause)
+    }
+
+    // Delta to update with, None to skip this update.
+    pub(crate) fn gate(&mut self, now: u64, delta: f64) -> Option<f64> {
+        if !self.is_active() {
+            self.pending = 0.0;
+            self.last_update = None;
+            return Some(delta);
+        }
+
+        match self.updates {
+            BackgroundUpdates::Full => Some(delta),
+            BackgroundUpdates::Stop => None,
+            BackgroundUpdates::Reduced(rate) => {
+                self.pending += delta;
+                let interval = 1000 / rate.max(1) as u64;
+                let last = *self.last_update.get_or_insert(now);
+                if now.saturating_sub(last) < interval {
+                    return None;
+                }
+                self.last_update = Some(now);
+                Some(std::mem::take(&mut self.pending))
+            }
+        }
+    }
+}
+
+impl Default for BackgroundPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use stagehand::draw::{Draw, DrawBatch, DrawDestination};
+
+pub type BatchComparator = Box<dyn Fn(&DrawBatch<Draw, ()>, &DrawBatch<Draw, ()>) -> Ordering>;
+pub type DrawComparator = Box<dyn Fn(&Draw, &Draw) -> Ordering>;
+
+// Orders draws by a key, lowest drawn first.
+pub fn draw_key<F: Fn(&Draw) -> f32 + 'static>(key: F) -> DrawComparator {
+    Box::new(move |a, b| key(a).total_cmp(&key(b)))
+}
+
+// Depth sorting for isometric and top down scenes: draws whose bottom edge is lower on
+// screen go on top. Only Rect destinations carry a height, Location ones sort by the `y`
+// they were placed at, so use `by_bottom_edge_with` for sprites placed by location.
+pub fn by_bottom_edge() -> DrawComparator {
+    by_bottom_edge_with(|_| 0.0)
+}
+
+// `height` gives the drawn height of a Location draw, usually its texture's.
+pub fn by_bottom_edge_with<F: Fn(&Draw) -> f32 + 'static>(height: F) -> DrawComparator {
+    draw_key(move |draw| match &draw.data.destination {
+        Some(DrawDestination::Rect(r)) => r.y + r.height,
+        Some(DrawDestination::Location { y, .. }) => *y + height(draw),
+        None => 0.0,
+    })
+}
+
+// Sorting is stable everywhere: batches or draws that compare equal keep the order the
+// stage returned them in, after any order set with `set_order`. Everything here is keyed
+// by the index a batch has in the list the stage returns, not by the batch itself. That
+// index only stays put while the active scenes and the batches they draw do; after adding,
+// removing or toggling scenes, set overrides again.
+pub struct BatchControl {
+    hidden: HashSet<usize>,
+    order: Option<Vec<usize>>,
+    batch_sort: Option<BatchComparator>,
+    draw_sort: Option<DrawComparator>,
+    batch_draw_sort: HashMap<usize, DrawComparator>,
+}
+
+impl BatchControl {
+    pub fn new() -> Self {
+        BatchControl {
+            hidden: HashSet::new(),
+            order: None,
+            batch_sort: None,
+            draw_sort: None,
+            batch_draw_sort: HashMap::new(),
+        }
+    }
+
+    // Batches are keyed by the index the stage returns them at each frame.
+    pub fn set_enabled(&mut self, batch_index: usize, enabled: bool) {
+        match enabled {
+            true => self.hidden.remove(&batch_index),
+            false => self.hidden.insert(batch_index),
+        };
+    }
+
+    pub fn toggle(&mut self, batch_index: usize) {
+        let enabled = self.is_enabled(batch_index);
+        self.set_enabled(batch_index, !enabled);
+    }
+
+    pub fn is_enabled(&self, batch_index: usize) -> bool {
+        !self.hidden.contains(&batch_index)
+    }
+
+    // Batches missing from the order are drawn afterwards in their original order. An
+    // index given more than once is only drawn at its first place.
+    pub fn set_order(&mut self, order: Option<Vec<usize>>) {
+        self.order = order.map(|order| {
+            let mut seen = HashSet::new();
+            order.into_iter().filter(|i| seen.insert(*i)).collect()
+        });
+    }
+
+    pub fn set_batch_sort(&mut self, comparator: Option<BatchComparator>) {
+        self.batch_sort = comparator;
+    }
+
+    // Sorts the draws of every batch without a comparator of its own.
+    pub fn set_draw_sort(&mut self, comparator: Option<DrawComparator>) {
+        self.draw_sort = comparator;
+    }
+
+    pub fn set_batch_draw_sort(&mut self, batch_index: usize, comparator: Option<DrawComparator>) {
+        match comparator {
+            Some(c) => self.batch_draw_sort.insert(batch_index, c),
+            None => self.batch_draw_sort.remove(&batch_index),
+        };
+    }
+
+    pub(crate) fn sort_batches(&self, order: &mut [usize], batches: &[DrawBatch<Draw, ()>]) {
+        if let Some(compare) = &self.batch_sort {
+            order.sort_by(|a, b| compare(&batches[*a], &batches[*b]));
+        }
+    }
+
+    // Fills `indices` with the draw order of the batch, false when it isn't sorted.
+    pub(crate) fn sort_draws(
+        &self,
+        batch_index: usize,
+        draws: &[Draw],
+        indices: &mut Vec<usize>,
+    ) -> bool {
+        let compare = match self
+            .batch_draw_sort
+            .get(&batch_index)
+            .or(self.draw_sort.as_ref())
+        {
+            Some(c) => c,
+            None => return false,
+        };
+
+        indices.clear();
+        indices.extend(0..draws.len());
+        indices.sort_by(|a, b| compare(&draws[*a], &draws[*b]));
+        true
+    }
+
+    pub(crate) fn resolve(&self, count: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = match &self.order {
+            Some(order) => order.iter().copied().filter(|i| *i < count).collect(),
+            None => Vec::with_capacity(count),
+        };
+
+        for i in 0..count {
+            if !indices.contains(&i) {
+                indices.push(i);
+            }
+        }
+
+        indices.retain(|i| self.is_enabled(*i));
+        indices
+    }
+}
+
+impl Default for BatchControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,192 @@
+use std::{fmt::Write as _, fs, path::Path};
+
+use stagehand::app::App;
+
+use crate::{coords::Camera, golden::InputScript, SDLApp};
+
+// Camera keyframes in seconds from the start of the run, blended linearly between keys.
+pub struct CameraPath {
+    keys: Vec<(f64, Camera)>,
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        CameraPath { keys: Vec::new() }
+    }
+
+    pub fn key(mut self, seconds: f64, camera: Camera) -> Self {
+        let at = self.keys.partition_point(|(t, _)| *t <= seconds);
+        self.keys.insert(at, (seconds, camera));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // None when there are no keys, leaving the camera to the game.
+    pub fn at(&self, seconds: f64) -> Option<Camera> {
+        let next = self.keys.partition_point(|(t, _)| *t <= seconds);
+        match (
+            next.checked_sub(1).map(|i| self.keys[i]),
+            self.keys.get(next),
+        ) {
+            (Some((t0, a)), Some((t1, b))) => {
+                let f = ((seconds - t0) / (t1 - t0).max(f64::EPSILON)) as f32;
+                Some(Camera {
+                    x: a.x + (b.x - a.x) * f,
+                    y: a.y + (b.y - a.y) * f,
+                    zoom: a.zoom + (b.zoom - a.zoom) * f,
+                })
+            }
+            (Some((_, a)), None) => Some(a),
+            (None, Some((_, b))) => Some(*b),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BenchmarkResult {
+    // Milliseconds spent on each frame's events, update and draw.
+    pub frame_times: Vec<f64>,
+    pub draws: Vec<usize>,
+}
+
+impl BenchmarkResult {
+    pub fn frames(&self) -> usize {
+        self.frame_times.len()
+    }
+
+    pub fn average(&self) -> f64 {
+        self.frame_times.iter().sum::<f64>() / self.frame_times.len().max(1) as f64
+    }
+
+    // Frame time that `percent` of frames stay at or under, so 99.0 gives the 1% lows.
+    pub fn percentile(&self, percent: f64) -> f64 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        let mut sorted = self.frame_times.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = (percent / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+        sorted[rank.min(sorted.len() - 1)]
+    }
+
+    pub fn average_draws(&self) -> f64 {
+        self.draws.iter().sum::<usize>() as f64 / self.draws.len().max(1) as f64
+    }
+
+    pub fn max_draws(&self) -> usize {
+        self.draws.iter().copied().max().unwrap_or(0)
+    }
+
+    // One row per frame.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "frame,ms,draws\n".to_string();
+        for (i, (ms, draws)) in self.frame_times.iter().zip(&self.draws).enumerate() {
+            let _ = writeln!(csv, "{},{:.3},{}", i, ms, draws);
+        }
+        csv
+    }
+
+    // Summary figures followed by the per-frame numbers.
+    pub fn to_json(&self) -> String {
+        let times: Vec<String> = self
+            .frame_times
+            .iter()
+            .map(|t| format!("{:.3}", t))
+            .collect();
+        let draws: Vec<String> = self.draws.iter().map(|d| d.to_string()).collect();
+        format!(
+            "{{\"frames\":{},\"average_ms\":{:.3},\"p50_ms\":{:.3},\"p95_ms\":{:.3},\"p99_ms\":{:.3},\
+             \"max_ms\":{:.3},\"average_draws\":{:.1},\"max_draws\":{},\
+             \"frame_times\":[{}],\"draws\":[{}]}}",
+            self.frames(),
+            self.average(),
+            self.percentile(50.0),
+            self.percentile(95.0),
+            self.percentile(99.0),
+            self.percentile(100.0),
+            self.average_draws(),
+            self.max_draws(),
+            times.join(","),
+            draws.join(",")
+        )
+    }
+
+    pub fn write_csv(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_csv()).map_err(|e| e.to_string())
+    }
+
+    pub fn write_json(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_json()).map_err(|e| e.to_string())
+    }
+}
+
+impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
+    // Runs the active scenes for `seconds` at a fixed timestep as fast as they go, with
+    // scripted input and camera, so runs on different machines or builds are comparable.
+    // Real input is ignored for the run. The camera only moves world coordinates, so a
+    // camera path needs a unit size on the coordinate space.
+    pub fn run_benchmark(
+        &mut self,
+        seconds: f64,
+        timestep: f64,
+        script: &InputScript,
+        camera: &CameraPath,
+    ) -> Result<BenchmarkResult, String> {
+        if !camera.is_empty() && self.coordinates.borrow().pixels_per_unit.is_none() {
+            return Err(
+                "A benchmark camera path needs pixels_per_unit set on the coordinate space"
+                    .to_string(),
+            );
+        }
+
+        self.scripted.clear();
+        self.scripted_only = true;
+        let result = self.benchmark_frames(seconds, timestep, script, camera);
+        self.scripted_only = false;
+        self.scripted.clear();
+        result
+    }
+
+    fn benchmark_frames(
+        &mut self,
+        seconds: f64,
+        timestep: f64,
+        script: &InputScript,
+        camera: &CameraPath,
+    ) -> Result<BenchmarkResult, String> {
+        let frames = (seconds / timestep).ceil() as u32;
+        let mut result = BenchmarkResult {
+            frame_times: Vec::with_capacity(frames as usize),
+            draws: Vec::with_capacity(frames as usize),
+        };
+
+        for frame in 0..frames {
+            let time = frame as f64 * timestep;
+            self.apply_script(script, frame);
+            if let Some(c) = camera.at(time) {
+                self.coordinates.borrow_mut().camera = c;
+            }
+
+            let start = self.precise_ticks();
+            if !self.processed_events()? {
+                break;
+            }
+            self.update(timestep);
+            self.draw(0.0, time as u64);
+
+            result.frame_times.push(self.precise_ticks() - start);
+            result.draws.push(self.draw_count());
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,175 @@
+use sdl2::controller::Axis;
+
+pub const AXES: [Axis; 6] = [
+    Axis::LeftX,
+    Axis::LeftY,
+    Axis::RightX,
+    Axis::RightY,
+    Axis::TriggerLeft,
+    Axis::TriggerRight,
+];
+
+// Axes that moved less than this while capturing the range keep SDL's default mapping.
+const MIN_SPAN: i32 = 8192;
+
+pub fn axis_index(axis: Axis) -> usize {
+    AXES.iter().position(|a| *a == axis).unwrap_or(0)
+}
+
+// Raw readings of one axis at rest and at both ends of its travel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AxisRange {
+    pub min: i16,
+    pub center: i16,
+    pub max: i16,
+}
+
+impl AxisRange {
+    // Maps a raw reading to -1.0..=1.0 with the measured center at zero, scaling each side
+    // separately so a pad that can't reach full deflection still reaches 1.0.
+    pub fn apply(&self, raw: i16) -> f32 {
+        let (raw, center) = (raw as f32, self.center as f32);
+        let value = match raw >= center {
+            true => (raw - center) / (self.max as f32 - center).max(1.0),
+            false => (raw - center) / (center - self.min as f32).max(1.0),
+        };
+        value.clamp(-1.0, 1.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationPhase {
+    // The player leaves the sticks and triggers alone.
+    Center,
+    // The player rolls the sticks around their edges and presses both triggers fully.
+    Range,
+}
+
+struct Capture {
+    instance_id: u32,
+    center_ms: u64,
+    range_ms: u64,
+    started: Option<u64>,
+    sums: [i64; 6],
+    samples: i64,
+    min: [i16; 6],
+    max: [i16; 6],
+}
+
+// Guided capture of a calibration profile for one controller, sampled on every input
+// poll. Show `phase` to the player; the finished profile is stored in the controller
+// registry under the controller's GUID.
+pub struct AxisCalibrator {
+    capture: Option<Capture>,
+    now: u64,
+}
+
+impl AxisCalibrator {
+    pub fn new() -> Self {
+        AxisCalibrator {
+            capture: None,
+            now: 0,
+        }
+    }
+
+    pub fn start(&mut self, instance_id: u32, center_ms: u64, range_ms: u64) {
+        self.capture = Some(Capture {
+            instance_id,
+            center_ms,
+            range_ms,
+            started: None,
+            sums: [0; 6],
+            samples: 0,
+            min: [i16::MAX; 6],
+            max: [i16::MIN; 6],
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        self.capture = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    pub fn phase(&self) -> Option<CalibrationPhase> {
+        let capture = self.capture.as_ref()?;
+        match self.elapsed(capture) < capture.center_ms {
+            true => Some(CalibrationPhase::Center),
+            false => Some(CalibrationPhase::Range),
+        }
+    }
+
+    // Share of the whole capture done, from 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        match &self.capture {
+            Some(c) => {
+                let total = (c.center_ms + c.range_ms).max(1);
+                (self.elapsed(c) as f32 / total as f32).min(1.0)
+            }
+            None => 0.0,
+        }
+    }
+
+    pub(crate) fn target(&self) -> Option<u32> {
+        self.capture.as_ref().map(|c| c.instance_id)
+    }
+
+    // Returns the profile once the capture is over, with None for axes that never moved.
+    pub(crate) fn sample(&mut self, raw: [i16; 6], now: u64) -> Option<[Option<AxisRange>; 6]> {
+        self.now = now;
+        let phase = self.phase()?;
+        let capture = self.capture.as_mut()?;
+        capture.started.get_or_insert(now);
+
+        match phase {
+            CalibrationPhase::Center => {
+                for (sum, value) in capture.sums.iter_mut().zip(raw) {
+                    *sum += value as i64;
+                }
+                capture.samples += 1;
+            }
+            CalibrationPhase::Range => {
+                for i in 0..raw.len() {
+                    capture.min[i] = capture.min[i].min(raw[i]);
+                    capture.max[i] = capture.max[i].max(raw[i]);
+                }
+            }
+        }
+
+        let elapsed = now.saturating_sub(capture.started.unwrap_or(now));
+        if elapsed < capture.center_ms + capture.range_ms {
+            return None;
+        }
+
+        let capture = self.capture.take()?;
+        let mut profile = [None; 6];
+        for (i, range) in profile.iter_mut().enumerate() {
+            let (min, max) = (capture.min[i], capture.max[i]);
+            if capture.samples == 0 || (max as i32 - min as i32) < MIN_SPAN {
+                continue;
+            }
+            let center = (capture.sums[i] / capture.samples) as i16;
+            *range = Some(AxisRange {
+                min,
+                center: center.clamp(min, max),
+                max,
+            });
+        }
+        Some(profile)
+    }
+
+    fn elapsed(&self, capture: &Capture) -> u64 {
+        match capture.started {
+            Some(s) => self.now.saturating_sub(s),
+            None => 0,
+        }
+    }
+}
+
+impl Default for AxisCalibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use sdl2::pixels::Color;
+use stagehand::loading::Ticket;
+
+use crate::overlay::Anchor;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Caption {
+    // Seconds into the music track.
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CaptionTrack {
+    captions: Vec<Caption>,
+}
+
+impl CaptionTrack {
+    pub fn new() -> Self {
+        CaptionTrack {
+            captions: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, start: f64, end: f64, text: &str) -> &mut Self {
+        let caption = Caption {
+            start,
+            end: end.max(start),
+            text: text.to_string(),
+        };
+        let index = self.captions.partition_point(|c| c.start <= start);
+        self.captions.insert(index, caption);
+        self
+    }
+
+    // Latest caption that started at or before `position` and hasn't ended yet.
+    pub fn at(&self, position: f64) -> Option<&Caption> {
+        let started = self.captions.partition_point(|c| c.start <= position);
+        self.captions[..started]
+            .iter()
+            .rev()
+            .find(|c| position < c.end)
+    }
+
+    pub fn captions(&self) -> &[Caption] {
+        &self.captions
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct CaptionStyle {
+    pub font: Ticket,
+    pub color: Color,
+    pub background: Color,
+    pub anchor: Anchor,
+    pub offset: (i32, i32),
+    pub padding: u32,
+}
+
+impl CaptionStyle {
+    pub fn new(font: Ticket) -> Self {
+        CaptionStyle {
+            font,
+            color: Color::RGB(255, 255, 255),
+            background: Color::RGBA(0, 0, 0, 180),
+            anchor: Anchor::BottomLeft,
+            offset: (16, 24),
+            padding: 6,
+        }
+    }
+}
+
+pub struct Captions {
+    pub enabled: bool,
+    // Drawn by the backend when set, otherwise scenes read `active` and draw it themselves.
+    pub style: Option<CaptionStyle>,
+    tracks: HashMap<Ticket, CaptionTrack>,
+    active: Option<Caption>,
+    changed: bool,
+}
+
+impl Captions {
+    pub fn new() -> Self {
+        Captions {
+            enabled: true,
+            style: None,
+            tracks: HashMap::new(),
+            active: None,
+            changed: false,
+        }
+    }
+
+    pub fn register(&mut self, music: Ticket, track: CaptionTrack) {
+        self.tracks.insert(music, track);
+    }
+
+    pub fn unregister(&mut self, music: Ticket) -> Option<CaptionTrack> {
+        self.tracks.remove(&music)
+    }
+
+    pub fn active(&self) -> Option<&Caption> {
+        match self.enabled {
+            true => self.active.as_ref(),
+            false => None,
+        }
+    }
+
+    // True on the update the active caption appeared, changed or cleared.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    pub(crate) fn update(&mut self, music: Option<Ticket>, position: Option<f64>) {
+        let next = match (music, position) {
+            (Some(m), Some(p)) => self.tracks.get(&m).and_then(|t| t.at(p)).cloned(),
+            _ => None,
+        };
+
+        self.changed = next != self.active;
+        self.active = next;
+    }
+}
+
+impl Default for Captions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
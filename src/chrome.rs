@@ -0,0 +1,146 @@
+use std::{cell::RefCell, os::raw::c_void};
+
+use sdl2::{
+    rect::{Point, Rect},
+    sys::{SDL_GetWindowSize, SDL_HitTestResult, SDL_Point, SDL_Window},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeEdge {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChromeHit {
+    Normal,
+    Draggable,
+    Resize(ResizeEdge),
+}
+
+// Callbacks get the point and window size in window pixels, and return None to fall back
+// to the regions.
+pub type ChromeCallback = Box<dyn Fn(Point, (u32, u32)) -> Option<ChromeHit>>;
+
+// Custom title bars and resize edges for borderless windows. SDL asks on every press,
+// before the press becomes an event, so a drag or resize started here never reaches input
+// bindings. Regions are in window pixels and stay until changed.
+pub struct WindowChrome {
+    // Width of the resize band along each edge, 0 to leave resizing to the platform.
+    pub resize_border: u32,
+    drag: Vec<Rect>,
+    exclude: Vec<Rect>,
+    callback: Option<ChromeCallback>,
+}
+
+impl WindowChrome {
+    pub fn new() -> Self {
+        WindowChrome {
+            resize_border: 0,
+            drag: Vec::new(),
+            exclude: Vec::new(),
+            callback: None,
+        }
+    }
+
+    pub fn add_drag_region(&mut self, rect: Rect) {
+        self.drag.push(rect);
+    }
+
+    // Spots inside drag regions that stay clickable, like a close button on the title bar.
+    pub fn add_exclusion(&mut self, rect: Rect) {
+        self.exclude.push(rect);
+    }
+
+    pub fn clear_regions(&mut self) {
+        self.drag.clear();
+        self.exclude.clear();
+    }
+
+    pub fn set_callback(&mut self, callback: Option<ChromeCallback>) {
+        self.callback = callback;
+    }
+
+    pub fn hit(&self, point: Point, size: (u32, u32)) -> ChromeHit {
+        if let Some(hit) = self.callback.as_ref().and_then(|c| c(point, size)) {
+            return hit;
+        }
+        if let Some(edge) = self.edge(point, size) {
+            return ChromeHit::Resize(edge);
+        }
+        if self.exclude.iter().any(|r| r.contains_point(point)) {
+            return ChromeHit::Normal;
+        }
+        match self.drag.iter().any(|r| r.contains_point(point)) {
+            true => ChromeHit::Draggable,
+            false => ChromeHit::Normal,
+        }
+    }
+
+    fn edge(&self, point: Point, (w, h): (u32, u32)) -> Option<ResizeEdge> {
+        let border = self.resize_border as i32;
+        if border == 0 {
+            return None;
+        }
+
+        let left = point.x() < border;
+        let right = point.x() >= w as i32 - border;
+        let top = point.y() < border;
+        let bottom = point.y() >= h as i32 - border;
+        match (left, right, top, bottom) {
+            (true, _, true, _) => Some(ResizeEdge::TopLeft),
+            (_, true, true, _) => Some(ResizeEdge::TopRight),
+            (true, _, _, true) => Some(ResizeEdge::BottomLeft),
+            (_, true, _, true) => Some(ResizeEdge::BottomRight),
+            (true, ..) => Some(ResizeEdge::Left),
+            (_, true, ..) => Some(ResizeEdge::Right),
+            (_, _, true, _) => Some(ResizeEdge::Top),
+            (.., true) => Some(ResizeEdge::Bottom),
+            _ => None,
+        }
+    }
+}
+
+impl Default for WindowChrome {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// `data` points at the app's RefCell<WindowChrome>, which outlives the window.
+pub(crate) unsafe extern "C" fn hit_test_callback(
+    window: *mut SDL_Window,
+    area: *const SDL_Point,
+    data: *mut c_void,
+) -> SDL_HitTestResult {
+    let chrome = &*(data as *const RefCell<WindowChrome>);
+    let chrome = match chrome.try_borrow() {
+        Ok(c) => c,
+        Err(_) => return SDL_HitTestResult::SDL_HITTEST_NORMAL,
+    };
+
+    let (mut w, mut h) = (0, 0);
+    SDL_GetWindowSize(window, &mut w, &mut h);
+    let point = Point::new((*area).x, (*area).y);
+
+    match chrome.hit(point, (w.max(0) as u32, h.max(0) as u32)) {
+        ChromeHit::Normal => SDL_HitTestResult::SDL_HITTEST_NORMAL,
+        ChromeHit::Draggable => SDL_HitTestResult::SDL_HITTEST_DRAGGABLE,
+        ChromeHit::Resize(edge) => match edge {
+            ResizeEdge::TopLeft => SDL_HitTestResult::SDL_HITTEST_RESIZE_TOPLEFT,
+            ResizeEdge::Top => SDL_HitTestResult::SDL_HITTEST_RESIZE_TOP,
+            ResizeEdge::TopRight => SDL_HitTestResult::SDL_HITTEST_RESIZE_TOPRIGHT,
+            ResizeEdge::Right => SDL_HitTestResult::SDL_HITTEST_RESIZE_RIGHT,
+            ResizeEdge::BottomRight => SDL_HitTestResult::SDL_HITTEST_RESIZE_BOTTOMRIGHT,
+            ResizeEdge::Bottom => SDL_HitTestResult::SDL_HITTEST_RESIZE_BOTTOM,
+            ResizeEdge::BottomLeft => SDL_HitTestResult::SDL_HITTEST_RESIZE_BOTTOMLEFT,
+            ResizeEdge::Left => SDL_HitTestResult::SDL_HITTEST_RESIZE_LEFT,
+        },
+    }
+}
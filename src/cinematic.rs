@@ -0,0 +1,92 @@
+use sdl2::pixels::Color;
+
+use crate::tween::Easing;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BarSize {
+    // Each bar covers this share of the screen height.
+    Fraction(f32),
+    // Bars leave a view of this aspect ratio, 2.39 for the usual widescreen look.
+    Aspect(f32),
+}
+
+// Black bars that slide in over the top and bottom of the screen for cutscenes. Drawn
+// above every batch and below captions and overlays.
+pub struct CinematicBars {
+    pub color: Color,
+    pub easing: Easing,
+    size: BarSize,
+    from: f32,
+    to: f32,
+    start: u64,
+    duration: u64,
+    now: u64,
+}
+
+impl CinematicBars {
+    pub fn new() -> Self {
+        CinematicBars {
+            color: Color::RGB(0, 0, 0),
+            easing: Easing::QuadInOut,
+            size: BarSize::Fraction(0.12),
+            from: 0.0,
+            to: 0.0,
+            start: 0,
+            duration: 0,
+            now: 0,
+        }
+    }
+
+    // Slides the bars in over `over_ms`. A new size applies at once to bars already in.
+    pub fn show(&mut self, size: BarSize, over_ms: u64) {
+        self.from = self.extent();
+        self.size = size;
+        self.to = 1.0;
+        self.start = self.now;
+        self.duration = over_ms;
+    }
+
+    pub fn hide(&mut self, over_ms: u64) {
+        self.from = self.extent();
+        self.to = 0.0;
+        self.start = self.now;
+        self.duration = over_ms;
+    }
+
+    // True while the bars cover any of the screen, including while they slide.
+    pub fn is_showing(&self) -> bool {
+        self.extent() > 0.0
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.now < self.start + self.duration
+    }
+
+    // How far the bars are in, from 0 when hidden to 1 at full size.
+    pub fn extent(&self) -> f32 {
+        if self.duration == 0 {
+            return self.to;
+        }
+        let t = self.now.saturating_sub(self.start) as f32 / self.duration as f32;
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+
+    pub(crate) fn advance(&mut self, now: u64) {
+        self.now = now;
+    }
+
+    // Pixel height of each bar on an output of `size`.
+    pub(crate) fn bar_height(&self, (w, h): (u32, u32)) -> u32 {
+        let full = match self.size {
+            BarSize::Fraction(f) => h as f32 * f.clamp(0.0, 0.5),
+            BarSize::Aspect(ratio) => ((h as f32 - w as f32 / ratio.max(0.01)) / 2.0).max(0.0),
+        };
+        (full * self.extent()).round() as u32
+    }
+}
+
+impl Default for CinematicBars {
+    fn default() -> Self {
+        Self::new()
+    }
+}
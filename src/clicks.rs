@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use sdl2::mouse::MouseButton;
+
+struct Press {
+    position: (f32, f32),
+    this_frame: bool,
+    window: (i32, i32),
+    time: u64,
+    count: u32,
+}
+
+// Where the mouse was when each button went down, taken from the button event itself
+// rather than the position at the end of the frame, so a quick flick while clicking
+// still lands on the widget under the press. Positions are in the same space as hit
+// regions: logical canvas coordinates, or world units when those are enabled. Presses of
+// the same button close together in time and space count up as a double or triple click,
+// for binding with `SDLCommand::MultiClick`.
+pub struct Clicks {
+    // Most milliseconds between presses that still continue a multi-click.
+    pub interval: u64,
+    // Most window pixels the mouse may move between presses of a multi-click.
+    pub distance: i32,
+    presses: HashMap<MouseButton, Press>,
+    actions: HashMap<(usize, usize), (f32, f32)>,
+}
+
+impl Clicks {
+    pub fn new() -> Self {
+        Clicks {
+            interval: 400,
+            distance: 4,
+            presses: HashMap::new(),
+            actions: HashMap::new(),
+        }
+    }
+
+    pub fn press_position(&self, button: MouseButton) -> Option<(f32, f32)> {
+        self.presses.get(&button).map(|p| p.position)
+    }
+
+    pub fn pressed_this_frame(&self, button: MouseButton) -> bool {
+        self.presses.get(&button).map_or(false, |p| p.this_frame)
+    }
+
+    // 1 for a single click, 2 for a double click and so on, counting the latest press.
+    pub fn click_count(&self, button: MouseButton) -> u32 {
+        self.presses.get(&button).map_or(0, |p| p.count)
+    }
+
+    // Press position for an action held down through a mouse button binding.
+    pub fn action_position(&self, user_index: usize, action_index: usize) -> Option<(f32, f32)> {
+        self.actions.get(&(user_index, action_index)).copied()
+    }
+
+    pub(crate) fn begin(&mut self) {
+        for press in self.presses.values_mut() {
+            press.this_frame = false;
+        }
+    }
+
+    pub(crate) fn press(
+        &mut self,
+        button: MouseButton,
+        position: (f32, f32),
+        window: (i32, i32),
+        now: u64,
+    ) {
+        let count = match self.presses.get(&button) {
+            Some(p)
+                if now.saturating_sub(p.time) <= self.interval
+                    && (window.0 - p.window.0).abs() <= self.distance
+                    && (window.1 - p.window.1).abs() <= self.distance =>
+            {
+                p.count + 1
+            }
+            _ => 1,
+        };
+
+        self.presses.insert(
+            button,
+            Press {
+                position,
+                this_frame: true,
+                window,
+                time: now,
+                count,
+            },
+        );
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        button: Option<MouseButton>,
+    ) {
+        match button.and_then(|b| self.press_position(b)) {
+            Some(p) => self.actions.insert((user_index, action_index), p),
+            None => self.actions.remove(&(user_index, action_index)),
+        };
+    }
+}
+
+impl Default for Clicks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
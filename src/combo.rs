@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+type ActionKey = (usize, usize);
+
+// One input in a combo: every action in `actions` held and none of `without`, reached on
+// the poll the step is checked. Holding a step doesn't repeat it, it has to be let go of
+// or changed first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ComboStep {
+    pub actions: Vec<usize>,
+    pub without: Vec<usize>,
+}
+
+impl ComboStep {
+    pub fn press(actions: &[usize]) -> Self {
+        ComboStep {
+            actions: actions.to_vec(),
+            without: Vec::new(),
+        }
+    }
+
+    // Down then Forward only counts once Down is released, so a quarter circle doesn't
+    // match with Down still held.
+    pub fn without(mut self, actions: &[usize]) -> Self {
+        self.without.extend_from_slice(actions);
+        self
+    }
+
+    fn is_met(&self, user_index: usize, held: &HashSet<ActionKey>) -> bool {
+        !self.actions.is_empty()
+            && self
+                .actions
+                .iter()
+                .all(|a| held.contains(&(user_index, *a)))
+            && !self
+                .without
+                .iter()
+                .any(|a| held.contains(&(user_index, *a)))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Combo {
+    pub user_index: usize,
+    pub steps: Vec<ComboStep>,
+    // Milliseconds allowed from one step to the next.
+    pub max_gap: u64,
+    // Milliseconds allowed from the first step to the last, None for no limit.
+    pub max_total: Option<u64>,
+}
+
+impl Combo {
+    pub fn new(user_index: usize, steps: Vec<ComboStep>) -> Self {
+        Combo {
+            user_index,
+            steps,
+            max_gap: 300,
+            max_total: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComboTrigger {
+    pub name: String,
+    pub user_index: usize,
+    // Ticks of the poll that completed the combo.
+    pub at: u64,
+    // Milliseconds from the first step to the last.
+    pub duration: u64,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Progress {
+    next: usize,
+    started: u64,
+    last: u64,
+}
+
+// Fighting game style input sequences over actions, checked by the backend on every input
+// poll with its timing. Triggers stay until the next update has seen them.
+pub struct Combos {
+    combos: HashMap<String, Combo>,
+    progress: HashMap<String, Progress>,
+    held: HashSet<ActionKey>,
+    previous: HashSet<ActionKey>,
+    triggered: Vec<ComboTrigger>,
+    consumed: bool,
+}
+
+impl Combos {
+    pub fn new() -> Self {
+        Combos {
+            combos: HashMap::new(),
+            progress: HashMap::new(),
+            held: HashSet::new(),
+            previous: HashSet::new(),
+            triggered: Vec::new(),
+            consumed: true,
+        }
+    }
+
+    pub fn define(&mut self, name: &str, combo: Combo) {
+        self.combos.insert(name.to_string(), combo);
+        self.progress.remove(name);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.combos.remove(name);
+        self.progress.remove(name);
+    }
+
+    pub fn clear(&mut self) {
+        self.combos.clear();
+        self.progress.clear();
+    }
+
+    // Longest combos first when several finish on the same poll.
+    pub fn triggered(&self) -> &[ComboTrigger] {
+        &self.triggered
+    }
+
+    pub fn was_triggered(&self, name: &str) -> bool {
+        self.triggered.iter().any(|t| t.name == name)
+    }
+
+    // Steps of the combo matched so far, for showing a player how far they got.
+    pub fn progress(&self, name: &str) -> usize {
+        self.progress.get(name).map_or(0, |p| p.next)
+    }
+
+    pub(crate) fn begin(&mut self) {
+        if self.consumed {
+            self.triggered.clear();
+            self.consumed = false;
+        }
+        std::mem::swap(&mut self.held, &mut self.previous);
+        self.held.clear();
+    }
+
+    pub(crate) fn record(&mut self, user_index: usize, action_index: usize, down: bool) {
+        if down && !self.combos.is_empty() {
+            self.held.insert((user_index, action_index));
+        }
+    }
+
+    pub(crate) fn evaluate(&mut self, now: u64) {
+        let mut finished = Vec::new();
+        for (name, combo) in self.combos.iter() {
+            let progress = self.progress.entry(name.clone()).or_default();
+            let expired = progress.next > 0
+                && (now.saturating_sub(progress.last) > combo.max_gap
+                    || combo
+                        .max_total
+                        .is_some_and(|t| now.saturating_sub(progress.started) > t));
+            if expired {
+                *progress = Progress::default();
+            }
+
+            let reached = |step: &ComboStep| {
+                step.is_met(combo.user_index, &self.held)
+                    && !step.is_met(combo.user_index, &self.previous)
+            };
+            let step = match combo.steps.get(progress.next) {
+                Some(s) => s,
+                None => continue,
+            };
+            if reached(step) {
+                if progress.next == 0 {
+                    progress.started = now;
+                }
+                progress.next += 1;
+                progress.last = now;
+            } else if progress.next > 0 && combo.steps.first().is_some_and(reached) {
+                // A fresh start partway through a failed attempt.
+                *progress = Progress {
+                    next: 1,
+                    started: now,
+                    last: now,
+                };
+            }
+
+            if progress.next == combo.steps.len() {
+                finished.push(ComboTrigger {
+                    name: name.clone(),
+                    user_index: combo.user_index,
+                    at: now,
+                    duration: now - progress.started,
+                });
+                *progress = Progress::default();
+            }
+        }
+
+        finished.sort_by_key(|t| std::cmp::Reverse(self.combos[&t.name].steps.len()));
+        self.triggered.extend(finished);
+    }
+
+    pub(crate) fn consume(&mut self) {
+        self.consumed = true;
+    }
+}
+
+impl Default for Combos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,498 @@
+use std::{fs, path::Path};
+
+// SDL's renderers take no block compressed formats, so DDS and KTX containers are decoded
+// to RGBA here and uploaded like any other image. Only the top mip level is read.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockFormat {
+    Bc1,
+    Bc2,
+    Bc3,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    // Tightly packed RGBA32.
+    pub pixels: Vec<u8>,
+}
+
+pub fn is_container(path: &str) -> bool {
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some(e) => e.eq_ignore_ascii_case("dds") || e.eq_ignore_ascii_case("ktx"),
+        None => false,
+    }
+}
+
+pub fn decode_file(path: &str) -> Result<DecodedImage, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    decode(&bytes)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<DecodedImage, String> {
+    if bytes.starts_with(b"DDS ") {
+        decode_dds(bytes)
+    } else if bytes.starts_with(&KTX_IDENTIFIER) {
+        decode_ktx(bytes)
+    } else {
+        Err("Not a DDS or KTX container".to_string())
+    }
+}
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+
+pub fn decode_dds(bytes: &[u8]) -> Result<DecodedImage, String> {
+    let le = |offset: usize| read_u32(bytes, offset, false);
+
+    let height = le(12)?;
+    let width = le(16)?;
+    let flags = le(80)?;
+    let four_cc = bytes.get(84..88).ok_or("Truncated DDS header")?;
+
+    if flags & DDPF_FOURCC != 0 {
+        let (format, offset) = match four_cc {
+            b"DXT1" => (BlockFormat::Bc1, 128),
+            b"DXT2" | b"DXT3" => (BlockFormat::Bc2, 128),
+            b"DXT4" | b"DXT5" => (BlockFormat::Bc3, 128),
+            b"DX10" => match le(128)? {
+                71 | 72 => (BlockFormat::Bc1, 148),
+                74 | 75 => (BlockFormat::Bc2, 148),
+                77 | 78 => (BlockFormat::Bc3, 148),
+                28 | 29 => return decode_rgba(bytes, 148, width, height, [0, 1, 2, 3]),
+                87 | 91 => return decode_rgba(bytes, 148, width, height, [2, 1, 0, 3]),
+                f => return Err(format!("Unsupported DXGI format {}", f)),
+            },
+            other => {
+                return Err(format!(
+                    "Unsupported DDS format {}",
+                    String::from_utf8_lossy(other)
+                ))
+            }
+        };
+        let mut image = decode_blocks(&bytes[offset.min(bytes.len())..], width, height, format)?;
+        if matches!(four_cc, b"DXT2" | b"DXT4") {
+            unpremultiply(&mut image.pixels);
+        }
+        return Ok(image);
+    }
+
+    if flags & DDPF_RGB == 0 {
+        return Err("DDS has neither RGB nor block compressed data".to_string());
+    }
+
+    let bits = le(88)?;
+    let masks = [
+        le(92)?,
+        le(96)?,
+        le(100)?,
+        if flags & DDPF_ALPHAPIXELS != 0 {
+            le(104)?
+        } else {
+            0
+        },
+    ];
+    decode_masked(&bytes[128.min(bytes.len())..], width, height, bits, masks)
+}
+
+const KTX_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x31, 0x31, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+const GL_UNSIGNED_BYTE: u32 = 0x1401;
+const GL_RGB: u32 = 0x1907;
+const GL_RGBA: u32 = 0x1908;
+const GL_BGRA: u32 = 0x80E1;
+const GL_RGB_S3TC_DXT1: u32 = 0x83F0;
+const GL_RGBA_S3TC_DXT1: u32 = 0x83F1;
+const GL_RGBA_S3TC_DXT3: u32 = 0x83F2;
+const GL_RGBA_S3TC_DXT5: u32 = 0x83F3;
+
+pub fn decode_ktx(bytes: &[u8]) -> Result<DecodedImage, String> {
+    let big_endian = match read_u32(bytes, 12, false)? {
+        0x0403_0201 => false,
+        0x0102_0304 => true,
+        _ => return Err("Bad KTX endianness marker".to_string()),
+    };
+    let field = |offset: usize| read_u32(bytes, offset, big_endian);
+
+    let gl_type = field(16)?;
+    let gl_format = field(24)?;
+    let internal_format = field(28)?;
+    let width = field(36)?;
+    let height = field(40)?.max(1);
+    let key_values = field(60)? as usize;
+
+    let offset = 64usize
+        .checked_add(key_values)
+        .ok_or("Truncated KTX header")?;
+    let size = field(offset)? as usize;
+    let end = offset
+        .checked_add(4)
+        .and_then(|o| o.checked_add(size))
+        .ok_or("Truncated KTX image data")?;
+    let data = bytes
+        .get(offset + 4..end)
+        .ok_or("Truncated KTX image data")?;
+
+    match internal_format {
+        GL_RGB_S3TC_DXT1 | GL_RGBA_S3TC_DXT1 => {
+            return decode_blocks(data, width, height, BlockFormat::Bc1)
+        }
+        GL_RGBA_S3TC_DXT3 => return decode_blocks(data, width, height, BlockFormat::Bc2),
+        GL_RGBA_S3TC_DXT5 => return decode_blocks(data, width, height, BlockFormat::Bc3),
+        _ => (),
+    }
+
+    if gl_type != GL_UNSIGNED_BYTE {
+        return Err(format!("Unsupported KTX format {:#x}", internal_format));
+    }
+
+    let (channels, order) = match gl_format {
+        GL_RGBA => (4, [0, 1, 2, 3]),
+        GL_BGRA => (4, [2, 1, 0, 3]),
+        GL_RGB => (3, [0, 1, 2, 3]),
+        f => return Err(format!("Unsupported KTX pixel format {:#x}", f)),
+    };
+
+    // Rows are padded to four bytes.
+    let count = image_bytes(width, height, 4)?;
+    let pitch = (width as usize)
+        .checked_mul(channels)
+        .and_then(|p| p.checked_add(3))
+        .ok_or("KTX image is too large")?
+        & !3;
+    if pitch
+        .checked_mul(height as usize)
+        .map_or(true, |n| data.len() < n)
+    {
+        return Err("Truncated KTX image data".to_string());
+    }
+    let mut pixels = Vec::with_capacity(count);
+    for y in 0..height as usize {
+        let row = data
+            .get(y * pitch..y * pitch + width as usize * channels)
+            .ok_or("Truncated KTX image data")?;
+        for texel in row.chunks_exact(channels) {
+            let alpha = match channels {
+                4 => texel[order[3]],
+                _ => 255,
+            };
+            pixels.extend_from_slice(&[texel[order[0]], texel[order[1]], texel[order[2]], alpha]);
+        }
+    }
+
+    Ok(DecodedImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+pub fn decode_blocks(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    format: BlockFormat,
+) -> Result<DecodedImage, String> {
+    let block_size = match format {
+        BlockFormat::Bc1 => 8,
+        BlockFormat::Bc2 | BlockFormat::Bc3 => 16,
+    };
+    let count = image_bytes(width, height, 4)?;
+    let (blocks_x, blocks_y) = ((width as usize).div_ceil(4), (height as usize).div_ceil(4));
+    let needed = blocks_x
+        .checked_mul(blocks_y)
+        .and_then(|b| b.checked_mul(block_size))
+        .ok_or("Block compressed image is too large")?;
+    if data.len() < needed {
+        return Err("Truncated block compressed data".to_string());
+    }
+
+    let (w, h) = (width as usize, height as usize);
+    let mut pixels = vec![0u8; count];
+
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let start = (by * blocks_x + bx) * block_size;
+            let block = &data[start..start + block_size];
+
+            let texels = match format {
+                BlockFormat::Bc1 => decode_color_block(block, true),
+                BlockFormat::Bc2 => {
+                    let mut texels = decode_color_block(&block[8..], false);
+                    let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+                    for (i, texel) in texels.iter_mut().enumerate() {
+                        texel[3] = ((alpha >> (i * 4)) & 0xF) as u8 * 17;
+                    }
+                    texels
+                }
+                BlockFormat::Bc3 => {
+                    let mut texels = decode_color_block(&block[8..], false);
+                    let alphas = alpha_palette(block[0], block[1]);
+                    let mut bits = [0u8; 8];
+                    bits[..6].copy_from_slice(&block[2..8]);
+                    let indices = u64::from_le_bytes(bits);
+                    for (i, texel) in texels.iter_mut().enumerate() {
+                        texel[3] = alphas[((indices >> (i * 3)) & 0x7) as usize];
+                    }
+                    texels
+                }
+            };
+
+            for (i, texel) in texels.iter().enumerate() {
+                let (x, y) = (bx * 4 + i % 4, by * 4 + i / 4);
+                if x < w && y < h {
+                    let offset = (y * w + x) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(texel);
+                }
+            }
+        }
+    }
+
+    Ok(DecodedImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn decode_color_block(block: &[u8], allow_transparent: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u16, wb: u16, total: u16| -> [u8; 4] {
+        let channel = |i: usize| ((a[i] as u16 * wa + b[i] as u16 * wb) / total) as u8;
+        [channel(0), channel(1), channel(2), 255]
+    };
+
+    let palette = match c0 > c1 || !allow_transparent {
+        true => [a, b, mix(2, 1, 3), mix(1, 2, 3)],
+        false => [a, b, mix(1, 1, 2), [0, 0, 0, 0]],
+    };
+
+    let mut texels = [[0u8; 4]; 16];
+    for (i, texel) in texels.iter_mut().enumerate() {
+        *texel = palette[((indices >> (i * 2)) & 0x3) as usize];
+    }
+    texels
+}
+
+fn alpha_palette(a0: u8, a1: u8) -> [u8; 8] {
+    let (a, b) = (a0 as u16, a1 as u16);
+    let mut palette = [a0, a1, 0, 0, 0, 0, 0, 255];
+    match a0 > a1 {
+        true => {
+            for i in 1..7u16 {
+                palette[i as usize + 1] = ((a * (7 - i) + b * i) / 7) as u8;
+            }
+        }
+        false => {
+            for i in 1..5u16 {
+                palette[i as usize + 1] = ((a * (5 - i) + b * i) / 5) as u8;
+            }
+        }
+    }
+    palette
+}
+
+fn rgb565(c: u16) -> [u8; 4] {
+    let r = ((c >> 11) & 0x1F) as u8;
+    let g = ((c >> 5) & 0x3F) as u8;
+    let b = (c & 0x1F) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+        255,
+    ]
+}
+
+fn decode_rgba(
+    bytes: &[u8],
+    offset: usize,
+    width: u32,
+    height: u32,
+    order: [usize; 4],
+) -> Result<DecodedImage, String> {
+    let count = image_bytes(width, height, 4)?;
+    let data = offset
+        .checked_add(count)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or("Truncated DDS image data")?;
+
+    let pixels = data
+        .chunks_exact(4)
+        .flat_map(|t| [t[order[0]], t[order[1]], t[order[2]], t[order[3]]])
+        .collect();
+
+    Ok(DecodedImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn decode_masked(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    bits: u32,
+    masks: [u32; 4],
+) -> Result<DecodedImage, String> {
+    let stride = match bits {
+        16 | 24 | 32 => bits as usize / 8,
+        b => return Err(format!("Unsupported DDS bit depth {}", b)),
+    };
+    if masks[..3].iter().all(|m| *m == 0) {
+        return Err("DDS has no color channels".to_string());
+    }
+    let size = image_bytes(width, height, stride)?;
+    if data.len() < size {
+        return Err("Truncated DDS image data".to_string());
+    }
+
+    let extract = |value: u32, mask: u32| -> u8 {
+        if mask == 0 {
+            return 255;
+        }
+        let shifted = ((value & mask) >> mask.trailing_zeros()) as u64;
+        let max = (mask >> mask.trailing_zeros()) as u64;
+        (shifted * 255 / max) as u8
+    };
+
+    let mut pixels = Vec::with_capacity(image_bytes(width, height, 4)?);
+    for texel in data[..size].chunks_exact(stride) {
+        let mut value = [0u8; 4];
+        value[..stride].copy_from_slice(texel);
+        let value = u32::from_le_bytes(value);
+        pixels.extend_from_slice(&[
+            extract(value, masks[0]),
+            extract(value, masks[1]),
+            extract(value, masks[2]),
+            extract(value, masks[3]),
+        ]);
+    }
+
+    Ok(DecodedImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+// Sizes come straight from file headers, so they are checked before anything is allocated.
+fn image_bytes(width: u32, height: u32, bytes_per_pixel: usize) -> Result<usize, String> {
+    if width == 0 || height == 0 {
+        return Err("Image has no pixels".to_string());
+    }
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|n| n.checked_mul(bytes_per_pixel))
+        .ok_or_else(|| format!("Image of {}x{} is too large", width, height))
+}
+
+// DXT2 and DXT4 store color premultiplied by alpha, everything else here is straight.
+fn unpremultiply(pixels: &mut [u8]) {
+    for texel in pixels.chunks_exact_mut(4) {
+        let alpha = texel[3] as u32;
+        if alpha == 0 {
+            continue;
+        }
+        for channel in texel[..3].iter_mut() {
+            *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize, big_endian: bool) -> Result<u32, String> {
+    let raw: [u8; 4] = bytes
+        .get(offset..offset + 4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Truncated header")?;
+    Ok(match big_endian {
+        true => u32::from_be_bytes(raw),
+        false => u32::from_le_bytes(raw),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dds(width: u32, height: u32, flags: u32, four_cc: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 128];
+        bytes[..4].copy_from_slice(b"DDS ");
+        bytes[4..8].copy_from_slice(&124u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[80..84].copy_from_slice(&flags.to_le_bytes());
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    // One BC2 block: alpha 8 of 15 everywhere, color 565 (16, 32, 16) everywhere.
+    const BC2_BLOCK: [u8; 16] = [
+        0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x10, 0x84, 0x10, 0x84, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn bc1_block_crops_to_image_size() {
+        let white = [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0];
+        let image = decode(&dds(2, 3, DDPF_FOURCC, b"DXT1", &white)).unwrap();
+        assert_eq!((image.width, image.height), (2, 3));
+        assert_eq!(image.pixels.len(), 2 * 3 * 4);
+        assert!(image
+            .pixels
+            .chunks_exact(4)
+            .all(|t| t == [255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn dxt3_is_straight_alpha() {
+        let image = decode(&dds(4, 4, DDPF_FOURCC, b"DXT3", &BC2_BLOCK)).unwrap();
+        assert_eq!(&image.pixels[..4], &[132, 130, 132, 136]);
+    }
+
+    #[test]
+    fn dxt2_is_unpremultiplied() {
+        let image = decode(&dds(4, 4, DDPF_FOURCC, b"DXT2", &BC2_BLOCK)).unwrap();
+        assert_eq!(&image.pixels[..4], &[248, 244, 248, 136]);
+    }
+
+    #[test]
+    fn rejects_empty_and_oversized_images() {
+        let block = [0u8; 8];
+        assert!(decode(&dds(0, 4, DDPF_FOURCC, b"DXT1", &block)).is_err());
+        assert!(decode(&dds(4, 0, DDPF_FOURCC, b"DXT1", &block)).is_err());
+        assert!(decode(&dds(u32::MAX, u32::MAX, DDPF_FOURCC, b"DXT1", &block)).is_err());
+    }
+
+    #[test]
+    fn rejects_rgb_without_color_masks() {
+        let mut bytes = dds(1, 1, DDPF_RGB, &[0; 4], &[1, 2, 3, 4]);
+        bytes[88..92].copy_from_slice(&32u32.to_le_bytes());
+        assert!(decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn ktx_rgb_rows_skip_padding() {
+        let mut bytes = vec![0u8; 64];
+        bytes[..12].copy_from_slice(&KTX_IDENTIFIER);
+        bytes[12..16].copy_from_slice(&0x0403_0201u32.to_le_bytes());
+        bytes[16..20].copy_from_slice(&GL_UNSIGNED_BYTE.to_le_bytes());
+        bytes[24..28].copy_from_slice(&GL_RGB.to_le_bytes());
+        bytes[36..40].copy_from_slice(&1u32.to_le_bytes());
+        bytes[40..44].copy_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&8u32.to_le_bytes());
+        bytes.extend_from_slice(&[1, 2, 3, 0, 4, 5, 6, 0]);
+
+        let image = decode(&bytes).unwrap();
+        assert_eq!(image.pixels, vec![1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+pub enum RenderDriver {
+    Default,
+    Software,
+    Accelerated,
+    Named(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleQuality {
+    Nearest,
+    Linear,
+    Best,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SDLHint {
+    ScaleQuality(ScaleQuality),
+    AllowScreensaver(bool),
+    JoystickBackgroundEvents(bool),
+    XInput(bool),
+    // Lets the click that focuses the window also reach it as a mouse event.
+    MouseFocusClickThrough(bool),
+    Custom(String, String),
+}
+
+impl SDLHint {
+    pub fn name(&self) -> &str {
+        match self {
+            SDLHint::ScaleQuality(_) => "SDL_RENDER_SCALE_QUALITY",
+            SDLHint::AllowScreensaver(_) => "SDL_VIDEO_ALLOW_SCREENSAVER",
+            SDLHint::JoystickBackgroundEvents(_) => "SDL_JOYSTICK_ALLOW_BACKGROUND_EVENTS",
+            SDLHint::XInput(_) => "SDL_XINPUT_ENABLED",
+            SDLHint::MouseFocusClickThrough(_) => "SDL_MOUSE_FOCUS_CLICKTHROUGH",
+            SDLHint::Custom(name, _) => name,
+        }
+    }
+
+    pub fn value(&self) -> String {
+        let flag = |b: &bool| if *b { "1" } else { "0" }.to_string();
+        match self {
+            SDLHint::ScaleQuality(ScaleQuality::Nearest) => "0".to_string(),
+            SDLHint::ScaleQuality(ScaleQuality::Linear) => "1".to_string(),
+            SDLHint::ScaleQuality(ScaleQuality::Best) => "2".to_string(),
+            SDLHint::AllowScreensaver(b)
+            | SDLHint::JoystickBackgroundEvents(b)
+            | SDLHint::XInput(b)
+            | SDLHint::MouseFocusClickThrough(b) => flag(b),
+            SDLHint::Custom(_, value) => value.clone(),
+        }
+    }
+
+    pub fn apply(&self) -> bool {
+        let applied = sdl2::hint::set(self.name(), &self.value());
+        if !applied {
+            log::warn!("SDL rejected hint {}={}", self.name(), self.value());
+        }
+        applied
+    }
+}
+
+pub struct SDLConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub driver: RenderDriver,
+    // Applied before any subsystem is created.
+    pub hints: Vec<SDLHint>,
+    // Samples per mixer buffer; smaller is lower latency but more prone to crackling.
+    pub audio_buffer: i32,
+    // Asks the renderer to present in step with the display. Off by default; drivers that
+    // can't do it fall back to `SDLApp::frame_skip` when that is configured.
+    pub vsync: bool,
+}
+
+impl Default for SDLConfig {
+    fn default() -> Self {
+        SDLConfig {
+            title: "Stagehand SDL2 Example".to_string(),
+            width: 800,
+            height: 600,
+            driver: RenderDriver::Default,
+            hints: Vec::new(),
+            audio_buffer: 1024,
+            vsync: false,
+        }
+    }
+}
+
+// Per-user directory for save data and settings, created if missing.
+pub fn save_path(org: &str, app: &str) -> Result<PathBuf, String> {
+    sdl2::filesystem::pref_path(org, app)
+        .map(PathBuf::from)
+        .map_err(|e| e.to_string())
+}
+
+pub fn render_drivers() -> Vec<String> {
+    sdl2::render::drivers()
+        .map(|d| d.name.to_string())
+        .collect()
+}
+
+pub(crate) fn driver_index(name: &str) -> Option<u32> {
+    sdl2::render::drivers()
+        .position(|d| d.name == name)
+        .map(|i| i as u32)
+}
@@ -0,0 +1,88 @@
+use std::collections::{HashMap, HashSet};
+
+struct ContextEntry {
+    name: String,
+    exclusive: bool,
+}
+
+pub struct InputContexts {
+    bindings: HashMap<(usize, usize), HashSet<String>>,
+    stack: Vec<ContextEntry>,
+}
+
+impl InputContexts {
+    pub fn new() -> Self {
+        InputContexts {
+            bindings: HashMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn bind(&mut self, context: &str, user_index: usize, action_index: usize) {
+        self.bindings
+            .entry((user_index, action_index))
+            .or_default()
+            .insert(context.to_string());
+    }
+
+    pub fn unbind(&mut self, context: &str, user_index: usize, action_index: usize) {
+        if let Some(contexts) = self.bindings.get_mut(&(user_index, action_index)) {
+            contexts.remove(context);
+            if contexts.is_empty() {
+                self.bindings.remove(&(user_index, action_index));
+            }
+        }
+    }
+
+    pub fn push(&mut self, context: &str) {
+        self.stack.push(ContextEntry {
+            name: context.to_string(),
+            exclusive: false,
+        });
+    }
+
+    pub fn push_exclusive(&mut self, context: &str) {
+        self.stack.push(ContextEntry {
+            name: context.to_string(),
+            exclusive: true,
+        });
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.stack.pop().map(|c| c.name)
+    }
+
+    pub fn clear(&mut self) {
+        self.stack.clear();
+    }
+
+    pub fn top(&self) -> Option<&str> {
+        self.stack.last().map(|c| c.name.as_str())
+    }
+
+    pub fn is_active(&self, context: &str) -> bool {
+        for entry in self.stack.iter().rev() {
+            if entry.name == context {
+                return true;
+            }
+            if entry.exclusive {
+                break;
+            }
+        }
+        false
+    }
+
+    // Actions that were never bound to a context are global and always polled.
+    pub fn is_polled(&self, user_index: usize, action_index: usize) -> bool {
+        match self.bindings.get(&(user_index, action_index)) {
+            Some(contexts) => contexts.iter().any(|c| self.is_active(c)),
+            None => true,
+        }
+    }
+}
+
+impl Default for InputContexts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
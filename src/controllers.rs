@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::warn;
+use sdl2::{
+    controller::{Axis, GameController},
+    sys::{SDL_GameControllerFromInstanceID, SDL_GameControllerGetJoystick, SDL_JoystickGetGUID},
+};
+
+use crate::{
+    calibration::{axis_index, AxisRange},
+    input::translate_axis,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControllerCalibration {
+    // Stick and trigger values below this are treated as centered, on top of the
+    // threshold the binding already uses.
+    pub deadzone: f32,
+    // Measured ranges in `calibration::AXES` order, None keeps SDL's mapping.
+    pub axes: [Option<AxisRange>; 6],
+}
+
+impl ControllerCalibration {
+    pub fn axis(&self, axis: Axis, raw: i16) -> f32 {
+        match self.axes[axis_index(axis)] {
+            Some(range) => range.apply(raw),
+            None => translate_axis(raw),
+        }
+    }
+
+    fn axes_to_string(&self) -> String {
+        let axes: Vec<String> = self
+            .axes
+            .iter()
+            .map(|a| match a {
+                Some(r) => format!("{}:{}:{}", r.min, r.center, r.max),
+                None => "_".to_string(),
+            })
+            .collect();
+        axes.join(",")
+    }
+
+    fn axes_from_str(text: &str) -> [Option<AxisRange>; 6] {
+        let mut axes = [None; 6];
+        for (range, field) in axes.iter_mut().zip(text.split(',')) {
+            let values: Vec<i16> = field.split(':').filter_map(|v| v.parse().ok()).collect();
+            if let [min, center, max] = values.as_slice() {
+                *range = Some(AxisRange {
+                    min: *min,
+                    center: *center,
+                    max: *max,
+                });
+            }
+        }
+        axes
+    }
+}
+
+impl Default for ControllerCalibration {
+    fn default() -> Self {
+        ControllerCalibration {
+            deadzone: 0.0,
+            axes: [None; 6],
+        }
+    }
+}
+
+// Remembers which player slot and calibration each controller had, by GUID, so a pad
+// that drops out and reconnects comes back at the same controller index with the same
+// settings. Identical pads share a GUID and get its remembered slots in order.
+pub struct ControllerRegistry {
+    path: Option<PathBuf>,
+    slots: HashMap<String, Vec<usize>>,
+    calibrations: HashMap<String, ControllerCalibration>,
+    connected: HashMap<u32, (String, usize)>,
+}
+
+impl ControllerRegistry {
+    pub fn new() -> Self {
+        ControllerRegistry {
+            path: None,
+            slots: HashMap::new(),
+            calibrations: HashMap::new(),
+            connected: HashMap::new(),
+        }
+    }
+
+    // Reads what was saved at `path`, if anything, and saves there on every change.
+    pub fn persist_to(&mut self, path: &Path) -> Result<(), String> {
+        self.path = Some(path.to_path_buf());
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            // Files from before axis calibration have no fourth field.
+            let (guid, slots, deadzone, axes) = match fields.as_slice() {
+                [guid, slots, deadzone] => (guid, slots, deadzone, ""),
+                [guid, slots, deadzone, axes] => (guid, slots, deadzone, *axes),
+                _ => continue,
+            };
+
+            let slots: Vec<usize> = slots.split(',').filter_map(|s| s.parse().ok()).collect();
+            if !slots.is_empty() {
+                self.slots.insert(guid.to_string(), slots);
+            }
+            if let Ok(deadzone) = deadzone.parse() {
+                let calibration = ControllerCalibration {
+                    deadzone,
+                    axes: ControllerCalibration::axes_from_str(axes),
+                };
+                self.calibrations.insert(guid.to_string(), calibration);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+
+        let mut guids: Vec<&String> = self.slots.keys().chain(self.calibrations.keys()).collect();
+        guids.sort();
+        guids.dedup();
+
+        let mut text = String::new();
+        for guid in guids {
+            let slots: Vec<String> = self
+                .slots
+                .get(guid)
+                .map(|s| s.iter().map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+            let calibration = self.calibration(guid);
+            let _ = writeln!(
+                text,
+                "{}\t{}\t{}\t{}",
+                guid,
+                slots.join(","),
+                calibration.deadzone,
+                calibration.axes_to_string()
+            );
+        }
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    // Slot of a connected controller, by instance id.
+    pub fn slot(&self, instance_id: u32) -> Option<usize> {
+        self.connected.get(&instance_id).map(|(_, slot)| *slot)
+    }
+
+    pub fn guid(&self, instance_id: u32) -> Option<&str> {
+        self.connected
+            .get(&instance_id)
+            .map(|(guid, _)| guid.as_str())
+    }
+
+    pub fn calibration(&self, guid: &str) -> ControllerCalibration {
+        self.calibrations.get(guid).copied().unwrap_or_default()
+    }
+
+    pub fn set_calibration(&mut self, guid: &str, calibration: Option<ControllerCalibration>) {
+        match calibration {
+            Some(c) => self.calibrations.insert(guid.to_string(), c),
+            None => self.calibrations.remove(guid),
+        };
+        self.save_logged();
+    }
+
+    pub fn forget(&mut self, guid: &str) {
+        self.slots.remove(guid);
+        self.calibrations.remove(guid);
+        self.save_logged();
+    }
+
+    pub(crate) fn calibration_for(&self, instance_id: u32) -> ControllerCalibration {
+        match self.guid(instance_id) {
+            Some(guid) => self.calibration(guid),
+            None => ControllerCalibration::default(),
+        }
+    }
+
+    // Picks the slot a newly connected controller goes in: one it had before if that's
+    // free, otherwise the lowest slot no other known controller is remembered in.
+    pub(crate) fn connect(&mut self, instance_id: u32, guid: &str) -> usize {
+        if let Some(slot) = self.slot(instance_id) {
+            return slot;
+        }
+
+        let taken: Vec<usize> = self.connected.values().map(|(_, s)| *s).collect();
+        let remembered = self
+            .slots
+            .get(guid)
+            .and_then(|s| s.iter().find(|s| !taken.contains(s)).copied());
+
+        let slot = match remembered {
+            Some(s) => s,
+            None => {
+                let reserved: Vec<usize> = self
+                    .slots
+                    .iter()
+                    .filter(|(g, _)| g.as_str() != guid)
+                    .flat_map(|(_, s)| s.iter().copied())
+                    .collect();
+                (0..)
+                    .find(|s| !taken.contains(s) && !reserved.contains(s))
+                    .unwrap_or(taken.len())
+            }
+        };
+
+        self.connected.insert(instance_id, (guid.to_string(), slot));
+        let slots = self.slots.entry(guid.to_string()).or_default();
+        if !slots.contains(&slot) {
+            slots.push(slot);
+            self.save_logged();
+        }
+        slot
+    }
+
+    pub(crate) fn disconnect(&mut self, instance_id: u32) {
+        self.connected.remove(&instance_id);
+    }
+
+    pub(crate) fn clear_connected(&mut self) {
+        self.connected.clear();
+    }
+
+    fn save_logged(&self) {
+        if let Err(e) = self.save() {
+            warn!("Could not save controller settings: {}", e);
+        }
+    }
+}
+
+impl Default for ControllerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn controller_guid(controller: &GameController) -> String {
+    let guid = unsafe {
+        let raw = SDL_GameControllerFromInstanceID(controller.instance_id() as i32);
+        if raw.is_null() {
+            return String::new();
+        }
+        SDL_JoystickGetGUID(SDL_GameControllerGetJoystick(raw))
+    };
+
+    guid.data.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,121 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+        }
+    }
+}
+
+// With `Up`, draw positions and the logical coordinates below put the origin at the
+// bottom-left of the canvas and grow upwards, like most physics and 3D engines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YAxis {
+    Down,
+    Up,
+}
+
+pub struct CoordinateSpace {
+    pub camera: Camera,
+    pub y_axis: YAxis,
+    // When set, draws and mouse positions are in world units: the camera offset applies,
+    // and a unit spans this many pixels at zoom 1.
+    pub pixels_per_unit: Option<f32>,
+    dpi: (f32, f32),
+    scale: (f32, f32),
+    viewport: (f32, f32),
+    height: f32,
+}
+
+impl CoordinateSpace {
+    pub fn new() -> Self {
+        CoordinateSpace {
+            camera: Camera::default(),
+            y_axis: YAxis::Down,
+            pixels_per_unit: None,
+            dpi: (1.0, 1.0),
+            scale: (1.0, 1.0),
+            viewport: (0.0, 0.0),
+            height: 0.0,
+        }
+    }
+
+    // Converts between the configured axis and SDL's top-down one, in either direction.
+    pub fn flip_y(&self, y: f32) -> f32 {
+        match self.y_axis {
+            YAxis::Down => y,
+            YAxis::Up => self.height - y,
+        }
+    }
+
+    pub fn window_to_logical(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * self.dpi.0 / self.scale.0 - self.viewport.0,
+            self.flip_y(y * self.dpi.1 / self.scale.1 - self.viewport.1),
+        )
+    }
+
+    pub fn logical_to_window(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x + self.viewport.0) * self.scale.0 / self.dpi.0,
+            (self.flip_y(y) + self.viewport.1) * self.scale.1 / self.dpi.1,
+        )
+    }
+
+    // Output pixels covered by one logical pixel on each axis.
+    pub fn render_scale(&self) -> (f32, f32) {
+        self.scale
+    }
+
+    // Logical pixels covered by one world unit.
+    pub fn unit_scale(&self) -> f32 {
+        self.camera.zoom * self.pixels_per_unit.unwrap_or(1.0)
+    }
+
+    pub fn logical_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let scale = self.unit_scale().max(f32::EPSILON);
+        (x / scale + self.camera.x, y / scale + self.camera.y)
+    }
+
+    pub fn world_to_logical(&self, x: f32, y: f32) -> (f32, f32) {
+        let scale = self.unit_scale();
+        ((x - self.camera.x) * scale, (y - self.camera.y) * scale)
+    }
+
+    pub fn window_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = self.window_to_logical(x, y);
+        self.logical_to_world(x, y)
+    }
+
+    pub fn world_to_window(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = self.world_to_logical(x, y);
+        self.logical_to_window(x, y)
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        dpi: (f32, f32),
+        scale: (f32, f32),
+        viewport: (f32, f32),
+        height: f32,
+    ) {
+        self.dpi = dpi;
+        self.scale = scale;
+        self.viewport = viewport;
+        self.height = height;
+    }
+}
+
+impl Default for CoordinateSpace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
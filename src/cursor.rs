@@ -0,0 +1,107 @@
+use sdl2::event::Event;
+
+pub struct CursorControl {
+    // Milliseconds without mouse movement before the cursor hides, None to never hide.
+    // Moving or clicking shows it again.
+    pub auto_hide: Option<u64>,
+    grab: bool,
+    applied_grab: bool,
+    grab_broken: bool,
+    warp: Option<(f32, f32)>,
+    last_motion: u64,
+    hidden: bool,
+}
+
+impl CursorControl {
+    pub fn new() -> Self {
+        CursorControl {
+            auto_hide: None,
+            grab: false,
+            applied_grab: false,
+            grab_broken: false,
+            warp: None,
+            last_motion: 0,
+            hidden: false,
+        }
+    }
+
+    // Position is in logical canvas coordinates, applied on the next event poll.
+    pub fn warp_to(&mut self, x: f32, y: f32) {
+        self.warp = Some((x, y));
+    }
+
+    // Confines the cursor to the window; released while unfocused and restored on return.
+    pub fn set_grab(&mut self, grab: bool) {
+        self.grab = grab;
+    }
+
+    pub fn is_grabbed(&self) -> bool {
+        self.applied_grab
+    }
+
+    // True for the frame in which losing focus released a requested grab.
+    pub fn grab_broken(&self) -> bool {
+        self.grab_broken
+    }
+
+    pub fn is_auto_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub(crate) fn record(&mut self, event: &Event, now: u64) {
+        if let Event::MouseMotion { .. }
+        | Event::MouseButtonDown { .. }
+        | Event::MouseWheel { .. } = event
+        {
+            self.last_motion = now;
+        }
+    }
+
+    // Visibility the cursor should have now, if it differs from what was last applied.
+    pub(crate) fn pending_visibility(&mut self, now: u64) -> Option<bool> {
+        let hide = match self.auto_hide {
+            Some(after) => now.saturating_sub(self.last_motion) >= after,
+            None => false,
+        };
+        match hide != self.hidden {
+            true => {
+                self.hidden = hide;
+                Some(!hide)
+            }
+            false => None,
+        }
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.grab_broken = false;
+    }
+
+    pub(crate) fn focus_changed(&mut self, focused: bool) {
+        if !focused && self.applied_grab {
+            self.grab_broken = true;
+            self.applied_grab = false;
+        }
+    }
+
+    // Grab state the window should have now, if it differs from what was last applied.
+    pub(crate) fn pending_grab(&mut self, focused: bool) -> Option<bool> {
+        let wanted = self.grab && focused;
+        match wanted != self.applied_grab {
+            true => {
+                self.applied_grab = wanted;
+                Some(wanted)
+            }
+            false => None,
+        }
+    }
+
+    pub(crate) fn take_warp(&mut self) -> Option<(f32, f32)> {
+        self.warp.take()
+    }
+}
+
+impl Default for CursorControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+};
+
+use log::warn;
+use sdl2::{rect::FPoint, render::Canvas, video::Window};
+
+use crate::{
+    coords::{CoordinateSpace, YAxis},
+    thickness::{self, LineThickness},
+};
+
+// What a custom draw callback gets to render with.
+pub struct CustomDrawContext<'r> {
+    pub canvas: &'r mut Canvas<Window>,
+    pub coordinates: &'r CoordinateSpace,
+    pub(crate) offset: (f32, f32),
+}
+
+impl<'r> CustomDrawContext<'r> {
+    // Converts a position given like a regular draw's into canvas pixels.
+    pub fn to_canvas(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = match self.coordinates.pixels_per_unit {
+            Some(_) => self.coordinates.world_to_logical(x, y),
+            None => (x, y),
+        };
+        let y = match self.coordinates.y_axis {
+            YAxis::Up => self.coordinates.flip_y(y),
+            YAxis::Down => y,
+        };
+        (x + self.offset.0, y + self.offset.1)
+    }
+
+    // Converts a length in draw units into canvas pixels.
+    pub fn to_canvas_length(&self, length: f32) -> f32 {
+        match self.coordinates.pixels_per_unit {
+            Some(_) => length * self.coordinates.unit_scale(),
+            None => length,
+        }
+    }
+
+    // Draws a line through `points`, given like a regular draw's position, in the current
+    // draw color.
+    pub fn draw_lines(
+        &mut self,
+        points: &[(f32, f32)],
+        thickness: LineThickness,
+    ) -> Result<(), String> {
+        let points: Vec<FPoint> = points
+            .iter()
+            .map(|(x, y)| {
+                let (x, y) = self.to_canvas(*x, *y);
+                FPoint::new(x, y)
+            })
+            .collect();
+        let width = thickness.canvas_width(self.coordinates);
+        thickness::draw_lines(self.canvas, &points, width)
+    }
+}
+
+pub type CustomDrawCallback = Box<dyn FnMut(&mut CustomDrawContext, &dyn Any)>;
+
+// An instruction the backend has no renderer for. The payload goes to the callback
+// registered for `kind`, which downcasts it.
+pub struct CustomDraw {
+    pub kind: String,
+    pub payload: Box<dyn Any>,
+    // Drawn right after this batch, or above every batch when None. Skipped along with a
+    // hidden batch.
+    pub after_batch: Option<usize>,
+}
+
+impl CustomDraw {
+    pub fn new<P: Any>(kind: &str, payload: P) -> Self {
+        CustomDraw {
+            kind: kind.to_string(),
+            payload: Box::new(payload),
+            after_batch: None,
+        }
+    }
+}
+
+// Extension point for rendering the backend doesn't do itself, without forking the draw
+// loop. Callbacks stay registered, draws are queued for the next frame only.
+pub struct CustomDraws {
+    callbacks: HashMap<String, CustomDrawCallback>,
+    draws: Vec<CustomDraw>,
+    warned: HashSet<String>,
+}
+
+impl CustomDraws {
+    pub fn new() -> Self {
+        CustomDraws {
+            callbacks: HashMap::new(),
+            draws: Vec::new(),
+            warned: HashSet::new(),
+        }
+    }
+
+    pub fn register(&mut self, kind: &str, callback: CustomDrawCallback) {
+        self.callbacks.insert(kind.to_string(), callback);
+        self.warned.remove(kind);
+    }
+
+    pub fn unregister(&mut self, kind: &str) {
+        self.callbacks.remove(kind);
+    }
+
+    pub fn is_registered(&self, kind: &str) -> bool {
+        self.callbacks.contains_key(kind)
+    }
+
+    pub fn draw(&mut self, draw: CustomDraw) {
+        self.draws.push(draw);
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<CustomDraw> {
+        std::mem::take(&mut self.draws)
+    }
+
+    // Taken out while it runs, so the callback can queue draws for the next frame.
+    pub(crate) fn take_callback(&mut self, kind: &str) -> Option<CustomDrawCallback> {
+        let callback = self.callbacks.remove(kind);
+        if callback.is_none() && self.warned.insert(kind.to_string()) {
+            warn!("No callback registered for custom draws of kind '{}'", kind);
+        }
+        callback
+    }
+
+    pub(crate) fn restore_callback(&mut self, kind: &str, callback: CustomDrawCallback) {
+        self.callbacks.entry(kind.to_string()).or_insert(callback);
+    }
+}
+
+impl Default for CustomDraws {
+    fn default() -> Self {
+        Self::new()
+    }
+}
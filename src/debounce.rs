@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DebounceConfig {
+    // A press starting within this many milliseconds of the last accepted release is
+    // treated as a bounce and ignored until released again.
+    pub debounce: u64,
+    // Milliseconds a press has to be held before the action reports down.
+    pub min_hold: u64,
+}
+
+#[derive(Default)]
+struct PressState {
+    raw: bool,
+    since: u64,
+    reported: bool,
+    rejected: bool,
+    released: Option<u64>,
+}
+
+// Filters digital actions against double inputs from worn switches or tremor. Settings
+// apply per user, with per action overrides.
+pub struct ActionDebounce {
+    users: HashMap<usize, DebounceConfig>,
+    actions: HashMap<(usize, usize), DebounceConfig>,
+    states: HashMap<(usize, usize), PressState>,
+}
+
+impl ActionDebounce {
+    pub fn new() -> Self {
+        ActionDebounce {
+            users: HashMap::new(),
+            actions: HashMap::new(),
+            states: HashMap::new(),
+        }
+    }
+
+    pub fn set_user(&mut self, user_index: usize, config: Option<DebounceConfig>) {
+        match config {
+            Some(c) => self.users.insert(user_index, c),
+            None => self.users.remove(&user_index),
+        };
+    }
+
+    pub fn set_action(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        config: Option<DebounceConfig>,
+    ) {
+        match config {
+            Some(c) => self.actions.insert((user_index, action_index), c),
+            None => self.actions.remove(&(user_index, action_index)),
+        };
+    }
+
+    pub fn config(&self, user_index: usize, action_index: usize) -> Option<DebounceConfig> {
+        self.actions
+            .get(&(user_index, action_index))
+            .or_else(|| self.users.get(&user_index))
+            .copied()
+    }
+
+    pub(crate) fn filter(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        down: bool,
+        now: u64,
+    ) -> bool {
+        let config = match self.config(user_index, action_index) {
+            Some(c) => c,
+            None => return down,
+        };
+
+        let state = self.states.entry((user_index, action_index)).or_default();
+        if down && !state.raw {
+            state.since = now;
+            state.rejected = state
+                .released
+                .map_or(false, |r| now.saturating_sub(r) < config.debounce);
+        } else if !down && state.raw && state.reported {
+            state.released = Some(now);
+        }
+        state.raw = down;
+
+        state.reported =
+            down && !state.rejected && now.saturating_sub(state.since) >= config.min_hold;
+        state.reported
+    }
+}
+
+impl Default for ActionDebounce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
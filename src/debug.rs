@@ -0,0 +1,143 @@
+use stagehand::{
+    input::{ActionState, ActionType},
+    loading::Ticket,
+};
+
+use crate::input::{
+    keycode_display_name, scancode_display_name, Chord, SDLCommand, SDLGamepadFeature,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DebugActionState {
+    Up,
+    Down,
+    Axis(f32),
+    Analog(f32, f32),
+}
+
+impl From<&ActionType> for DebugActionState {
+    fn from(action: &ActionType) -> Self {
+        match action {
+            ActionType::Digital(ActionState::Down) => DebugActionState::Down,
+            ActionType::Digital(_) => DebugActionState::Up,
+            ActionType::Axis(v) => DebugActionState::Axis(*v),
+            ActionType::Analog { x, y } => DebugActionState::Analog(*x, *y),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugAction {
+    pub user_index: usize,
+    pub action_index: usize,
+    pub state: DebugActionState,
+    pub source: Option<String>,
+}
+
+pub struct InputDebug {
+    pub enabled: bool,
+    pub font: Option<Ticket>,
+    pub actions: Vec<DebugAction>,
+    pub mouse: (i32, i32),
+}
+
+impl InputDebug {
+    pub fn new() -> Self {
+        InputDebug {
+            enabled: false,
+            font: None,
+            actions: Vec::new(),
+            mouse: (0, 0),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+}
+
+impl Default for InputDebug {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn describe_command(command: &SDLCommand) -> String {
+    match command {
+        SDLCommand::Key(keys) => {
+            let names: Vec<_> = keys
+                .items()
+                .iter()
+                .map(|k| scancode_display_name(*k))
+                .collect();
+            format!("Key {}", join(keys, &names))
+        }
+        SDLCommand::KeyCode(keys, modifiers) => {
+            let names: Vec<_> = keys
+                .items()
+                .iter()
+                .map(|k| keycode_display_name(*k))
+                .collect();
+            match modifiers.is_empty() {
+                true => format!("Key {}", join(keys, &names)),
+                false => format!("Key {:?}+{}", modifiers, join(keys, &names)),
+            }
+        }
+        SDLCommand::MouseButton(buttons) => {
+            let names: Vec<_> = buttons.items().iter().map(|b| format!("{:?}", b)).collect();
+            format!("Mouse {}", join(buttons, &names))
+        }
+        SDLCommand::MultiClick(button, count) => format!("Mouse {:?} x{}", button, count),
+        SDLCommand::MousePosition => "Mouse position".to_string(),
+        SDLCommand::MouseWheel => "Mouse wheel".to_string(),
+        SDLCommand::Touch(control) => format!("Touch control {}", control),
+        SDLCommand::DigitalAxis { negative, positive } => {
+            format!(
+                "{} / {}",
+                describe_command(negative),
+                describe_command(positive)
+            )
+        }
+        SDLCommand::DigitalStick {
+            up,
+            down,
+            left,
+            right,
+            ..
+        } => format!(
+            "{} / {} / {} / {}",
+            describe_command(up),
+            describe_command(down),
+            describe_command(left),
+            describe_command(right)
+        ),
+        SDLCommand::Gamepad(feature, controller) => {
+            let controller = match controller {
+                Some(i) => format!("Pad {}", i),
+                None => "Any pad".to_string(),
+            };
+            match feature {
+                SDLGamepadFeature::Button(buttons) => {
+                    let names: Vec<_> = buttons.items().iter().map(|b| b.string()).collect();
+                    format!("{} {}", controller, join(buttons, &names))
+                }
+                SDLGamepadFeature::Axis(axis) => format!("{} {}", controller, axis.string()),
+                SDLGamepadFeature::Stick(x, y) => {
+                    format!("{} {}/{}", controller, x.string(), y.string())
+                }
+                SDLGamepadFeature::Gyro { .. } => format!("{} gyro", controller),
+                SDLGamepadFeature::Accelerometer { .. } => format!("{} accelerometer", controller),
+                SDLGamepadFeature::Touchpad(finger) => {
+                    format!("{} touchpad finger {}", controller, finger)
+                }
+            }
+        }
+    }
+}
+
+fn join<T>(chord: &Chord<T>, names: &[String]) -> String {
+    match chord {
+        Chord::All(_) => names.join("+"),
+        Chord::Any(_) => names.join("|"),
+    }
+}
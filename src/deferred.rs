@@ -0,0 +1,73 @@
+use sdl2::surface::Surface;
+
+use crate::loading::{AssetSource, LoadReport};
+
+pub(crate) enum StorageRequest<'c> {
+    Load(Vec<AssetSource<'c>>),
+    LoadGroup(String, Vec<AssetSource<'c>>),
+    ReleaseGroup(String),
+    Texture(String, Surface<'static>),
+}
+
+// Loads and releases asked for while scenes update, applied once the stage update is over
+// and before anything is drawn, so storage is never borrowed twice. Share it with scenes
+// through their update content.
+pub struct StorageQueue<'c> {
+    pub(crate) requests: Vec<StorageRequest<'c>>,
+    report: LoadReport,
+    released: usize,
+}
+
+impl<'c> StorageQueue<'c> {
+    pub fn new() -> Self {
+        StorageQueue {
+            requests: Vec::new(),
+            report: LoadReport::default(),
+            released: 0,
+        }
+    }
+
+    // Missing textures get the placeholder, like `SDLStorage::load_all` with `substitute`.
+    pub fn load(&mut self, assets: Vec<AssetSource<'c>>) {
+        self.requests.push(StorageRequest::Load(assets));
+    }
+
+    pub fn load_group(&mut self, group: &str, assets: Vec<AssetSource<'c>>) {
+        self.requests
+            .push(StorageRequest::LoadGroup(group.to_string(), assets));
+    }
+
+    pub fn release_group(&mut self, group: &str) {
+        self.requests
+            .push(StorageRequest::ReleaseGroup(group.to_string()));
+    }
+
+    pub fn set_texture(&mut self, key: &str, surface: Surface<'static>) {
+        self.requests
+            .push(StorageRequest::Texture(key.to_string(), surface));
+    }
+
+    pub fn pending(&self) -> usize {
+        self.requests.len()
+    }
+
+    // What the requests applied after the last update did.
+    pub fn last_report(&self) -> &LoadReport {
+        &self.report
+    }
+
+    pub fn last_released(&self) -> usize {
+        self.released
+    }
+
+    pub(crate) fn finish(&mut self, report: LoadReport, released: usize) {
+        self.report = report;
+        self.released = released;
+    }
+}
+
+impl<'c> Default for StorageQueue<'c> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
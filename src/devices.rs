@@ -0,0 +1,104 @@
+use crate::prompts::PromptFamily;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControllerDevice {
+    pub instance_id: u32,
+    // What `SDLGamepadFeature` indices refer to. Controllers keep it across other
+    // controllers disconnecting, and it matches `slot` once connected.
+    pub index: usize,
+    // Player slot remembered by `ControllerRegistry`.
+    pub slot: usize,
+    pub name: String,
+    pub family: PromptFamily,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControllerEvent {
+    Added(ControllerDevice),
+    // The device as it was before it went away.
+    Removed(ControllerDevice),
+}
+
+// Connected controllers and the changes to them since the last frame, for "controller
+// disconnected, reconnect to continue" prompts and player join screens.
+pub struct ControllerEvents {
+    devices: Vec<ControllerDevice>,
+    frame: Vec<ControllerEvent>,
+    lost: Vec<usize>,
+}
+
+impl ControllerEvents {
+    pub fn new() -> Self {
+        ControllerEvents {
+            devices: Vec::new(),
+            frame: Vec::new(),
+            lost: Vec::new(),
+        }
+    }
+
+    // In controller index order.
+    pub fn devices(&self) -> &[ControllerDevice] {
+        &self.devices
+    }
+
+    pub fn count(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn device_in_slot(&self, slot: usize) -> Option<&ControllerDevice> {
+        self.devices.iter().find(|d| d.slot == slot)
+    }
+
+    pub fn this_frame(&self) -> &[ControllerEvent] {
+        &self.frame
+    }
+
+    pub fn changed_this_frame(&self) -> bool {
+        !self.frame.is_empty()
+    }
+
+    // Slots whose controller disconnected and hasn't come back, oldest first.
+    pub fn lost_slots(&self) -> &[usize] {
+        &self.lost
+    }
+
+    // Stops waiting for a slot, for a player who dropped out instead of reconnecting.
+    pub fn forget_slot(&mut self, slot: usize) {
+        self.lost.retain(|s| *s != slot);
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.frame.clear();
+    }
+
+    pub(crate) fn set_devices(&mut self, devices: Vec<ControllerDevice>) {
+        self.devices = devices;
+    }
+
+    pub(crate) fn added(&mut self, instance_id: u32) {
+        let device = match self.devices.iter().find(|d| d.instance_id == instance_id) {
+            Some(d) => d.clone(),
+            None => return,
+        };
+        self.lost.retain(|s| *s != device.slot);
+        self.frame.push(ControllerEvent::Added(device));
+    }
+
+    // Call before the device leaves the list.
+    pub(crate) fn removed(&mut self, instance_id: u32) {
+        let device = match self.devices.iter().find(|d| d.instance_id == instance_id) {
+            Some(d) => d.clone(),
+            None => return,
+        };
+        if !self.lost.contains(&device.slot) {
+            self.lost.push(device.slot);
+        }
+        self.frame.push(ControllerEvent::Removed(device));
+    }
+}
+
+impl Default for ControllerEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
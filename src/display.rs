@@ -0,0 +1,121 @@
+use sdl2::{pixels::Color, render::BlendMode};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorBlindness {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    Achromatopsia,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorFilter {
+    None,
+    Simulate(ColorBlindness),
+    Compensate(ColorBlindness),
+}
+
+// Brightness is an overlay drawn over the frame and gamma a ramp on the window, neither
+// touches the frame's pixels. A color filter reads the whole frame back to apply its matrix
+// on the CPU, which costs a readback every frame while one is set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayAdjust {
+    pub brightness: f32,
+    pub gamma: f32,
+    pub filter: ColorFilter,
+}
+
+impl Default for DisplayAdjust {
+    fn default() -> Self {
+        DisplayAdjust {
+            brightness: 1.0,
+            gamma: 1.0,
+            filter: ColorFilter::None,
+        }
+    }
+}
+
+impl DisplayAdjust {
+    pub(crate) fn needs_pixels(&self) -> bool {
+        self.filter != ColorFilter::None
+    }
+
+    pub(crate) fn gamma_ramp(&self) -> [u16; 256] {
+        let exponent = 1.0 / self.gamma.max(0.01);
+        let mut ramp = [0u16; 256];
+        for (i, v) in ramp.iter_mut().enumerate() {
+            *v = ((i as f32 / 255.0).powf(exponent) * u16::MAX as f32) as u16;
+        }
+        ramp
+    }
+
+    pub(crate) fn overlay(&self) -> Option<(Color, BlendMode)> {
+        let amount = ((self.brightness - 1.0).abs().min(1.0) * u8::MAX as f32) as u8;
+        if amount == 0 {
+            None
+        } else if self.brightness < 1.0 {
+            Some((Color::RGBA(0, 0, 0, amount), BlendMode::Blend))
+        } else {
+            Some((Color::RGBA(amount, amount, amount, u8::MAX), BlendMode::Add))
+        }
+    }
+
+    // Expects ARGB8888 pixels in native byte order.
+    pub(crate) fn apply_pixels(&self, pixels: &mut [u8]) {
+        for chunk in pixels.chunks_exact_mut(4) {
+            let pixel = u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            let a = pixel >> 24;
+            let rgb = [
+                ((pixel >> 16) & 0xFF) as f32 / 255.0,
+                ((pixel >> 8) & 0xFF) as f32 / 255.0,
+                (pixel & 0xFF) as f32 / 255.0,
+            ];
+
+            let [r, g, b] = match self.filter {
+                ColorFilter::None => rgb,
+                ColorFilter::Simulate(kind) => transform(&simulation(kind), rgb),
+                ColorFilter::Compensate(kind) => {
+                    let sim = transform(&simulation(kind), rgb);
+                    let error = [rgb[0] - sim[0], rgb[1] - sim[1], rgb[2] - sim[2]];
+                    [
+                        rgb[0],
+                        rgb[1] + 0.7 * error[0] + error[1],
+                        rgb[2] + 0.7 * error[0] + error[2],
+                    ]
+                }
+            };
+
+            let pixel = (a << 24) | (channel(r) << 16) | (channel(g) << 8) | channel(b);
+            chunk.copy_from_slice(&pixel.to_ne_bytes());
+        }
+    }
+}
+
+fn channel(v: f32) -> u32 {
+    (v.clamp(0.0, 1.0) * 255.0) as u32
+}
+
+fn transform(m: &[[f32; 3]; 3], c: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * c[0] + m[0][1] * c[1] + m[0][2] * c[2],
+        m[1][0] * c[0] + m[1][1] * c[1] + m[1][2] * c[2],
+        m[2][0] * c[0] + m[2][1] * c[1] + m[2][2] * c[2],
+    ]
+}
+
+fn simulation(kind: ColorBlindness) -> [[f32; 3]; 3] {
+    match kind {
+        ColorBlindness::Protanopia => [
+            [0.567, 0.433, 0.0],
+            [0.558, 0.442, 0.0],
+            [0.0, 0.242, 0.758],
+        ],
+        ColorBlindness::Deuteranopia => [[0.625, 0.375, 0.0], [0.7, 0.3, 0.0], [0.0, 0.3, 0.7]],
+        ColorBlindness::Tritanopia => [[0.95, 0.05, 0.0], [0.0, 0.433, 0.567], [0.0, 0.475, 0.525]],
+        ColorBlindness::Achromatopsia => [
+            [0.299, 0.587, 0.114],
+            [0.299, 0.587, 0.114],
+            [0.299, 0.587, 0.114],
+        ],
+    }
+}
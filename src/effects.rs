@@ -0,0 +1,95 @@
+use sdl2::pixels::Color;
+
+struct Shake {
+    amplitude: f32,
+    duration: u64,
+    start: Option<u64>,
+}
+
+struct Flash {
+    color: Color,
+    duration: u64,
+    start: Option<u64>,
+}
+
+pub struct ScreenEffects {
+    shake: Option<Shake>,
+    flash: Option<Flash>,
+}
+
+impl ScreenEffects {
+    pub fn new() -> Self {
+        ScreenEffects {
+            shake: None,
+            flash: None,
+        }
+    }
+
+    pub fn shake(&mut self, amplitude: f32, duration: u64) {
+        self.shake = Some(Shake {
+            amplitude,
+            duration,
+            start: None,
+        });
+    }
+
+    pub fn flash(&mut self, color: Color, duration: u64) {
+        self.flash = Some(Flash {
+            color,
+            duration,
+            start: None,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.shake = None;
+        self.flash = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.shake.is_some() || self.flash.is_some()
+    }
+
+    pub(crate) fn offset(&mut self, now: u64) -> (i32, i32) {
+        let shake = match &mut self.shake {
+            Some(s) => s,
+            None => return (0, 0),
+        };
+
+        let start = *shake.start.get_or_insert(now);
+        let elapsed = now.saturating_sub(start);
+        if elapsed >= shake.duration {
+            self.shake = None;
+            return (0, 0);
+        }
+
+        let strength = shake.amplitude * (1.0 - elapsed as f32 / shake.duration as f32);
+        let t = elapsed as f32;
+        (
+            (strength * (t * 0.091).sin() * (t * 0.037).cos()) as i32,
+            (strength * (t * 0.073).cos() * (t * 0.053).sin()) as i32,
+        )
+    }
+
+    pub(crate) fn overlay(&mut self, now: u64) -> Option<Color> {
+        let flash = self.flash.as_mut()?;
+
+        let start = *flash.start.get_or_insert(now);
+        let elapsed = now.saturating_sub(start);
+        if elapsed >= flash.duration {
+            self.flash = None;
+            return None;
+        }
+
+        let fade = 1.0 - elapsed as f32 / flash.duration as f32;
+        let mut color = flash.color;
+        color.a = (color.a as f32 * fade) as u8;
+        Some(color)
+    }
+}
+
+impl Default for ScreenEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
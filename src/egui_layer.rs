@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+
+use egui::{
+    epaint::{ImageData, Primitive},
+    ClippedPrimitive, Context, Modifiers, PointerButton, Pos2, RawInput, Rect as EguiRect,
+    TextureId, Vec2,
+};
+use log::warn;
+use sdl2::{
+    event::Event,
+    keyboard::{Keycode, Mod},
+    mouse::MouseButton,
+    pixels::PixelFormatEnum,
+    rect::Rect,
+    render::{Canvas, Texture, TextureCreator},
+    sys::{
+        SDL_BlendFactor, SDL_BlendMode, SDL_BlendOperation, SDL_Color, SDL_ComposeCustomBlendMode,
+        SDL_FPoint, SDL_RenderGeometry, SDL_SetRenderDrawBlendMode, SDL_SetTextureBlendMode,
+        SDL_Vertex,
+    },
+    video::{Window, WindowContext},
+};
+
+pub struct SDLEguiLayer<'a> {
+    context: Context,
+    input: RawInput,
+    modifiers: Modifiers,
+    pointer: Pos2,
+    textures: HashMap<TextureId, Texture<'a>>,
+    ui: Option<Box<dyn FnMut(&Context) + 'a>>,
+}
+
+impl<'a> SDLEguiLayer<'a> {
+    pub fn new() -> Self {
+        SDLEguiLayer {
+            context: Context::default(),
+            input: RawInput::default(),
+            modifiers: Modifiers::default(),
+            pointer: Pos2::ZERO,
+            textures: HashMap::new(),
+            ui: None,
+        }
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    pub fn set_ui(&mut self, ui: Box<dyn FnMut(&Context) + 'a>) {
+        self.ui = Some(ui);
+    }
+
+    pub fn wants_input(&self) -> bool {
+        self.wants_pointer_input() || self.wants_keyboard_input()
+    }
+
+    // As of the last frame, which is what input is checked against before the game sees it.
+    pub fn wants_pointer_input(&self) -> bool {
+        self.context.wants_pointer_input()
+    }
+
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.context.wants_keyboard_input()
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::MouseMotion { x, y, .. } => {
+                self.pointer = Pos2::new(*x as f32, *y as f32);
+                self.input
+                    .events
+                    .push(egui::Event::PointerMoved(self.pointer));
+            }
+            Event::MouseButtonDown { mouse_btn, .. } | Event::MouseButtonUp { mouse_btn, .. } => {
+                let button = match mouse_btn {
+                    MouseButton::Left => PointerButton::Primary,
+                    MouseButton::Right => PointerButton::Secondary,
+                    MouseButton::Middle => PointerButton::Middle,
+                    _ => return,
+                };
+                self.input.events.push(egui::Event::PointerButton {
+                    pos: self.pointer,
+                    button,
+                    pressed: matches!(event, Event::MouseButtonDown { .. }),
+                    modifiers: self.modifiers,
+                });
+            }
+            Event::MouseWheel { x, y, .. } => {
+                self.input
+                    .events
+                    .push(egui::Event::Scroll(Vec2::new(*x as f32, *y as f32) * 20.0));
+            }
+            Event::TextInput { text, .. } => {
+                self.input.events.push(egui::Event::Text(text.clone()));
+            }
+            Event::KeyDown {
+                keycode: Some(k),
+                keymod,
+                repeat,
+                ..
+            }
+            | Event::KeyUp {
+                keycode: Some(k),
+                keymod,
+                repeat,
+                ..
+            } => {
+                self.modifiers = modifiers(*keymod);
+                self.input.modifiers = self.modifiers;
+
+                if let Some(key) = translate_key(*k) {
+                    self.input.events.push(egui::Event::Key {
+                        key,
+                        physical_key: None,
+                        pressed: matches!(event, Event::KeyDown { .. }),
+                        repeat: *repeat,
+                        modifiers: self.modifiers,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        creator: &'a TextureCreator<WindowContext>,
+        seconds: f64,
+    ) {
+        let ui = match &mut self.ui {
+            Some(ui) => ui,
+            None => return,
+        };
+
+        let (width, height) = canvas.output_size().unwrap_or((0, 0));
+        let mut input = std::mem::take(&mut self.input);
+        input.time = Some(seconds);
+        input.screen_rect = Some(EguiRect::from_min_size(
+            Pos2::ZERO,
+            Vec2::new(width as f32, height as f32),
+        ));
+        input.modifiers = self.modifiers;
+
+        let output = self.context.run(input, |ctx| ui(ctx));
+
+        for (id, delta) in output.textures_delta.set.iter() {
+            self.update_texture(creator, *id, delta);
+        }
+
+        let primitives = self
+            .context
+            .tessellate(output.shapes, output.pixels_per_point);
+        let previous_clip = canvas.clip_rect();
+        let previous_blend = canvas.blend_mode();
+        // Untextured meshes use the draw blend mode.
+        if unsafe { SDL_SetRenderDrawBlendMode(canvas.raw(), premultiplied_blend()) } != 0 {
+            warn!("Premultiplied blending unavailable: {}", sdl2::get_error());
+        }
+        for primitive in primitives.iter() {
+            self.draw_primitive(canvas, primitive);
+        }
+        canvas.set_blend_mode(previous_blend);
+        canvas.set_clip_rect(previous_clip);
+
+        for id in output.textures_delta.free.iter() {
+            self.textures.remove(id);
+        }
+    }
+
+    fn update_texture(
+        &mut self,
+        creator: &'a TextureCreator<WindowContext>,
+        id: TextureId,
+        delta: &egui::epaint::ImageDelta,
+    ) {
+        let (size, pixels): ([usize; 2], Vec<u8>) = match &delta.image {
+            ImageData::Color(image) => (
+                image.size,
+                image.pixels.iter().flat_map(|c| c.to_array()).collect(),
+            ),
+            ImageData::Font(image) => (
+                image.size,
+                image
+                    .srgba_pixels(None)
+                    .flat_map(|c| c.to_array())
+                    .collect(),
+            ),
+        };
+
+        let [x, y] = delta.pos.unwrap_or([0, 0]);
+        if delta.pos.is_none() {
+            match creator.create_texture_streaming(
+                PixelFormatEnum::ABGR8888,
+                size[0] as u32,
+                size[1] as u32,
+            ) {
+                Ok(t) => {
+                    if unsafe { SDL_SetTextureBlendMode(t.raw(), premultiplied_blend()) } != 0 {
+                        warn!("Premultiplied blending unavailable: {}", sdl2::get_error());
+                    }
+                    self.textures.insert(id, t);
+                }
+                Err(e) => {
+                    warn!("Could not create egui texture: {}", e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(texture) = self.textures.get_mut(&id) {
+            let rect = Rect::new(x as i32, y as i32, size[0] as u32, size[1] as u32);
+            if let Err(e) = texture.update(rect, &pixels, size[0] * 4) {
+                warn!("Could not update egui texture: {}", e);
+            }
+        }
+    }
+
+    fn draw_primitive(&self, canvas: &mut Canvas<Window>, primitive: &ClippedPrimitive) {
+        let mesh = match &primitive.primitive {
+            Primitive::Mesh(m) => m,
+            Primitive::Callback(_) => return,
+        };
+
+        let clip = primitive.clip_rect;
+        canvas.set_clip_rect(Rect::new(
+            clip.min.x as i32,
+            clip.min.y as i32,
+            clip.width().max(0.0) as u32,
+            clip.height().max(0.0) as u32,
+        ));
+
+        let vertices: Vec<SDL_Vertex> = mesh
+            .vertices
+            .iter()
+            .map(|v| SDL_Vertex {
+                position: SDL_FPoint {
+                    x: v.pos.x,
+                    y: v.pos.y,
+                },
+                color: SDL_Color {
+                    r: v.color.r(),
+                    g: v.color.g(),
+                    b: v.color.b(),
+                    a: v.color.a(),
+                },
+                tex_coord: SDL_FPoint {
+                    x: v.uv.x,
+                    y: v.uv.y,
+                },
+            })
+            .collect();
+        let indices: Vec<i32> = mesh.indices.iter().map(|i| *i as i32).collect();
+
+        let texture = match self.textures.get(&mesh.texture_id) {
+            Some(t) => t.raw(),
+            None => std::ptr::null_mut(),
+        };
+
+        let result = unsafe {
+            SDL_RenderGeometry(
+                canvas.raw(),
+                texture,
+                vertices.as_ptr(),
+                vertices.len() as i32,
+                indices.as_ptr(),
+                indices.len() as i32,
+            )
+        };
+        if result != 0 {
+            warn!("Could not render egui mesh: {}", sdl2::get_error());
+        }
+    }
+}
+
+impl<'a> Default for SDLEguiLayer<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// egui's colors and textures are premultiplied, which SDL's blend modes don't cover.
+fn premultiplied_blend() -> SDL_BlendMode {
+    unsafe {
+        SDL_ComposeCustomBlendMode(
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+            SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+            SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+        )
+    }
+}
+
+fn modifiers(keymod: Mod) -> Modifiers {
+    let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+    let command = keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD);
+    Modifiers {
+        alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+        ctrl,
+        shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+        mac_cmd: cfg!(target_os = "macos") && command,
+        command: if cfg!(target_os = "macos") {
+            command
+        } else {
+            ctrl
+        },
+    }
+}
+
+fn translate_key(key: Keycode) -> Option<egui::Key> {
+    use egui::Key;
+
+    Some(match key {
+        Keycode::Left => Key::ArrowLeft,
+        Keycode::Right => Key::ArrowRight,
+        Keycode::Up => Key::ArrowUp,
+        Keycode::Down => Key::ArrowDown,
+        Keycode::Escape => Key::Escape,
+        Keycode::Tab => Key::Tab,
+        Keycode::Backspace => Key::Backspace,
+        Keycode::Return | Keycode::KpEnter => Key::Enter,
+        Keycode::Space => Key::Space,
+        Keycode::Insert => Key::Insert,
+        Keycode::Delete => Key::Delete,
+        Keycode::Home => Key::Home,
+        Keycode::End => Key::End,
+        Keycode::PageUp => Key::PageUp,
+        Keycode::PageDown => Key::PageDown,
+        Keycode::Num0 | Keycode::Kp0 => Key::Num0,
+        Keycode::Num1 | Keycode::Kp1 => Key::Num1,
+        Keycode::Num2 | Keycode::Kp2 => Key::Num2,
+        Keycode::Num3 | Keycode::Kp3 => Key::Num3,
+        Keycode::Num4 | Keycode::Kp4 => Key::Num4,
+        Keycode::Num5 | Keycode::Kp5 => Key::Num5,
+        Keycode::Num6 | Keycode::Kp6 => Key::Num6,
+        Keycode::Num7 | Keycode::Kp7 => Key::Num7,
+        Keycode::Num8 | Keycode::Kp8 => Key::Num8,
+        Keycode::Num9 | Keycode::Kp9 => Key::Num9,
+        Keycode::A => Key::A,
+        Keycode::B => Key::B,
+        Keycode::C => Key::C,
+        Keycode::D => Key::D,
+        Keycode::E => Key::E,
+        Keycode::F => Key::F,
+        Keycode::G => Key::G,
+        Keycode::H => Key::H,
+        Keycode::I => Key::I,
+        Keycode::J => Key::J,
+        Keycode::K => Key::K,
+        Keycode::L => Key::L,
+        Keycode::M => Key::M,
+        Keycode::N => Key::N,
+        Keycode::O => Key::O,
+        Keycode::P => Key::P,
+        Keycode::Q => Key::Q,
+        Keycode::R => Key::R,
+        Keycode::S => Key::S,
+        Keycode::T => Key::T,
+        Keycode::U => Key::U,
+        Keycode::V => Key::V,
+        Keycode::W => Key::W,
+        Keycode::X => Key::X,
+        Keycode::Y => Key::Y,
+        Keycode::Z => Key::Z,
+        _ => return None,
+    })
+}
@@ -0,0 +1,14 @@
+use sdl2::event::Event;
+
+pub trait EventHook {
+    fn handle(&mut self, event: &Event);
+}
+
+impl<F> EventHook for F
+where
+    F: FnMut(&Event),
+{
+    fn handle(&mut self, event: &Event) {
+        self(event)
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnalogFilter {
+    // Weight of the previous value, 0.0 disables smoothing.
+    pub smoothing: f32,
+    // Angle tolerance in degrees around the four cardinal directions.
+    pub snap: Option<f32>,
+}
+
+impl Default for AnalogFilter {
+    fn default() -> Self {
+        AnalogFilter {
+            smoothing: 0.0,
+            snap: None,
+        }
+    }
+}
+
+impl AnalogFilter {
+    fn apply(&self, previous: (f32, f32), value: (f32, f32)) -> (f32, f32) {
+        let (mut x, mut y) = value;
+
+        if let Some(tolerance) = self.snap {
+            let angle = y.atan2(x).to_degrees();
+            let nearest = (angle / 90.0).round() * 90.0;
+            if (angle - nearest).abs() <= tolerance {
+                let length = (x * x + y * y).sqrt();
+                let radians = nearest.to_radians();
+                x = radians.cos() * length;
+                y = radians.sin() * length;
+            }
+        }
+
+        let k = self.smoothing.clamp(0.0, 0.99);
+        (
+            previous.0 * k + x * (1.0 - k),
+            previous.1 * k + y * (1.0 - k),
+        )
+    }
+}
+
+pub struct AnalogFilters {
+    filters: HashMap<(usize, usize), AnalogFilter>,
+    state: HashMap<(usize, usize), (f32, f32)>,
+}
+
+impl AnalogFilters {
+    pub fn new() -> Self {
+        AnalogFilters {
+            filters: HashMap::new(),
+            state: HashMap::new(),
+        }
+    }
+
+    pub fn set(&mut self, user_index: usize, action_index: usize, filter: Option<AnalogFilter>) {
+        let key = (user_index, action_index);
+        self.state.remove(&key);
+        match filter {
+            Some(f) => self.filters.insert(key, f),
+            None => self.filters.remove(&key),
+        };
+    }
+
+    pub(crate) fn apply(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        value: Option<(f32, f32)>,
+    ) -> Option<(f32, f32)> {
+        let key = (user_index, action_index);
+        let filter = match self.filters.get(&key) {
+            Some(f) => f,
+            None => return value,
+        };
+
+        match value {
+            Some(v) => {
+                let previous = self.state.get(&key).copied().unwrap_or(v);
+                let filtered = filter.apply(previous, v);
+                self.state.insert(key, filtered);
+                Some(filtered)
+            }
+            None => {
+                self.state.remove(&key);
+                None
+            }
+        }
+    }
+}
+
+impl Default for AnalogFilters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use sdl2::{
+    render::Texture,
+    sys::{SDL_GetTextureScaleMode, SDL_ScaleMode, SDL_SetTextureScaleMode},
+};
+use stagehand::loading::Ticket;
+
+use crate::config::ScaleQuality;
+
+// Scaling filter per texture or per batch, so crisp pixel art and smoothly scaled UI can
+// share a frame. Textures without a setting keep the filter they were created with, which
+// follows the `ScaleQuality` hint.
+pub struct TextureFilters {
+    textures: HashMap<Ticket, ScaleQuality>,
+    batches: HashMap<usize, ScaleQuality>,
+}
+
+impl TextureFilters {
+    pub fn new() -> Self {
+        TextureFilters {
+            textures: HashMap::new(),
+            batches: HashMap::new(),
+        }
+    }
+
+    pub fn set_texture(&mut self, ticket: Ticket, quality: Option<ScaleQuality>) {
+        match quality {
+            Some(q) => self.textures.insert(ticket, q),
+            None => self.textures.remove(&ticket),
+        };
+    }
+
+    pub fn set_batch(&mut self, batch: usize, quality: Option<ScaleQuality>) {
+        match quality {
+            Some(q) => self.batches.insert(batch, q),
+            None => self.batches.remove(&batch),
+        };
+    }
+
+    // A texture's own setting wins over its batch's.
+    pub fn get(&self, ticket: Ticket, batch: usize) -> Option<ScaleQuality> {
+        self.textures
+            .get(&ticket)
+            .or_else(|| self.batches.get(&batch))
+            .copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty() && self.batches.is_empty()
+    }
+}
+
+impl Default for TextureFilters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Returns the mode the texture had, to put back after the draw.
+pub(crate) fn set_scale_mode(texture: &Texture, quality: ScaleQuality) -> Option<ScaleQuality> {
+    let mut previous = SDL_ScaleMode::SDL_ScaleModeNearest;
+    unsafe {
+        if SDL_GetTextureScaleMode(texture.raw(), &mut previous) != 0 {
+            return None;
+        }
+        let mode = match quality {
+            ScaleQuality::Nearest => SDL_ScaleMode::SDL_ScaleModeNearest,
+            ScaleQuality::Linear => SDL_ScaleMode::SDL_ScaleModeLinear,
+            ScaleQuality::Best => SDL_ScaleMode::SDL_ScaleModeBest,
+        };
+        SDL_SetTextureScaleMode(texture.raw(), mode);
+    }
+
+    Some(match previous {
+        SDL_ScaleMode::SDL_ScaleModeNearest => ScaleQuality::Nearest,
+        SDL_ScaleMode::SDL_ScaleModeLinear => ScaleQuality::Linear,
+        _ => ScaleQuality::Best,
+    })
+}
@@ -0,0 +1,200 @@
+use sdl2::controller::{Axis, Button, GameController};
+use stagehand::draw::DrawRect;
+
+use crate::{hold::RepeatRate, input::translate_axis};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusEvent {
+    Changed { from: Option<u64>, to: u64 },
+    Activate(u64),
+    Cancel,
+}
+
+pub struct FocusNavigator {
+    pub enabled: bool,
+    pub repeat: RepeatRate,
+    regions: Vec<(u64, DrawRect)>,
+    focused: Option<u64>,
+    events: Vec<FocusEvent>,
+    held: Option<(FocusDirection, u64)>,
+    buttons: (bool, bool),
+}
+
+impl FocusNavigator {
+    pub fn new() -> Self {
+        FocusNavigator {
+            enabled: true,
+            repeat: RepeatRate {
+                delay: 400,
+                interval: 120,
+            },
+            regions: Vec::new(),
+            focused: None,
+            events: Vec::new(),
+            held: None,
+            buttons: (false, false),
+        }
+    }
+
+    // Like hit regions, focusables only live for the frame they were registered in.
+    pub fn register(&mut self, id: u64, rect: DrawRect) {
+        self.regions.push((id, rect));
+    }
+
+    pub fn focused(&self) -> Option<u64> {
+        self.focused
+    }
+
+    pub fn focus(&mut self, id: u64) {
+        if self.focused != Some(id) {
+            self.events.push(FocusEvent::Changed {
+                from: self.focused,
+                to: id,
+            });
+            self.focused = Some(id);
+        }
+    }
+
+    pub fn events(&self) -> &[FocusEvent] {
+        &self.events
+    }
+
+    pub fn is_activated(&self, id: u64) -> bool {
+        self.events.contains(&FocusEvent::Activate(id))
+    }
+
+    pub fn navigate(&mut self, direction: FocusDirection) {
+        let current = self
+            .focused
+            .and_then(|id| self.regions.iter().find(|(r, _)| *r == id))
+            .map(|(_, rect)| center(rect));
+
+        let next = match current {
+            Some(from) => nearest(&self.regions, from, direction),
+            None => self.regions.first().map(|(id, _)| *id),
+        };
+
+        if let Some(id) = next {
+            self.focus(id);
+        }
+    }
+
+    // `masked` while something else, like the on-screen keyboard, reads the controllers.
+    // Buttons are still tracked so one held at the end doesn't activate afterwards.
+    pub(crate) fn resolve(
+        &mut self,
+        controllers: &[Option<GameController>],
+        now: u64,
+        masked: bool,
+    ) {
+        self.events.clear();
+
+        if self.enabled && !self.regions.is_empty() {
+            if self.focused.is_none() {
+                self.navigate(FocusDirection::Down);
+            }
+
+            let direction = match masked {
+                true => None,
+                false => controllers.iter().flatten().find_map(read_direction),
+            };
+            match direction {
+                Some(direction) => {
+                    let pulse = match self.held {
+                        Some((held, next)) if held == direction => {
+                            if now >= next {
+                                self.held = Some((direction, now + self.repeat.interval.max(1)));
+                                true
+                            } else {
+                                false
+                            }
+                        }
+                        _ => {
+                            self.held = Some((direction, now + self.repeat.delay));
+                            true
+                        }
+                    };
+                    if pulse {
+                        self.navigate(direction);
+                    }
+                }
+                None => self.held = None,
+            }
+
+            let accept = controllers.iter().flatten().any(|c| c.button(Button::A));
+            let back = controllers.iter().flatten().any(|c| c.button(Button::B));
+            if !masked && accept && !self.buttons.0 {
+                if let Some(id) = self.focused {
+                    self.events.push(FocusEvent::Activate(id));
+                }
+            }
+            if !masked && back && !self.buttons.1 {
+                self.events.push(FocusEvent::Cancel);
+            }
+            self.buttons = (accept, back);
+        }
+
+        self.regions.clear();
+    }
+}
+
+impl Default for FocusNavigator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_direction(controller: &GameController) -> Option<FocusDirection> {
+    let x = translate_axis(controller.axis(Axis::LeftX));
+    let y = translate_axis(controller.axis(Axis::LeftY));
+
+    if controller.button(Button::DPadUp) || y <= -0.5 {
+        Some(FocusDirection::Up)
+    } else if controller.button(Button::DPadDown) || y >= 0.5 {
+        Some(FocusDirection::Down)
+    } else if controller.button(Button::DPadLeft) || x <= -0.5 {
+        Some(FocusDirection::Left)
+    } else if controller.button(Button::DPadRight) || x >= 0.5 {
+        Some(FocusDirection::Right)
+    } else {
+        None
+    }
+}
+
+// Picks the closest rect in the given direction, penalising distance off the movement axis.
+fn nearest(
+    regions: &[(u64, DrawRect)],
+    (fx, fy): (f32, f32),
+    direction: FocusDirection,
+) -> Option<u64> {
+    regions
+        .iter()
+        .filter_map(|(id, rect)| {
+            let (cx, cy) = center(rect);
+            let (dx, dy) = (cx - fx, cy - fy);
+            let (along, across) = match direction {
+                FocusDirection::Up => (-dy, dx),
+                FocusDirection::Down => (dy, dx),
+                FocusDirection::Left => (-dx, dy),
+                FocusDirection::Right => (dx, dy),
+            };
+            match along > 0.0 {
+                true => Some((*id, along + across.abs() * 2.0)),
+                false => None,
+            }
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(id, _)| id)
+}
+
+fn center(rect: &DrawRect) -> (f32, f32) {
+    (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0)
+}
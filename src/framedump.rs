@@ -0,0 +1,184 @@
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use log::{info, warn};
+use sdl2::rect::FRect;
+use stagehand::draw::{DrawData, DrawDestination, DrawRect};
+
+pub(crate) struct DumpedDraw {
+    pub(crate) batch: usize,
+    pub(crate) index: usize,
+    pub(crate) key: Option<String>,
+    pub(crate) text: Option<String>,
+    pub(crate) data: String,
+    // Why the draw never reached the canvas, for draws dropped while preparing the frame.
+    pub(crate) dropped: Option<&'static str>,
+    pub(crate) result: Option<(Option<FRect>, f64, bool)>,
+}
+
+// Writes every draw instruction of the next frame to a JSON file: where it came from, the
+// draw data as the stage sent it, and the rect it ended up at on the canvas after coordinate
+// conversion, anchors and culling, or why it was dropped before reaching the canvas.
+pub struct FrameDump {
+    requested: Option<PathBuf>,
+    capturing: Option<PathBuf>,
+    draws: Vec<DumpedDraw>,
+    current: Option<usize>,
+}
+
+impl FrameDump {
+    pub fn new() -> Self {
+        FrameDump {
+            requested: None,
+            capturing: None,
+            draws: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn dump_next_frame(&mut self, path: PathBuf) {
+        self.requested = Some(path);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.requested.is_some() || self.capturing.is_some()
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.capturing = self.requested.take();
+        self.draws.clear();
+        self.current = None;
+    }
+
+    pub(crate) fn is_capturing(&self) -> bool {
+        self.capturing.is_some()
+    }
+
+    pub(crate) fn push(&mut self, draw: DumpedDraw) {
+        self.draws.push(draw);
+    }
+
+    // The next result recorded belongs to this draw.
+    pub(crate) fn select(&mut self, batch: usize, index: usize) {
+        self.current = self
+            .draws
+            .iter()
+            .position(|d| d.batch == batch && d.index == index);
+    }
+
+    // Fills in the selected draw; draws the backend makes on its own are skipped.
+    pub(crate) fn record_result(&mut self, dest: Option<FRect>, angle: f64, culled: bool) {
+        if let Some(i) = self.current.take() {
+            self.draws[i].result = Some((dest, angle, culled));
+        }
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        let path = match self.capturing.take() {
+            Some(p) => p,
+            None => return,
+        };
+
+        let draws: Vec<String> = self.draws.iter().map(draw_json).collect();
+        let json = format!("{{\"draws\":[\n{}\n]}}\n", draws.join(",\n"));
+        match fs::write(&path, json) {
+            Ok(_) => info!("Dumped {} draws to {}", self.draws.len(), path.display()),
+            Err(e) => warn!("Could not write frame dump to {}: {}", path.display(), e),
+        }
+        self.draws.clear();
+    }
+}
+
+impl Default for FrameDump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn data_json(data: &DrawData) -> String {
+    let mut json = String::from("{");
+    let _ = write!(
+        json,
+        "\"source\":{}",
+        optional(data.source.as_ref().map(rect_json))
+    );
+    let destination = data.destination.as_ref().map(|d| match d {
+        DrawDestination::Location { x, y } => format!("{{\"x\":{},\"y\":{}}}", x, y),
+        DrawDestination::Rect(r) => rect_json(r),
+    });
+    let _ = write!(json, ",\"destination\":{}", optional(destination));
+    let rotation = data.rotation.as_ref().map(|r| {
+        format!(
+            "{{\"angle\":{},\"origin\":[{},{}]}}",
+            r.angle, r.origin.0, r.origin.1
+        )
+    });
+    let _ = write!(json, ",\"rotation\":{}", optional(rotation));
+    let flip = data.flip.as_ref().map(|f| {
+        format!(
+            "{{\"horizontal\":{},\"vertical\":{}}}",
+            f.horizontal, f.vertical
+        )
+    });
+    let _ = write!(json, ",\"flip\":{}}}", optional(flip));
+    json
+}
+
+fn draw_json(draw: &DumpedDraw) -> String {
+    let (result, angle, culled) = match &draw.result {
+        Some((dest, angle, culled)) => (
+            optional(dest.map(|d| {
+                format!(
+                    "{{\"x\":{},\"y\":{},\"width\":{},\"height\":{}}}",
+                    d.x(),
+                    d.y(),
+                    d.width(),
+                    d.height()
+                )
+            })),
+            angle.to_string(),
+            culled.to_string(),
+        ),
+        None => ("null".to_string(), "null".to_string(), "null".to_string()),
+    };
+
+    format!(
+        "{{\"batch\":{},\"index\":{},\"key\":{},\"text\":{},\"data\":{},\"dropped\":{},\"canvas_rect\":{},\"angle\":{},\"culled\":{}}}",
+        draw.batch,
+        draw.index,
+        optional(draw.key.as_deref().map(string_json)),
+        optional(draw.text.as_deref().map(string_json)),
+        draw.data,
+        optional(draw.dropped.map(string_json)),
+        result,
+        angle,
+        culled
+    )
+}
+
+fn rect_json(r: &DrawRect) -> String {
+    format!(
+        "{{\"x\":{},\"y\":{},\"width\":{},\"height\":{}}}",
+        r.x, r.y, r.width, r.height
+    )
+}
+
+fn string_json(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "null".to_string())
+}
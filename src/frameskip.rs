@@ -0,0 +1,113 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipPolicy {
+    // Draw after every update batch, however far behind the loop is.
+    Never,
+    // Drop draws while updates are catching up, but never more than `max_skipped` in a row
+    // so the screen keeps moving on machines that can't keep up at all.
+    WhenBehind { max_skipped: u32 },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameSkipStats {
+    pub presented: u64,
+    pub skipped: u64,
+    // Most draws dropped in a row.
+    pub longest_run: u32,
+}
+
+// What to do when the renderer couldn't get vsync: drop draws while the update loop is
+// behind, so slow machines spend their time catching up instead of spiraling, and sleep
+// after presenting so fast ones don't render frames the display never shows. Both are off
+// until a policy is set and the cap is turned on, so every update batch draws as before.
+pub struct FrameSkip {
+    pub policy: SkipPolicy,
+    // Skip only without vsync; with it, presenting already waits for the display.
+    pub only_without_vsync: bool,
+    // Without vsync, hold each frame to the pacing budget.
+    pub cap_without_vsync: bool,
+    vsync: bool,
+    updates: u32,
+    run: u32,
+    last_present: Option<f64>,
+    stats: FrameSkipStats,
+}
+
+impl FrameSkip {
+    pub fn new() -> Self {
+        FrameSkip {
+            policy: SkipPolicy::Never,
+            only_without_vsync: true,
+            cap_without_vsync: false,
+            vsync: false,
+            updates: 0,
+            run: 0,
+            last_present: None,
+            stats: FrameSkipStats::default(),
+        }
+    }
+
+    // Whether the renderer presents in step with the display.
+    pub fn vsync(&self) -> bool {
+        self.vsync
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.policy != SkipPolicy::Never && !(self.only_without_vsync && self.vsync)
+    }
+
+    pub fn stats(&self) -> FrameSkipStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = FrameSkipStats::default();
+    }
+
+    pub(crate) fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+    }
+
+    pub(crate) fn record_update(&mut self) {
+        self.updates += 1;
+    }
+
+    // More than one update since the last draw means the loop is catching up.
+    pub(crate) fn should_draw(&mut self) -> bool {
+        let behind = std::mem::take(&mut self.updates) > 1;
+        let skip = match self.policy {
+            SkipPolicy::WhenBehind { max_skipped } => {
+                self.is_active() && behind && self.run < max_skipped
+            }
+            SkipPolicy::Never => false,
+        };
+
+        if skip {
+            self.run += 1;
+            self.stats.skipped += 1;
+            self.stats.longest_run = self.stats.longest_run.max(self.run);
+        } else {
+            self.run = 0;
+        }
+        !skip
+    }
+
+    // Milliseconds to wait before presenting to stay within `budget`.
+    pub(crate) fn cap_delay(&self, now: f64, budget: f64) -> Option<f64> {
+        if self.vsync || !self.cap_without_vsync {
+            return None;
+        }
+        let remaining = budget - (now - self.last_present?);
+        (remaining > 0.0).then_some(remaining)
+    }
+
+    pub(crate) fn presented(&mut self, now: f64) {
+        self.last_present = Some(now);
+        self.stats.presented += 1;
+    }
+}
+
+impl Default for FrameSkip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+use log::{log, Level};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    pub frame: u64,
+    // Instructions each batch submitted, in batch order.
+    pub batch_instructions: Vec<usize>,
+    pub draws: usize,
+    pub culled: usize,
+    pub text_hits: usize,
+    pub text_misses: usize,
+    // Started since the frame before, by the updates that led up to this one.
+    pub sounds_played: usize,
+    pub music_started: usize,
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "frame {}: {} batches {:?}, {} draws ({} culled), text {} hits {} misses, {} sounds, {} music",
+            self.frame,
+            self.batch_instructions.len(),
+            self.batch_instructions,
+            self.draws,
+            self.culled,
+            self.text_hits,
+            self.text_misses,
+            self.sounds_played,
+            self.music_started
+        )
+    }
+}
+
+// Logs a one-line summary of what the backend did every `interval` frames, a lighter look
+// than the profiler at why a scene is slow or silent.
+pub struct FrameSummary {
+    // Frames between log lines, None to stay quiet.
+    pub interval: Option<u64>,
+    pub level: Level,
+    last: FrameStats,
+    frame: u64,
+    sounds: usize,
+    music: usize,
+}
+
+impl FrameSummary {
+    pub fn new() -> Self {
+        FrameSummary {
+            interval: None,
+            level: Level::Info,
+            last: FrameStats::default(),
+            frame: 0,
+            sounds: 0,
+            music: 0,
+        }
+    }
+
+    // Kept whether or not logging is on.
+    pub fn last(&self) -> &FrameStats {
+        &self.last
+    }
+
+    pub(crate) fn sound_played(&mut self) {
+        self.sounds += 1;
+    }
+
+    pub(crate) fn music_started(&mut self) {
+        self.music += 1;
+    }
+
+    pub(crate) fn finish(&mut self, mut stats: FrameStats) {
+        self.frame += 1;
+        stats.frame = self.frame;
+        stats.sounds_played = std::mem::take(&mut self.sounds);
+        stats.music_started = std::mem::take(&mut self.music);
+
+        if let Some(interval) = self.interval {
+            if self.frame % interval.max(1) == 0 {
+                log!(self.level, "{}", stats);
+            }
+        }
+        self.last = stats;
+    }
+}
+
+impl Default for FrameSummary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
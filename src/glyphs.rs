@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet};
+
+use log::warn;
+use sdl2::ttf::Font;
+use stagehand::loading::Ticket;
+
+// Characters the font has no glyph for, in order of first use, without repeats. Whitespace
+// and control characters don't count since fonts often leave them out.
+pub fn missing_glyphs(font: &Font, text: &str) -> Vec<char> {
+    let mut missing = Vec::new();
+    for c in text.chars() {
+        if c.is_whitespace() || c.is_control() || missing.contains(&c) {
+            continue;
+        }
+        if font.find_glyph(c).is_none() {
+            missing.push(c);
+        }
+    }
+    missing
+}
+
+// Collects glyphs missing from each font, from a validation pass over the game's strings
+// or from text as it's first rendered, so localization gaps show up as a list instead of
+// boxes on screen. Each missing glyph is logged once per font.
+pub struct GlyphValidator {
+    // Check text as it's rendered, on by default.
+    pub check_on_render: bool,
+    missing: HashMap<Ticket, Vec<char>>,
+    seen: HashSet<(Ticket, char)>,
+}
+
+impl GlyphValidator {
+    pub fn new() -> Self {
+        GlyphValidator {
+            check_on_render: true,
+            missing: HashMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    pub fn missing(&self, font: Ticket) -> &[char] {
+        self.missing.get(&font).map_or(&[], |m| m.as_slice())
+    }
+
+    pub fn has_missing(&self) -> bool {
+        self.missing.values().any(|m| !m.is_empty())
+    }
+
+    // Printable list of the missing glyphs with their code points.
+    pub fn report(&self, font: Ticket) -> String {
+        let glyphs: Vec<String> = self
+            .missing(font)
+            .iter()
+            .map(|c| format!("'{}' U+{:04X}", c, *c as u32))
+            .collect();
+        glyphs.join(", ")
+    }
+
+    pub fn clear(&mut self) {
+        self.missing.clear();
+        self.seen.clear();
+    }
+
+    // Returns the glyphs from `text` that were not known to be missing before.
+    pub(crate) fn check(&mut self, ticket: Ticket, font: &Font, text: &str) -> Vec<char> {
+        let mut new = Vec::new();
+        for c in missing_glyphs(font, text) {
+            if self.seen.insert((ticket, c)) {
+                self.missing.entry(ticket).or_default().push(c);
+                new.push(c);
+            }
+        }
+
+        if !new.is_empty() {
+            let glyphs: String = new.iter().collect();
+            warn!(
+                "Font {:?} has no glyphs for \"{}\", seen in \"{}\"",
+                font.face_family_name().unwrap_or_default(),
+                glyphs,
+                text
+            );
+        }
+        new
+    }
+}
+
+impl Default for GlyphValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,206 @@
+use std::{collections::HashMap, path::Path};
+
+use sdl2::pixels::PixelFormatEnum;
+#[cfg(all(feature = "sdl2-image", not(feature = "image")))]
+use sdl2::{image::SaveSurface, surface::Surface};
+use stagehand::{app::App, input::ActionType};
+
+use crate::{loading, render::PixelBuffer, SDLApp};
+
+// Scripted action states, keyed by the frame they take effect on. A state holds until the
+// script changes it again, so presses need a matching release entry.
+pub struct InputScript {
+    steps: Vec<(u32, usize, usize, ActionType)>,
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        InputScript { steps: Vec::new() }
+    }
+
+    pub fn set(
+        mut self,
+        frame: u32,
+        user_index: usize,
+        action_index: usize,
+        state: ActionType,
+    ) -> Self {
+        self.steps.push((frame, user_index, action_index, state));
+        self
+    }
+}
+
+impl Default for InputScript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GoldenComparison {
+    pub mismatched: usize,
+    pub total: usize,
+    pub max_difference: u8,
+}
+
+impl GoldenComparison {
+    pub fn passed(&self) -> bool {
+        self.mismatched == 0
+    }
+}
+
+// Must run before initialize_sdl2 so no window is shown; pair it with the software driver.
+pub fn prepare_headless() {
+    std::env::set_var("SDL_VIDEODRIVER", "offscreen");
+    std::env::set_var("SDL_AUDIODRIVER", "dummy");
+}
+
+impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
+    // Steps the app at a fixed timestep without pacing and returns the last frame. Real
+    // input is ignored, so only the script drives actions.
+    pub fn run_frames(
+        &mut self,
+        frames: u32,
+        timestep: f64,
+        script: &InputScript,
+    ) -> Result<PixelBuffer, String> {
+        self.scripted.clear();
+        self.scripted_only = true;
+
+        for frame in 0..frames {
+            self.apply_script(script, frame);
+
+            match self.processed_events() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    self.scripted_only = false;
+                    return Err(e);
+                }
+            }
+            self.update(timestep);
+            self.draw(0.0, (frame as f64 * timestep) as u64);
+        }
+
+        self.scripted_only = false;
+        self.scripted.clear();
+        self.read_pixels(None)
+    }
+
+    pub(crate) fn apply_script(&mut self, script: &InputScript, frame: u32) {
+        for (_, user, action, state) in script.steps.iter().filter(|s| s.0 == frame) {
+            self.scripted.insert((*user, *action), state.clone());
+        }
+    }
+}
+
+// Channels may differ by `tolerance` before a pixel counts as mismatched.
+pub fn compare_golden(
+    buffer: &PixelBuffer,
+    path: &Path,
+    tolerance: u8,
+) -> Result<GoldenComparison, String> {
+    let golden = loading::load_surface_file(&path.to_string_lossy())?
+        .convert_format(PixelFormatEnum::RGBA32)?;
+    if golden.width() != buffer.width || golden.height() != buffer.height {
+        return Err(format!(
+            "Golden image is {}x{}, frame is {}x{}",
+            golden.width(),
+            golden.height(),
+            buffer.width,
+            buffer.height
+        ));
+    }
+
+    let pitch = golden.pitch() as usize;
+    Ok(golden.with_lock(|pixels| compare_pixels(pixels, pitch, buffer, tolerance)))
+}
+
+// `expected` is RGBA32 rows `pitch` bytes apart, at the buffer's size.
+fn compare_pixels(
+    expected: &[u8],
+    pitch: usize,
+    buffer: &PixelBuffer,
+    tolerance: u8,
+) -> GoldenComparison {
+    let row = buffer.width as usize * 4;
+    let mut comparison = GoldenComparison {
+        mismatched: 0,
+        total: (buffer.width * buffer.height) as usize,
+        max_difference: 0,
+    };
+
+    for y in 0..buffer.height as usize {
+        let expected = &expected[y * pitch..y * pitch + row];
+        let actual = &buffer.pixels[y * row..(y + 1) * row];
+        for (e, a) in expected.chunks(4).zip(actual.chunks(4)) {
+            let difference = e
+                .iter()
+                .zip(a)
+                .map(|(e, a)| e.abs_diff(*a))
+                .max()
+                .unwrap_or(0);
+            comparison.max_difference = comparison.max_difference.max(difference);
+            if difference > tolerance {
+                comparison.mismatched += 1;
+            }
+        }
+    }
+
+    comparison
+}
+
+// For blessing a new golden image from a known good run.
+pub fn write_golden(buffer: &PixelBuffer, path: &Path) -> Result<(), String> {
+    #[cfg(feature = "image")]
+    {
+        crate::imagefile::save_file(path, buffer.width, buffer.height, &buffer.pixels)
+    }
+    #[cfg(all(feature = "sdl2-image", not(feature = "image")))]
+    {
+        let mut pixels = buffer.pixels.clone();
+        let surface = Surface::from_data(
+            &mut pixels,
+            buffer.width,
+            buffer.height,
+            buffer.width * 4,
+            PixelFormatEnum::RGBA32,
+        )?;
+        surface.save(path)
+    }
+    #[cfg(not(any(feature = "image", feature = "sdl2-image")))]
+    {
+        Err("Writing images needs the `image` or `sdl2-image` feature".to_string())
+    }
+}
+
+pub(crate) type ScriptedActions = HashMap<(usize, usize), ActionType>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_within_tolerance() {
+        let buffer = PixelBuffer {
+            width: 2,
+            height: 2,
+            pixels: vec![
+                10, 20, 30, 255, 0, 0, 0, 255, //
+                0, 0, 0, 0, 255, 255, 255, 255,
+            ],
+        };
+        // Padded rows, as surfaces have, with one pixel off by 2 and one off by 40.
+        let golden = [
+            12, 20, 30, 255, 0, 0, 0, 255, 9, 9, //
+            0, 0, 0, 0, 215, 255, 255, 255, 9, 9,
+        ];
+
+        let comparison = compare_pixels(&golden, 10, &buffer, 2);
+        assert_eq!(comparison.total, 4);
+        assert_eq!(comparison.mismatched, 1);
+        assert_eq!(comparison.max_difference, 40);
+        assert!(!comparison.passed());
+        assert!(compare_pixels(&golden, 10, &buffer, 40).passed());
+    }
+}
@@ -0,0 +1,89 @@
+use stagehand::draw::DrawRect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitEvent {
+    Enter(u64),
+    Leave(u64),
+    Press(u64),
+    Click(u64),
+}
+
+pub struct HitRegions {
+    regions: Vec<(u64, DrawRect)>,
+    hovered: Option<u64>,
+    pressed: Option<u64>,
+    events: Vec<HitEvent>,
+    was_down: bool,
+}
+
+impl HitRegions {
+    pub fn new() -> Self {
+        HitRegions {
+            regions: Vec::new(),
+            hovered: None,
+            pressed: None,
+            events: Vec::new(),
+            was_down: false,
+        }
+    }
+
+    // Regions are only kept for the frame they were registered in; later registrations win overlaps.
+    pub fn register(&mut self, id: u64, rect: DrawRect) {
+        self.regions.push((id, rect));
+    }
+
+    pub fn hovered(&self) -> Option<u64> {
+        self.hovered
+    }
+
+    pub fn events(&self) -> &[HitEvent] {
+        &self.events
+    }
+
+    pub fn is_clicked(&self, id: u64) -> bool {
+        self.events.contains(&HitEvent::Click(id))
+    }
+
+    pub(crate) fn resolve(&mut self, x: f32, y: f32, down: bool) {
+        self.events.clear();
+
+        let hit = self
+            .regions
+            .iter()
+            .rev()
+            .find(|(_, r)| x >= r.x && y >= r.y && x < r.x + r.width && y < r.y + r.height)
+            .map(|(id, _)| *id);
+
+        if hit != self.hovered {
+            if let Some(id) = self.hovered {
+                self.events.push(HitEvent::Leave(id));
+            }
+            if let Some(id) = hit {
+                self.events.push(HitEvent::Enter(id));
+            }
+            self.hovered = hit;
+        }
+
+        if down && !self.was_down {
+            self.pressed = hit;
+            if let Some(id) = hit {
+                self.events.push(HitEvent::Press(id));
+            }
+        } else if !down && self.was_down {
+            if let Some(id) = self.pressed.take() {
+                if hit == Some(id) {
+                    self.events.push(HitEvent::Click(id));
+                }
+            }
+        }
+
+        self.was_down = down;
+        self.regions.clear();
+    }
+}
+
+impl Default for HitRegions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
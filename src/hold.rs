@@ -0,0 +1,115 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RepeatRate {
+    pub delay: u64,
+    pub interval: u64,
+}
+
+struct Hold {
+    since: u64,
+    next_pulse: Option<u64>,
+    pulsed: bool,
+}
+
+pub struct ActionHolds {
+    holds: HashMap<(usize, usize), Hold>,
+    rates: HashMap<(usize, usize), RepeatRate>,
+    navigation: HashSet<(usize, usize)>,
+    pub navigation_rate: RepeatRate,
+    now: u64,
+}
+
+impl ActionHolds {
+    pub fn new() -> Self {
+        ActionHolds {
+            holds: HashMap::new(),
+            rates: HashMap::new(),
+            navigation: HashSet::new(),
+            navigation_rate: RepeatRate {
+                delay: 400,
+                interval: 100,
+            },
+            now: 0,
+        }
+    }
+
+    pub fn set_repeat(&mut self, user_index: usize, action_index: usize, rate: Option<RepeatRate>) {
+        match rate {
+            Some(r) => self.rates.insert((user_index, action_index), r),
+            None => self.rates.remove(&(user_index, action_index)),
+        };
+    }
+
+    // Navigation actions report down only on pulse frames, so a held DPad or arrow key
+    // reads as repeated presses. They all share `navigation_rate` unless given their own.
+    pub fn set_navigation(&mut self, user_index: usize, action_index: usize, navigation: bool) {
+        match navigation {
+            true => self.navigation.insert((user_index, action_index)),
+            false => self.navigation.remove(&(user_index, action_index)),
+        };
+    }
+
+    pub fn is_navigation(&self, user_index: usize, action_index: usize) -> bool {
+        self.navigation.contains(&(user_index, action_index))
+    }
+
+    pub fn held_for(&self, user_index: usize, action_index: usize) -> Option<u64> {
+        self.holds
+            .get(&(user_index, action_index))
+            .map(|h| self.now.saturating_sub(h.since))
+    }
+
+    // True on the frame the action goes down and then on every repeat tick while held.
+    pub fn pulsed(&self, user_index: usize, action_index: usize) -> bool {
+        self.holds
+            .get(&(user_index, action_index))
+            .map_or(false, |h| h.pulsed)
+    }
+
+    pub(crate) fn record(&mut self, user_index: usize, action_index: usize, down: bool, now: u64) {
+        self.now = now;
+        let key = (user_index, action_index);
+
+        if !down {
+            self.holds.remove(&key);
+            return;
+        }
+
+        let rate = match self.navigation.contains(&key) {
+            true => self.rates.get(&key).copied().or(Some(self.navigation_rate)),
+            false => self.rates.get(&key).copied(),
+        };
+        match self.holds.get_mut(&key) {
+            Some(hold) => {
+                hold.pulsed = false;
+                if let (Some(next), Some(rate)) = (hold.next_pulse, rate) {
+                    // Ticks missed during a stall are skipped rather than caught up, so a
+                    // long frame pulses once and the cadence carries on from there.
+                    if now >= next {
+                        let interval = rate.interval.max(1);
+                        let missed = (now - next) / interval;
+                        hold.pulsed = true;
+                        hold.next_pulse = Some(next + (missed + 1) * interval);
+                    }
+                }
+            }
+            None => {
+                self.holds.insert(
+                    key,
+                    Hold {
+                        since: now,
+                        next_pulse: rate.map(|r| now + r.delay),
+                        pulsed: true,
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl Default for ActionHolds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
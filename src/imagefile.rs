@@ -0,0 +1,22 @@
+use std::path::Path;
+
+use image::ColorType;
+
+use crate::compressed::DecodedImage;
+
+// Decodes images in Rust with the `image` crate, so games that only use the common formats
+// can ship without the SDL2_image library. Built with PNG, JPEG, BMP, GIF and TGA support.
+pub fn decode_file(path: &str) -> Result<DecodedImage, String> {
+    let image = image::open(path).map_err(|e| format!("Could not decode {}: {}", path, e))?;
+    let rgba = image.into_rgba8();
+    Ok(DecodedImage {
+        width: rgba.width(),
+        height: rgba.height(),
+        pixels: rgba.into_raw(),
+    })
+}
+
+// `pixels` is tightly packed RGBA32, the format follows the file extension.
+pub fn save_file(path: &Path, width: u32, height: u32, pixels: &[u8]) -> Result<(), String> {
+    image::save_buffer(path, pixels, width, height, ColorType::Rgba8).map_err(|e| e.to_string())
+}
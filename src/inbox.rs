@@ -0,0 +1,73 @@
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, SyncSender, TryRecvError},
+};
+
+pub struct MessageInbox<Message> {
+    // Caps how many messages move from the channel per update, the rest wait in the channel
+    // and keep it full so senders see backpressure.
+    pub per_update: usize,
+    receiver: Option<Receiver<Message>>,
+    pending: VecDeque<Message>,
+}
+
+impl<Message> MessageInbox<Message> {
+    pub fn new() -> Self {
+        MessageInbox {
+            per_update: 256,
+            receiver: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Message> {
+        self.pending.pop_front()
+    }
+
+    pub fn drain(&mut self) -> Vec<Message> {
+        self.pending.drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // A new channel replaces the old one; senders for the old one start failing.
+    pub(crate) fn open(&mut self, capacity: usize) -> SyncSender<Message> {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        self.receiver = Some(receiver);
+        sender
+    }
+
+    pub(crate) fn deliver(&mut self, message: Message) {
+        self.pending.push_back(message);
+    }
+
+    pub(crate) fn pump(&mut self) {
+        let receiver = match &self.receiver {
+            Some(r) => r,
+            None => return,
+        };
+
+        for _ in 0..self.per_update {
+            match receiver.try_recv() {
+                Ok(m) => self.pending.push_back(m),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.receiver = None;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl<Message> Default for MessageInbox<Message> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
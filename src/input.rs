@@ -1,21 +1,264 @@
 use sdl2::{
-    controller::{Axis, Button},
-    keyboard::Scancode,
+    controller::{Axis, Button, GameController},
+    keyboard::{Keycode, Mod, Scancode},
     mouse::MouseButton,
-    sys::{SDL_JOYSTICK_AXIS_MAX, SDL_JOYSTICK_AXIS_MIN},
+    sys::{
+        SDL_GameControllerFromInstanceID, SDL_GameControllerGetSensorData,
+        SDL_GameControllerGetTouchpadFinger, SDL_GameControllerHasSensor,
+        SDL_GameControllerSetSensorEnabled, SDL_SensorType, SDL_bool, SDL_JOYSTICK_AXIS_MAX,
+        SDL_JOYSTICK_AXIS_MIN,
+    },
 };
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Chord<T> {
+    All(Vec<T>),
+    Any(Vec<T>),
+}
+
+impl<T> Chord<T> {
+    pub fn is_active<F>(&self, pressed: F) -> bool
+    where
+        F: Fn(&T) -> bool,
+    {
+        match self {
+            Chord::All(v) => !v.is_empty() && v.iter().all(pressed),
+            Chord::Any(v) => v.iter().any(pressed),
+        }
+    }
+
+    pub fn items(&self) -> &[T] {
+        match self {
+            Chord::All(v) | Chord::Any(v) => v,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum SDLCommand {
-    Key(Vec<Scancode>),
-    MouseButton(Vec<MouseButton>),
+    Key(Chord<Scancode>),
+    KeyCode(Chord<Keycode>, Mod),
+    MouseButton(Chord<MouseButton>),
+    // Down on the frame of at least this many quick presses, 2 for a double click, even
+    // when the button came back up within the frame, and for as long as it stays held.
+    // Thresholds are set on `Clicks`.
+    MultiClick(MouseButton, u8),
     MousePosition,
+    // Scrolling this frame in notches, positive to the right and away from the user, with
+    // fractions from trackpads and the platform's natural scrolling undone.
+    MouseWheel,
     Gamepad(SDLGamepadFeature, Option<usize>),
+    Touch(usize),
+    DigitalAxis {
+        negative: Box<SDLCommand>,
+        positive: Box<SDLCommand>,
+    },
+    DigitalStick {
+        up: Box<SDLCommand>,
+        down: Box<SDLCommand>,
+        left: Box<SDLCommand>,
+        right: Box<SDLCommand>,
+        normalize: bool,
+    },
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum SDLGamepadFeature {
-    Button(Vec<Button>),
+    Button(Chord<Button>),
     Axis(Axis),
     Stick(Axis, Axis),
+    Gyro { scale: f32 },
+    Accelerometer { scale: f32 },
+    Touchpad(usize),
+}
+
+impl SDLCommand {
+    pub fn key(key: Scancode) -> Self {
+        SDLCommand::Key(Chord::All(vec![key]))
+    }
+
+    pub fn keys(keys: Vec<Scancode>) -> Self {
+        SDLCommand::Key(Chord::All(keys))
+    }
+
+    pub fn any_key(keys: Vec<Scancode>) -> Self {
+        SDLCommand::Key(Chord::Any(keys))
+    }
+
+    pub fn keycode(key: Keycode) -> Self {
+        SDLCommand::KeyCode(Chord::All(vec![key]), Mod::NOMOD)
+    }
+
+    pub fn keycode_with(key: Keycode, modifiers: Mod) -> Self {
+        SDLCommand::KeyCode(Chord::All(vec![key]), modifiers)
+    }
+
+    // Swap a scancode binding for the key the current layout places there.
+    pub fn to_keycode(self) -> Self {
+        match self {
+            SDLCommand::Key(chord) => {
+                SDLCommand::KeyCode(map_chord(chord, Keycode::from_scancode), Mod::NOMOD)
+            }
+            other => other,
+        }
+    }
+
+    // Swap a keycode binding for the physical key that produces it on the current layout.
+    // Scancode bindings have no modifiers, so one that needs any stays a keycode binding.
+    pub fn to_scancode(self) -> Self {
+        match self {
+            SDLCommand::KeyCode(chord, modifiers) if modifiers.is_empty() => {
+                SDLCommand::Key(map_chord(chord, Scancode::from_keycode))
+            }
+            other => other,
+        }
+    }
+
+    pub fn key_axis(negative: Scancode, positive: Scancode) -> Self {
+        SDLCommand::DigitalAxis {
+            negative: Box::new(SDLCommand::key(negative)),
+            positive: Box::new(SDLCommand::key(positive)),
+        }
+    }
+
+    // Y follows stick convention, so `up` reads as negative.
+    pub fn key_stick(up: Scancode, down: Scancode, left: Scancode, right: Scancode) -> Self {
+        SDLCommand::DigitalStick {
+            up: Box::new(SDLCommand::key(up)),
+            down: Box::new(SDLCommand::key(down)),
+            left: Box::new(SDLCommand::key(left)),
+            right: Box::new(SDLCommand::key(right)),
+            normalize: true,
+        }
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        SDLCommand::MouseButton(Chord::All(vec![button]))
+    }
+
+    pub fn double_click(button: MouseButton) -> Self {
+        SDLCommand::MultiClick(button, 2)
+    }
+
+    pub fn button(button: Button) -> Self {
+        SDLCommand::Gamepad(SDLGamepadFeature::Button(Chord::All(vec![button])), None)
+    }
+
+    pub fn button_on(button: Button, controller: usize) -> Self {
+        SDLCommand::Gamepad(
+            SDLGamepadFeature::Button(Chord::All(vec![button])),
+            Some(controller),
+        )
+    }
+
+    pub fn axis(axis: Axis) -> Self {
+        SDLCommand::Gamepad(SDLGamepadFeature::Axis(axis), None)
+    }
+
+    pub fn stick(x: Axis, y: Axis) -> Self {
+        SDLCommand::Gamepad(SDLGamepadFeature::Stick(x, y), None)
+    }
+
+    pub fn on_controller(self, controller: usize) -> Self {
+        match self {
+            SDLCommand::Gamepad(feature, _) => SDLCommand::Gamepad(feature, Some(controller)),
+            other => other,
+        }
+    }
+}
+
+fn map_chord<T, U, F>(chord: Chord<T>, f: F) -> Chord<U>
+where
+    F: Fn(T) -> Option<U>,
+{
+    match chord {
+        Chord::All(v) => Chord::All(v.into_iter().filter_map(f).collect()),
+        Chord::Any(v) => Chord::Any(v.into_iter().filter_map(f).collect()),
+    }
+}
+
+pub fn keycode_to_scancode(key: Keycode) -> Option<Scancode> {
+    Scancode::from_keycode(key)
+}
+
+pub fn scancode_to_keycode(key: Scancode) -> Option<Keycode> {
+    Keycode::from_scancode(key)
+}
+
+// Name of the key the current layout places at this physical position, as printed on
+// the keycap: "Z" for the bottom-left letter on a QWERTZ board.
+pub fn scancode_display_name(key: Scancode) -> String {
+    match Keycode::from_scancode(key) {
+        Some(code) => keycode_display_name(code),
+        None => scancode_fallback_name(key),
+    }
+}
+
+pub fn keycode_display_name(key: Keycode) -> String {
+    let name = key.name();
+    if !name.is_empty() {
+        return name;
+    }
+    match Scancode::from_keycode(key) {
+        Some(code) => scancode_fallback_name(code),
+        None => format!("Key {}", key as i32),
+    }
+}
+
+// Keys SDL has no name for still need something to show in a bindings list.
+fn scancode_fallback_name(key: Scancode) -> String {
+    match key.name() {
+        "" => format!("Key {}", key as i32),
+        name => name.to_string(),
+    }
+}
+
+// Each modifier group in `required` must be held on either side.
+pub fn modifiers_held(required: Mod, current: Mod) -> bool {
+    [
+        Mod::LSHIFTMOD | Mod::RSHIFTMOD,
+        Mod::LCTRLMOD | Mod::RCTRLMOD,
+        Mod::LALTMOD | Mod::RALTMOD,
+        Mod::LGUIMOD | Mod::RGUIMOD,
+    ]
+    .iter()
+    .all(|group| !required.intersects(*group) || current.intersects(*group))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ControllerCapabilities {
+    pub rumble: bool,
+    pub trigger_rumble: bool,
+    pub led: bool,
+    pub gyro: bool,
+    pub accelerometer: bool,
+}
+
+impl ControllerCapabilities {
+    pub fn query(controller: &GameController) -> Self {
+        ControllerCapabilities {
+            rumble: controller.has_rumble(),
+            trigger_rumble: controller.has_rumble_triggers(),
+            led: controller.has_led(),
+            gyro: has_sensor(controller, SDLSensor::Gyro),
+            accelerometer: has_sensor(controller, SDLSensor::Accelerometer),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SDLSensor {
+    Gyro,
+    Accelerometer,
+}
+
+impl SDLSensor {
+    fn raw(self) -> SDL_SensorType {
+        match self {
+            SDLSensor::Gyro => SDL_SensorType::SDL_SENSOR_GYRO,
+            SDLSensor::Accelerometer => SDL_SensorType::SDL_SENSOR_ACCEL,
+        }
+    }
 }
 
 pub fn translate_axis(axis: i16) -> f32 {
@@ -25,3 +268,77 @@ pub fn translate_axis(axis: i16) -> f32 {
         -(axis as f32 / SDL_JOYSTICK_AXIS_MIN as f32)
     }
 }
+
+// Drops the stick inside a circle of radius `deadzone`, clamps diagonals to a unit
+// magnitude and rescales the rest so output still starts from zero at the deadzone edge.
+pub fn radial_deadzone(x: f32, y: f32, deadzone: f32) -> Option<(f32, f32)> {
+    let magnitude = (x * x + y * y).sqrt();
+    if magnitude < deadzone || magnitude <= 0.0 {
+        return None;
+    }
+
+    let deadzone = deadzone.clamp(0.0, 0.99);
+    let scaled = (magnitude.min(1.0) - deadzone) / (1.0 - deadzone);
+    Some((x / magnitude * scaled, y / magnitude * scaled))
+}
+
+pub fn has_sensor(controller: &GameController, sensor: SDLSensor) -> bool {
+    unsafe {
+        let raw = SDL_GameControllerFromInstanceID(controller.instance_id() as i32);
+        !raw.is_null() && SDL_GameControllerHasSensor(raw, sensor.raw()) == SDL_bool::SDL_TRUE
+    }
+}
+
+pub fn enable_sensors(controller: &GameController) {
+    for sensor in [SDLSensor::Gyro, SDLSensor::Accelerometer] {
+        if !has_sensor(controller, sensor) {
+            continue;
+        }
+
+        unsafe {
+            let raw = SDL_GameControllerFromInstanceID(controller.instance_id() as i32);
+            SDL_GameControllerSetSensorEnabled(raw, sensor.raw(), SDL_bool::SDL_TRUE);
+        }
+    }
+}
+
+pub fn read_sensor(controller: &GameController, sensor: SDLSensor) -> Option<[f32; 3]> {
+    let mut data = [0.0f32; 3];
+    let result = unsafe {
+        let raw = SDL_GameControllerFromInstanceID(controller.instance_id() as i32);
+        if raw.is_null() {
+            return None;
+        }
+        SDL_GameControllerGetSensorData(raw, sensor.raw(), data.as_mut_ptr(), 3)
+    };
+
+    match result {
+        0 => Some(data),
+        _ => None,
+    }
+}
+
+// Returns the normalized finger position while it is touching the first touchpad.
+pub fn read_touchpad(controller: &GameController, finger: usize) -> Option<(f32, f32)> {
+    let (mut state, mut x, mut y, mut pressure) = (0u8, 0.0f32, 0.0f32, 0.0f32);
+    let result = unsafe {
+        let raw = SDL_GameControllerFromInstanceID(controller.instance_id() as i32);
+        if raw.is_null() {
+            return None;
+        }
+        SDL_GameControllerGetTouchpadFinger(
+            raw,
+            0,
+            finger as i32,
+            &mut state,
+            &mut x,
+            &mut y,
+            &mut pressure,
+        )
+    };
+
+    match (result, state) {
+        (0, 1) => Some((x, y)),
+        _ => None,
+    }
+}
@@ -0,0 +1,72 @@
+use std::f64::consts::TAU;
+
+use crate::render::AngleUnit;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterpolationMode {
+    Linear,
+    // Draws always show the latest update, for pixel art that shouldn't land between pixels.
+    Disabled,
+}
+
+// The blend factor between the previous and the latest update for the frame being drawn,
+// with helpers so every scene interpolates the same way.
+pub struct Interpolation {
+    pub mode: InterpolationMode,
+    pub unit: AngleUnit,
+    alpha: f64,
+}
+
+impl Interpolation {
+    pub fn new() -> Self {
+        Interpolation {
+            mode: InterpolationMode::Linear,
+            unit: AngleUnit::Degrees,
+            alpha: 1.0,
+        }
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn lerp(&self, previous: f32, current: f32) -> f32 {
+        previous + (current - previous) * self.alpha as f32
+    }
+
+    pub fn lerp_point(&self, previous: (f32, f32), current: (f32, f32)) -> (f32, f32) {
+        (
+            self.lerp(previous.0, current.0),
+            self.lerp(previous.1, current.1),
+        )
+    }
+
+    // Takes the short way around, so 350 to 10 degrees passes through 0 rather than 180.
+    pub fn lerp_angle(&self, previous: f32, current: f32) -> f32 {
+        let turn = match self.unit {
+            AngleUnit::Degrees => 360.0,
+            AngleUnit::Radians => TAU,
+        };
+        let (previous, current) = (previous as f64, current as f64);
+        let difference = (current - previous).rem_euclid(turn);
+        let difference = match difference > turn / 2.0 {
+            true => difference - turn,
+            false => difference,
+        };
+        (previous + difference * self.alpha) as f32
+    }
+
+    pub(crate) fn set(&mut self, raw: f64) -> f64 {
+        self.alpha = match self.mode {
+            InterpolationMode::Linear => raw.clamp(0.0, 1.0),
+            InterpolationMode::Disabled => 1.0,
+        };
+        self.alpha
+    }
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
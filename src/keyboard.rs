@@ -0,0 +1,267 @@
+use sdl2::{
+    controller::Button,
+    event::Event,
+    keyboard::{Keycode, Mod, Scancode},
+    rect::Rect,
+};
+use stagehand::loading::Ticket;
+
+use crate::{focus::FocusDirection, overlay::Anchor};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyboardKey {
+    Char(char),
+    Shift,
+    Space,
+    Backspace,
+    Done,
+}
+
+impl KeyboardKey {
+    pub fn label(&self, shift: bool) -> String {
+        match self {
+            KeyboardKey::Char(c) if shift => c.to_uppercase().collect(),
+            KeyboardKey::Char(c) => c.to_string(),
+            KeyboardKey::Shift => "Shift".to_string(),
+            KeyboardKey::Space => "Space".to_string(),
+            KeyboardKey::Backspace => "Del".to_string(),
+            KeyboardKey::Done => "Done".to_string(),
+        }
+    }
+
+    // Wide keys take this many regular key widths.
+    fn span(&self) -> u32 {
+        match self {
+            KeyboardKey::Char(_) => 1,
+            KeyboardKey::Space => 4,
+            _ => 2,
+        }
+    }
+}
+
+// Navigated with the dpad, A types, B deletes, X toggles shift, Y adds a space and Start
+// finishes. Output reaches event hooks and senders as regular text input and key events,
+// so text fields don't need to know where it came from.
+pub struct OnScreenKeyboard {
+    pub font: Option<Ticket>,
+    pub anchor: Anchor,
+    pub offset: (i32, i32),
+    pub key_size: (u32, u32),
+    pub spacing: u32,
+    rows: Vec<Vec<KeyboardKey>>,
+    cursor: (usize, usize),
+    shift: bool,
+    open: bool,
+}
+
+impl OnScreenKeyboard {
+    pub fn new() -> Self {
+        let mut rows: Vec<Vec<KeyboardKey>> =
+            ["1234567890", "qwertyuiop", "asdfghjkl-", "zxcvbnm.,'"]
+                .iter()
+                .map(|r| r.chars().map(KeyboardKey::Char).collect())
+                .collect();
+        rows.push(vec![
+            KeyboardKey::Shift,
+            KeyboardKey::Space,
+            KeyboardKey::Backspace,
+            KeyboardKey::Done,
+        ]);
+
+        OnScreenKeyboard {
+            font: None,
+            anchor: Anchor::BottomLeft,
+            offset: (16, 16),
+            key_size: (40, 40),
+            spacing: 4,
+            rows,
+            cursor: (0, 0),
+            shift: false,
+            open: false,
+        }
+    }
+
+    pub fn set_rows(&mut self, rows: Vec<Vec<KeyboardKey>>) {
+        self.rows = rows;
+        self.cursor = (0, 0);
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.cursor = (0, 0);
+        self.shift = false;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open && !self.rows.is_empty()
+    }
+
+    pub fn is_shifted(&self) -> bool {
+        self.shift
+    }
+
+    pub fn selected(&self) -> Option<KeyboardKey> {
+        self.rows.get(self.cursor.0)?.get(self.cursor.1).copied()
+    }
+
+    pub fn navigate(&mut self, direction: FocusDirection) {
+        if !self.is_open() {
+            return;
+        }
+
+        let (row, column) = self.cursor;
+        let rows = self.rows.len();
+        let row = match direction {
+            FocusDirection::Up => (row + rows - 1) % rows,
+            FocusDirection::Down => (row + 1) % rows,
+            _ => row,
+        };
+        let length = self.rows[row].len().max(1);
+        let column = match direction {
+            FocusDirection::Left => (column.min(length - 1) + length - 1) % length,
+            FocusDirection::Right => (column.min(length - 1) + 1) % length,
+            _ => column.min(length - 1),
+        };
+        self.cursor = (row, column);
+    }
+
+    // Events to deliver for the selected key.
+    pub fn press(&mut self, window_id: u32) -> Vec<Event> {
+        match self.selected() {
+            Some(key) => self.activate(key, window_id),
+            None => Vec::new(),
+        }
+    }
+
+    // Buttons the open keyboard reads, which are kept from game actions and focus
+    // navigation until it closes.
+    pub(crate) fn captures(&self, button: Button) -> bool {
+        self.is_open()
+            && matches!(
+                button,
+                Button::DPadUp
+                    | Button::DPadDown
+                    | Button::DPadLeft
+                    | Button::DPadRight
+                    | Button::A
+                    | Button::B
+                    | Button::X
+                    | Button::Y
+                    | Button::Start
+            )
+    }
+
+    pub(crate) fn handle(&mut self, event: &Event, window_id: u32) -> Vec<Event> {
+        if !self.is_open() {
+            return Vec::new();
+        }
+
+        let button = match event {
+            Event::ControllerButtonDown { button, .. } => *button,
+            _ => return Vec::new(),
+        };
+
+        match button {
+            Button::DPadUp => self.navigate(FocusDirection::Up),
+            Button::DPadDown => self.navigate(FocusDirection::Down),
+            Button::DPadLeft => self.navigate(FocusDirection::Left),
+            Button::DPadRight => self.navigate(FocusDirection::Right),
+            Button::A => return self.press(window_id),
+            Button::B => return self.activate(KeyboardKey::Backspace, window_id),
+            Button::X => return self.activate(KeyboardKey::Shift, window_id),
+            Button::Y => return self.activate(KeyboardKey::Space, window_id),
+            Button::Start => return self.activate(KeyboardKey::Done, window_id),
+            _ => (),
+        }
+        Vec::new()
+    }
+
+    // Rect, label and whether it's selected for every key, relative to `bounds`.
+    pub(crate) fn layout(&self, bounds: (u32, u32)) -> Vec<(Rect, String, bool)> {
+        let (kw, kh) = self.key_size;
+        let step = kw + self.spacing;
+        let width = self
+            .rows
+            .iter()
+            .map(|r| r.iter().map(|k| k.span() * step).sum::<u32>())
+            .max()
+            .unwrap_or(0);
+        let height = self.rows.len() as u32 * (kh + self.spacing);
+        let (x, y) = self.anchor.place(bounds, (width, height), self.offset);
+
+        let mut keys = Vec::new();
+        for (r, row) in self.rows.iter().enumerate() {
+            let mut kx = x;
+            let ky = y + r as i32 * (kh + self.spacing) as i32;
+            for (c, key) in row.iter().enumerate() {
+                let w = key.span() * step - self.spacing;
+                keys.push((
+                    Rect::new(kx, ky, w, kh),
+                    key.label(self.shift),
+                    (r, c) == self.cursor,
+                ));
+                kx += (w + self.spacing) as i32;
+            }
+        }
+        keys
+    }
+
+    fn activate(&mut self, key: KeyboardKey, window_id: u32) -> Vec<Event> {
+        let text = |text: String| Event::TextInput {
+            timestamp: 0,
+            window_id,
+            text,
+        };
+
+        match key {
+            KeyboardKey::Char(_) => {
+                let typed = key.label(self.shift);
+                self.shift = false;
+                vec![text(typed)]
+            }
+            KeyboardKey::Space => vec![text(" ".to_string())],
+            KeyboardKey::Shift => {
+                self.shift = !self.shift;
+                Vec::new()
+            }
+            KeyboardKey::Backspace => {
+                key_events(Keycode::Backspace, Scancode::Backspace, window_id)
+            }
+            KeyboardKey::Done => {
+                self.open = false;
+                key_events(Keycode::Return, Scancode::Return, window_id)
+            }
+        }
+    }
+}
+
+impl Default for OnScreenKeyboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn key_events(keycode: Keycode, scancode: Scancode, window_id: u32) -> Vec<Event> {
+    vec![
+        Event::KeyDown {
+            timestamp: 0,
+            window_id,
+            keycode: Some(keycode),
+            scancode: Some(scancode),
+            keymod: Mod::NOMOD,
+            repeat: false,
+        },
+        Event::KeyUp {
+            timestamp: 0,
+            window_id,
+            keycode: Some(keycode),
+            scancode: Some(scancode),
+            keymod: Mod::NOMOD,
+            repeat: false,
+        },
+    ]
+}
@@ -0,0 +1,144 @@
+use std::collections::{HashMap, HashSet};
+
+use log::warn;
+use sdl2::pixels::Color;
+use stagehand::{draw::DrawData, loading::Ticket};
+
+use crate::loading::{AssetKind, SDLStorage};
+
+// The last field is the batch the draw goes after, like `DrawRepeated::after_batch`.
+pub(crate) enum KeyedDraw {
+    Texture(String, DrawData, Option<usize>),
+    Text(String, String, Color, DrawData, Option<usize>),
+}
+
+impl KeyedDraw {
+    pub(crate) fn after_batch(&self) -> Option<usize> {
+        match self {
+            KeyedDraw::Texture(.., after) | KeyedDraw::Text(.., after) => *after,
+        }
+    }
+}
+
+pub(crate) enum KeyedSound {
+    Sound(String, f32),
+    Music(String, i32, f32),
+}
+
+// Draws and sounds that name their resource by storage key instead of ticket, for
+// prototypes that don't want to collect tickets during initialization first. Keys resolve
+// when the instruction runs and the ticket is cached from then on. Keyed draws render
+// above the stage's batches unless queued after one.
+pub struct KeyedInstructions {
+    draws: Vec<KeyedDraw>,
+    sounds: Vec<KeyedSound>,
+    tickets: HashMap<(AssetKind, String), Ticket>,
+    warned: HashSet<(AssetKind, String)>,
+}
+
+impl KeyedInstructions {
+    pub fn new() -> Self {
+        KeyedInstructions {
+            draws: Vec::new(),
+            sounds: Vec::new(),
+            tickets: HashMap::new(),
+            warned: HashSet::new(),
+        }
+    }
+
+    // Draws are queued for the next frame only.
+    pub fn draw(&mut self, texture: &str, data: DrawData) {
+        self.draws
+            .push(KeyedDraw::Texture(texture.to_string(), data, None));
+    }
+
+    pub fn draw_text(&mut self, font: &str, text: &str, color: Color, data: DrawData) {
+        self.draws.push(KeyedDraw::Text(
+            font.to_string(),
+            text.to_string(),
+            color,
+            data,
+            None,
+        ));
+    }
+
+    // Drawn right after `batch` and skipped along with it when it's hidden.
+    pub fn draw_after(&mut self, batch: usize, texture: &str, data: DrawData) {
+        self.draws
+            .push(KeyedDraw::Texture(texture.to_string(), data, Some(batch)));
+    }
+
+    pub fn draw_text_after(
+        &mut self,
+        batch: usize,
+        font: &str,
+        text: &str,
+        color: Color,
+        data: DrawData,
+    ) {
+        self.draws.push(KeyedDraw::Text(
+            font.to_string(),
+            text.to_string(),
+            color,
+            data,
+            Some(batch),
+        ));
+    }
+
+    pub fn play_sound(&mut self, sound: &str, volume: f32) {
+        self.sounds
+            .push(KeyedSound::Sound(sound.to_string(), volume));
+    }
+
+    pub fn play_music(&mut self, music: &str, loops: i32, volume: f32) {
+        self.sounds
+            .push(KeyedSound::Music(music.to_string(), loops, volume));
+    }
+
+    // Drops cached tickets, for after a key was loaded again under a new ticket.
+    pub fn clear_cache(&mut self) {
+        self.tickets.clear();
+        self.warned.clear();
+    }
+
+    pub(crate) fn take_draws(&mut self) -> Vec<KeyedDraw> {
+        std::mem::take(&mut self.draws)
+    }
+
+    pub(crate) fn take_sounds(&mut self) -> Vec<KeyedSound> {
+        std::mem::take(&mut self.sounds)
+    }
+
+    // Unknown keys are retried every time, but only reported once.
+    pub(crate) fn resolve(
+        &mut self,
+        storage: &SDLStorage,
+        kind: AssetKind,
+        key: &str,
+    ) -> Option<Ticket> {
+        let entry = (kind, key.to_string());
+        if let Some(ticket) = self.tickets.get(&entry) {
+            return Some(*ticket);
+        }
+
+        match storage.ticket(kind, key) {
+            Ok(ticket) => {
+                self.warned.remove(&entry);
+                self.tickets.insert(entry, ticket);
+                Some(ticket)
+            }
+            Err(_) => {
+                if self.warned.insert(entry) {
+                    warn!("No {:?} loaded under the key '{}'", kind, key);
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Default for KeyedInstructions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::{HashMap, HashSet};
+
+use sdl2::keyboard::Scancode;
+
+// One half of a shared keyboard, acting as its own device.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyboardProfile {
+    keys: HashSet<Scancode>,
+}
+
+impl KeyboardProfile {
+    pub fn new(keys: &[Scancode]) -> Self {
+        KeyboardProfile {
+            keys: keys.iter().copied().collect(),
+        }
+    }
+
+    // The left-hand cluster around WASD.
+    pub fn wasd() -> Self {
+        use Scancode::*;
+        Self::new(&[
+            W, A, S, D, Q, E, R, F, Z, X, C, V, Tab, CapsLock, LShift, LCtrl, LAlt, Space, Num1,
+            Num2, Num3, Num4, Num5,
+        ])
+    }
+
+    // The arrow keys and everything within reach of the right hand around them.
+    pub fn arrows() -> Self {
+        use Scancode::*;
+        Self::new(&[
+            Up, Down, Left, Right, RShift, RCtrl, RAlt, Return, Backspace, Delete, End, PageDown,
+            Insert, Home, PageUp, Slash, Period, Comma, Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7,
+            Kp8, Kp9, KpEnter, KpPeriod, KpPlus, KpMinus,
+        ])
+    }
+
+    pub fn contains(&self, key: Scancode) -> bool {
+        self.keys.contains(&key)
+    }
+}
+
+// Splits one keyboard between local players. A user assigned a profile only reads the keys
+// in it, so a binding shared through the input map can't fire for the other player. Users
+// without a profile read the whole keyboard, which keeps menus usable by either player.
+pub struct KeyboardSplit {
+    profiles: HashMap<String, KeyboardProfile>,
+    users: HashMap<usize, String>,
+}
+
+impl KeyboardSplit {
+    pub fn new() -> Self {
+        KeyboardSplit {
+            profiles: HashMap::new(),
+            users: HashMap::new(),
+        }
+    }
+
+    pub fn define(&mut self, name: &str, profile: KeyboardProfile) {
+        self.profiles.insert(name.to_string(), profile);
+    }
+
+    pub fn assign(&mut self, user_index: usize, profile: Option<&str>) {
+        match profile {
+            Some(p) => self.users.insert(user_index, p.to_string()),
+            None => self.users.remove(&user_index),
+        };
+    }
+
+    pub fn profile_name(&self, user_index: usize) -> Option<&str> {
+        self.users.get(&user_index).map(|p| p.as_str())
+    }
+
+    // Users assigned a profile that was never defined read no keys at all.
+    pub(crate) fn allows(&self, user_index: usize, key: Scancode) -> bool {
+        match self.users.get(&user_index) {
+            Some(name) => self.profiles.get(name).map_or(false, |p| p.contains(key)),
+            None => true,
+        }
+    }
+}
+
+impl Default for KeyboardSplit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,15 +1,25 @@
 use loading::{FontLoader, TextureLoader};
-use log::{error, warn};
+use log::{error, info, warn};
 use sdl2::{
     controller::GameController,
-    mixer::{InitFlag, AUDIO_S16LSB, DEFAULT_CHANNELS},
-    pixels::Color,
-    rect::{Point, Rect},
-    render::{Canvas, Texture, TextureCreator},
-    video::{Window, WindowContext},
+    event::Event,
+    messagebox::MessageBoxFlag,
+    mixer::{Channel, InitFlag, AUDIO_S16LSB, DEFAULT_CHANNELS},
+    pixels::{Color, PixelFormatEnum},
+    rect::{FPoint, FRect, Point, Rect},
+    render::{BlendMode, Canvas, RendererInfo, Texture, TextureCreator},
+    sys::{SDL_FlashWindow, SDL_RenderSetVSync, SDL_RendererFlags, SDL_SetWindowHitTest},
+    video::{FullscreenType, Window, WindowContext},
     Sdl, TimerSubsystem,
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    os::raw::c_void,
+    path::Path,
+    rc::Rc,
+    sync::mpsc::{self, Receiver, Sender, SyncSender},
+};
 
 use stagehand::{
     draw::{Draw, DrawBatch, DrawColor, DrawData, DrawDestination, DrawRect},
@@ -20,12 +30,206 @@ use stagehand::{
     Stage,
 };
 
-use {input::SDLCommand, loading::SDLStorage};
+use {
+    accessibility::InputAccessibility,
+    activity::InputActivity,
+    adaptive::AdaptiveResolution,
+    anchor::{ScreenAnchor, ScreenAnchors},
+    announce::Announcements,
+    audio::{
+        AudioBuses, Bus, ChannelReservation, MixerStats, MusicState, PauseReason, PolicyPause,
+        SoundLimits, SoundVariations,
+    },
+    axismotion::AxisMotionLog,
+    background::BackgroundPolicy,
+    batches::BatchControl,
+    calibration::{self, AxisCalibrator},
+    captions::{CaptionStyle, Captions},
+    chrome::WindowChrome,
+    cinematic::CinematicBars,
+    clicks::Clicks,
+    combo::Combos,
+    config::{RenderDriver, SDLConfig, SDLHint},
+    context::InputContexts,
+    controllers::{controller_guid, ControllerCalibration, ControllerRegistry},
+    coords::{CoordinateSpace, YAxis},
+    cursor::CursorControl,
+    custom::CustomDraws,
+    debounce::ActionDebounce,
+    debug::{DebugActionState, InputDebug},
+    deferred::{StorageQueue, StorageRequest},
+    devices::{ControllerDevice, ControllerEvents},
+    display::DisplayAdjust,
+    effects::ScreenEffects,
+    events::EventHook,
+    filter::AnalogFilters,
+    filtering::TextureFilters,
+    focus::FocusNavigator,
+    framedump::FrameDump,
+    frameskip::FrameSkip,
+    framestats::{FrameStats, FrameSummary},
+    glyphs::GlyphValidator,
+    golden::ScriptedActions,
+    hit::HitRegions,
+    hold::ActionHolds,
+    inbox::MessageInbox,
+    input::{ControllerCapabilities, SDLCommand},
+    interpolation::Interpolation,
+    keyboard::OnScreenKeyboard,
+    keyed::{KeyedInstructions, KeyedSound},
+    keysplit::KeyboardSplit,
+    lifecycle::{Lifecycle, LifecycleEvent},
+    lighting::Lighting,
+    liveresize::LiveResize,
+    loading::{AssetKind, AssetSource, LoadReport, MissingAssetError, SDLStorage},
+    metrics::{DisplayMetrics, SafeArea},
+    modes::{FullscreenMode, ModeConfirmation, PendingMode},
+    monitor::AudioMonitor,
+    overlay::Overlays,
+    pacing::FramePacing,
+    panic::PanicOptions,
+    placeholder::MissingAssets,
+    postmix::PostMix,
+    prepare::FrameCommands,
+    presses::{InputSampling, PressQueue},
+    profiler::Profiler,
+    prompts::{PromptFamily, PromptIcon, PromptPack},
+    random::RandomService,
+    recording::Recorder,
+    render::{
+        AngleUnit, ClearMode, FrameContext, PivotMode, PixelBuffer, PositionMode, PostProcess,
+        RotationConfig,
+    },
+    repeated::RepeatedDraws,
+    resolution::ControllerResolution,
+    reveal::TextReveals,
+    rhythm::BeatClock,
+    richtext::TextRenderMode,
+    rumble::Rumble,
+    scale::DrawScales,
+    scenequeue::{SceneQueue, SceneRequest},
+    scheduler::Scheduler,
+    settings::{FullscreenSetting, Settings},
+    snapshot::InputSnapshot,
+    soundscape::{AudioSnapshot, MusicSnapshot, Soundscape, SoundscapeRequest},
+    streaming::RegionStreaming,
+    strings::StringTable,
+    system::{Locale, SystemStatus, SystemTheme},
+    taskbar::{Attention, Taskbar},
+    textcache::{TextCache, TextCacheStats},
+    timeline::InputTimeline,
+    tint::Tints,
+    touch::{VirtualControlKind, VirtualGamepad},
+    trail::{TrailConfig, Trails},
+    tween::Tweens,
+    visualizer::AudioVisualizer,
+    wireframe::DrawBounds,
+    work::WorkQueue,
+};
 
 mod app;
 
+pub mod accessibility;
+pub mod activity;
+pub mod adaptive;
+pub mod anchor;
+pub mod announce;
+pub mod atlas;
+pub mod audio;
+pub mod axismotion;
+pub mod background;
+pub mod batches;
+pub mod benchmark;
+pub mod bitmapfont;
+pub mod calibration;
+pub mod captions;
+pub mod chrome;
+pub mod cinematic;
+pub mod clicks;
+pub mod combo;
+pub mod compressed;
+pub mod config;
+pub mod context;
+pub mod controllers;
+pub mod coords;
+pub mod cursor;
+pub mod custom;
+pub mod debounce;
+pub mod debug;
+pub mod deferred;
+pub mod devices;
+pub mod display;
+pub mod effects;
+#[cfg(feature = "egui")]
+pub mod egui_layer;
+pub mod events;
+pub mod filter;
+pub mod filtering;
+pub mod focus;
+pub mod framedump;
+pub mod frameskip;
+pub mod framestats;
+pub mod glyphs;
+pub mod golden;
+pub mod hit;
+pub mod hold;
+#[cfg(feature = "image")]
+pub mod imagefile;
+pub mod inbox;
 pub mod input;
+pub mod interpolation;
+pub mod keyboard;
+pub mod keyed;
+pub mod keysplit;
+pub mod lifecycle;
+pub mod lighting;
+pub mod liveresize;
 pub mod loading;
+pub mod metrics;
+pub mod modes;
+pub mod monitor;
+pub mod overlay;
+pub mod pacing;
+pub mod palette;
+pub mod panic;
+pub mod placeholder;
+pub mod postmix;
+pub mod prepare;
+pub mod presses;
+pub mod profiler;
+pub mod prompts;
+pub mod random;
+pub mod recording;
+pub mod render;
+pub mod repeated;
+pub mod residency;
+pub mod resolution;
+pub mod reveal;
+pub mod rhythm;
+pub mod richtext;
+pub mod rumble;
+pub mod scale;
+pub mod scenequeue;
+pub mod scheduler;
+pub mod settings;
+pub mod snapshot;
+pub mod soundscape;
+pub mod splash;
+pub mod step;
+pub mod streaming;
+pub mod strings;
+pub mod system;
+pub mod taskbar;
+pub mod textcache;
+pub mod thickness;
+pub mod timeline;
+pub mod tint;
+pub mod touch;
+pub mod trail;
+pub mod tween;
+pub mod visualizer;
+pub mod wireframe;
+pub mod work;
 
 pub fn initialize_sdl2<'a, 'c>() -> Result<
     (
@@ -36,34 +240,119 @@ pub fn initialize_sdl2<'a, 'c>() -> Result<
     ),
     String,
 > {
-    let sdl_context = sdl2::init()?;
-    sdl_context.audio()?;
+    initialize_sdl2_with_config(&SDLConfig::default())
+}
+
+pub fn initialize_sdl2_with_config<'a, 'c>(
+    config: &SDLConfig,
+) -> Result<
+    (
+        Sdl,
+        Canvas<Window>,
+        TextureLoader<'a, WindowContext>,
+        FontLoader<'a, 'c>,
+    ),
+    String,
+> {
+    for hint in config.hints.iter() {
+        hint.apply();
+    }
 
-    sdl2::image::init(sdl2::image::InitFlag::PNG)?;
+    let sdl_context = sdl2::init()?;
 
-    sdl2::mixer::open_audio(44100, AUDIO_S16LSB, DEFAULT_CHANNELS, 1024)?;
-    sdl2::mixer::init(InitFlag::MP3)?;
-    sdl2::mixer::allocate_channels(4);
+    // The image and mixer contexts quit their libraries on drop, SDLApp::shutdown does that
+    // once nothing can use them any more.
+    #[cfg(feature = "sdl2-image")]
+    std::mem::forget(sdl2::image::init(sdl2::image::InitFlag::PNG)?);
 
-    let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
+    // Machines without a sound card, like CI containers, still run the game silently.
+    let opened = sdl_context.audio().and_then(|_| {
+        sdl2::mixer::open_audio(
+            audio::SAMPLE_RATE,
+            AUDIO_S16LSB,
+            DEFAULT_CHANNELS,
+            config.audio_buffer,
+        )
+    });
+    match opened {
+        Ok(()) => {
+            // The emscripten SDL_mixer port is built without MP3 support.
+            #[cfg(not(target_os = "emscripten"))]
+            std::mem::forget(sdl2::mixer::init(InitFlag::MP3)?);
+            #[cfg(target_os = "emscripten")]
+            std::mem::forget(sdl2::mixer::init(InitFlag::OGG)?);
+            sdl2::mixer::allocate_channels(4);
+        }
+        Err(e) => {
+            warn!("Could not open audio, running without sound: {}", e);
+            audio::set_unavailable();
+        }
+    }
 
     let video_subsystem = sdl_context.video()?;
     let window = video_subsystem
-        .window("Stagehand SDL2 Example", 800, 600)
+        .window(&config.title, config.width, config.height)
         .position_centered()
         .opengl()
         .build()
         .map_err(|e| e.to_string())?;
 
-    let canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+    let mut builder = window.into_canvas();
+    builder = match &config.driver {
+        RenderDriver::Default => builder,
+        RenderDriver::Software => builder.software(),
+        RenderDriver::Accelerated => builder.accelerated(),
+        RenderDriver::Named(name) => match config::driver_index(name) {
+            Some(index) => builder.index(index),
+            None => {
+                warn!("Render driver '{}' not available, using default", name);
+                builder
+            }
+        },
+    };
+    if config.vsync {
+        builder = builder.present_vsync();
+    }
+    let canvas = builder.build().map_err(|e| e.to_string())?;
     let texture_creator = canvas.texture_creator();
 
     let texture_loader = TextureLoader::from_creator(texture_creator);
-    let font_loader = FontLoader::from_context(ttf_context);
+    let font_loader = match sdl2::ttf::init() {
+        Ok(context) => FontLoader::from_context(context),
+        Err(e) => {
+            warn!(
+                "Could not start SDL_ttf, drawing text with the built in font: {}",
+                e
+            );
+            FontLoader::unavailable()
+        }
+    };
 
     Ok((sdl_context, canvas, texture_loader, font_loader))
 }
 
+type SceneBox<'a, 'b, 'c, IContent, UContent, Message> = Box<
+    dyn Scene<
+            Key = String,
+            Initialize = Initialize<SDLCommand, SDLStorage<'a, 'b, 'c>, IContent>,
+            Update = Update<SDLCommand, UContent>,
+            Message = Message,
+            Instruction = UpdateInstruction,
+            Draw = (),
+            DrawBatch = DrawBatch<Draw, ()>,
+        > + 'a,
+>;
+
+// Neither initialized nor handed to the stage until its assets are all in storage.
+struct PendingScene<'a, 'b, 'c, IContent, UContent, Message> {
+    key: String,
+    scene: SceneBox<'a, 'b, 'c, IContent, UContent, Message>,
+    active: bool,
+    initialize: bool,
+    remaining: Vec<AssetSource<'c>>,
+    failed: Vec<(String, String)>,
+}
+
 pub struct SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
     stage: Stage<
         'a,
@@ -76,9 +365,14 @@ pub struct SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
         DrawBatch<Draw, ()>,
     >,
 
+    scenes: Vec<(String, bool)>,
+    pending_scenes: Vec<PendingScene<'a, 'b, 'c, IContent, UContent, Message>>,
+
     sdl: Sdl,
     canvas: Canvas<Window>,
-    controllers: Vec<GameController>,
+    // Indexed by player slot, a disconnected controller leaves a hole so the others
+    // keep their indices.
+    controllers: Vec<Option<GameController>>,
 
     i_content: Rc<RefCell<IContent>>,
     u_content: Rc<RefCell<UContent>>,
@@ -87,8 +381,134 @@ pub struct SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
 
     storage: Rc<RefCell<SDLStorage<'a, 'b, 'c>>>,
     texture_creator: &'a TextureCreator<WindowContext>,
+    placeholder: Option<Rc<RefCell<Texture<'a>>>>,
+    missing: MissingAssets,
+
+    clear: ClearMode,
+    position_mode: PositionMode,
+    rotation: RotationConfig,
+    post_process: Option<Box<dyn PostProcess + 'a>>,
+    frame_target: Option<Texture<'a>>,
+    offscreen: bool,
+    adaptive: Rc<RefCell<AdaptiveResolution>>,
+    frame_scale: Option<(f32, f32)>,
+    display: Rc<RefCell<DisplayAdjust>>,
+    applied_gamma: f32,
+    effects: Rc<RefCell<ScreenEffects>>,
+    lighting: Rc<RefCell<Lighting>>,
+    light_map: Option<Texture<'a>>,
+    light_sprite: Option<Texture<'a>>,
+    tints: Rc<RefCell<Tints>>,
+    trails: Rc<RefCell<Trails>>,
+    // Current and previous frame of each trailing batch, and frames since it last trailed.
+    trail_buffers: HashMap<usize, (Texture<'a>, Texture<'a>, u32)>,
+    commands: FrameCommands<'a>,
+    culling: bool,
+    rich_text: bool,
+    tabular_fonts: HashSet<Ticket>,
+    text_modes: HashMap<Ticket, TextRenderMode>,
+    reveals: Rc<RefCell<TextReveals>>,
+    reveal_cache: HashMap<Ticket, HashMap<String, (usize, Color, Rc<RefCell<Texture<'a>>>)>>,
+    text_cache: TextCache<'a>,
+    culled: usize,
+    draw_count: usize,
+    frame_dump: Rc<RefCell<FrameDump>>,
+    batch_control: Rc<RefCell<BatchControl>>,
+    scales: Rc<RefCell<DrawScales>>,
+    filters: Rc<RefCell<TextureFilters>>,
+    draw_bounds: Rc<RefCell<DrawBounds>>,
+    fallback_text_scale: u32,
+    anchors: Rc<RefCell<ScreenAnchors>>,
+    draw_offset: (i32, i32),
+    recorder: Rc<RefCell<Recorder>>,
+    scheduler: Rc<RefCell<Scheduler<Message>>>,
+    tweens: Rc<RefCell<Tweens>>,
+    random: Rc<RefCell<RandomService>>,
+    work: Rc<RefCell<WorkQueue<'a>>>,
+    input_debug: Rc<RefCell<InputDebug>>,
+    input_contexts: Rc<RefCell<InputContexts>>,
+    action_holds: Rc<RefCell<ActionHolds>>,
+    combos: Rc<RefCell<Combos>>,
+    accessibility: Rc<RefCell<InputAccessibility>>,
+    debounce: Rc<RefCell<ActionDebounce>>,
+    analog_filters: Rc<RefCell<AnalogFilters>>,
+    controller_resolution: Rc<RefCell<ControllerResolution>>,
+    controller_registry: Rc<RefCell<ControllerRegistry>>,
+    controller_events: Rc<RefCell<ControllerEvents>>,
+    settings: Rc<RefCell<Settings>>,
+    calibrator: Rc<RefCell<AxisCalibrator>>,
+    hit_regions: Rc<RefCell<HitRegions>>,
+    clicks: Rc<RefCell<Clicks>>,
+    input_activity: Rc<RefCell<InputActivity>>,
+    axis_motion: Rc<RefCell<AxisMotionLog>>,
+    rumble: Rc<RefCell<Rumble>>,
+    focus: Rc<RefCell<FocusNavigator>>,
+    virtual_gamepad: Rc<RefCell<VirtualGamepad>>,
+    overlays: Rc<RefCell<Overlays>>,
+    cinematic_bars: Rc<RefCell<CinematicBars>>,
+    mouse: (i32, i32, bool),
+    taskbar: Rc<RefCell<Taskbar>>,
+    storage_queue: Rc<RefCell<StorageQueue<'c>>>,
+    scene_queue: Rc<RefCell<SceneQueue>>,
+    streaming: Rc<RefCell<RegionStreaming<'c>>>,
+    keyboard_split: Rc<RefCell<KeyboardSplit>>,
+    presses: Rc<RefCell<PressQueue>>,
+    timeline: Rc<RefCell<InputTimeline>>,
+    snapshot: Rc<RefCell<InputSnapshot>>,
+    glyphs: Rc<RefCell<GlyphValidator>>,
+    mode_confirmation: Rc<RefCell<ModeConfirmation>>,
+    window_chrome: Rc<RefCell<WindowChrome>>,
+    wheel: (f32, f32),
+    window_focused: bool,
+    prompt_family: PromptFamily,
+    cursor: Rc<RefCell<CursorControl>>,
+    lifecycle: Rc<RefCell<Lifecycle>>,
+    background: Rc<RefCell<BackgroundPolicy>>,
+    live_resize: Rc<RefCell<LiveResize>>,
+    pending_events: Vec<Event>,
+    background_input: bool,
+    focus_gated_input: bool,
+    coordinates: Rc<RefCell<CoordinateSpace>>,
+    metrics: Rc<RefCell<DisplayMetrics>>,
+    pacing: Rc<RefCell<FramePacing>>,
+    frame_skip: Rc<RefCell<FrameSkip>>,
+    frame_summary: Rc<RefCell<FrameSummary>>,
+    interpolation: Rc<RefCell<Interpolation>>,
+    event_hooks: Vec<Box<dyn EventHook + 'a>>,
+    event_senders: Vec<Sender<Event>>,
+    keyboard: Rc<RefCell<OnScreenKeyboard>>,
+    inbox: Rc<RefCell<MessageInbox<Message>>>,
+    #[cfg(feature = "egui")]
+    egui: Option<egui_layer::SDLEguiLayer<'a>>,
+
+    frame_counter: u64,
+    frame_time: f64,
+    profiler: Rc<RefCell<Profiler>>,
+
+    system: Rc<RefCell<SystemStatus>>,
+    strings: Rc<RefCell<StringTable<'a>>>,
+    sound_limits: Rc<RefCell<SoundLimits>>,
+    keyed: Rc<RefCell<KeyedInstructions>>,
+    repeated: Rc<RefCell<RepeatedDraws>>,
+    custom_draws: Rc<RefCell<CustomDraws>>,
+    mixer_stats: Rc<RefCell<MixerStats>>,
+    channel_reservation: Rc<RefCell<ChannelReservation>>,
+    soundscape: Rc<RefCell<Soundscape>>,
+    sound_variations: Rc<RefCell<SoundVariations>>,
+    music_state: Rc<RefCell<MusicState>>,
+    audio_buses: Rc<RefCell<AudioBuses>>,
+    policy_pause: PolicyPause,
+    audio_monitor: Rc<RefCell<AudioMonitor>>,
+    post_mix: Rc<RefCell<PostMix>>,
+    visualizer: Rc<RefCell<AudioVisualizer>>,
+    beat_clock: Rc<RefCell<BeatClock>>,
+    captions: Rc<RefCell<Captions>>,
+    announcements: Rc<RefCell<Announcements>>,
 
     timer: TimerSubsystem,
+    scripted: ScriptedActions,
+    scripted_only: bool,
+    shut_down: bool,
 }
 
 impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
@@ -112,6 +532,7 @@ impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UCont
 
             match controller_system.open(index) {
                 Ok(c) => {
+                    input::enable_sensors(&c);
                     controllers.push(c);
                 }
                 Err(e) => {
@@ -144,12 +565,14 @@ impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UCont
     ) -> Result<Self, String> {
         let timer = sdl.timer()?;
 
-        Ok(SDLApp {
+        let mut app = SDLApp {
             stage: Stage::new(),
+            scenes: Vec::new(),
+            pending_scenes: Vec::new(),
 
             sdl,
             canvas,
-            controllers,
+            controllers: controllers.into_iter().map(Some).collect(),
 
             i_content: Rc::new(RefCell::new(i_content)),
             u_content: Rc::new(RefCell::new(u_content)),
@@ -158,18 +581,198 @@ impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UCont
 
             storage: Rc::new(RefCell::new(storage)),
             texture_creator: creator,
+            placeholder: None,
+            missing: MissingAssets::default(),
+
+            clear: ClearMode::default(),
+            position_mode: PositionMode::Integer,
+            rotation: RotationConfig::default(),
+            post_process: None,
+            frame_target: None,
+            offscreen: false,
+            adaptive: Rc::new(RefCell::new(AdaptiveResolution::new())),
+            frame_scale: None,
+            display: Rc::new(RefCell::new(DisplayAdjust::default())),
+            applied_gamma: 1.0,
+            effects: Rc::new(RefCell::new(ScreenEffects::new())),
+            lighting: Rc::new(RefCell::new(Lighting::new())),
+            light_map: None,
+            light_sprite: None,
+            tints: Rc::new(RefCell::new(Tints::new())),
+            trails: Rc::new(RefCell::new(Trails::new())),
+            trail_buffers: HashMap::new(),
+            commands: FrameCommands::new(),
+            culling: true,
+            rich_text: false,
+            tabular_fonts: HashSet::new(),
+            text_modes: HashMap::new(),
+            reveals: Rc::new(RefCell::new(TextReveals::new())),
+            reveal_cache: HashMap::new(),
+            text_cache: TextCache::new(),
+            culled: 0,
+            draw_count: 0,
+            frame_dump: Rc::new(RefCell::new(FrameDump::new())),
+            batch_control: Rc::new(RefCell::new(BatchControl::new())),
+            scales: Rc::new(RefCell::new(DrawScales::new())),
+            filters: Rc::new(RefCell::new(TextureFilters::new())),
+            draw_bounds: Rc::new(RefCell::new(DrawBounds::new())),
+            fallback_text_scale: 2,
+            anchors: Rc::new(RefCell::new(ScreenAnchors::new())),
+            draw_offset: (0, 0),
+            recorder: Rc::new(RefCell::new(Recorder::new())),
+            scheduler: Rc::new(RefCell::new(Scheduler::new())),
+            tweens: Rc::new(RefCell::new(Tweens::new())),
+            random: Rc::new(RefCell::new(RandomService::default())),
+            work: Rc::new(RefCell::new(WorkQueue::new())),
+            input_debug: Rc::new(RefCell::new(InputDebug::new())),
+            input_contexts: Rc::new(RefCell::new(InputContexts::new())),
+            action_holds: Rc::new(RefCell::new(ActionHolds::new())),
+            combos: Rc::new(RefCell::new(Combos::new())),
+            accessibility: Rc::new(RefCell::new(InputAccessibility::new())),
+            debounce: Rc::new(RefCell::new(ActionDebounce::new())),
+            analog_filters: Rc::new(RefCell::new(AnalogFilters::new())),
+            controller_resolution: Rc::new(RefCell::new(ControllerResolution::new())),
+            controller_registry: Rc::new(RefCell::new(ControllerRegistry::new())),
+            controller_events: Rc::new(RefCell::new(ControllerEvents::new())),
+            settings: Rc::new(RefCell::new(Settings::new())),
+            calibrator: Rc::new(RefCell::new(AxisCalibrator::new())),
+            hit_regions: Rc::new(RefCell::new(HitRegions::new())),
+            clicks: Rc::new(RefCell::new(Clicks::new())),
+            input_activity: Rc::new(RefCell::new(InputActivity::new())),
+            axis_motion: Rc::new(RefCell::new(AxisMotionLog::new())),
+            rumble: Rc::new(RefCell::new(Rumble::new())),
+            focus: Rc::new(RefCell::new(FocusNavigator::new())),
+            virtual_gamepad: Rc::new(RefCell::new(VirtualGamepad::new())),
+            overlays: Rc::new(RefCell::new(Overlays::new())),
+            cinematic_bars: Rc::new(RefCell::new(CinematicBars::new())),
+            mouse: (0, 0, false),
+            taskbar: Rc::new(RefCell::new(Taskbar::new())),
+            storage_queue: Rc::new(RefCell::new(StorageQueue::new())),
+            scene_queue: Rc::new(RefCell::new(SceneQueue::new())),
+            streaming: Rc::new(RefCell::new(RegionStreaming::new())),
+            keyboard_split: Rc::new(RefCell::new(KeyboardSplit::new())),
+            presses: Rc::new(RefCell::new(PressQueue::new())),
+            timeline: Rc::new(RefCell::new(InputTimeline::new())),
+            snapshot: Rc::new(RefCell::new(InputSnapshot::new())),
+            glyphs: Rc::new(RefCell::new(GlyphValidator::new())),
+            mode_confirmation: Rc::new(RefCell::new(ModeConfirmation::new())),
+            window_chrome: Rc::new(RefCell::new(WindowChrome::new())),
+            wheel: (0.0, 0.0),
+            window_focused: true,
+            prompt_family: PromptFamily::KeyboardMouse,
+            cursor: Rc::new(RefCell::new(CursorControl::new())),
+            lifecycle: Rc::new(RefCell::new(Lifecycle::new())),
+            background: Rc::new(RefCell::new(BackgroundPolicy::new())),
+            live_resize: Rc::new(RefCell::new(LiveResize::new())),
+            pending_events: Vec::new(),
+            background_input: false,
+            focus_gated_input: false,
+            coordinates: Rc::new(RefCell::new(CoordinateSpace::new())),
+            metrics: Rc::new(RefCell::new(DisplayMetrics::new())),
+            pacing: Rc::new(RefCell::new(FramePacing::new())),
+            frame_skip: Rc::new(RefCell::new(FrameSkip::new())),
+            frame_summary: Rc::new(RefCell::new(FrameSummary::new())),
+            interpolation: Rc::new(RefCell::new(Interpolation::new())),
+            event_hooks: Vec::new(),
+            event_senders: Vec::new(),
+            keyboard: Rc::new(RefCell::new(OnScreenKeyboard::new())),
+            inbox: Rc::new(RefCell::new(MessageInbox::new())),
+            #[cfg(feature = "egui")]
+            egui: None,
+
+            frame_counter: timer.performance_counter(),
+            frame_time: 0.0,
+            profiler: Rc::new(RefCell::new(Profiler::new(timer.clone()))),
+
+            system: Rc::new(RefCell::new(SystemStatus::new())),
+            strings: Rc::new(RefCell::new(StringTable::new())),
+            sound_limits: Rc::new(RefCell::new(SoundLimits::new())),
+            keyed: Rc::new(RefCell::new(KeyedInstructions::new())),
+            repeated: Rc::new(RefCell::new(RepeatedDraws::new())),
+            custom_draws: Rc::new(RefCell::new(CustomDraws::new())),
+            mixer_stats: Rc::new(RefCell::new(MixerStats::new())),
+            channel_reservation: Rc::new(RefCell::new(ChannelReservation::new())),
+            soundscape: Rc::new(RefCell::new(Soundscape::new())),
+            sound_variations: Rc::new(RefCell::new(SoundVariations::new())),
+            music_state: Rc::new(RefCell::new(MusicState::new())),
+            audio_buses: Rc::new(RefCell::new(AudioBuses::new())),
+            policy_pause: PolicyPause::new(),
+            audio_monitor: Rc::new(RefCell::new(AudioMonitor::new())),
+            post_mix: Rc::new(RefCell::new(PostMix::new())),
+            visualizer: Rc::new(RefCell::new(AudioVisualizer::new())),
+            beat_clock: Rc::new(RefCell::new(BeatClock::new())),
+            captions: Rc::new(RefCell::new(Captions::new())),
+            announcements: Rc::new(RefCell::new(Announcements::new())),
 
             timer,
-        })
+            scripted: ScriptedActions::new(),
+            scripted_only: false,
+            shut_down: false,
+        };
+        app.detect_vsync();
+        app.arrange_controllers();
+        Ok(app)
+    }
+
+    // Halts playback and frees what depends on the mixer and renderer before closing them.
+    // Scenes are dropped first, so their clones of storage and its resources go with them.
+    // If anything outside the app still holds storage or a sound, the libraries are left
+    // open for the process exit to clean up rather than freeing that sound after them.
+    // Called from Drop as well, so calling it early is optional.
+    pub fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+
+        sdl2::mixer::Music::halt();
+        sdl2::mixer::Channel::all().halt();
+        let _ = audio::clear_effects(audio::POST_MIX);
+        self.stage = Stage::new();
+        self.scenes.clear();
+        self.pending_scenes.clear();
+        self.sound_variations.borrow_mut().clear_variants();
+        self.soundscape.borrow_mut().clear();
+        self.streaming.borrow_mut().clear();
+        let released = self.storage.borrow_mut().release_audio();
+        let unshared = Rc::strong_count(&self.storage) == 1;
+
+        self.live_resize.borrow_mut().set_frame(None);
+        self.frame_target = None;
+        self.light_map = None;
+        self.light_sprite = None;
+        self.trail_buffers.clear();
+        self.post_process = None;
+        self.placeholder = None;
+        #[cfg(feature = "egui")]
+        {
+            self.egui = None;
+        }
+        self.controllers.clear();
+
+        if !released || !unshared {
+            warn!(
+                "Storage or sounds are still held elsewhere, leaving SDL_mixer and SDL_image open"
+            );
+            return;
+        }
+
+        sdl2::mixer::close_audio();
+        unsafe {
+            sdl2::sys::mixer::Mix_Quit();
+            #[cfg(feature = "sdl2-image")]
+            sdl2::sys::image::IMG_Quit();
+        }
     }
 
     pub fn prepare_info(&mut self) {
         let mut info = self.info.borrow_mut();
         info.clear();
 
-        if !sdl2::mixer::Music::is_playing() {
+        if audio::is_available() && !sdl2::mixer::Music::is_playing() {
             info.push(UpdateInfo::MusicStopped);
         }
+        self.mixer_stats.borrow_mut().begin_frame();
     }
 
     pub fn add_scene(
@@ -198,101 +801,2944 @@ impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UCont
             scene.initialize(&mut init);
         }
 
+        self.scenes.retain(|(k, _)| *k != key);
+        self.scenes.push((key.clone(), active));
         self.stage.add_scene(key, scene, active);
     }
 
-    fn volume(v: f32) -> i32 {
-        (v * sdl2::mixer::MAX_VOLUME as f32) as i32
+    // Loads whichever of `assets` aren't in storage yet and adds the scene once they all
+    // are. Without `deferred` that happens now, and a scene whose assets fail is dropped
+    // with the errors. With it, the scene waits and one of its assets is loaded per update
+    // on the main thread, so a loading screen keeps drawing. Failed assets aren't retried;
+    // `pending_scenes` reports them until the scene is cancelled.
+    pub fn add_scene_requiring(
+        &mut self,
+        key: String,
+        scene: SceneBox<'a, 'b, 'c, IContent, UContent, Message>,
+        active: bool,
+        initialize: bool,
+        assets: &[AssetSource<'c>],
+        deferred: bool,
+    ) -> Result<(), MissingAssetError> {
+        let remaining: Vec<AssetSource<'c>> = {
+            let storage = self.storage.borrow();
+            assets
+                .iter()
+                .filter(|a| !storage.missing(&[a.requirement()]).is_empty())
+                .copied()
+                .collect()
+        };
+
+        self.pending_scenes.retain(|p| p.key != key);
+        if deferred && !remaining.is_empty() {
+            self.pending_scenes.push(PendingScene {
+                key,
+                scene,
+                active,
+                initialize,
+                remaining,
+                failed: Vec::new(),
+            });
+            return Ok(());
+        }
+
+        let mut storage = self.storage.borrow_mut();
+        let report = storage.load_all(&remaining, false);
+        if !report.failed.is_empty() {
+            let required: Vec<_> = remaining.iter().map(|a| a.requirement()).collect();
+            return Err(MissingAssetError {
+                scene: key,
+                missing: storage.missing(&required),
+                failed: report.failed,
+            });
+        }
+        drop(storage);
+
+        self.add_scene(key, scene, active, initialize);
+        Ok(())
     }
 
-    fn play_music(&mut self, ticket: Ticket, loops: i32, volume: f32) {
-        match self.storage.borrow().music.get_by_ticket(ticket) {
-            Ok(m) => {
-                sdl2::mixer::Music::set_volume(Self::volume(volume));
-                match m.borrow().play(loops) {
-                    Ok(()) => {}
-                    Err(e) => error!("Error playing music: {}", e),
+    pub fn pending_scenes(&self) -> Vec<MissingAssetError> {
+        self.pending_scenes
+            .iter()
+            .map(|p| MissingAssetError {
+                scene: p.key.clone(),
+                missing: p.remaining.iter().map(|a| a.requirement()).collect(),
+                failed: p.failed.clone(),
+            })
+            .collect()
+    }
+
+    pub fn cancel_pending_scene(&mut self, key: &str) -> bool {
+        let before = self.pending_scenes.len();
+        self.pending_scenes.retain(|p| p.key != key);
+        self.pending_scenes.len() != before
+    }
+
+    fn add_ready_scenes(&mut self) {
+        if self.pending_scenes.is_empty() {
+            return;
+        }
+
+        let pending = std::mem::take(&mut self.pending_scenes);
+        for mut p in pending {
+            if p.failed.is_empty() && !p.remaining.is_empty() {
+                let asset = p.remaining.remove(0);
+                let mut storage = self.storage.borrow_mut();
+                let loaded = storage.missing(&[asset.requirement()]).is_empty();
+                let report = match loaded {
+                    true => LoadReport::default(),
+                    false => storage.load_all(&[asset], false),
+                };
+                if !report.failed.is_empty() {
+                    p.remaining.insert(0, asset);
+                    p.failed = report.failed;
                 }
             }
-            Err(e) => ResourceError::log_failure(e),
+
+            match p.remaining.is_empty() {
+                true => self.add_scene(p.key, p.scene, p.active, p.initialize),
+                false => self.pending_scenes.push(p),
+            }
         }
     }
 
-    fn play_sound(&mut self, ticket: Ticket, volume: f32) {
-        match self.storage.borrow().sounds.get_by_ticket(ticket) {
-            Ok(s) => {
-                match s.try_borrow_mut() {
-                    Ok(mut s_v) => {
-                        s_v.set_volume(Self::volume(volume));
+    pub fn scene_keys(&self) -> Vec<String> {
+        self.scenes.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    pub fn has_scene(&self, key: &str) -> bool {
+        self.scenes.iter().any(|(k, _)| k == key)
+    }
+
+    // The stage only changes scenes through SDLApp, directly or from `scene_queue`, so this
+    // matches it as long as scenes don't hold the stage themselves.
+    pub fn is_scene_active(&self, key: &str) -> Option<bool> {
+        self.scenes.iter().find(|(k, _)| k == key).map(|(_, a)| *a)
+    }
+
+    pub fn set_scene_active(&mut self, key: &str, active: bool) -> bool {
+        match self.scenes.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => {
+                entry.1 = active;
+                self.stage.set_active(&key.to_string(), active);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_scene(&mut self, key: &str) -> bool {
+        if self.cancel_pending_scene(key) {
+            return true;
+        }
+
+        let before = self.scenes.len();
+        self.scenes.retain(|(k, _)| k != key);
+        if self.scenes.len() == before {
+            return false;
+        }
+
+        self.stage.remove_scene(&key.to_string());
+        true
+    }
+
+    pub fn scene_queue(&self) -> Rc<RefCell<SceneQueue>> {
+        self.scene_queue.clone()
+    }
+
+    fn apply_scene_requests(&mut self) {
+        let requests = std::mem::take(&mut self.scene_queue.borrow_mut().requests);
+        for request in requests {
+            let (key, found) = match request {
+                SceneRequest::SetActive(key, active) => {
+                    let found = self.set_scene_active(&key, active);
+                    (key, found)
+                }
+                SceneRequest::Remove(key) => {
+                    let found = self.remove_scene(&key);
+                    (key, found)
+                }
+            };
+            if !found {
+                warn!("Scene request for unknown scene '{}'", key);
+            }
+        }
+    }
+
+    pub fn performance_counter(&self) -> u64 {
+        self.timer.performance_counter()
+    }
+
+    pub fn performance_frequency(&self) -> u64 {
+        self.timer.performance_frequency()
+    }
+
+    pub fn precise_ticks(&self) -> f64 {
+        self.timer.performance_counter() as f64 * 1000.0 / self.timer.performance_frequency() as f64
+    }
+
+    pub fn frame_time(&self) -> f64 {
+        self.frame_time
+    }
+
+    pub fn profiler(&self) -> Rc<RefCell<Profiler>> {
+        self.profiler.clone()
+    }
+
+    pub fn profile<T, F: FnOnce() -> T>(&self, name: &str, f: F) -> T {
+        self.profiler.borrow_mut().begin(name);
+        let result = f();
+        self.profiler.borrow_mut().end(name);
+        result
+    }
+
+    fn draw_profiler(&mut self) {
+        let profiler = self.profiler.clone();
+        let profiler = profiler.borrow();
+        if !profiler.overlay {
+            return;
+        }
+
+        let (width, _) = self.canvas.output_size().unwrap_or((800, 600));
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+
+        let row = 16;
+        let budget = self.pacing.borrow().frame_budget();
+        for (i, (name, ms)) in profiler.last_frame().iter().enumerate() {
+            let y = 8 + i as i32 * row;
+            let x = width as i32 - 248;
+
+            self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+            let _ = self
+                .canvas
+                .fill_rect(Rect::new(x - 4, y - 2, 244, row as u32));
+
+            let bar = ((ms / budget).min(1.0) * 80.0) as u32;
+            self.canvas.set_draw_color(match *ms > budget * 0.5 {
+                true => Color::RGB(220, 80, 80),
+                false => Color::RGB(80, 200, 120),
+            });
+            let _ = self.canvas.fill_rect(Rect::new(x, y + 2, bar.max(1), 8));
+
+            if let Some(ticket) = profiler.font {
+                let label = format!("{} {:.2}ms", self.strings.borrow().get(name), ms);
+                match self.text_texture(ticket, &label, Color::RGB(230, 230, 230)) {
+                    Ok(t) => {
+                        let query = t.query();
+                        let scale = (row - 4) as f32 / query.height.max(1) as f32;
+                        let dest = Rect::new(
+                            x + 88,
+                            y,
+                            (query.width as f32 * scale) as u32,
+                            (row - 4) as u32,
+                        );
+                        let _ = self.canvas.copy(&t, None, dest);
                     }
-                    Err(e) => warn!(
-                        "Cannot set volume on a sound effect already borrowed elsewhere: {}",
-                        e
-                    ),
+                    Err(e) => warn!("Could not render profiler label: {}", e),
                 }
+            }
+        }
 
-                match sdl2::mixer::Channel::all().play(&s.borrow(), 0) {
-                    Ok(_c) => {}
-                    Err(e) => error!("Error playing sound: {}", e),
+        if let Some(ticket) = profiler.font {
+            let allocations = self.commands.allocations();
+            let label = format!(
+                "draws {} / batches {} capacity, grew {} ({} total)",
+                allocations.draw_capacity,
+                allocations.batch_capacity,
+                allocations.last_frame,
+                allocations.total
+            );
+            let y = 8 + profiler.last_frame().len() as i32 * row;
+            match self.text_texture(ticket, &label, Color::RGB(230, 230, 230)) {
+                Ok(t) => {
+                    let query = t.query();
+                    let scale = (row - 4) as f32 / query.height.max(1) as f32;
+                    let w = (query.width as f32 * scale) as u32;
+                    let x = width as i32 - w as i32 - 8;
+                    self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+                    let _ = self
+                        .canvas
+                        .fill_rect(Rect::new(x - 4, y - 2, w + 8, row as u32));
+                    let _ = self
+                        .canvas
+                        .copy(&t, None, Rect::new(x, y, w, (row - 4) as u32));
                 }
+                Err(e) => warn!("Could not render allocation counters: {}", e),
             }
-            Err(e) => ResourceError::log_failure(e),
         }
+
+        self.canvas.set_blend_mode(previous);
     }
 
-    fn render_texture(&mut self, texture: Rc<RefCell<Texture<'_>>>, data: &DrawData) {
-        let tex = texture.borrow();
-        let query = tex.query();
+    fn measure_frame(&mut self) {
+        self.profiler.borrow_mut().next_frame();
 
-        let source = match &data.source {
-            Some(r) => Some(to_rect(r)),
-            None => None,
+        let now = self.timer.performance_counter();
+        self.frame_time = now.saturating_sub(self.frame_counter) as f64 * 1000.0
+            / self.timer.performance_frequency() as f64;
+        self.frame_counter = now;
+    }
+
+    // By player slot, `None` for slots whose controller is disconnected.
+    pub fn controller_capabilities(&self) -> Vec<Option<ControllerCapabilities>> {
+        self.controllers
+            .iter()
+            .map(|c| c.as_ref().map(ControllerCapabilities::query))
+            .collect()
+    }
+
+    pub fn set_controller_led(&mut self, index: usize, color: Color) -> Result<(), String> {
+        let controller = self
+            .controllers
+            .get_mut(index)
+            .and_then(Option::as_mut)
+            .ok_or_else(|| format!("No controller at index {}", index))?;
+
+        if !controller.has_led() {
+            return Err(format!("Controller {} has no LED", index));
+        }
+
+        controller
+            .set_led(color.r, color.g, color.b)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn missing_assets(&self) -> MissingAssets {
+        self.missing
+    }
+
+    fn placeholder_texture(&mut self) -> Option<Rc<RefCell<Texture<'a>>>> {
+        if self.placeholder.is_none() {
+            let texture = placeholder::checkerboard_surface().and_then(|s| {
+                self.texture_creator
+                    .create_texture_from_surface(&s)
+                    .map_err(|e| e.to_string())
+            });
+
+            match texture {
+                Ok(t) => self.placeholder = Some(Rc::new(RefCell::new(t))),
+                Err(e) => error!("Could not create placeholder texture: {}", e),
+            }
+        }
+
+        self.placeholder.clone()
+    }
+
+    pub fn add_scene_with_assets(
+        &mut self,
+        key: String,
+        scene: Box<
+            dyn Scene<
+                    Key = String,
+                    Initialize = Initialize<SDLCommand, SDLStorage<'a, 'b, 'c>, IContent>,
+                    Update = Update<SDLCommand, UContent>,
+                    Message = Message,
+                    Instruction = UpdateInstruction,
+                    Draw = (),
+                    DrawBatch = DrawBatch<Draw, ()>,
+                > + 'a,
+        >,
+        active: bool,
+        initialize: bool,
+        assets: &[AssetSource<'c>],
+    ) -> LoadReport {
+        let report = self.storage.borrow_mut().load_group(&key, assets, true);
+        self.add_scene(key, scene, active, initialize);
+        report
+    }
+
+    pub fn storage_queue(&self) -> Rc<RefCell<StorageQueue<'c>>> {
+        self.storage_queue.clone()
+    }
+
+    fn apply_storage_requests(&mut self) {
+        let requests = std::mem::take(&mut self.storage_queue.borrow_mut().requests);
+        if requests.is_empty() {
+            return;
+        }
+
+        let mut report = LoadReport::default();
+        let mut released = 0;
+        let mut storage = self.storage.borrow_mut();
+        for request in requests {
+            let loaded = match request {
+                StorageRequest::Load(assets) => storage.load_all(&assets, true),
+                StorageRequest::LoadGroup(group, assets) => {
+                    storage.load_group(&group, &assets, true)
+                }
+                StorageRequest::ReleaseGroup(group) => {
+                    released += storage.release_group(&group);
+                    continue;
+                }
+                StorageRequest::Texture(key, surface) => {
+                    match storage.set_texture_from_surface(&key, &surface) {
+                        Ok(_) => report.loaded.push(key),
+                        Err(e) => report.failed.push((key, e)),
+                    }
+                    continue;
+                }
+            };
+            report.loaded.extend(loaded.loaded);
+            report.substituted.extend(loaded.substituted);
+            report.failed.extend(loaded.failed);
+        }
+        drop(storage);
+
+        self.storage_queue.borrow_mut().finish(report, released);
+    }
+
+    pub fn region_streaming(&self) -> Rc<RefCell<RegionStreaming<'c>>> {
+        self.streaming.clone()
+    }
+
+    fn stream_regions(&mut self) {
+        let (w, h) = self.canvas.window().size();
+        let center = self
+            .coordinates
+            .borrow()
+            .window_to_world(w as f32 / 2.0, h as f32 / 2.0);
+        let mut storage = self.storage.borrow_mut();
+        self.streaming
+            .borrow_mut()
+            .update(center, &mut storage, || self.precise_ticks());
+    }
+
+    pub fn release_scene_assets(&mut self, key: &str) -> usize {
+        self.storage.borrow_mut().release_group(key)
+    }
+
+    pub fn renderer_info(&self) -> RendererInfo {
+        self.canvas.info()
+    }
+
+    pub fn read_pixels(&self, rect: Option<Rect>) -> Result<PixelBuffer, String> {
+        render::read_canvas(&self.canvas, rect)
+    }
+
+    pub fn read_texture_pixels(
+        &mut self,
+        texture: &Texture,
+        rect: Option<Rect>,
+    ) -> Result<PixelBuffer, String> {
+        render::read_texture(&mut self.canvas, texture, rect)
+    }
+
+    pub fn set_position_mode(&mut self, mode: PositionMode) {
+        self.position_mode = mode;
+    }
+
+    pub fn set_rotation_config(&mut self, rotation: RotationConfig) {
+        self.rotation = rotation;
+    }
+
+    pub fn clear_mode(&self) -> ClearMode {
+        self.clear
+    }
+
+    pub fn set_clear_mode(&mut self, clear: ClearMode) {
+        self.clear = clear;
+    }
+
+    pub fn set_post_process(&mut self, hook: Option<Box<dyn PostProcess + 'a>>) {
+        self.post_process = hook;
+    }
+
+    pub fn adaptive_resolution(&self) -> Rc<RefCell<AdaptiveResolution>> {
+        self.adaptive.clone()
+    }
+
+    pub fn display_adjust(&self) -> Rc<RefCell<DisplayAdjust>> {
+        self.display.clone()
+    }
+
+    pub fn screen_effects(&self) -> Rc<RefCell<ScreenEffects>> {
+        self.effects.clone()
+    }
+
+    pub fn recorder(&self) -> Rc<RefCell<Recorder>> {
+        self.recorder.clone()
+    }
+
+    fn capture_frame(&mut self) {
+        let mut recorder = self.recorder.borrow_mut();
+        if !recorder.wants_frame() {
+            return;
+        }
+
+        let (width, height) = match self.canvas.output_size() {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Could not query canvas size for recording: {}", e);
+                return;
+            }
         };
 
-        let (angle, origin) = match &data.rotation {
-            Some(r) => (
-                r.angle as f64,
-                Point::new(
-                    (r.origin.0 * query.width as f32) as i32,
-                    (r.origin.1 * query.height as f32) as i32,
-                ),
-            ),
-            None => (0.0, Point::new(0, 0)),
+        match self.canvas.read_pixels(None, PixelFormatEnum::RGB24) {
+            Ok(pixels) => recorder.push(width, height, self.timer.ticks64(), pixels),
+            Err(e) => warn!("Could not read canvas for recording: {}", e),
+        }
+    }
+
+    pub fn tints(&self) -> Rc<RefCell<Tints>> {
+        self.tints.clone()
+    }
+
+    pub fn batch_control(&self) -> Rc<RefCell<BatchControl>> {
+        self.batch_control.clone()
+    }
+
+    pub fn draw_scales(&self) -> Rc<RefCell<DrawScales>> {
+        self.scales.clone()
+    }
+
+    pub fn texture_filters(&self) -> Rc<RefCell<TextureFilters>> {
+        self.filters.clone()
+    }
+
+    pub fn screen_anchors(&self) -> Rc<RefCell<ScreenAnchors>> {
+        self.anchors.clone()
+    }
+
+    // Logical size when one is set, otherwise the output in pixels.
+    fn screen_size(&self) -> (u32, u32) {
+        match self.canvas.logical_size() {
+            (0, 0) => self.canvas.output_size().unwrap_or((800, 600)),
+            size => size,
+        }
+    }
+
+    fn draw_global_tint(&mut self) {
+        let color = match self.tints.borrow().global {
+            Some(c) => c,
+            None => return,
         };
 
-        let dest = match &data.destination {
-            Some(d) => match d {
-                DrawDestination::Location { x, y } => Some(Rect::new(
-                    (*x as i32) - origin.x,
-                    (*y as i32) - origin.y,
-                    query.width,
-                    query.height,
-                )),
-                DrawDestination::Rect(rect) => Some(to_rect(rect)),
-            },
-            None => None,
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Mod);
+        self.canvas.set_draw_color(color);
+        if let Err(e) = self.canvas.fill_rect(None) {
+            warn!("Could not apply global tint: {}", e);
+        }
+        self.canvas.set_blend_mode(previous);
+    }
+
+    pub fn trails(&self) -> Rc<RefCell<Trails>> {
+        self.trails.clone()
+    }
+
+    // Redirects a batch into a ping-pong buffer seeded with the faded previous frame.
+    // Returns false if the buffers couldn't be set up and the batch should draw directly.
+    fn begin_trail(&mut self, batch_index: usize, trail: TrailConfig) -> bool {
+        let (width, height) = match self.canvas.output_size() {
+            Ok(s) => s,
+            Err(_) => return false,
         };
 
-        let (horizontal, vertical) = match &data.flip {
-            Some(f) => (f.horizontal, f.vertical),
-            None => (false, false),
+        let stale = match self.trail_buffers.get(&batch_index) {
+            Some((t, _, _)) => {
+                let query = t.query();
+                query.width != width || query.height != height
+            }
+            None => true,
+        };
+        if stale {
+            let create = || {
+                let mut t = self.texture_creator.create_texture_target(
+                    PixelFormatEnum::ARGB8888,
+                    width,
+                    height,
+                )?;
+                t.set_blend_mode(BlendMode::Blend);
+                Ok::<_, sdl2::render::TextureValueError>(t)
+            };
+            match (create(), create()) {
+                (Ok(a), Ok(b)) => {
+                    self.trail_buffers.insert(batch_index, (a, b, 0));
+                }
+                _ => {
+                    error!("Could not create trail buffers for batch {}", batch_index);
+                    return false;
+                }
+            }
+        }
+
+        let (current, previous) = match self.trail_buffers.get_mut(&batch_index) {
+            Some((a, b, idle)) => {
+                *idle = 0;
+                (a, b)
+            }
+            None => return false,
         };
+        std::mem::swap(current, previous);
 
-        if let Err(e) = self
-            .canvas
-            .copy_ex(&tex, source, dest, angle, origin, horizontal, vertical)
-        {
-            warn!("SDL2 Texture Rendering failed: {}", e);
+        if let Err(e) = render::set_target(&mut self.canvas, Some(&*current)) {
+            error!("Could not bind trail buffer: {}", e);
+            return false;
         }
-    }
-}
 
-fn to_rect(r: &DrawRect) -> Rect {
-    Rect::new(r.x as i32, r.y as i32, r.width as u32, r.height as u32)
-}
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+        self.canvas.clear();
+        previous.set_alpha_mod(trail.persistence);
+        if let Err(e) = self.canvas.copy(previous, None, None) {
+            warn!("Could not fade trail buffer: {}", e);
+        }
 
-fn to_color(c: &DrawColor) -> Color {
-    let max = u8::MAX as f32;
+        true
+    }
+
+    fn end_trail(&mut self, batch_index: usize) {
+        let restore = match self.offscreen {
+            true => self.frame_target.as_ref(),
+            false => None,
+        };
+        if let Err(e) = render::bind_frame(&mut self.canvas, restore, self.frame_scale) {
+            error!("Could not restore render target after trail: {}", e);
+            return;
+        }
+
+        if let Some((current, _, _)) = self.trail_buffers.get(&batch_index) {
+            if let Err(e) = self.canvas.copy(current, None, None) {
+                warn!("Could not composite trail buffer: {}", e);
+            }
+        }
+    }
+
+    // Frees the buffers of batches that stopped trailing, or whose index no longer has a
+    // batch, once they sat unused for the configured number of frames.
+    fn evict_idle_trails(&mut self) {
+        let limit = self.trails.borrow().idle_frames.max(1);
+        self.trail_buffers.retain(|_, (_, _, idle)| {
+            *idle += 1;
+            *idle <= limit
+        });
+    }
+
+    pub fn lighting(&self) -> Rc<RefCell<Lighting>> {
+        self.lighting.clone()
+    }
+
+    // Lights are added into a lightmap filled with the ambient color, which is then
+    // multiplied over everything drawn so far.
+    fn draw_lighting(&mut self) {
+        let lighting = self.lighting.clone();
+        let mut lighting = lighting.borrow_mut();
+        if !lighting.enabled {
+            lighting.clear();
+            return;
+        }
+
+        let (width, height) = match self.canvas.output_size() {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Could not query canvas size for lighting: {}", e);
+                return;
+            }
+        };
+
+        let stale = match &self.light_map {
+            Some(t) => {
+                let query = t.query();
+                query.width != width || query.height != height
+            }
+            None => true,
+        };
+        if stale {
+            self.light_map = self
+                .texture_creator
+                .create_texture_target(PixelFormatEnum::ARGB8888, width, height)
+                .map_err(|e| error!("Could not create lightmap: {}", e))
+                .ok();
+        }
+        if self.light_sprite.is_none() {
+            self.light_sprite = lighting::light_surface()
+                .and_then(|s| {
+                    self.texture_creator
+                        .create_texture_from_surface(&s)
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|e| error!("Could not create light sprite: {}", e))
+                .ok();
+        }
+
+        let (light_map, sprite) = match (&mut self.light_map, &mut self.light_sprite) {
+            (Some(m), Some(s)) => (m, s),
+            _ => return,
+        };
+
+        let restore = match self.offscreen {
+            true => self.frame_target.as_ref(),
+            false => None,
+        };
+        if let Err(e) = render::set_target(&mut self.canvas, Some(&*light_map)) {
+            error!("Could not bind lightmap: {}", e);
+            return;
+        }
+
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_draw_color(lighting.ambient);
+        self.canvas.clear();
+
+        sprite.set_blend_mode(BlendMode::Add);
+        for light in lighting.lights().iter() {
+            sprite.set_color_mod(light.color.r, light.color.g, light.color.b);
+            let size = (light.radius * 2.0).max(1.0) as u32;
+            let dest = Rect::new(
+                (light.x - light.radius) as i32,
+                (light.y - light.radius) as i32,
+                size,
+                size,
+            );
+            if let Err(e) = self.canvas.copy(sprite, None, dest) {
+                warn!("Could not draw light: {}", e);
+            }
+        }
+
+        if let Err(e) = render::bind_frame(&mut self.canvas, restore, self.frame_scale) {
+            error!("Could not restore render target after lighting: {}", e);
+        }
+
+        light_map.set_blend_mode(BlendMode::Mod);
+        if let Err(e) = self.canvas.copy(light_map, None, None) {
+            warn!("Could not apply lightmap: {}", e);
+        }
+        self.canvas.set_blend_mode(previous);
+        lighting.clear();
+    }
+
+    fn draw_flash(&mut self) {
+        let color = match self.effects.borrow_mut().overlay(self.timer.ticks64()) {
+            Some(c) => c,
+            None => return,
+        };
+
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(color);
+        if let Err(e) = self.canvas.fill_rect(None) {
+            warn!("Could not draw screen flash: {}", e);
+        }
+        self.canvas.set_blend_mode(previous);
+    }
+
+    pub fn cinematic_bars(&self) -> Rc<RefCell<CinematicBars>> {
+        self.cinematic_bars.clone()
+    }
+
+    fn draw_cinematic_bars(&mut self) {
+        let mut bars = self.cinematic_bars.borrow_mut();
+        bars.advance(self.timer.ticks64());
+        if !bars.is_showing() {
+            return;
+        }
+
+        let (w, h) = self.canvas.output_size().unwrap_or((800, 600));
+        let height = bars.bar_height((w, h));
+        if height == 0 {
+            return;
+        }
+
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(bars.color);
+        let rects = [
+            Rect::new(0, 0, w, height),
+            Rect::new(0, h as i32 - height as i32, w, height),
+        ];
+        if let Err(e) = self.canvas.fill_rects(&rects) {
+            warn!("Could not draw cinematic bars: {}", e);
+        }
+        self.canvas.set_blend_mode(previous);
+    }
+
+    fn begin_frame(&mut self) {
+        self.culled = 0;
+        self.frame_dump.borrow_mut().begin_frame();
+        self.draw_offset = self.effects.borrow_mut().offset(self.timer.ticks64());
+
+        self.offscreen = false;
+        self.frame_scale = None;
+        self.adaptive.borrow_mut().record(self.frame_time);
+        let scale = self.adaptive.borrow().scale();
+        let live = self.live_resize.borrow().enabled;
+        if self.post_process.is_none()
+            && !self.display.borrow().needs_pixels()
+            && scale >= 1.0
+            && !live
+        {
+            return;
+        }
+
+        let (width, height) = match self.canvas.output_size() {
+            Ok((w, h)) => (
+                ((w as f32 * scale) as u32).max(1),
+                ((h as f32 * scale) as u32).max(1),
+            ),
+            Err(e) => {
+                warn!("Could not query canvas size for post-processing: {}", e);
+                return;
+            }
+        };
+
+        if scale < 1.0 {
+            let (sx, sy) = self.canvas.scale();
+            self.frame_scale = Some((sx * scale, sy * scale));
+        }
+
+        let stale = match &self.frame_target {
+            Some(t) => {
+                let query = t.query();
+                query.width != width || query.height != height
+            }
+            None => true,
+        };
+
+        if stale {
+            self.live_resize.borrow_mut().set_frame(None);
+            self.frame_target = match self.texture_creator.create_texture_target(
+                PixelFormatEnum::ARGB8888,
+                width,
+                height,
+            ) {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    error!("Could not create post-processing render target: {}", e);
+                    None
+                }
+            };
+        }
+
+        match render::bind_frame(
+            &mut self.canvas,
+            self.frame_target.as_ref(),
+            self.frame_scale,
+        ) {
+            Ok(()) => self.offscreen = self.frame_target.is_some(),
+            Err(e) => error!("Could not bind post-processing render target: {}", e),
+        }
+    }
+
+    fn end_frame(&mut self) {
+        let display = *self.display.borrow();
+
+        if (display.gamma - self.applied_gamma).abs() > f32::EPSILON {
+            self.applied_gamma = display.gamma;
+            let ramp = display.gamma_ramp();
+            if let Err(e) = self.canvas.window().set_gamma_ramp(&ramp, &ramp, &ramp) {
+                warn!("Could not set display gamma: {}", e);
+            }
+        }
+
+        if self.offscreen {
+            if let Some(frame) = &mut self.frame_target {
+                if display.needs_pixels() {
+                    let pitch = frame.query().width as usize * 4;
+                    match self.canvas.read_pixels(None, PixelFormatEnum::ARGB8888) {
+                        Ok(mut pixels) => {
+                            display.apply_pixels(&mut pixels);
+                            if let Err(e) = frame.update(None, &pixels, pitch) {
+                                warn!("Could not apply display filter: {}", e);
+                            }
+                        }
+                        Err(e) => warn!("Could not read frame for display filter: {}", e),
+                    }
+                }
+
+                if let Err(e) = render::set_target(&mut self.canvas, None) {
+                    error!("Could not restore the window render target: {}", e);
+                    return;
+                }
+
+                let live = match self.live_resize.borrow().enabled {
+                    true => Some((self.canvas.raw(), frame.raw())),
+                    false => None,
+                };
+                self.live_resize.borrow_mut().set_frame(live);
+
+                match &mut self.post_process {
+                    Some(hook) => {
+                        let mut context = FrameContext {
+                            canvas: &mut self.canvas,
+                            frame,
+                        };
+                        hook.process(&mut context);
+                    }
+                    None => {
+                        if let Err(e) = self.canvas.copy(frame, None, None) {
+                            warn!("Could not present offscreen frame: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((color, blend)) = display.overlay() {
+            let previous = self.canvas.blend_mode();
+            self.canvas.set_blend_mode(blend);
+            self.canvas.set_draw_color(color);
+            if let Err(e) = self.canvas.fill_rect(None) {
+                warn!("Could not draw brightness overlay: {}", e);
+            }
+            self.canvas.set_blend_mode(previous);
+        }
+    }
+
+    pub fn scheduler(&self) -> Rc<RefCell<Scheduler<Message>>> {
+        self.scheduler.clone()
+    }
+
+    pub fn tweens(&self) -> Rc<RefCell<Tweens>> {
+        self.tweens.clone()
+    }
+
+    pub fn random(&self) -> Rc<RefCell<RandomService>> {
+        self.random.clone()
+    }
+
+    pub fn work(&self) -> Rc<RefCell<WorkQueue<'a>>> {
+        self.work.clone()
+    }
+
+    pub fn input_debug(&self) -> Rc<RefCell<InputDebug>> {
+        self.input_debug.clone()
+    }
+
+    pub fn input_contexts(&self) -> Rc<RefCell<InputContexts>> {
+        self.input_contexts.clone()
+    }
+
+    pub fn action_holds(&self) -> Rc<RefCell<ActionHolds>> {
+        self.action_holds.clone()
+    }
+
+    pub fn input_accessibility(&self) -> Rc<RefCell<InputAccessibility>> {
+        self.accessibility.clone()
+    }
+
+    pub fn action_debounce(&self) -> Rc<RefCell<ActionDebounce>> {
+        self.debounce.clone()
+    }
+
+    pub fn add_event_hook(&mut self, hook: Box<dyn EventHook + 'a>) {
+        self.event_hooks.push(hook);
+    }
+
+    pub fn event_receiver(&mut self) -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        self.event_senders.push(sender);
+        receiver
+    }
+
+    fn dispatch_event(&mut self, event: &Event) {
+        #[cfg(feature = "egui")]
+        if let Some(layer) = &mut self.egui {
+            layer.handle_event(event);
+        }
+
+        for hook in self.event_hooks.iter_mut() {
+            hook.handle(event);
+        }
+        self.event_senders
+            .retain(|sender| sender.send(event.clone()).is_ok());
+    }
+
+    pub fn on_screen_keyboard(&self) -> Rc<RefCell<OnScreenKeyboard>> {
+        self.keyboard.clone()
+    }
+
+    fn draw_keyboard(&mut self) {
+        let (keys, font) = {
+            let keyboard = self.keyboard.borrow();
+            if !keyboard.is_open() {
+                return;
+            }
+            let bounds = self.canvas.output_size().unwrap_or((800, 600));
+            (keyboard.layout(bounds), keyboard.font)
+        };
+
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+
+        for (rect, label, selected) in keys.iter() {
+            self.canvas.set_draw_color(match selected {
+                true => Color::RGBA(230, 230, 230, 230),
+                false => Color::RGBA(30, 30, 30, 200),
+            });
+            let _ = self.canvas.fill_rect(*rect);
+
+            let font = match font {
+                Some(f) => f,
+                None => continue,
+            };
+            let color = match selected {
+                true => Color::RGB(20, 20, 20),
+                false => Color::RGB(240, 240, 240),
+            };
+            if let Ok(texture) = self.text_texture(font, label, color) {
+                let query = texture.query();
+                let dest = Rect::new(
+                    rect.x() + (rect.width() as i32 - query.width as i32) / 2,
+                    rect.y() + (rect.height() as i32 - query.height as i32) / 2,
+                    query.width,
+                    query.height,
+                );
+                let _ = self.canvas.copy(&texture, None, dest);
+            }
+        }
+
+        self.canvas.set_blend_mode(previous);
+    }
+
+    // For other threads to feed scenes; `send` blocks once `capacity` messages are waiting.
+    pub fn message_sender(&mut self, capacity: usize) -> SyncSender<Message>
+    where
+        Message: Send,
+    {
+        self.inbox.borrow_mut().open(capacity)
+    }
+
+    // Messages received before an update are readable by scenes during it.
+    pub fn inbox(&self) -> Rc<RefCell<MessageInbox<Message>>> {
+        self.inbox.clone()
+    }
+
+    #[cfg(feature = "egui")]
+    pub fn set_egui_layer(&mut self, layer: Option<egui_layer::SDLEguiLayer<'a>>) {
+        self.egui = layer;
+    }
+
+    #[cfg(feature = "egui")]
+    pub fn egui_layer(&mut self) -> Option<&mut egui_layer::SDLEguiLayer<'a>> {
+        self.egui.as_mut()
+    }
+
+    pub fn analog_filters(&self) -> Rc<RefCell<AnalogFilters>> {
+        self.analog_filters.clone()
+    }
+
+    pub fn controller_resolution(&self) -> Rc<RefCell<ControllerResolution>> {
+        self.controller_resolution.clone()
+    }
+
+    pub fn controller_registry(&self) -> Rc<RefCell<ControllerRegistry>> {
+        self.controller_registry.clone()
+    }
+
+    pub fn controller_events(&self) -> Rc<RefCell<ControllerEvents>> {
+        self.controller_events.clone()
+    }
+
+    fn refresh_devices(&mut self) {
+        let registry = self.controller_registry.borrow();
+        let devices = self
+            .controllers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, c)| {
+                let c = c.as_ref()?;
+                Some(ControllerDevice {
+                    instance_id: c.instance_id(),
+                    index,
+                    slot: registry.slot(c.instance_id()).unwrap_or(index),
+                    name: c.name(),
+                    family: PromptFamily::from_controller(c),
+                })
+            })
+            .collect();
+        self.controller_events.borrow_mut().set_devices(devices);
+    }
+
+    pub fn axis_calibrator(&self) -> Rc<RefCell<AxisCalibrator>> {
+        self.calibrator.clone()
+    }
+
+    fn sample_calibration(&mut self) {
+        let mut calibrator = self.calibrator.borrow_mut();
+        let target = match calibrator.target() {
+            Some(t) => t,
+            None => return,
+        };
+        let controller = match self
+            .controllers
+            .iter()
+            .flatten()
+            .find(|c| c.instance_id() == target)
+        {
+            Some(c) => c,
+            None => {
+                warn!("Calibration cancelled, the controller was disconnected");
+                calibrator.cancel();
+                return;
+            }
+        };
+
+        let raw = calibration::AXES.map(|a| controller.axis(a));
+        if let Some(axes) = calibrator.sample(raw, self.timer.ticks64()) {
+            let mut registry = self.controller_registry.borrow_mut();
+            let guid = controller_guid(controller);
+            let calibration = ControllerCalibration {
+                axes,
+                ..registry.calibration(&guid)
+            };
+            registry.set_calibration(&guid, Some(calibration));
+        }
+    }
+
+    // Loads remembered slots and calibrations from `path` and reorders the connected
+    // controllers to match, see `config::save_path` for a per-user location.
+    pub fn persist_controllers(&mut self, path: &Path) -> Result<(), String> {
+        self.controller_registry.borrow_mut().persist_to(path)?;
+        self.arrange_controllers();
+        Ok(())
+    }
+
+    // Controller indices are registry slots, so a reconnected controller gets its old
+    // index back even while the ones before it are still missing.
+    fn arrange_controllers(&mut self) {
+        let mut registry = self.controller_registry.borrow_mut();
+        registry.clear_connected();
+
+        let connected: Vec<GameController> = self.controllers.drain(..).flatten().collect();
+        for controller in connected {
+            let slot = registry.connect(controller.instance_id(), &controller_guid(&controller));
+            place_controller(&mut self.controllers, slot, controller);
+        }
+        drop(registry);
+        self.refresh_devices();
+    }
+
+    pub(crate) fn controller_slot(&self, instance_id: u32) -> Option<usize> {
+        self.controllers
+            .iter()
+            .position(|c| c.as_ref().map_or(false, |c| c.instance_id() == instance_id))
+    }
+
+    fn handle_controller_device(&mut self, event: &Event) {
+        match event {
+            Event::ControllerDeviceAdded { which, .. } => {
+                let controller = match self
+                    .sdl
+                    .game_controller()
+                    .and_then(|s| s.open(*which).map_err(|e| e.to_string()))
+                {
+                    Ok(c) => c,
+                    Err(e) => {
+                        warn!("Problem opening controller: {}", e);
+                        return;
+                    }
+                };
+
+                // Controllers present at startup are announced as well.
+                let instance = controller.instance_id();
+                if self.controller_slot(instance).is_some() {
+                    return;
+                }
+
+                input::enable_sensors(&controller);
+                let slot = self
+                    .controller_registry
+                    .borrow_mut()
+                    .connect(instance, &controller_guid(&controller));
+                self.axis_motion.borrow_mut().forget(slot);
+                self.rumble.borrow_mut().forget(slot);
+                place_controller(&mut self.controllers, slot, controller);
+                self.refresh_devices();
+                self.controller_events.borrow_mut().added(instance);
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controller_events.borrow_mut().removed(*which);
+                if let Some(slot) = self.controller_slot(*which) {
+                    self.controllers[slot] = None;
+                    while let Some(None) = self.controllers.last() {
+                        self.controllers.pop();
+                    }
+                    self.axis_motion.borrow_mut().forget(slot);
+                    self.rumble.borrow_mut().forget(slot);
+                }
+                self.controller_registry.borrow_mut().disconnect(*which);
+                self.refresh_devices();
+            }
+            _ => (),
+        }
+    }
+
+    pub fn hit_regions(&self) -> Rc<RefCell<HitRegions>> {
+        self.hit_regions.clone()
+    }
+
+    pub fn keyboard_split(&self) -> Rc<RefCell<KeyboardSplit>> {
+        self.keyboard_split.clone()
+    }
+
+    pub fn set_input_sampling(&mut self, sampling: InputSampling) {
+        self.presses.borrow_mut().sampling = sampling;
+    }
+
+    pub fn input_sampling(&self) -> InputSampling {
+        self.presses.borrow().sampling
+    }
+
+    pub fn combos(&self) -> Rc<RefCell<Combos>> {
+        self.combos.clone()
+    }
+
+    pub fn input_timeline(&self) -> Rc<RefCell<InputTimeline>> {
+        self.timeline.clone()
+    }
+
+    pub fn input_snapshot(&self) -> Rc<RefCell<InputSnapshot>> {
+        self.snapshot.clone()
+    }
+
+    pub fn clicks(&self) -> Rc<RefCell<Clicks>> {
+        self.clicks.clone()
+    }
+
+    // Window pixels to the space hit regions and click positions use.
+    fn pointer_position(&self, x: i32, y: i32) -> (f32, f32) {
+        let coordinates = self.coordinates.borrow();
+        let (x, y) = (x as f32, y as f32);
+        match coordinates.pixels_per_unit {
+            Some(_) => coordinates.window_to_world(x, y),
+            None => coordinates.window_to_logical(x, y),
+        }
+    }
+
+    // Keeps controller bindings live while another window has focus, for companion tools
+    // and overlays. With focus gating on, keyboard and mouse bindings still stop.
+    pub fn set_background_input(&mut self, enabled: bool) {
+        self.background_input = enabled;
+        SDLHint::JoystickBackgroundEvents(enabled).apply();
+    }
+
+    // Ignores keyboard and mouse bindings, and controller bindings unless background input
+    // is on, while the window is unfocused. Off by default, so input the platform still
+    // delivers, like the mouse over an unfocused window, keeps reaching actions.
+    pub fn set_focus_gated_input(&mut self, enabled: bool) {
+        self.focus_gated_input = enabled;
+    }
+
+    pub fn is_window_focused(&self) -> bool {
+        self.window_focused
+    }
+
+    // The device the player touched last, for picking which button prompts to show.
+    pub fn prompt_family(&self) -> PromptFamily {
+        self.prompt_family
+    }
+
+    pub fn prompt(&self, pack: &PromptPack, command: &SDLCommand) -> Option<PromptIcon> {
+        pack.prompt(command, self.prompt_family)
+    }
+
+    pub fn cursor(&self) -> Rc<RefCell<CursorControl>> {
+        self.cursor.clone()
+    }
+
+    pub fn live_resize(&self) -> Rc<RefCell<LiveResize>> {
+        self.live_resize.clone()
+    }
+
+    pub fn lifecycle(&self) -> Rc<RefCell<Lifecycle>> {
+        self.lifecycle.clone()
+    }
+
+    // Render targets are recreated lazily on their next use; stored textures are uploaded
+    // again from their sources since their contents may be gone.
+    fn handle_device_reset(&mut self, event: &Event) {
+        let device = match event {
+            Event::RenderTargetsReset { .. } => false,
+            Event::RenderDeviceReset { .. } => true,
+            _ => return,
+        };
+
+        self.live_resize.borrow_mut().set_frame(None);
+        self.frame_target = None;
+        self.light_map = None;
+        self.trail_buffers.clear();
+
+        if device {
+            self.placeholder = None;
+            self.light_sprite = None;
+            let failed = self.storage.borrow_mut().restore_textures();
+            if failed > 0 {
+                warn!(
+                    "{} textures could not be restored after a device reset",
+                    failed
+                );
+            }
+        }
+    }
+
+    pub fn background_policy(&self) -> Rc<RefCell<BackgroundPolicy>> {
+        self.background.clone()
+    }
+
+    fn handle_background(&mut self, event: &Event) {
+        let mut background = self.background.borrow_mut();
+        background.record(event);
+        match background.audio_change() {
+            Some(true) => self.policy_pause.pause(PauseReason::Background),
+            Some(false) => self.policy_pause.resume(PauseReason::Background),
+            None => (),
+        }
+    }
+
+    fn handle_lifecycle(&mut self, event: &Event) {
+        let mut lifecycle = self.lifecycle.borrow_mut();
+        match lifecycle.record(event) {
+            Some(LifecycleEvent::EnteringBackground) if lifecycle.pause_audio => {
+                self.policy_pause.pause(PauseReason::Lifecycle)
+            }
+            Some(LifecycleEvent::EnteredForeground) => {
+                self.policy_pause.resume(PauseReason::Lifecycle)
+            }
+            Some(LifecycleEvent::LowMemory) => {
+                warn!("System reported low memory");
+                self.sound_variations.borrow_mut().clear_variants();
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_cursor(&mut self) {
+        let mut cursor = self.cursor.borrow_mut();
+
+        if let Some(visible) = cursor.pending_visibility(self.timer.ticks64()) {
+            self.sdl.mouse().show_cursor(visible);
+        }
+
+        if let Some(grab) = cursor.pending_grab(self.window_focused) {
+            self.canvas.window_mut().set_grab(grab);
+        }
+
+        if let Some((x, y)) = cursor.take_warp() {
+            let (x, y) = self.coordinates.borrow().logical_to_window(x, y);
+            self.sdl
+                .mouse()
+                .warp_mouse_in_window(self.canvas.window(), x as i32, y as i32);
+        }
+    }
+
+    pub fn rumble(&self) -> Rc<RefCell<Rumble>> {
+        self.rumble.clone()
+    }
+
+    // Each call runs the motors a little past the next refresh, so a stalled frame doesn't
+    // cut a steady rumble short.
+    fn apply_rumble(&mut self) {
+        const REFRESH: u64 = 250;
+        let send = self
+            .rumble
+            .borrow_mut()
+            .advance(self.timer.ticks64(), REFRESH);
+        for (index, low, high) in send {
+            let controller = match self.controllers.get_mut(index) {
+                Some(Some(c)) if c.has_rumble() => c,
+                _ => continue,
+            };
+            if let Err(e) = controller.set_rumble(low, high, REFRESH as u32) {
+                warn!("Could not rumble controller {}: {}", index, e);
+            }
+        }
+    }
+
+    pub fn axis_motion(&self) -> Rc<RefCell<AxisMotionLog>> {
+        self.axis_motion.clone()
+    }
+
+    pub fn input_activity(&self) -> Rc<RefCell<InputActivity>> {
+        self.input_activity.clone()
+    }
+
+    pub fn focus(&self) -> Rc<RefCell<FocusNavigator>> {
+        self.focus.clone()
+    }
+
+    pub fn virtual_gamepad(&self) -> Rc<RefCell<VirtualGamepad>> {
+        self.virtual_gamepad.clone()
+    }
+
+    // Finger positions arrive normalised to the window. The virtual gamepad draws straight
+    // to the canvas, so it stays top-down whatever the configured Y axis.
+    fn touch_to_canvas(&self, x: f32, y: f32) -> (f32, f32) {
+        let (width, height) = self.canvas.window().size();
+        let (x, y) = self.window_to_canvas((x * width as f32) as i32, (y * height as f32) as i32);
+        (x, self.coordinates.borrow().flip_y(y))
+    }
+
+    fn draw_virtual_gamepad(&mut self) {
+        let pad = self.virtual_gamepad.clone();
+        let pad = pad.borrow();
+        if !pad.enabled {
+            return;
+        }
+
+        for (index, control) in pad.controls().iter().enumerate() {
+            let ticket = match (pad.is_pressed(index), control.pressed_texture) {
+                (true, Some(t)) => t,
+                _ => control.texture,
+            };
+            let r = &control.rect;
+            self.copy_overlay(
+                ticket,
+                Rect::new(r.x as i32, r.y as i32, r.width as u32, r.height as u32),
+                pad.opacity,
+            );
+
+            if let (VirtualControlKind::Stick { radius }, Some(knob)) =
+                (control.kind, control.knob_texture)
+            {
+                let (dx, dy) = pad.stick(index).unwrap_or((0.0, 0.0));
+                let size = (r.width.min(r.height) / 2.0) as u32;
+                let x = r.x + r.width / 2.0 + dx * radius - size as f32 / 2.0;
+                let y = r.y + r.height / 2.0 + dy * radius - size as f32 / 2.0;
+                self.copy_overlay(knob, Rect::new(x as i32, y as i32, size, size), pad.opacity);
+            }
+        }
+    }
+
+    pub fn overlays(&self) -> Rc<RefCell<Overlays>> {
+        self.overlays.clone()
+    }
+
+    fn draw_overlays(&mut self) {
+        let watermarks = self.overlays.borrow().visible();
+        if watermarks.is_empty() {
+            return;
+        }
+
+        let bounds = self.canvas.output_size().unwrap_or((800, 600));
+        for watermark in watermarks.iter() {
+            let info = match self.storage.borrow().texture_info(watermark.texture) {
+                Ok(i) => i,
+                Err(e) => {
+                    ResourceError::log_failure(e);
+                    continue;
+                }
+            };
+
+            let size = (
+                (info.width as f32 * watermark.scale) as u32,
+                (info.height as f32 * watermark.scale) as u32,
+            );
+            let (x, y) = watermark.anchor.place(bounds, size, watermark.offset);
+            self.copy_overlay(
+                watermark.texture,
+                Rect::new(x, y, size.0, size.1),
+                watermark.opacity,
+            );
+        }
+    }
+
+    fn copy_overlay(&mut self, ticket: Ticket, rect: Rect, alpha: u8) {
+        let texture = match self.storage.borrow().textures.get_by_ticket(ticket) {
+            Ok(t) => t,
+            Err(e) => {
+                ResourceError::log_failure(e);
+                return;
+            }
+        };
+
+        let mut texture = texture.borrow_mut();
+        let previous = texture.alpha_mod();
+        texture.set_alpha_mod(alpha);
+        if let Err(e) = self.canvas.copy(&texture, None, rect) {
+            warn!("Could not draw overlay texture: {}", e);
+        }
+        texture.set_alpha_mod(previous);
+    }
+
+    pub fn coordinates(&self) -> Rc<RefCell<CoordinateSpace>> {
+        self.coordinates.clone()
+    }
+
+    pub fn window_to_canvas(&self, x: i32, y: i32) -> (f32, f32) {
+        self.coordinates
+            .borrow()
+            .window_to_logical(x as f32, y as f32)
+    }
+
+    pub fn display_metrics(&self) -> Rc<RefCell<DisplayMetrics>> {
+        self.metrics.clone()
+    }
+
+    fn update_metrics(&mut self) {
+        let window = self.canvas.window();
+        let size = window.size();
+        let drawable = self.canvas.output_size().unwrap_or(size);
+
+        let (x, y) = window.position();
+        let bounds = window
+            .display_index()
+            .and_then(|i| window.subsystem().display_usable_bounds(i));
+        let safe_area = match bounds {
+            Ok(b) => SafeArea {
+                top: (b.top() - y).max(0) as u32,
+                bottom: ((y + size.1 as i32) - b.bottom()).max(0) as u32,
+                left: (b.left() - x).max(0) as u32,
+                right: ((x + size.0 as i32) - b.right()).max(0) as u32,
+            },
+            Err(_) => SafeArea::default(),
+        };
+
+        let mut metrics = self.metrics.borrow_mut();
+        metrics.drawable = drawable;
+        metrics.window = size;
+        metrics.safe_area = safe_area;
+
+        let refresh = window
+            .display_index()
+            .and_then(|i| window.subsystem().current_display_mode(i))
+            .ok()
+            .map(|m| m.refresh_rate as u32);
+        if self.pacing.borrow_mut().set_refresh_rate(refresh) {
+            info!("Display refresh rate is now {:?}", refresh);
+        }
+    }
+
+    // Exclusive fullscreen modes of the display the window is on, largest and fastest first.
+    pub fn fullscreen_modes(&self) -> Result<Vec<FullscreenMode>, String> {
+        let window = self.canvas.window();
+        let video = window.subsystem();
+        let display = window.display_index()?;
+
+        let mut modes = Vec::new();
+        for i in 0..video.num_display_modes(display)? {
+            let mode = FullscreenMode::from_display_mode(video.display_mode(display, i)?);
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+        Ok(modes)
+    }
+
+    pub fn mode_confirmation(&self) -> Rc<RefCell<ModeConfirmation>> {
+        self.mode_confirmation.clone()
+    }
+
+    // Switches to exclusive fullscreen at `mode`. With `confirm`, the previous mode comes
+    // back unless `confirm_fullscreen_mode` is called before the confirmation times out.
+    pub fn set_fullscreen_mode(
+        &mut self,
+        mode: &FullscreenMode,
+        confirm: bool,
+    ) -> Result<(), String> {
+        let window = self.canvas.window_mut();
+        let previous = PendingMode {
+            fullscreen: window.fullscreen_state(),
+            mode: window.display_mode()?,
+            deadline: self.timer.ticks64() + self.mode_confirmation.borrow().timeout,
+        };
+
+        window.set_display_mode(Some(mode.mode))?;
+        window.set_fullscreen(FullscreenType::True)?;
+
+        let mut confirmation = self.mode_confirmation.borrow_mut();
+        // Changing again before confirming keeps the mode from before the first change.
+        if confirm && confirmation.pending.is_none() {
+            confirmation.pending = Some(previous);
+        }
+        Ok(())
+    }
+
+    pub fn confirm_fullscreen_mode(&mut self) {
+        self.mode_confirmation.borrow_mut().pending = None;
+    }
+
+    pub fn revert_fullscreen_mode(&mut self) -> Result<(), String> {
+        let pending = match self.mode_confirmation.borrow_mut().pending.take() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+
+        let window = self.canvas.window_mut();
+        window.set_fullscreen(FullscreenType::Off)?;
+        window.set_display_mode(Some(pending.mode))?;
+        window.set_fullscreen(pending.fullscreen)
+    }
+
+    fn check_mode_confirmation(&mut self) {
+        let now = self.timer.ticks64();
+        let expired = {
+            let mut confirmation = self.mode_confirmation.borrow_mut();
+            confirmation.reverted = false;
+            confirmation.remaining(now) == Some(0)
+        };
+        if !expired {
+            return;
+        }
+
+        info!("Display mode was not confirmed, reverting");
+        if let Err(e) = self.revert_fullscreen_mode() {
+            warn!("Could not revert display mode: {}", e);
+        }
+        self.mode_confirmation.borrow_mut().reverted = true;
+    }
+
+    pub fn settings(&self) -> Rc<RefCell<Settings>> {
+        self.settings.clone()
+    }
+
+    // Loads saved settings from `path` and applies them, see `config::save_path` for a
+    // per-user location. Call after the input map has all its actions.
+    pub fn persist_settings(&mut self, path: &Path) -> Result<(), String> {
+        self.settings.borrow_mut().persist_to(path)?;
+        self.apply_settings()
+    }
+
+    // Puts the window, bus levels and bindings in `Settings` into effect.
+    pub fn apply_settings(&mut self) -> Result<(), String> {
+        let (window_size, fullscreen, levels, bindings) = {
+            let settings = self.settings.borrow();
+            let levels = [Bus::Music, Bus::Sounds].map(|b| (b, settings.level(b)));
+            let bindings: Vec<_> = settings.bindings().map(|(k, c)| (*k, c.clone())).collect();
+            (settings.window_size, settings.fullscreen, levels, bindings)
+        };
+
+        let window = self.canvas.window_mut();
+        if let Some((w, h)) = window_size {
+            window.set_fullscreen(FullscreenType::Off)?;
+            window.set_size(w, h).map_err(|e| e.to_string())?;
+        }
+        match fullscreen {
+            FullscreenSetting::Windowed => window.set_fullscreen(FullscreenType::Off)?,
+            FullscreenSetting::Desktop => window.set_fullscreen(FullscreenType::Desktop)?,
+            FullscreenSetting::Exclusive {
+                width,
+                height,
+                refresh_rate,
+            } => {
+                // The saved refresh rate is preferred, but any rate at that size will do.
+                let modes = self.fullscreen_modes()?;
+                let mode = modes
+                    .iter()
+                    .filter(|m| m.width == width && m.height == height)
+                    .max_by_key(|m| m.refresh_rate == refresh_rate);
+                match mode {
+                    Some(m) => self.set_fullscreen_mode(m, false)?,
+                    None => warn!("No {}x{} fullscreen mode, staying windowed", width, height),
+                }
+            }
+        }
+
+        let mut buses = self.audio_buses.borrow_mut();
+        for (bus, level) in levels {
+            if let Some(level) = level {
+                buses.set_volume(bus, level);
+            }
+        }
+        drop(buses);
+
+        for ((user, action), commands) in bindings {
+            if !self.replace_commands(user, action, commands) {
+                warn!(
+                    "Saved binding for unknown action {} of user {}",
+                    action, user
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Records the current window and bus volumes into `Settings` and saves them. Ducks are
+    // temporary and never saved.
+    pub fn save_settings(&mut self) -> Result<(), String> {
+        let mut settings = self.settings.borrow_mut();
+        let window = self.canvas.window();
+        settings.fullscreen = match window.fullscreen_state() {
+            FullscreenType::Off => {
+                settings.window_size = Some(window.size());
+                FullscreenSetting::Windowed
+            }
+            FullscreenType::Desktop => FullscreenSetting::Desktop,
+            FullscreenType::True => {
+                let mode = FullscreenMode::from_display_mode(window.display_mode()?);
+                FullscreenSetting::Exclusive {
+                    width: mode.width,
+                    height: mode.height,
+                    refresh_rate: mode.refresh_rate,
+                }
+            }
+        };
+
+        let buses = self.audio_buses.borrow();
+        for bus in [Bus::Music, Bus::Sounds] {
+            settings.set_level(bus, buses.volume(bus));
+        }
+        settings.save()
+    }
+
+    // Rebinds an action and saves the new binding. Returns false for an unknown action.
+    pub fn set_binding(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        commands: Vec<SDLCommand>,
+    ) -> bool {
+        if !self.replace_commands(user_index, action_index, commands.clone()) {
+            return false;
+        }
+
+        let mut settings = self.settings.borrow_mut();
+        settings.set_binding(user_index, action_index, Some(commands));
+        settings.save_logged();
+        true
+    }
+
+    // Goes back to the commands the game set up for the action.
+    pub fn reset_binding(&mut self, user_index: usize, action_index: usize) {
+        let defaults = {
+            let mut settings = self.settings.borrow_mut();
+            if settings.binding(user_index, action_index).is_none() {
+                return;
+            }
+            settings.set_binding(user_index, action_index, None);
+            settings.save_logged();
+            settings.defaults.remove(&(user_index, action_index))
+        };
+        if let Some(defaults) = defaults {
+            self.replace_commands(user_index, action_index, defaults);
+            self.settings
+                .borrow_mut()
+                .defaults
+                .remove(&(user_index, action_index));
+        }
+    }
+
+    // Keeps the first commands an action had, so a reset can go back to them.
+    fn replace_commands(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        commands: Vec<SDLCommand>,
+    ) -> bool {
+        let mut input = self.input.borrow_mut();
+        let entry = match input
+            .commands
+            .iter_mut()
+            .find(|c| c.user_index == user_index && c.action_index == action_index)
+        {
+            Some(c) => c,
+            None => return false,
+        };
+
+        let previous = std::mem::replace(&mut entry.commands, commands);
+        self.settings
+            .borrow_mut()
+            .defaults
+            .entry((user_index, action_index))
+            .or_insert(previous);
+        true
+    }
+
+    pub fn frame_pacing(&self) -> Rc<RefCell<FramePacing>> {
+        self.pacing.clone()
+    }
+
+    pub fn frame_skip(&self) -> Rc<RefCell<FrameSkip>> {
+        self.frame_skip.clone()
+    }
+
+    // Needs SDL 2.0.18; older versions and some drivers keep whatever the canvas was built with.
+    pub fn set_vsync(&mut self, vsync: bool) -> Result<(), String> {
+        let result = unsafe { SDL_RenderSetVSync(self.canvas.raw(), vsync as i32) };
+        self.detect_vsync();
+        match result {
+            0 => Ok(()),
+            _ => Err(sdl2::get_error()),
+        }
+    }
+
+    fn detect_vsync(&mut self) {
+        let flags = self.canvas.info().flags;
+        let vsync = flags & SDL_RendererFlags::SDL_RENDERER_PRESENTVSYNC as u32 != 0;
+        let mut frame_skip = self.frame_skip.borrow_mut();
+        frame_skip.set_vsync(vsync);
+        if frame_skip.is_active() {
+            info!("Renderer has no vsync, frame skipping applies");
+        }
+    }
+
+    pub fn interpolation(&self) -> Rc<RefCell<Interpolation>> {
+        self.interpolation.clone()
+    }
+
+    fn update_coordinates(&mut self) {
+        let (window_w, window_h) = self.canvas.window().size();
+        let (output_w, output_h) = self.canvas.output_size().unwrap_or((window_w, window_h));
+        let dpi = (
+            output_w as f32 / window_w.max(1) as f32,
+            output_h as f32 / window_h.max(1) as f32,
+        );
+        let viewport = self.canvas.viewport();
+        let height = self.screen_size().1 as f32;
+
+        self.coordinates.borrow_mut().update(
+            dpi,
+            self.canvas.scale(),
+            (viewport.x() as f32, viewport.y() as f32),
+            height,
+        );
+    }
+
+    fn text_texture(
+        &self,
+        ticket: Ticket,
+        text: &str,
+        color: Color,
+    ) -> Result<Texture<'a>, String> {
+        let font = match self.storage.borrow().fonts.get_by_ticket(ticket) {
+            Ok(f) => f,
+            Err(e) => {
+                ResourceError::log_failure(e);
+                return self.fallback_text_texture(text, color);
+            }
+        };
+
+        let surface = match self.text_mode(ticket).render(&font.borrow(), text, color) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Font could not render text, using the built in font: {}", e);
+                return self.fallback_text_texture(text, color);
+            }
+        };
+
+        self.texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn fallback_text_texture(
+        &self,
+        text: &str,
+        color: Color,
+    ) -> Result<Texture<'a>, String> {
+        let surface = bitmapfont::render(text, color, self.fallback_text_scale)?;
+        self.texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())
+    }
+
+    // Pixel size of the built in font used when a font is missing, 1 draws 5x7 glyphs.
+    pub fn set_fallback_text_scale(&mut self, scale: u32) {
+        self.fallback_text_scale = scale.max(1);
+        self.text_cache.clear();
+    }
+
+    fn draw_input_debug(&mut self) {
+        let debug = self.input_debug.clone();
+        let debug = debug.borrow();
+        if !debug.enabled {
+            return;
+        }
+
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+
+        let row = 16;
+        for (i, action) in debug.actions.iter().enumerate() {
+            let y = 8 + i as i32 * row;
+            let indicator = Rect::new(8, y, 12, 12);
+
+            self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+            let _ = self.canvas.fill_rect(Rect::new(4, y - 2, 320, row as u32));
+
+            let result = match action.state {
+                DebugActionState::Up => {
+                    self.canvas.set_draw_color(Color::RGB(120, 120, 120));
+                    self.canvas.draw_rect(indicator)
+                }
+                DebugActionState::Down => {
+                    self.canvas.set_draw_color(Color::RGB(80, 220, 80));
+                    self.canvas.fill_rect(indicator)
+                }
+                DebugActionState::Axis(v) => {
+                    self.canvas.set_draw_color(Color::RGB(80, 160, 220));
+                    let width = (v.abs().min(1.0) * 24.0) as u32;
+                    let x = if v < 0.0 { 32 - width as i32 } else { 32 };
+                    self.canvas.fill_rect(Rect::new(x, y + 4, width.max(1), 4))
+                }
+                DebugActionState::Analog(x, y_axis) => {
+                    self.canvas.set_draw_color(Color::RGB(120, 120, 120));
+                    let _ = self.canvas.draw_rect(indicator);
+                    self.canvas.set_draw_color(Color::RGB(220, 200, 80));
+                    let (cx, cy) = (
+                        14 + (x.clamp(-1.0, 1.0) * 6.0) as i32,
+                        y + 6 + (y_axis.clamp(-1.0, 1.0) * 6.0) as i32,
+                    );
+                    self.canvas.fill_rect(Rect::new(cx - 1, cy - 1, 3, 3))
+                }
+            };
+            if let Err(e) = result {
+                warn!("Could not draw input debug overlay: {}", e);
+            }
+
+            let ticket = match debug.font {
+                Some(t) => t,
+                None => continue,
+            };
+
+            let label = format!(
+                "u{} a{} {}",
+                action.user_index,
+                action.action_index,
+                action.source.as_deref().unwrap_or("-")
+            );
+            match self.text_texture(ticket, &label, Color::RGB(230, 230, 230)) {
+                Ok(t) => {
+                    let query = t.query();
+                    let scale = (row - 4) as f32 / query.height.max(1) as f32;
+                    let dest =
+                        Rect::new(60, y, (query.width as f32 * scale) as u32, (row - 4) as u32);
+                    if let Err(e) = self.canvas.copy(&t, None, dest) {
+                        warn!("Could not draw input debug label: {}", e);
+                    }
+                }
+                Err(e) => warn!("Could not render input debug label: {}", e),
+            }
+        }
+
+        let (mx, my) = debug.mouse;
+        self.canvas.set_draw_color(Color::RGB(255, 80, 80));
+        let _ = self
+            .canvas
+            .draw_line(Point::new(mx - 6, my), Point::new(mx + 6, my));
+        let _ = self
+            .canvas
+            .draw_line(Point::new(mx, my - 6), Point::new(mx, my + 6));
+
+        self.canvas.set_blend_mode(previous);
+    }
+
+    fn run_instruction(&mut self, instruction: &UpdateInstruction) {
+        match instruction {
+            UpdateInstruction::PlayMusic(ticket, loops, volume) => {
+                self.play_music(*ticket, *loops, *volume)
+            }
+            UpdateInstruction::PlaySound(ticket, volume) => self.play_sound(*ticket, *volume),
+        }
+    }
+
+    pub fn keyed_instructions(&self) -> Rc<RefCell<KeyedInstructions>> {
+        self.keyed.clone()
+    }
+
+    pub fn repeated_draws(&self) -> Rc<RefCell<RepeatedDraws>> {
+        self.repeated.clone()
+    }
+
+    pub fn custom_draws(&self) -> Rc<RefCell<CustomDraws>> {
+        self.custom_draws.clone()
+    }
+
+    fn run_keyed_sounds(&mut self) {
+        let sounds = self.keyed.borrow_mut().take_sounds();
+        if !audio::is_available() {
+            if !sounds.is_empty() {
+                audio::warn_unavailable("keyed sounds");
+            }
+            return;
+        }
+        for sound in sounds {
+            let (kind, key) = match &sound {
+                KeyedSound::Sound(key, _) => (AssetKind::Sound, key),
+                KeyedSound::Music(key, ..) => (AssetKind::Music, key),
+            };
+            let ticket = self
+                .keyed
+                .borrow_mut()
+                .resolve(&self.storage.borrow(), kind, key);
+            match (ticket, sound) {
+                (Some(t), KeyedSound::Sound(_, volume)) => self.play_sound(t, volume),
+                (Some(t), KeyedSound::Music(_, loops, volume)) => self.play_music(t, loops, volume),
+                (None, _) => (),
+            }
+        }
+    }
+
+    pub fn system_status(&self) -> Rc<RefCell<SystemStatus>> {
+        self.system.clone()
+    }
+
+    pub fn locale(&self) -> Option<Locale> {
+        self.system.borrow().locale().cloned()
+    }
+
+    pub fn system_theme(&self) -> SystemTheme {
+        self.system.borrow().theme()
+    }
+
+    pub fn strings(&self) -> Rc<RefCell<StringTable<'a>>> {
+        self.strings.clone()
+    }
+
+    pub fn set_window_title(&mut self, key: &str) -> Result<(), String> {
+        let title = self.strings.borrow().get(key);
+        self.canvas
+            .window_mut()
+            .set_title(&title)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn show_message_box(
+        &self,
+        flags: MessageBoxFlag,
+        title_key: &str,
+        message_key: &str,
+    ) -> Result<(), String> {
+        let strings = self.strings.borrow();
+        sdl2::messagebox::show_simple_message_box(
+            flags,
+            &strings.get(title_key),
+            &strings.get(message_key),
+            self.canvas.window(),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // 0.0 is fully transparent. Not every platform supports this; the error says so.
+    pub fn set_window_opacity(&mut self, opacity: f32) -> Result<(), String> {
+        self.canvas
+            .window_mut()
+            .set_opacity(opacity.clamp(0.0, 1.0))
+    }
+
+    pub fn window_opacity(&self) -> f32 {
+        self.canvas.window().opacity().unwrap_or(1.0)
+    }
+
+    // When the window isn't focused, the click that focuses it is also delivered as input
+    // instead of only bringing the window forward. This is SDL's focus click-through and
+    // doesn't let clicks pass through the window to apps behind it. Returns false when the
+    // hint wasn't accepted.
+    pub fn set_focus_click_through(&mut self, enabled: bool) -> bool {
+        SDLHint::MouseFocusClickThrough(enabled).apply()
+    }
+
+    pub fn window_chrome(&self) -> Rc<RefCell<WindowChrome>> {
+        self.window_chrome.clone()
+    }
+
+    // Routes presses through `window_chrome` so borderless windows can be dragged and
+    // resized from their own title bar and edges.
+    pub fn set_custom_chrome(&mut self, enabled: bool) -> Result<(), String> {
+        let data = Rc::as_ptr(&self.window_chrome) as *mut c_void;
+        let callback = match enabled {
+            true => Some(chrome::hit_test_callback as _),
+            false => None,
+        };
+        let result = unsafe { SDL_SetWindowHitTest(self.canvas.window().raw(), callback, data) };
+        match result {
+            0 => Ok(()),
+            _ => Err(sdl2::get_error()),
+        }
+    }
+
+    pub fn taskbar(&self) -> Rc<RefCell<Taskbar>> {
+        self.taskbar.clone()
+    }
+
+    // Flashes the taskbar entry; platforms without flashing ignore it.
+    pub fn request_attention(&mut self, attention: Attention) -> Result<(), String> {
+        let result = unsafe { SDL_FlashWindow(self.canvas.window().raw(), attention.operation()) };
+        match result {
+            0 => Ok(()),
+            _ => Err(sdl2::get_error()),
+        }
+    }
+
+    fn apply_taskbar(&mut self) {
+        let mut taskbar = self.taskbar.borrow_mut();
+        if let Some(icon) = taskbar.advance(self.timer.ticks64()) {
+            self.canvas.window_mut().set_icon(icon);
+        }
+
+        let current = self.canvas.window().title().to_string();
+        if let Some(title) = taskbar.title(&current) {
+            if let Err(e) = self.canvas.window_mut().set_title(&title) {
+                warn!("Could not show progress in the window title: {}", e);
+            }
+        }
+    }
+
+    // On panic: leave fullscreen, release the mouse, stop audio and show the panic message,
+    // so a crash doesn't leave a black screen and a captured cursor behind.
+    pub fn install_panic_handler(&self, options: PanicOptions) {
+        panic::install(self.canvas.window().id(), options);
+    }
+
+    pub fn set_screensaver(&mut self, enabled: bool) {
+        let video = self.canvas.window().subsystem();
+        match enabled {
+            true => video.enable_screen_saver(),
+            false => video.disable_screen_saver(),
+        }
+    }
+
+    pub fn audio_monitor(&self) -> Rc<RefCell<AudioMonitor>> {
+        self.audio_monitor.clone()
+    }
+
+    // Reopens the device with a new buffer size, trading latency for stability. Playing
+    // sounds stop; channel counts, reservations, groups, bus levels and the post-mix
+    // callback carry over. Effects on POST_MIX can't, so they have to be cleared first.
+    pub fn set_audio_buffer_samples(&mut self, samples: i32) -> Result<(), String> {
+        if audio::post_mix_effects() > 0 {
+            return Err("Clear the POST_MIX effects before changing the buffer size".to_string());
+        }
+        self.audio_monitor.borrow_mut().reopen(samples)?;
+        self.channel_reservation.borrow_mut().reapply();
+        self.mixer_stats.borrow_mut().reapply();
+        self.audio_buses.borrow_mut().reapply();
+        self.post_mix.borrow_mut().reapply();
+        let visualizer = self.visualizer.borrow();
+        if visualizer.is_enabled() {
+            let bands = visualizer.band_count();
+            self.post_mix.borrow_mut().set_analysis(Some(bands));
+        }
+        Ok(())
+    }
+
+    pub fn post_mix(&self) -> Rc<RefCell<PostMix>> {
+        self.post_mix.clone()
+    }
+
+    pub fn audio_visualizer(&self) -> Rc<RefCell<AudioVisualizer>> {
+        self.visualizer.clone()
+    }
+
+    // Analysis runs on the audio thread for every buffer, so it's off until asked for.
+    pub fn set_audio_visualizer(&mut self, bands: Option<usize>) {
+        let bands = bands.map(|b| b.max(1));
+        self.post_mix.borrow_mut().set_analysis(bands);
+        self.visualizer
+            .borrow_mut()
+            .set_enabled(bands.is_some(), bands.unwrap_or(16));
+    }
+
+    fn poll_system(&mut self) {
+        let underruns = self.audio_monitor.borrow_mut().new_underruns();
+        if underruns > 0 {
+            let stats = self.audio_monitor.borrow().stats();
+            warn!(
+                "{} audio underruns ({} total, worst gap {:.1}ms at {} samples)",
+                underruns, stats.underruns, stats.worst_gap, stats.buffer_samples
+            );
+        }
+
+        let mut system = self.system.borrow_mut();
+        system.poll(self.timer.ticks64());
+
+        if let Some(enabled) = system.take_screensaver_request() {
+            drop(system);
+            self.set_screensaver(enabled);
+        }
+    }
+
+    pub fn sound_limits(&self) -> Rc<RefCell<SoundLimits>> {
+        self.sound_limits.clone()
+    }
+
+    pub fn sound_variations(&self) -> Rc<RefCell<SoundVariations>> {
+        self.sound_variations.clone()
+    }
+
+    pub fn music_state(&self) -> Rc<RefCell<MusicState>> {
+        self.music_state.clone()
+    }
+
+    fn refresh_music_state(&mut self) {
+        let mut state = self.music_state.borrow_mut();
+        let music = state
+            .ticket()
+            .and_then(|t| self.storage.borrow().music.get_by_ticket(t).ok());
+
+        match music {
+            Some(m) => state.refresh(Some(&m.borrow())),
+            None => state.refresh(None),
+        }
+
+        let latency = self.accessibility.borrow().latency;
+        self.beat_clock
+            .borrow_mut()
+            .update(state.position(), latency);
+        self.captions
+            .borrow_mut()
+            .update(state.ticket(), state.position());
+    }
+
+    pub fn captions(&self) -> Rc<RefCell<Captions>> {
+        self.captions.clone()
+    }
+
+    fn draw_captions(&mut self) {
+        let (style, text) = {
+            let captions = self.captions.borrow();
+            match (captions.style, captions.active()) {
+                (Some(style), Some(caption)) if !caption.text.is_empty() => {
+                    (style, caption.text.clone())
+                }
+                _ => return,
+            }
+        };
+
+        self.draw_text_box(style, &text, style.offset);
+    }
+
+    pub fn announcements(&self) -> Rc<RefCell<Announcements>> {
+        self.announcements.clone()
+    }
+
+    // Newest toast sits nearest the anchor, older ones stack away from it.
+    fn draw_announcements(&mut self) {
+        let (style, toasts) = {
+            let announcements = self.announcements.borrow();
+            match announcements.toast_style {
+                Some(style) => (
+                    style,
+                    announcements
+                        .toasts()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>(),
+                ),
+                None => return,
+            }
+        };
+
+        let mut offset = style.offset;
+        for toast in toasts.iter().rev().filter(|t| !t.is_empty()) {
+            match self.draw_text_box(style, toast, offset) {
+                Some(height) => offset.1 += height as i32 + style.padding as i32,
+                None => return,
+            }
+        }
+    }
+
+    // Returns the height of the box drawn.
+    fn draw_text_box(
+        &mut self,
+        style: CaptionStyle,
+        text: &str,
+        offset: (i32, i32),
+    ) -> Option<u32> {
+        let texture = match self.text_texture(style.font, text, style.color) {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("Could not render text box: {}", e);
+                return None;
+            }
+        };
+
+        let query = texture.query();
+        let padding = style.padding;
+        let size = (query.width + padding * 2, query.height + padding * 2);
+        let bounds = self.canvas.output_size().unwrap_or((800, 600));
+        let (x, y) = style.anchor.place(bounds, size, offset);
+
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(style.background);
+        let _ = self.canvas.fill_rect(Rect::new(x, y, size.0, size.1));
+        self.canvas.set_blend_mode(previous);
+
+        let dest = Rect::new(
+            x + padding as i32,
+            y + padding as i32,
+            query.width,
+            query.height,
+        );
+        if let Err(e) = self.canvas.copy(&texture, None, dest) {
+            warn!("Could not draw text box: {}", e);
+        }
+        Some(size.1)
+    }
+
+    pub fn beat_clock(&self) -> Rc<RefCell<BeatClock>> {
+        self.beat_clock.clone()
+    }
+
+    pub fn audio_buses(&self) -> Rc<RefCell<AudioBuses>> {
+        self.audio_buses.clone()
+    }
+
+    pub fn mixer_stats(&self) -> Rc<RefCell<MixerStats>> {
+        self.mixer_stats.clone()
+    }
+
+    pub fn channel_reservation(&self) -> Rc<RefCell<ChannelReservation>> {
+        self.channel_reservation.clone()
+    }
+
+    fn volume(v: f32) -> i32 {
+        (v * sdl2::mixer::MAX_VOLUME as f32) as i32
+    }
+
+    fn play_music(&mut self, ticket: Ticket, loops: i32, volume: f32) {
+        if !audio::is_available() {
+            audio::warn_unavailable("music");
+            return;
+        }
+        match self.storage.borrow().music.get_by_ticket(ticket) {
+            Ok(m) => {
+                let mut buses = self.audio_buses.borrow_mut();
+                buses.set_music_volume(volume);
+                sdl2::mixer::Music::set_volume(Self::volume(volume * buses.effective(Bus::Music)));
+                match m.borrow().play(loops) {
+                    Ok(()) => {
+                        self.music_state.borrow_mut().started(ticket, loops, volume);
+                        self.frame_summary.borrow_mut().music_started();
+                    }
+                    Err(e) => error!("Error playing music: {}", e),
+                }
+            }
+            Err(e) => {
+                ResourceError::log_failure(e);
+                self.missing.music += 1;
+            }
+        }
+    }
+
+    fn play_sound(&mut self, ticket: Ticket, volume: f32) {
+        self.start_sound(ticket, volume, 0);
+    }
+
+    // Returns the channel the sound went to, `loops` of -1 repeats until halted.
+    fn start_sound(&mut self, ticket: Ticket, volume: f32, loops: i32) -> Option<Channel> {
+        if !audio::is_available() {
+            audio::warn_unavailable("sound");
+            return None;
+        }
+        if !self.sound_limits.borrow_mut().admit(ticket) {
+            return None;
+        }
+
+        let channel = self
+            .channel_reservation
+            .borrow()
+            .channel(ticket, &self.sound_limits.borrow());
+
+        match self.storage.borrow().sounds.get_by_ticket(ticket) {
+            Ok(s) => {
+                let mut variations = self.sound_variations.borrow_mut();
+                let result = match variations.has_variation(ticket) {
+                    true => {
+                        let source = s.borrow();
+                        match variations.pick(ticket, &source) {
+                            Some(variant) => {
+                                variant.set_volume(Self::volume(volume));
+                                channel.play(variant, loops)
+                            }
+                            None => Err("No pitch variants available".to_string()),
+                        }
+                    }
+                    false => {
+                        match s.try_borrow_mut() {
+                            Ok(mut s_v) => {
+                                s_v.set_volume(Self::volume(volume));
+                            }
+                            Err(e) => warn!(
+                                "Cannot set volume on a sound effect already borrowed elsewhere: {}",
+                                e
+                            ),
+                        }
+
+                        channel.play(&s.borrow(), loops)
+                    }
+                };
+
+                match result {
+                    Ok(c) => {
+                        self.sound_limits.borrow_mut().started(ticket, c);
+                        self.frame_summary.borrow_mut().sound_played();
+                        return Some(c);
+                    }
+                    Err(e) => {
+                        // Reserved channels can be idle while every other one is busy.
+                        let reserved = self.channel_reservation.borrow().reserved() as usize;
+                        let mut stats = self.mixer_stats.borrow_mut();
+                        match stats.usage().free() <= reserved {
+                            true => {
+                                stats.record_drop();
+                                warn!("Sound dropped, all mixer channels are playing");
+                            }
+                            false => error!("Error playing sound: {}", e),
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                ResourceError::log_failure(e);
+                self.missing.sounds += 1;
+            }
+        }
+        None
+    }
+
+    pub fn soundscape(&self) -> Rc<RefCell<Soundscape>> {
+        self.soundscape.clone()
+    }
+
+    pub fn capture_audio(&self) -> AudioSnapshot {
+        let state = self.music_state.borrow();
+        let buses = self.audio_buses.borrow();
+        let mut soundscape = self.soundscape.borrow_mut();
+        soundscape.prune();
+
+        AudioSnapshot {
+            music: state
+                .ticket()
+                .filter(|_| sdl2::mixer::Music::is_playing())
+                .map(|ticket| MusicSnapshot {
+                    ticket,
+                    loops: state.loops(),
+                    volume: state.volume(),
+                    position: state.position(),
+                }),
+            music_bus: buses.volume(Bus::Music),
+            sounds_bus: buses.volume(Bus::Sounds),
+            loops: soundscape.loops.iter().map(|(t, v, _)| (*t, *v)).collect(),
+        }
+    }
+
+    // Replaces whatever is playing with the snapshot's music, loops and bus volumes. Ducks
+    // and fades in progress carry on over the restored volumes.
+    pub fn restore_audio(&mut self, snapshot: &AudioSnapshot) {
+        self.stop_loops();
+        sdl2::mixer::Music::halt();
+
+        {
+            let mut buses = self.audio_buses.borrow_mut();
+            buses.set_volume(Bus::Music, snapshot.music_bus);
+            buses.set_volume(Bus::Sounds, snapshot.sounds_bus);
+        }
+        if let Some(music) = snapshot.music {
+            self.play_music(music.ticket, music.loops, music.volume);
+            if let Some(position) = music.position {
+                self.music_state.borrow_mut().seek(position);
+            }
+        }
+        for (ticket, volume) in snapshot.loops.iter() {
+            self.play_loop(*ticket, *volume);
+        }
+    }
+
+    fn play_loop(&mut self, ticket: Ticket, volume: f32) {
+        if let Some(channel) = self.start_sound(ticket, volume, -1) {
+            self.soundscape
+                .borrow_mut()
+                .loops
+                .push((ticket, volume, channel));
+        }
+    }
+
+    fn stop_loops(&mut self) {
+        for (.., channel) in self.soundscape.borrow_mut().loops.drain(..) {
+            channel.halt();
+        }
+    }
+
+    fn apply_soundscape(&mut self) {
+        let requests = std::mem::take(&mut self.soundscape.borrow_mut().requests);
+        for request in requests {
+            match request {
+                SoundscapeRequest::PlayLoop(ticket, volume) => self.play_loop(ticket, volume),
+                SoundscapeRequest::StopLoop(ticket) => {
+                    self.soundscape
+                        .borrow_mut()
+                        .loops
+                        .retain(|(t, _, c)| match *t == ticket {
+                            true => {
+                                c.halt();
+                                false
+                            }
+                            false => true,
+                        });
+                }
+                SoundscapeRequest::StopLoops => self.stop_loops(),
+                SoundscapeRequest::Save(name) => {
+                    let snapshot = self.capture_audio();
+                    self.soundscape.borrow_mut().saved.insert(name, snapshot);
+                }
+                SoundscapeRequest::Restore(name) => {
+                    let snapshot = self.soundscape.borrow().saved.get(&name).cloned();
+                    match snapshot {
+                        Some(s) => self.restore_audio(&s),
+                        None => warn!("No saved audio state named '{}'", name),
+                    }
+                }
+            }
+        }
+    }
+
+    fn record_dump(&self, dest: Option<FRect>, angle: f64, culled: bool) {
+        let mut dump = self.frame_dump.borrow_mut();
+        if dump.is_capturing() {
+            dump.record_result(dest, angle, culled);
+        }
+    }
+
+    fn record_bounds(&self, dest: Option<FRect>, angle: f64, pivot: (f32, f32), culled: bool) {
+        let mut bounds = self.draw_bounds.borrow_mut();
+        if let (true, Some(dest)) = (bounds.enabled, dest) {
+            bounds.record(dest, angle, pivot, culled);
+        }
+    }
+
+    pub fn draw_bounds(&self) -> Rc<RefCell<DrawBounds>> {
+        self.draw_bounds.clone()
+    }
+
+    fn draw_wireframe(&mut self) {
+        let bounds = self.draw_bounds.clone();
+        let mut bounds = bounds.borrow_mut();
+        let shapes = std::mem::take(&mut bounds.shapes);
+        if !bounds.enabled {
+            return;
+        }
+
+        let width = bounds.thickness.canvas_width(&self.coordinates.borrow());
+        let previous = self.canvas.blend_mode();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        for shape in shapes.iter() {
+            let color = match (shape.culled, bounds.culled_color) {
+                (false, _) => bounds.color,
+                (true, Some(c)) => c,
+                (true, None) => continue,
+            };
+
+            let corners = wireframe::corners(shape);
+            let mut outline: Vec<FPoint> =
+                corners.iter().map(|(x, y)| FPoint::new(*x, *y)).collect();
+            outline.push(outline[0]);
+            self.canvas.set_draw_color(color);
+            let _ = thickness::draw_lines(&mut self.canvas, &outline, width);
+
+            let (ox, oy) = corners[0];
+            let dot = 2.0 + width;
+            self.canvas.set_draw_color(bounds.origin_color);
+            let _ = self
+                .canvas
+                .fill_frect(FRect::new(ox - dot / 2.0, oy - dot / 2.0, dot, dot));
+
+            let (px, py) = (
+                shape.dest.x() + shape.pivot.0,
+                shape.dest.y() + shape.pivot.1,
+            );
+            let arm = 3.0 + width;
+            self.canvas.set_draw_color(bounds.pivot_color);
+            let _ = thickness::draw_lines(
+                &mut self.canvas,
+                &[FPoint::new(px - arm, py), FPoint::new(px + arm, py)],
+                width,
+            );
+            let _ = thickness::draw_lines(
+                &mut self.canvas,
+                &[FPoint::new(px, py - arm), FPoint::new(px, py + arm)],
+                width,
+            );
+        }
+        self.canvas.set_blend_mode(previous);
+
+        // The buffer is handed back so its capacity carries over.
+        bounds.shapes = shapes;
+        bounds.shapes.clear();
+    }
+
+    // Rotated draws are tested with the circle around their pivot that reaches the farthest
+    // corner of the destination. Every rotation about that pivot stays inside it, so nothing
+    // visible is culled, even for an absolute pivot outside the rect. `origin` is the pivot
+    // relative to the destination, as SDL takes it.
+    fn is_visible(&self, dest: &FRect, angle: f64, origin: (f32, f32)) -> bool {
+        let viewport = self.canvas.viewport();
+        let (vw, vh) = (viewport.width() as f32, viewport.height() as f32);
+
+        let (x, y, w, h) = match angle == 0.0 {
+            true => (dest.x(), dest.y(), dest.width(), dest.height()),
+            false => {
+                let (ox, oy) = origin;
+                let dx = ox.abs().max((dest.width() - ox).abs());
+                let dy = oy.abs().max((dest.height() - oy).abs());
+                let radius = (dx * dx + dy * dy).sqrt();
+                let (cx, cy) = (dest.x() + ox, dest.y() + oy);
+                (cx - radius, cy - radius, radius * 2.0, radius * 2.0)
+            }
+        };
+
+        x < vw && y < vh && x + w > 0.0 && y + h > 0.0
+    }
+
+    // Text drawn with this font gives every digit the same advance.
+    pub fn set_tabular_figures(&mut self, font: Ticket, tabular: bool) {
+        match tabular {
+            true => self.tabular_fonts.insert(font),
+            false => self.tabular_fonts.remove(&font),
+        };
+        self.text_cache.remove_font(font);
+    }
+
+    pub fn glyph_validator(&self) -> Rc<RefCell<GlyphValidator>> {
+        self.glyphs.clone()
+    }
+
+    // Checks every string ahead of time and returns the glyphs `font` is missing for them.
+    pub fn validate_glyphs<'s, I>(&self, font: Ticket, strings: I) -> Result<Vec<char>, String>
+    where
+        I: IntoIterator<Item = &'s str>,
+    {
+        let loaded = match self.storage.borrow().fonts.get_by_ticket(font) {
+            Ok(f) => f,
+            Err(_) => return Err("Font not available".to_string()),
+        };
+
+        let mut glyphs = self.glyphs.borrow_mut();
+        for text in strings {
+            glyphs.check(font, &loaded.borrow(), text);
+        }
+        Ok(glyphs.missing(font).to_vec())
+    }
+
+    pub fn validate_string_table(&self, font: Ticket) -> Result<Vec<char>, String> {
+        let strings = self.strings.borrow();
+        self.validate_glyphs(font, strings.values())
+    }
+
+    // Text drawn with this font renders in `mode`, None goes back to blended.
+    pub fn set_text_render_mode(&mut self, font: Ticket, mode: Option<TextRenderMode>) {
+        match mode {
+            Some(m) => self.text_modes.insert(font, m),
+            None => self.text_modes.remove(&font),
+        };
+        self.reveal_cache.remove(&font);
+        self.text_cache.remove_font(font);
+    }
+
+    fn text_mode(&self, font: Ticket) -> TextRenderMode {
+        self.text_modes.get(&font).copied().unwrap_or_default()
+    }
+
+    pub fn text_reveals(&self) -> Rc<RefCell<TextReveals>> {
+        self.reveals.clone()
+    }
+
+    fn advance_reveals(&mut self, delta: f64) {
+        let mut reveals = self.reveals.borrow_mut();
+        reveals.advance(delta);
+        match reveals.is_empty() {
+            true => self.reveal_cache.clear(),
+            false => self.reveal_cache.retain(|font, texts| {
+                texts.retain(|text, _| reveals.visible(*font, text).is_some());
+                !texts.is_empty()
+            }),
+        }
+    }
+
+    // Off by default so brackets in existing text are drawn as written.
+    pub fn set_rich_text(&mut self, rich_text: bool) {
+        self.rich_text = rich_text;
+        self.reveals.borrow_mut().set_markup(rich_text);
+        self.text_cache.clear();
+    }
+
+    // Renders `texts` ahead of time, for example behind a loading screen, so the frame that
+    // first shows them doesn't stall on rendering. Returns how many were rendered.
+    pub fn warm_text(&mut self, font: Ticket, texts: &[&str], color: Color) -> usize {
+        if self.storage.borrow().fonts.get_by_ticket(font).is_err() {
+            warn!("Cannot warm text for a font that isn't loaded");
+            return 0;
+        }
+
+        let mut rendered = 0;
+        for text in texts {
+            if self.text_cache.contains(font, text, color) {
+                continue;
+            }
+            if let Some(texture) = self.render_text(font, text, color, None) {
+                self.text_cache.insert(font, text, color, texture, true);
+                rendered += 1;
+            }
+        }
+        rendered
+    }
+
+    pub fn text_cache_stats(&self) -> TextCacheStats {
+        self.text_cache.stats()
+    }
+
+    // Frames a cached text texture survives without being drawn, 0 keeps only this frame's.
+    pub fn set_text_cache_idle(&mut self, frames: u64) {
+        self.text_cache.max_idle = frames;
+    }
+
+    pub fn clear_text_cache(&mut self) {
+        self.text_cache.clear();
+    }
+
+    pub fn set_culling(&mut self, culling: bool) {
+        self.culling = culling;
+    }
+
+    // Draws skipped by culling in the last frame.
+    pub fn culled_draws(&self) -> usize {
+        self.culled
+    }
+
+    // Draws the stage submitted in the last frame, culled ones included.
+    pub fn draw_count(&self) -> usize {
+        self.draw_count
+    }
+
+    pub fn frame_summary(&self) -> Rc<RefCell<FrameSummary>> {
+        self.frame_summary.clone()
+    }
+
+    fn summarize_frame(&mut self, batches: &[DrawBatch<Draw, ()>]) {
+        let (text_hits, text_misses) = self.text_cache.counts();
+        self.frame_summary.borrow_mut().finish(FrameStats {
+            batch_instructions: batches.iter().map(|b| b.instructions.len()).collect(),
+            draws: self.draw_count,
+            culled: self.culled,
+            text_hits,
+            text_misses,
+            ..FrameStats::default()
+        });
+    }
+
+    pub fn frame_dump(&self) -> Rc<RefCell<FrameDump>> {
+        self.frame_dump.clone()
+    }
+
+    pub fn dump_next_frame(&mut self, path: &Path) {
+        self.frame_dump
+            .borrow_mut()
+            .dump_next_frame(path.to_path_buf());
+    }
+
+    fn render_texture(
+        &mut self,
+        texture: Rc<RefCell<Texture<'_>>>,
+        region: Option<Rect>,
+        data: &DrawData,
+        tint: Option<Color>,
+        scale: (f32, f32),
+        anchor: Option<ScreenAnchor>,
+    ) {
+        let mut tex = texture.borrow_mut();
+        let previous_tint = match tint {
+            Some(c) => {
+                let previous = (tex.color_mod(), tex.alpha_mod());
+                tex.set_color_mod(c.r, c.g, c.b);
+                tex.set_alpha_mod(c.a);
+                Some(previous)
+            }
+            None => None,
+        };
+        let (width, height) = match region {
+            Some(r) => (r.width(), r.height()),
+            None => {
+                let query = tex.query();
+                (query.width, query.height)
+            }
+        };
+        let (width, height) = (width as f32 * scale.0, height as f32 * scale.1);
+
+        // Source rects are relative to the packed texture, so they move with its region.
+        let source = match (&data.source, region) {
+            (Some(r), Some(region)) => {
+                let r = to_rect(r);
+                Some(Rect::new(
+                    region.x() + r.x(),
+                    region.y() + r.y(),
+                    r.width(),
+                    r.height(),
+                ))
+            }
+            (Some(r), None) => Some(to_rect(r)),
+            (None, region) => region,
+        };
+
+        let (horizontal, vertical) = match &data.flip {
+            Some(f) => (f.horizontal, f.vertical),
+            None => (false, false),
+        };
+
+        let coordinates = self.coordinates.clone();
+        let coordinates = coordinates.borrow();
+        let y_up = coordinates.y_axis == YAxis::Up;
+        let world = coordinates.pixels_per_unit.is_some();
+
+        // World unit draws go through the camera; textures keep their pixel size at zoom 1.
+        let (width, height) = match world {
+            true => (
+                width * coordinates.camera.zoom,
+                height * coordinates.camera.zoom,
+            ),
+            false => (width, height),
+        };
+        let to_logical = |x: f32, y: f32| match world {
+            true => coordinates.world_to_logical(x, y),
+            false => (x, y),
+        };
+
+        // Y-up angles turn counterclockwise, and origins are measured from the bottom.
+        let (angle, pivot) = match &data.rotation {
+            Some(r) => (
+                match (self.rotation.unit, y_up) {
+                    (AngleUnit::Degrees, false) => r.angle as f64,
+                    (AngleUnit::Degrees, true) => -r.angle as f64,
+                    (AngleUnit::Radians, false) => (r.angle as f64).to_degrees(),
+                    (AngleUnit::Radians, true) => -(r.angle as f64).to_degrees(),
+                },
+                Some((r.origin.0, r.origin.1)),
+            ),
+            None => (0.0, None),
+        };
+
+        // Flipping mirrors a normalized origin too, so the pivot stays on the same texel.
+        let origin = match (pivot, self.rotation.pivot) {
+            (Some((ox, oy)), PivotMode::Normalized) => {
+                let oy = if y_up { 1.0 - oy } else { oy };
+                let (ox, oy) = (ox * width, oy * height);
+                (
+                    if horizontal { width - ox } else { ox },
+                    if vertical { height - oy } else { oy },
+                )
+            }
+            // A Y-up location is the bottom-left corner rather than the top-left.
+            _ => (0.0, if y_up { height } else { 0.0 }),
+        };
+
+        let dest = match &data.destination {
+            Some(d) => match d {
+                DrawDestination::Location { x, y } => {
+                    let (x, y) = to_logical(*x, *y);
+                    Some(FRect::new(
+                        x - origin.0,
+                        coordinates.flip_y(y) - origin.1,
+                        width,
+                        height,
+                    ))
+                }
+                DrawDestination::Rect(rect) => {
+                    let (x, y) = to_logical(rect.x, rect.y);
+                    let (w, h) = match world {
+                        true => (
+                            rect.width * coordinates.unit_scale(),
+                            rect.height * coordinates.unit_scale(),
+                        ),
+                        false => (rect.width, rect.height),
+                    };
+                    let y = match y_up {
+                        true => coordinates.flip_y(y) - h,
+                        false => y,
+                    };
+                    Some(FRect::new(x, y, w, h))
+                }
+            },
+            None => None,
+        };
+
+        // An absolute pivot is given in world space, SDL wants it relative to the destination.
+        let origin = match (pivot, self.rotation.pivot, &dest) {
+            (Some((px, py)), PivotMode::Absolute, Some(d)) => {
+                let (px, py) = to_logical(px, py);
+                (px - d.x(), coordinates.flip_y(py) - d.y())
+            }
+            (Some((px, py)), PivotMode::Absolute, None) => {
+                let (px, py) = to_logical(px, py);
+                (px, coordinates.flip_y(py))
+            }
+            _ => origin,
+        };
+
+        let dest = match (dest, anchor) {
+            (Some(d), Some(a)) => Some(a.resolve(self.screen_size(), d)),
+            (d, _) => d,
+        };
+
+        let dest = match (dest, self.draw_offset) {
+            (d, (0, 0)) => d,
+            (Some(d), (x, y)) => Some(FRect::new(
+                d.x() + x as f32,
+                d.y() + y as f32,
+                d.width(),
+                d.height(),
+            )),
+            (None, (x, y)) => match self.canvas.output_size() {
+                Ok((w, h)) => Some(FRect::new(x as f32, y as f32, w as f32, h as f32)),
+                Err(_) => None,
+            },
+        };
+
+        if let (true, Some(d)) = (self.culling, &dest) {
+            if !self.is_visible(d, angle, origin) {
+                self.culled += 1;
+                self.record_dump(dest, angle, true);
+                self.record_bounds(dest, angle, origin, true);
+                if let Some(((r, g, b), a)) = previous_tint {
+                    tex.set_color_mod(r, g, b);
+                    tex.set_alpha_mod(a);
+                }
+                return;
+            }
+        }
+
+        let result = match self.position_mode {
+            PositionMode::Integer => self.canvas.copy_ex(
+                &tex,
+                source,
+                dest.map(|d| {
+                    Rect::new(
+                        d.x() as i32,
+                        d.y() as i32,
+                        d.width() as u32,
+                        d.height() as u32,
+                    )
+                }),
+                angle,
+                Point::new(origin.0 as i32, origin.1 as i32),
+                horizontal,
+                vertical,
+            ),
+            PositionMode::SubPixel => self.canvas.copy_ex_f(
+                &tex,
+                source,
+                dest,
+                angle,
+                FPoint::new(origin.0, origin.1),
+                horizontal,
+                vertical,
+            ),
+        };
+
+        if let Err(e) = result {
+            warn!("SDL2 Texture Rendering failed: {}", e);
+        }
+        self.record_dump(dest, angle, false);
+        self.record_bounds(dest, angle, origin, false);
+
+        if let Some(((r, g, b), a)) = previous_tint {
+            tex.set_color_mod(r, g, b);
+            tex.set_alpha_mod(a);
+        }
+    }
+}
+
+impl<'a, 'b, 'c, IContent, UContent, Message> Drop
+    for SDLApp<'a, 'b, 'c, IContent, UContent, Message>
+{
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn to_rect(r: &DrawRect) -> Rect {
+    Rect::new(r.x as i32, r.y as i32, r.width as u32, r.height as u32)
+}
+
+fn to_color(c: &DrawColor) -> Color {
+    let max = u8::MAX as f32;
     Color::RGBA(
         (max * c.r) as u8,
         (max * c.g) as u8,
@@ -300,3 +3746,14 @@ fn to_color(c: &DrawColor) -> Color {
         (max * c.a) as u8,
     )
 }
+
+fn place_controller(
+    controllers: &mut Vec<Option<GameController>>,
+    slot: usize,
+    controller: GameController,
+) {
+    if controllers.len() <= slot {
+        controllers.resize_with(slot + 1, || None);
+    }
+    controllers[slot] = Some(controller);
+}
@@ -0,0 +1,69 @@
+use sdl2::event::Event;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    EnteringBackground,
+    EnteredForeground,
+    Terminating,
+    LowMemory,
+}
+
+pub struct Lifecycle {
+    // Pause music and channels while backgrounded, resuming them on return.
+    pub pause_audio: bool,
+    background: bool,
+    events: Vec<LifecycleEvent>,
+}
+
+impl Lifecycle {
+    pub fn new() -> Self {
+        Lifecycle {
+            pause_audio: true,
+            background: false,
+            events: Vec::new(),
+        }
+    }
+
+    // Drawing is skipped while in the background, mobile platforms may have no surface then.
+    pub fn is_background(&self) -> bool {
+        self.background
+    }
+
+    // Events received since the previous frame.
+    pub fn events(&self) -> &[LifecycleEvent] {
+        &self.events
+    }
+
+    pub fn received(&self, event: LifecycleEvent) -> bool {
+        self.events.contains(&event)
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.events.clear();
+    }
+
+    pub(crate) fn record(&mut self, event: &Event) -> Option<LifecycleEvent> {
+        let lifecycle = match event {
+            Event::AppWillEnterBackground { .. } => {
+                self.background = true;
+                LifecycleEvent::EnteringBackground
+            }
+            Event::AppDidEnterForeground { .. } => {
+                self.background = false;
+                LifecycleEvent::EnteredForeground
+            }
+            Event::AppTerminating { .. } => LifecycleEvent::Terminating,
+            Event::AppLowMemory { .. } => LifecycleEvent::LowMemory,
+            _ => return None,
+        };
+
+        self.events.push(lifecycle);
+        Some(lifecycle)
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
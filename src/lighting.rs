@@ -0,0 +1,70 @@
+use sdl2::{pixels::Color, pixels::PixelFormatEnum, surface::Surface};
+
+pub const LIGHT_SPRITE_SIZE: u32 = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: Color,
+}
+
+pub struct Lighting {
+    pub enabled: bool,
+    // What unlit areas are multiplied by; black hides them completely.
+    pub ambient: Color,
+    lights: Vec<Light>,
+}
+
+impl Lighting {
+    pub fn new() -> Self {
+        Lighting {
+            enabled: false,
+            ambient: Color::RGB(40, 40, 60),
+            lights: Vec::new(),
+        }
+    }
+
+    // Lights only last for the frame they were added in.
+    pub fn add(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.lights.clear();
+    }
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// White disc whose alpha falls off quadratically, tinted per light through the color mod.
+pub fn light_surface() -> Result<Surface<'static>, String> {
+    let size = LIGHT_SPRITE_SIZE;
+    let mut surface = Surface::new(size, size, PixelFormatEnum::RGBA32)?;
+    let pitch = surface.pitch() as usize;
+    let half = size as f32 / 2.0;
+
+    surface.with_lock_mut(|pixels| {
+        for y in 0..size as usize {
+            for x in 0..size as usize {
+                let dx = (x as f32 + 0.5 - half) / half;
+                let dy = (y as f32 + 0.5 - half) / half;
+                let falloff = (1.0 - (dx * dx + dy * dy)).max(0.0);
+                let i = y * pitch + x * 4;
+                pixels[i..i + 3].copy_from_slice(&[255, 255, 255]);
+                pixels[i + 3] = (falloff * falloff * 255.0) as u8;
+            }
+        }
+    });
+
+    Ok(surface)
+}
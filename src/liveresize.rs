@@ -0,0 +1,148 @@
+use std::os::raw::{c_int, c_void};
+
+use sdl2::sys::{
+    SDL_AddEventWatch, SDL_DelEventWatch, SDL_Event, SDL_EventType, SDL_GetTicks64, SDL_RenderCopy,
+    SDL_RenderPresent, SDL_Renderer, SDL_SetRenderTarget, SDL_Texture, SDL_WindowEventID,
+};
+
+// On Windows and macOS, dragging or resizing the window runs a platform loop inside SDL's
+// event pumping, so no frames are drawn until the mouse is released. With this enabled the
+// backend draws every frame offscreen, watches window events while pumping, and presents
+// the last finished frame stretched to the window from inside that loop, at most once per
+// `min_interval` milliseconds. Scenes don't run during the loop; the frame is shown without
+// a post-process hook or brightness overlay. A size or move event arms the watch and only
+// window events after it in the same pump redraw, so ordinary resizes don't draw an extra
+// frame.
+pub struct LiveResize {
+    pub enabled: bool,
+    pub min_interval: u64,
+    last: u64,
+    redraws: u64,
+    modal: bool,
+    // Raw handles of the renderer and the offscreen frame, owned by the app. Cleared
+    // whenever the app drops or replaces the frame texture.
+    frame: Option<(*mut SDL_Renderer, *mut SDL_Texture)>,
+}
+
+impl LiveResize {
+    pub fn new() -> Self {
+        LiveResize {
+            enabled: false,
+            min_interval: 16,
+            last: 0,
+            redraws: 0,
+            modal: false,
+            frame: None,
+        }
+    }
+
+    // Frames drawn from inside event pumping so far.
+    pub fn redraws(&self) -> u64 {
+        self.redraws
+    }
+
+    // Whether the event, seen while SDL is pumping, means the modal loop is running.
+    pub(crate) fn observe(&mut self, event: &SDL_Event) -> bool {
+        if !is_redraw_event(event) {
+            return false;
+        }
+        if !self.modal {
+            self.modal = is_modal_start(event);
+            return false;
+        }
+        true
+    }
+
+    // The pump returned, so whatever loop SDL was in is over.
+    pub(crate) fn end_pump(&mut self) {
+        self.modal = false;
+    }
+
+    pub(crate) fn set_frame(&mut self, frame: Option<(*mut SDL_Renderer, *mut SDL_Texture)>) {
+        self.frame = frame;
+    }
+
+    // Called from the event watch on the main thread, while the app is inside the pump
+    // and not touching the renderer.
+    pub(crate) fn redraw(&mut self) {
+        let (renderer, texture) = match self.frame {
+            Some(f) => f,
+            None => return,
+        };
+        let now = unsafe { SDL_GetTicks64() };
+        if now.saturating_sub(self.last) < self.min_interval {
+            return;
+        }
+        self.last = now;
+        self.redraws += 1;
+
+        unsafe {
+            SDL_SetRenderTarget(renderer, std::ptr::null_mut());
+            SDL_RenderCopy(renderer, texture, std::ptr::null(), std::ptr::null());
+            SDL_RenderPresent(renderer);
+        }
+    }
+}
+
+impl Default for LiveResize {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_modal_start(event: &SDL_Event) -> bool {
+    unsafe {
+        let id = event.window.event;
+        id == SDL_WindowEventID::SDL_WINDOWEVENT_SIZE_CHANGED as u8
+            || id == SDL_WindowEventID::SDL_WINDOWEVENT_MOVED as u8
+    }
+}
+
+fn is_redraw_event(event: &SDL_Event) -> bool {
+    unsafe {
+        if event.type_ != SDL_EventType::SDL_WINDOWEVENT as u32 {
+            return false;
+        }
+        let id = event.window.event;
+        id == SDL_WindowEventID::SDL_WINDOWEVENT_SIZE_CHANGED as u8
+            || id == SDL_WindowEventID::SDL_WINDOWEVENT_EXPOSED as u8
+            || id == SDL_WindowEventID::SDL_WINDOWEVENT_MOVED as u8
+    }
+}
+
+// Registered for as long as it lives, so an early return from event handling can't leave
+// SDL holding a dangling callback.
+pub(crate) struct EventWatch<F: FnMut(&SDL_Event)> {
+    callback: Box<F>,
+}
+
+impl<F: FnMut(&SDL_Event)> EventWatch<F> {
+    pub(crate) fn add(callback: F) -> Self {
+        let mut watch = EventWatch {
+            callback: Box::new(callback),
+        };
+        unsafe { SDL_AddEventWatch(Some(trampoline::<F>), watch.data()) };
+        watch
+    }
+
+    fn data(&mut self) -> *mut c_void {
+        &mut *self.callback as *mut F as *mut c_void
+    }
+}
+
+impl<F: FnMut(&SDL_Event)> Drop for EventWatch<F> {
+    fn drop(&mut self) {
+        let data = self.data();
+        unsafe { SDL_DelEventWatch(Some(trampoline::<F>), data) };
+    }
+}
+
+unsafe extern "C" fn trampoline<F: FnMut(&SDL_Event)>(
+    data: *mut c_void,
+    event: *mut SDL_Event,
+) -> c_int {
+    let callback = &mut *(data as *mut F);
+    callback(&*event);
+    // Watches can't drop events, the return value is ignored.
+    0
+}
@@ -1,12 +1,35 @@
+#[cfg(feature = "sdl2-image")]
+use sdl2::image::LoadSurface;
+#[cfg(all(feature = "sdl2-image", not(feature = "image")))]
+use sdl2::image::LoadTexture;
 use sdl2::{
-    image::LoadTexture,
     mixer::{Chunk, Music},
-    render::{Texture, TextureCreator},
+    pixels::PixelFormatEnum,
+    rect::Rect,
+    render::{BlendMode, Texture, TextureCreator},
+    surface::Surface,
+    sys::mixer::{
+        Mix_GetMusicAlbumTag, Mix_GetMusicArtistTag, Mix_GetMusicCopyrightTag,
+        Mix_GetMusicTitleTag, Mix_MusicDuration,
+    },
     ttf::{Font, Sdl2TtfContext},
     video::WindowContext,
 };
-use std::{marker::PhantomData, str, string::ToString};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ffi::CStr,
+    fs,
+    hash::Hash,
+    marker::PhantomData,
+    os::raw::c_char,
+    rc::{Rc, Weak},
+    str,
+    string::ToString,
+};
 
+use log::warn;
 use stagehand::{
     loading::{
         resources::{ResourceLoadError, ResourceLoader, ResourceStorage},
@@ -15,27 +38,535 @@ use stagehand::{
     utility::StorageType,
 };
 
-type TextureStorage<'a> =
-    ResourceStorage<'a, String, Texture<'a>, TextureLoader<'a, WindowContext>>;
-type SoundStorage<'a> = ResourceStorage<'a, String, Chunk, EmptyLoader>;
-type MusicStorage<'a> = ResourceStorage<'a, String, Music<'a>, EmptyLoader>;
-type FontStorage<'a, 'b, 'c> = ResourceStorage<'a, String, Font<'a, 'b>, FontLoader<'a, 'c>>;
+use crate::{
+    atlas::{AtlasConfig, AtlasPacker, AtlasRegion, AtlasStats},
+    audio,
+    compressed::{self, DecodedImage},
+    palette::{self, Palette},
+    placeholder,
+    prompts::{PromptFamily, PromptIcon, PromptPack},
+};
+
+type WindowTextureLoader<'a> = TextureLoader<'a, WindowContext>;
+type TextureStorage<'a, K> = ResourceStorage<'a, K, Texture<'a>, WindowTextureLoader<'a>>;
+type SoundStorage<'a, K> = ResourceStorage<'a, K, Chunk, EmptyLoader>;
+type MusicStorage<'a, K> = ResourceStorage<'a, K, Music<'a>, EmptyLoader>;
+type FontStorage<'a, 'b, 'c, K> = ResourceStorage<'a, K, Font<'a, 'b>, FontLoader<'a, 'c>>;
 
-pub struct SDLStorage<'a, 'b, 'c> {
-    pub fonts: FontStorage<'a, 'b, 'c>,
-    pub textures: TextureStorage<'a>,
-    pub sounds: SoundStorage<'a>,
-    pub music: MusicStorage<'a>,
+// Anything resources can be stored under. Enum keys or interned symbols skip hashing and
+// allocating strings on every lookup, and a misspelled key no longer compiles.
+pub trait ResourceKey: Clone + Eq + Hash {}
+
+impl<T: Clone + Eq + Hash> ResourceKey for T {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureInfo {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormatEnum,
 }
 
-impl<'a, 'b, 'c> SDLStorage<'a, 'b, 'c> {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FontInfo {
+    pub height: i32,
+    pub line_skip: i32,
+    pub ascent: i32,
+    pub descent: i32,
+}
+
+// Tags are empty for formats whose decoder doesn't read them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MusicInfo {
+    pub duration: Option<f64>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub copyright: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetSource<'p> {
+    Texture(&'p str, &'p str),
+    Font(&'p str, &'p str, u16),
+    Sound(&'p str, &'p str),
+    Music(&'p str, &'p str),
+}
+
+impl<'p> AssetSource<'p> {
+    pub fn key(&self) -> &'p str {
+        match self {
+            AssetSource::Texture(k, _)
+            | AssetSource::Font(k, _, _)
+            | AssetSource::Sound(k, _)
+            | AssetSource::Music(k, _) => k,
+        }
+    }
+
+    pub fn path(&self) -> &'p str {
+        match self {
+            AssetSource::Texture(_, p)
+            | AssetSource::Font(_, p, _)
+            | AssetSource::Sound(_, p)
+            | AssetSource::Music(_, p) => p,
+        }
+    }
+
+    pub fn requirement(&self) -> AssetRequirement {
+        let kind = match self {
+            AssetSource::Texture(..) => AssetKind::Texture,
+            AssetSource::Font(..) => AssetKind::Font,
+            AssetSource::Sound(..) => AssetKind::Sound,
+            AssetSource::Music(..) => AssetKind::Music,
+        };
+        AssetRequirement::new(kind, self.key())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    Texture,
+    Font,
+    Sound,
+    Music,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AssetRequirement {
+    pub kind: AssetKind,
+    pub key: String,
+}
+
+impl AssetRequirement {
+    pub fn new(kind: AssetKind, key: &str) -> Self {
+        AssetRequirement {
+            kind,
+            key: key.to_string(),
+        }
+    }
+}
+
+// A scene held back because assets it declared aren't in storage yet. `failed` has the
+// load error of every asset that was tried and couldn't be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingAssetError {
+    pub scene: String,
+    pub missing: Vec<AssetRequirement>,
+    pub failed: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    pub loaded: Vec<String>,
+    pub substituted: Vec<String>,
+    pub failed: Vec<(String, String)>,
+    // Music and sounds passed over because no audio device is open.
+    pub skipped: Vec<String>,
+}
+
+impl LoadReport {
+    pub fn is_clean(&self) -> bool {
+        self.substituted.is_empty() && self.failed.is_empty()
+    }
+}
+
+// Keyed by strings unless told otherwise. File loading, groups and prompt packs name their
+// resources by path-like strings, so only the string-keyed storage has them. Every key
+// type looks resources up through `ticket` or `TicketManager`.
+pub struct SDLStorage<'a, 'b, 'c, K = String> {
+    pub fonts: FontStorage<'a, 'b, 'c, K>,
+    pub textures: TextureStorage<'a, K>,
+    pub sounds: SoundStorage<'a, K>,
+    pub music: MusicStorage<'a, K>,
+
+    texture_loader: &'a WindowTextureLoader<'a>,
+    font_loader: &'a FontLoader<'a, 'c>,
+    groups: HashMap<String, Vec<(StorageType, String)>>,
+    texture_sources: HashMap<Ticket, String>,
+    font_sources: HashMap<Ticket, &'c str>,
+    resolved: RefCell<HashSet<Ticket>>,
+    keys: HashMap<(AssetKind, Ticket), K>,
+    generated: HashMap<Ticket, (u32, u32, Vec<u8>)>,
+    atlas: Option<AtlasPacker>,
+    atlas_pages: Vec<Rc<RefCell<Texture<'a>>>>,
+    atlas_regions: HashMap<Ticket, AtlasRegion>,
+}
+
+pub type StringStorage<'a, 'b, 'c> = SDLStorage<'a, 'b, 'c, String>;
+
+impl<'a, 'b, 'c, K: ResourceKey> SDLStorage<'a, 'b, 'c, K> {
     pub fn new(texture: &'a TextureLoader<WindowContext>, font: &'a FontLoader<'a, 'c>) -> Self {
         SDLStorage {
             fonts: FontStorage::new(font),
             textures: TextureStorage::new(texture),
             sounds: SoundStorage::new(&EmptyLoader {}),
             music: MusicStorage::new(&EmptyLoader {}),
+
+            texture_loader: texture,
+            font_loader: font,
+            groups: HashMap::new(),
+            texture_sources: HashMap::new(),
+            font_sources: HashMap::new(),
+            resolved: RefCell::new(HashSet::new()),
+            keys: HashMap::new(),
+            generated: HashMap::new(),
+            atlas: None,
+            atlas_pages: Vec::new(),
+            atlas_regions: HashMap::new(),
+        }
+    }
+
+    pub fn contains<Q>(&self, kind: AssetKind, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ticket(kind, key).is_ok()
+    }
+
+    pub fn ticket<Q>(&self, kind: AssetKind, key: &Q) -> Result<Ticket, ResourceError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match kind {
+            AssetKind::Texture => self.texture_ticket(key),
+            AssetKind::Font => self.fonts.take_ticket(key),
+            AssetKind::Sound => self.sounds.take_ticket(key),
+            AssetKind::Music => self.music.take_ticket(key),
+        }
+    }
+
+    fn texture_ticket<Q>(&self, key: &Q) -> Result<Ticket, ResourceError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let ticket = self.textures.take_ticket(key)?;
+        self.resolved.borrow_mut().insert(ticket);
+        Ok(ticket)
+    }
+
+    // Returns false when the storage has no resource under the key.
+    pub fn track_key<Q>(&mut self, kind: AssetKind, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        match self.ticket(kind, key) {
+            Ok(ticket) => {
+                self.keys.insert((kind, ticket), key.to_owned());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    // Known for every texture that came from a file, including ones loaded through
+    // `textures.load` directly.
+    pub fn texture_source(&self, ticket: Ticket) -> Option<String> {
+        if let Some(path) = self.texture_sources.get(&ticket) {
+            return Some(path.clone());
         }
+        if self.generated.contains_key(&ticket) {
+            return None;
+        }
+        self.textures
+            .get_by_ticket(ticket)
+            .ok()
+            .and_then(|t| self.texture_loader.source_of(&t.borrow()))
+    }
+
+    // The loader only knows its textures by SDL handle, which changes when a texture is
+    // replaced, so sources of tickets handed out so far are pinned to the ticket first.
+    fn adopt_texture_sources(&mut self) {
+        for ticket in self.resolved.take() {
+            if self.texture_sources.contains_key(&ticket) {
+                continue;
+            }
+            if let Some(path) = self.texture_source(ticket) {
+                self.texture_sources.insert(ticket, path);
+            }
+        }
+    }
+
+    fn replace_texture(&self, existing: &RefCell<Texture<'a>>, texture: Texture<'a>) {
+        self.texture_loader.forget(&existing.borrow());
+        *existing.borrow_mut() = texture;
+    }
+
+    pub fn texture_info(&self, ticket: Ticket) -> Result<TextureInfo, ResourceError> {
+        let query = self.textures.get_by_ticket(ticket)?.borrow().query();
+        Ok(TextureInfo {
+            width: query.width,
+            height: query.height,
+            format: query.format,
+        })
+    }
+
+    pub fn music_info(&self, ticket: Ticket) -> Result<MusicInfo, ResourceError> {
+        let music = self.music.get_by_ticket(ticket)?;
+        let raw = music.borrow().raw;
+
+        let tag = |text: *const c_char| {
+            if text.is_null() {
+                return None;
+            }
+            let text = unsafe { CStr::from_ptr(text) }.to_string_lossy();
+            match text.trim() {
+                "" => None,
+                t => Some(t.to_string()),
+            }
+        };
+
+        let duration = unsafe { Mix_MusicDuration(raw) };
+        Ok(MusicInfo {
+            duration: match duration >= 0.0 {
+                true => Some(duration),
+                false => None,
+            },
+            title: tag(unsafe { Mix_GetMusicTitleTag(raw) }),
+            artist: tag(unsafe { Mix_GetMusicArtistTag(raw) }),
+            album: tag(unsafe { Mix_GetMusicAlbumTag(raw) }),
+            copyright: tag(unsafe { Mix_GetMusicCopyrightTag(raw) }),
+        })
+    }
+
+    pub fn font_info(&self, ticket: Ticket) -> Result<FontInfo, ResourceError> {
+        let font = self.fonts.get_by_ticket(ticket)?;
+        let font = font.borrow();
+        Ok(FontInfo {
+            height: font.height(),
+            line_skip: font.recommended_line_spacing(),
+            ascent: font.ascent(),
+            descent: font.descent(),
+        })
+    }
+
+    pub fn text_size(&self, ticket: Ticket, text: &str) -> Result<(u32, u32), ResourceError> {
+        let font = self.fonts.get_by_ticket(ticket)?;
+        let size = font.borrow().size_of(text).unwrap_or((0, 0));
+        Ok(size)
+    }
+
+    // Re-creates every texture with a known file or generated source in place, for after
+    // the renderer lost its device. Returns how many could not be restored.
+    pub fn restore_textures(&mut self) -> usize {
+        self.adopt_texture_sources();
+        let mut failed = 0;
+
+        for (ticket, path) in self.texture_sources.iter() {
+            let restored = load_texture_file(&self.texture_loader.creator, path);
+            match (restored, self.textures.get_by_ticket(*ticket)) {
+                (Ok(texture), Ok(existing)) => self.replace_texture(&existing, texture),
+                _ => {
+                    warn!("Could not restore texture from {}", path);
+                    failed += 1;
+                }
+            }
+        }
+
+        for (ticket, (width, height, pixels)) in self.generated.iter() {
+            let mut data = pixels.clone();
+            let restored = Surface::from_data(
+                &mut data,
+                *width,
+                *height,
+                width * 4,
+                PixelFormatEnum::RGBA32,
+            )
+            .and_then(|s| {
+                self.texture_loader
+                    .creator
+                    .create_texture_from_surface(&s)
+                    .map_err(|e| e.to_string())
+            });
+            match (restored, self.textures.get_by_ticket(*ticket)) {
+                (Ok(texture), Ok(existing)) => self.replace_texture(&existing, texture),
+                _ => failed += 1,
+            }
+        }
+
+        failed + self.restore_atlas()
+    }
+
+    // Textures loaded from files from now on are also copied into shared pages, and draws
+    // of their tickets use the page instead. The original textures stay for direct access.
+    pub fn enable_atlas(&mut self, config: AtlasConfig) {
+        if self.atlas.is_none() {
+            self.atlas = Some(AtlasPacker::new(config));
+        }
+    }
+
+    pub fn atlas_stats(&self) -> Option<AtlasStats> {
+        self.atlas.as_ref().map(|a| a.stats())
+    }
+
+    pub fn atlas_region(&self, ticket: Ticket) -> Option<(Rc<RefCell<Texture<'a>>>, Rect)> {
+        let region = self.atlas_regions.get(&ticket)?;
+        let page = self.atlas_pages.get(region.page)?;
+        Some((page.clone(), region.rect))
+    }
+
+    fn pack_texture(&mut self, ticket: Ticket, path: &str) -> Result<(), String> {
+        let atlas = match &mut self.atlas {
+            Some(a) => a,
+            None => return Ok(()),
+        };
+
+        let surface = load_surface_file(path)?.convert_format(PixelFormatEnum::RGBA32)?;
+        let size = atlas.config.page_size;
+        let (region, new_page) = match atlas.insert(surface.width(), surface.height()) {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        if new_page {
+            // Without its page the packer's indices would drift, so stop packing instead.
+            match self.create_atlas_page(size) {
+                Ok(page) => self.atlas_pages.push(Rc::new(RefCell::new(page))),
+                Err(e) => {
+                    self.atlas = None;
+                    return Err(e);
+                }
+            }
+        }
+
+        self.blit_region(&surface, region)?;
+        self.atlas_regions.insert(ticket, region);
+        Ok(())
+    }
+
+    // The region's space stays taken, the packer can't free it. Draws go back to the
+    // ticket's own texture.
+    fn unpack_texture(&mut self, ticket: Ticket) {
+        self.atlas_regions.remove(&ticket);
+    }
+
+    fn create_atlas_page(&self, size: u32) -> Result<Texture<'a>, String> {
+        let mut page = self
+            .texture_loader
+            .creator
+            .create_texture_static(PixelFormatEnum::RGBA32, size, size)
+            .map_err(|e| e.to_string())?;
+        page.set_blend_mode(BlendMode::Blend);
+
+        // Static textures start with undefined contents, padding has to be transparent.
+        let clear = vec![0u8; size as usize * size as usize * 4];
+        page.update(None, &clear, size as usize * 4)
+            .map_err(|e| e.to_string())?;
+        Ok(page)
+    }
+
+    fn blit_region(&self, surface: &Surface, region: AtlasRegion) -> Result<(), String> {
+        let page = self
+            .atlas_pages
+            .get(region.page)
+            .ok_or_else(|| "Atlas page missing".to_string())?;
+        let pitch = surface.pitch() as usize;
+        surface.with_lock(|pixels| {
+            page.borrow_mut()
+                .update(region.rect, pixels, pitch)
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn restore_atlas(&mut self) -> usize {
+        let size = match &self.atlas {
+            Some(a) => a.config.page_size,
+            None => return 0,
+        };
+
+        for page in self.atlas_pages.iter() {
+            match self.create_atlas_page(size) {
+                Ok(p) => *page.borrow_mut() = p,
+                Err(e) => warn!("Could not recreate atlas page: {}", e),
+            }
+        }
+
+        let mut failed = 0;
+        for (ticket, region) in self.atlas_regions.iter() {
+            let restored = self
+                .texture_sources
+                .get(ticket)
+                .ok_or_else(|| "No source".to_string())
+                .and_then(|path| load_surface_file(path))
+                .and_then(|s| s.convert_format(PixelFormatEnum::RGBA32))
+                .and_then(|s| self.blit_region(&s, *region));
+            if restored.is_err() {
+                failed += 1;
+            }
+        }
+
+        failed
+    }
+
+    // Drops every sound and music resource so nothing is freed against a closed mixer.
+    // Their tickets stop resolving. Returns false when a tracked sound or music is still
+    // held somewhere else, and would be freed later than this.
+    pub(crate) fn release_audio(&mut self) -> bool {
+        let mut sounds: Vec<Weak<RefCell<Chunk>>> = Vec::new();
+        let mut music: Vec<Weak<RefCell<Music<'a>>>> = Vec::new();
+        for (kind, ticket) in self.keys.keys() {
+            match kind {
+                AssetKind::Sound => {
+                    if let Ok(c) = self.sounds.get_by_ticket(*ticket) {
+                        sounds.push(Rc::downgrade(&c));
+                    }
+                }
+                AssetKind::Music => {
+                    if let Ok(m) = self.music.get_by_ticket(*ticket) {
+                        music.push(Rc::downgrade(&m));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        self.sounds = ResourceStorage::new(&EmptyLoader {});
+        self.music = ResourceStorage::new(&EmptyLoader {});
+        self.keys
+            .retain(|(kind, _), _| !matches!(kind, AssetKind::Sound | AssetKind::Music));
+
+        sounds.iter().all(|s| s.strong_count() == 0) && music.iter().all(|m| m.strong_count() == 0)
+    }
+
+    // Unlike loading through the storage fields, these track the key for `keys` and
+    // `ticket_key`.
+    pub fn load_texture(&mut self, key: K, path: &str) -> Result<(), ResourceError> {
+        self.textures.load(key.clone(), path)?;
+        self.track_key(AssetKind::Texture, &key);
+        Ok(())
+    }
+
+    pub fn load_font(&mut self, key: K, path: &'c str, size: u16) -> Result<(), ResourceError> {
+        self.fonts.load(key.clone(), &(path, size))?;
+        self.track_key(AssetKind::Font, &key);
+        if let Ok(ticket) = self.fonts.take_ticket(&key) {
+            self.font_sources.insert(ticket, path);
+        }
+        Ok(())
+    }
+
+    // Without an audio device these store nothing and return false instead of failing,
+    // like `load_all` skipping audio assets.
+    pub fn load_sound(&mut self, key: K, path: &str) -> Result<bool, ResourceError> {
+        if !audio::is_available() {
+            audio::warn_unavailable("sound loading");
+            return Ok(false);
+        }
+        self.sounds.load(key.clone(), path)?;
+        self.track_key(AssetKind::Sound, &key);
+        Ok(true)
+    }
+
+    pub fn load_music(&mut self, key: K, path: &str) -> Result<bool, ResourceError> {
+        if !audio::is_available() {
+            audio::warn_unavailable("music loading");
+            return Ok(false);
+        }
+        self.music.load(key.clone(), path)?;
+        self.track_key(AssetKind::Music, &key);
+        Ok(true)
     }
 
     pub fn lock(&mut self) {
@@ -46,14 +577,368 @@ impl<'a, 'b, 'c> SDLStorage<'a, 'b, 'c> {
     }
 }
 
-impl<'a, 'b, 'c> TicketManager<StorageType, StorageType, String, str> for SDLStorage<'a, 'b, 'c> {
+impl<'a, 'b, 'c> SDLStorage<'a, 'b, 'c> {
+    // Only textures can be substituted, the sound loaders have no way to accept generated audio.
+    pub fn load_all(&mut self, assets: &[AssetSource<'c>], substitute: bool) -> LoadReport {
+        self.load_all_with(assets, substitute, |_, _| true)
+    }
+
+    // `progress` gets the number of assets done and the total before each asset and once at
+    // the end, and can return false to stop loading. A stopped load skips that final call,
+    // and assets not reached count as failed.
+    pub fn load_all_with<F>(
+        &mut self,
+        assets: &[AssetSource<'c>],
+        substitute: bool,
+        mut progress: F,
+    ) -> LoadReport
+    where
+        F: FnMut(usize, usize) -> bool,
+    {
+        let mut report = LoadReport::default();
+
+        for (done, asset) in assets.iter().enumerate() {
+            let key = asset.key().to_string();
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("load", key = %key, path = %asset.path()).entered();
+            if !progress(done, assets.len()) {
+                for asset in assets[done..].iter() {
+                    report
+                        .failed
+                        .push((asset.key().to_string(), "Loading was cancelled".to_string()));
+                }
+                return report;
+            }
+
+            let audio = matches!(asset, AssetSource::Sound(..) | AssetSource::Music(..));
+            if audio && !audio::is_available() {
+                report.skipped.push(key);
+                continue;
+            }
+
+            let result = match asset {
+                AssetSource::Texture(_, path) => self.load_texture(key.clone(), path),
+                AssetSource::Font(_, path, size) => self.load_font(key.clone(), path, *size),
+                AssetSource::Sound(_, path) => self.load_sound(key.clone(), path).map(|_| ()),
+                AssetSource::Music(_, path) => self.load_music(key.clone(), path).map(|_| ()),
+            };
+
+            if let Err(e) = result {
+                let error = format!("Could not load from {}: {:?}", asset.path(), e);
+                warn!("Could not load '{}': {}", key, error);
+                if substitute {
+                    if let AssetSource::Texture(..) = asset {
+                        let result = placeholder::checkerboard_surface()
+                            .and_then(|s| self.set_texture_from_surface(&key, &s));
+                        if result.is_ok() {
+                            report.substituted.push(key);
+                            continue;
+                        }
+                    }
+                }
+                report.failed.push((key, error));
+                continue;
+            }
+
+            if let AssetSource::Texture(_, path) = asset {
+                if let Ok(ticket) = self.textures.take_ticket(&key) {
+                    self.texture_sources.insert(ticket, path.to_string());
+                    if let Err(e) = self.pack_texture(ticket, path) {
+                        warn!("Could not pack '{}' into the atlas: {}", key, e);
+                    }
+                }
+            }
+            report.loaded.push(key);
+        }
+
+        progress(assets.len(), assets.len());
+        report
+    }
+
+    // Audio is never missing without an audio device, it was skipped on purpose.
+    pub fn missing(&self, required: &[AssetRequirement]) -> Vec<AssetRequirement> {
+        let silent = !audio::is_available();
+        required
+            .iter()
+            .filter(|r| !(silent && matches!(r.kind, AssetKind::Sound | AssetKind::Music)))
+            .filter(|r| !self.contains(r.kind, &r.key))
+            .cloned()
+            .collect()
+    }
+
+    // Keys loaded through SDLStorage, sorted. Resources loaded straight through the storage
+    // fields only show up here after `track_key`, released ones not at all.
+    pub fn keys(&self, kind: AssetKind) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .keys
+            .iter()
+            .filter(|((k, _), _)| *k == kind)
+            .map(|(_, key)| key.as_str())
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    pub fn ticket_key(&self, kind: AssetKind, ticket: Ticket) -> Option<&str> {
+        self.keys.get(&(kind, ticket)).map(|k| k.as_str())
+    }
+
+    // Every storage numbers its tickets separately, so one ticket can name a resource of
+    // each kind.
+    pub fn ticket_keys(&self, ticket: Ticket) -> Vec<(AssetKind, &str)> {
+        [
+            AssetKind::Texture,
+            AssetKind::Font,
+            AssetKind::Sound,
+            AssetKind::Music,
+        ]
+        .into_iter()
+        .filter_map(|kind| self.ticket_key(kind, ticket).map(|k| (kind, k)))
+        .collect()
+    }
+
+    // Replaces the texture in place when the key already exists so held tickets stay valid.
+    pub fn set_texture_from_surface(
+        &mut self,
+        key: &str,
+        surface: &Surface,
+    ) -> Result<Ticket, String> {
+        let texture = self
+            .texture_loader
+            .creator
+            .create_texture_from_surface(surface)
+            .map_err(|e| e.to_string())?;
+
+        let ticket = self.store_texture(key, texture)?;
+
+        // Generated textures have no file to come back from after a device reset.
+        let converted = surface.convert_format(PixelFormatEnum::RGBA32)?;
+        let (width, height, pitch) = (converted.width(), converted.height(), converted.pitch());
+        let row = width as usize * 4;
+        let mut pixels = Vec::with_capacity(row * height as usize);
+        converted.with_lock(|data| {
+            for y in 0..height as usize {
+                let start = y * pitch as usize;
+                pixels.extend_from_slice(&data[start..start + row]);
+            }
+        });
+        self.generated.insert(ticket, (width, height, pixels));
+
+        Ok(ticket)
+    }
+
+    fn store_texture(&mut self, key: &str, texture: Texture<'a>) -> Result<Ticket, String> {
+        if let Ok(ticket) = self.textures.take_ticket(key) {
+            if let Ok(existing) = self.textures.get_by_ticket(ticket) {
+                self.replace_texture(&existing, texture);
+                self.texture_sources.remove(&ticket);
+                self.unpack_texture(ticket);
+                return Ok(ticket);
+            }
+        }
+
+        let source = self.texture_loader.stage(key, texture);
+        let result = self.textures.load(key.to_string(), &source);
+        self.texture_loader.unstage(key);
+
+        match result {
+            Ok(_) => {
+                self.track_key(AssetKind::Texture, key);
+                self.textures
+                    .take_ticket(key)
+                    .map_err(|_| format!("Texture '{}' was not stored", key))
+            }
+            Err(_) => Err(format!("Could not store generated texture '{}'", key)),
+        }
+    }
+
+    // Builds a recolored copy of a file-backed texture and stores it under `key`.
+    pub fn palette_variant(
+        &mut self,
+        ticket: Ticket,
+        key: &str,
+        palette: &Palette,
+    ) -> Result<Ticket, String> {
+        let path = self
+            .texture_source(ticket)
+            .ok_or_else(|| "Palette swaps need a texture loaded from a file".to_string())?;
+        let surface = load_surface_file(&path)?;
+        let recolored = palette::recolor_surface(&surface, palette)?;
+        self.set_texture_from_surface(key, &recolored)
+    }
+
+    pub fn load_group(
+        &mut self,
+        group: &str,
+        assets: &[AssetSource<'c>],
+        substitute: bool,
+    ) -> LoadReport {
+        let report = self.load_all(assets, substitute);
+
+        let entries = self.groups.entry(group.to_string()).or_default();
+        for asset in assets.iter() {
+            let key = asset.key().to_string();
+            if !report.loaded.contains(&key) && !report.substituted.contains(&key) {
+                continue;
+            }
+
+            let storage = match asset {
+                AssetSource::Texture(..) => StorageType::Texture,
+                AssetSource::Font(..) => StorageType::Font,
+                AssetSource::Sound(..) => StorageType::Sound,
+                AssetSource::Music(..) => StorageType::Music,
+            };
+            entries.push((storage, key));
+        }
+
+        report
+    }
+
+    // Loads every image in `dir` named after the prompt convention, keyed as
+    // `prompt/<file stem>`. Files without a known family prefix are skipped.
+    pub fn load_prompt_pack(&mut self, dir: &str) -> Result<PromptPack, String> {
+        let mut pack = PromptPack::new();
+        let entries = fs::read_dir(dir).map_err(|e| e.to_string())?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let (stem, path) = match (path.file_stem().and_then(|s| s.to_str()), path.to_str()) {
+                (Some(s), Some(p)) => (s.to_string(), p.to_string()),
+                _ => continue,
+            };
+
+            if PromptFamily::from_name(&stem).is_none() {
+                continue;
+            }
+
+            let key = format!("prompt/{}", stem);
+            if self.textures.load(key.clone(), &path).is_err() {
+                warn!("Could not load prompt icon from {}", path);
+                continue;
+            }
+            if let Ok(ticket) = self.textures.take_ticket(&key) {
+                self.track_key(AssetKind::Texture, &key);
+                let icon = PromptIcon {
+                    ticket,
+                    source: None,
+                };
+                pack.insert_named(&stem, icon);
+                self.texture_sources.insert(ticket, path);
+            }
+        }
+
+        Ok(pack)
+    }
+
+    pub fn group_keys(&self, group: &str) -> Vec<String> {
+        match self.groups.get(group) {
+            Some(entries) => entries.iter().map(|(_, k)| k.clone()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Storage entries can't be removed, so released resources are swapped for minimal
+    // stand-ins to free their memory. Tickets stay valid: textures draw the checkerboard,
+    // sounds and music play silence, and fonts are reopened at one point, which drops
+    // their glyph caches. Fonts not loaded through `load_font` can't be reopened and stay.
+    // Keys another group still holds are kept.
+    pub fn release_group(&mut self, group: &str) -> usize {
+        let entries = match self.groups.remove(group) {
+            Some(e) => e,
+            None => return 0,
+        };
+
+        let mut released = 0;
+        for (storage, key) in entries.iter() {
+            let held = self.groups.values().flatten().any(|(s, k)| {
+                k == key && std::mem::discriminant(s) == std::mem::discriminant(storage)
+            });
+            if held {
+                continue;
+            }
+            let ticket = match self.get_ticket_with_key(storage, key) {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+
+            match storage {
+                StorageType::Texture => {
+                    let checkerboard = placeholder::checkerboard_surface().and_then(|s| {
+                        self.texture_loader
+                            .creator
+                            .create_texture_from_surface(&s)
+                            .map_err(|e| e.to_string())
+                    });
+                    if let (Ok(texture), Ok(checkerboard)) =
+                        (self.textures.get_by_ticket(ticket), checkerboard)
+                    {
+                        self.replace_texture(&texture, checkerboard);
+                        self.keys.remove(&(AssetKind::Texture, ticket));
+                        // Restoring the device must not load it back.
+                        self.texture_sources.remove(&ticket);
+                        self.resolved.get_mut().remove(&ticket);
+                        self.generated.remove(&ticket);
+                        self.unpack_texture(ticket);
+                        released += 1;
+                    }
+                }
+                StorageType::Sound => {
+                    let silent = Chunk::from_raw_buffer(vec![0i16; 2].into_boxed_slice());
+                    if let (Ok(chunk), Ok(silent)) = (self.sounds.get_by_ticket(ticket), silent) {
+                        *chunk.borrow_mut() = silent;
+                        self.keys.remove(&(AssetKind::Sound, ticket));
+                        released += 1;
+                    }
+                }
+                StorageType::Music => {
+                    let silent = Music::from_static_bytes(&SILENT_WAV);
+                    if let (Ok(music), Ok(silent)) = (self.music.get_by_ticket(ticket), silent) {
+                        *music.borrow_mut() = silent;
+                        self.keys.remove(&(AssetKind::Music, ticket));
+                        released += 1;
+                    }
+                }
+                StorageType::Font => {
+                    let reopened = match self.font_sources.remove(&ticket) {
+                        Some(path) => self.font_loader.load(&(path, 1)).ok(),
+                        None => None,
+                    };
+                    match (self.fonts.get_by_ticket(ticket), reopened) {
+                        (Ok(font), Some(small)) => {
+                            *font.borrow_mut() = small;
+                            self.keys.remove(&(AssetKind::Font, ticket));
+                            released += 1;
+                        }
+                        _ => warn!("Could not release font '{}'", key),
+                    }
+                }
+                _ => warn!("Cannot release '{}' from unknown storage", key),
+            }
+        }
+
+        released
+    }
+}
+
+// A single silent sample, standing in for released music.
+const SILENT_WAV: [u8; 46] = [
+    b'R', b'I', b'F', b'F', 38, 0, 0, 0, b'W', b'A', b'V', b'E', b'f', b'm', b't', b' ', 16, 0, 0,
+    0, 1, 0, 1, 0, 0x40, 0x1f, 0, 0, 0x80, 0x3e, 0, 0, 2, 0, 16, 0, b'd', b'a', b't', b'a', 2, 0,
+    0, 0, 0, 0,
+];
+
+impl<'a, 'b, 'c, K, Q> TicketManager<StorageType, StorageType, K, Q> for SDLStorage<'a, 'b, 'c, K>
+where
+    K: ResourceKey + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+{
     fn get_ticket_with_key(
         &self,
         storage_key: &StorageType,
-        resource_key: &str,
+        resource_key: &Q,
     ) -> Result<Ticket, ResourceError> {
         match storage_key {
-            StorageType::Texture => self.textures.take_ticket(resource_key),
+            StorageType::Texture => self.texture_ticket(resource_key),
             StorageType::Font => self.fonts.take_ticket(resource_key),
             StorageType::Music => self.music.take_ticket(resource_key),
             StorageType::Sound => self.sounds.take_ticket(resource_key),
@@ -62,8 +947,74 @@ impl<'a, 'b, 'c> TicketManager<StorageType, StorageType, String, str> for SDLSto
     }
 }
 
+// DDS and KTX containers go through the software decoder, everything else through the
+// `image` crate or SDL_image, whichever features are enabled. With both, SDL_image gets the
+// files the image crate can't read.
+pub fn load_surface_file(path: &str) -> Result<Surface<'static>, String> {
+    if compressed::is_container(path) {
+        return surface_from_image(compressed::decode_file(path)?);
+    }
+
+    #[cfg(feature = "image")]
+    {
+        match crate::imagefile::decode_file(path) {
+            Ok(image) => surface_from_image(image),
+            #[cfg(feature = "sdl2-image")]
+            Err(_) => Surface::from_file(path),
+            #[cfg(not(feature = "sdl2-image"))]
+            Err(e) => Err(e),
+        }
+    }
+    #[cfg(all(feature = "sdl2-image", not(feature = "image")))]
+    {
+        Surface::from_file(path)
+    }
+    #[cfg(not(any(feature = "image", feature = "sdl2-image")))]
+    {
+        Err(format!(
+            "Could not load {}, enable the `image` or `sdl2-image` feature",
+            path
+        ))
+    }
+}
+
+fn surface_from_image(image: DecodedImage) -> Result<Surface<'static>, String> {
+    let mut surface = Surface::new(image.width, image.height, PixelFormatEnum::RGBA32)?;
+    let pitch = surface.pitch() as usize;
+    let row = image.width as usize * 4;
+    surface.with_lock_mut(|pixels| {
+        for (y, source) in image.pixels.chunks_exact(row).enumerate() {
+            pixels[y * pitch..y * pitch + row].copy_from_slice(source);
+        }
+    });
+    Ok(surface)
+}
+
+pub fn load_texture_file<'a, T>(
+    creator: &'a TextureCreator<T>,
+    path: &str,
+) -> Result<Texture<'a>, String> {
+    #[cfg(all(feature = "sdl2-image", not(feature = "image")))]
+    if !compressed::is_container(path) {
+        return creator.load_texture(path);
+    }
+
+    let surface = load_surface_file(path)?;
+    creator
+        .create_texture_from_surface(&surface)
+        .map_err(|e| e.to_string())
+}
+
+// Load arguments naming a texture handed over through `TextureLoader::stage` instead of
+// a file.
+const STAGED_PREFIX: &str = "staged:";
+
 pub struct TextureLoader<'a, T> {
     pub creator: TextureCreator<T>,
+    staged: RefCell<HashMap<String, Texture<'a>>>,
+    // File paths by SDL texture handle, for reloading after device resets and uploading to
+    // other windows.
+    sources: RefCell<HashMap<usize, String>>,
     phantom: PhantomData<&'a ()>,
 }
 
@@ -71,32 +1022,80 @@ impl<'a, T> TextureLoader<'a, T> {
     pub fn from_creator(creator: TextureCreator<T>) -> Self {
         TextureLoader {
             creator,
+            staged: RefCell::new(HashMap::new()),
+            sources: RefCell::new(HashMap::new()),
             phantom: PhantomData,
         }
     }
+
+    pub(crate) fn source_of(&self, texture: &Texture) -> Option<String> {
+        self.sources
+            .borrow()
+            .get(&(texture.raw() as usize))
+            .cloned()
+    }
+
+    // The storage only takes resources from its loader, so textures built at runtime are
+    // staged here under `name` and loaded from `STAGED_PREFIX` followed by the name.
+    // Returns the load argument.
+    pub(crate) fn stage(&self, name: &str, texture: Texture<'a>) -> String {
+        self.forget(&texture);
+        self.staged.borrow_mut().insert(name.to_string(), texture);
+        format!("{}{}", STAGED_PREFIX, name)
+    }
+
+    // Drops a staged texture the storage didn't take.
+    pub(crate) fn unstage(&self, name: &str) {
+        self.staged.borrow_mut().remove(name);
+    }
+
+    // A freed handle can be reused by the next texture SDL creates.
+    pub(crate) fn forget(&self, texture: &Texture) {
+        self.sources.borrow_mut().remove(&(texture.raw() as usize));
+    }
 }
 
 impl<'a, T> ResourceLoader<'a, Texture<'a>> for TextureLoader<'a, T> {
     type Arguments = str;
 
     fn load(&'a self, args: &Self::Arguments) -> Result<Texture<'a>, ResourceLoadError> {
-        let result = self.creator.load_texture(args);
+        if let Some(name) = args.strip_prefix(STAGED_PREFIX) {
+            return self.staged.borrow_mut().remove(name).ok_or_else(|| {
+                ResourceLoadError::LoadFailure(format!("No texture staged as '{}'", name))
+            });
+        }
+
+        let result = load_texture_file(&self.creator, args);
         match result {
-            Ok(t) => Ok(t),
+            Ok(t) => {
+                self.sources
+                    .borrow_mut()
+                    .insert(t.raw() as usize, args.to_string());
+                Ok(t)
+            }
             Err(e) => Err(ResourceLoadError::LoadFailure(e)),
         }
     }
 }
 
+// Without a context, when SDL_ttf failed to start, every font fails to load and text
+// is drawn with the built in font.
 pub struct FontLoader<'a, 'c> {
-    pub context: Sdl2TtfContext,
+    pub context: Option<Sdl2TtfContext>,
     phantom: PhantomData<(&'a (), &'c ())>,
 }
 
 impl<'a, 'c> FontLoader<'a, 'c> {
     pub fn from_context(context: Sdl2TtfContext) -> Self {
         FontLoader {
-            context,
+            context: Some(context),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn unavailable() -> Self {
+        FontLoader {
+            context: None,
             phantom: PhantomData,
         }
     }
@@ -106,7 +1105,10 @@ impl<'a, 'b, 'c> ResourceLoader<'a, Font<'a, 'b>> for FontLoader<'a, 'c> {
     type Arguments = (&'c str, u16);
 
     fn load(&'a self, args: &Self::Arguments) -> Result<Font<'a, 'b>, ResourceLoadError> {
-        let result = self.context.load_font(args.0, args.1);
+        let result = match &self.context {
+            Some(context) => context.load_font(args.0, args.1),
+            None => Err("Fonts are unavailable, SDL_ttf could not be initialized".to_string()),
+        };
         match result {
             Ok(t) => Ok(t),
             Err(e) => Err(ResourceLoadError::LoadFailure(e)),
@@ -0,0 +1,25 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SafeArea {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisplayMetrics {
+    pub drawable: (u32, u32),
+    pub window: (u32, u32),
+    pub safe_area: SafeArea,
+    pub safe_area_override: Option<SafeArea>,
+}
+
+impl DisplayMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn effective_safe_area(&self) -> SafeArea {
+        self.safe_area_override.unwrap_or(self.safe_area)
+    }
+}
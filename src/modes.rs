@@ -0,0 +1,67 @@
+use sdl2::video::{DisplayMode, FullscreenType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FullscreenMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: u32,
+    pub(crate) mode: DisplayMode,
+}
+
+impl FullscreenMode {
+    pub(crate) fn from_display_mode(mode: DisplayMode) -> Self {
+        FullscreenMode {
+            width: mode.w.max(0) as u32,
+            height: mode.h.max(0) as u32,
+            refresh_rate: mode.refresh_rate.max(0) as u32,
+            mode,
+        }
+    }
+}
+
+// What the window was doing before an unconfirmed mode change, and when to go back to it.
+pub(crate) struct PendingMode {
+    pub(crate) fullscreen: FullscreenType,
+    pub(crate) mode: DisplayMode,
+    pub(crate) deadline: u64,
+}
+
+// Mode changes that need the player to confirm they can still see the screen, the usual
+// "Keep these settings?" prompt. Unconfirmed changes revert once `timeout` runs out.
+pub struct ModeConfirmation {
+    // Milliseconds before an unconfirmed mode reverts.
+    pub timeout: u64,
+    pub(crate) pending: Option<PendingMode>,
+    pub(crate) reverted: bool,
+}
+
+impl ModeConfirmation {
+    pub fn new() -> Self {
+        ModeConfirmation {
+            timeout: 15000,
+            pending: None,
+            reverted: false,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn remaining(&self, now: u64) -> Option<u64> {
+        self.pending
+            .as_ref()
+            .map(|p| p.deadline.saturating_sub(now))
+    }
+
+    // True only on the update where an unconfirmed mode timed out and was reverted.
+    pub fn was_reverted(&self) -> bool {
+        self.reverted
+    }
+}
+
+impl Default for ModeConfirmation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
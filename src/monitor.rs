@@ -0,0 +1,148 @@
+use std::{
+    os::raw::c_void,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use sdl2::{
+    mixer::{AUDIO_S16LSB, DEFAULT_CHANNELS},
+    sys::mixer::Mix_RegisterEffect,
+};
+
+use crate::audio::{self, POST_MIX};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AudioStats {
+    pub buffer_samples: i32,
+    pub callbacks: u64,
+    pub underruns: u64,
+    // Longest gap between mixer callbacks, in milliseconds.
+    pub worst_gap: f64,
+}
+
+struct MonitorState {
+    started: Instant,
+    samples: AtomicU64,
+    last: AtomicU64,
+    callbacks: AtomicU64,
+    underruns: AtomicU64,
+    worst: AtomicU64,
+}
+
+// Times the post-mix callback, which SDL_mixer runs once per device buffer. A gap much
+// longer than one buffer means the device ran dry.
+pub struct AudioMonitor {
+    state: Arc<MonitorState>,
+    reported: u64,
+}
+
+impl AudioMonitor {
+    pub fn new() -> Self {
+        let monitor = AudioMonitor {
+            state: Arc::new(MonitorState {
+                started: Instant::now(),
+                samples: AtomicU64::new(0),
+                last: AtomicU64::new(0),
+                callbacks: AtomicU64::new(0),
+                underruns: AtomicU64::new(0),
+                worst: AtomicU64::new(0),
+            }),
+            reported: 0,
+        };
+        monitor.register();
+        monitor
+    }
+
+    pub fn stats(&self) -> AudioStats {
+        AudioStats {
+            buffer_samples: self.state.samples.load(Ordering::Relaxed) as i32,
+            callbacks: self.state.callbacks.load(Ordering::Relaxed),
+            underruns: self.state.underruns.load(Ordering::Relaxed),
+            worst_gap: self.state.worst.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state.underruns.store(0, Ordering::Relaxed);
+        self.state.worst.store(0, Ordering::Relaxed);
+        self.state.last.store(0, Ordering::Relaxed);
+        self.reported = 0;
+    }
+
+    // Loaded chunks stay valid since the format doesn't change; playing sounds are stopped.
+    // The rest of the mixer state is restored by `SDLApp::set_audio_buffer_samples`.
+    pub(crate) fn reopen(&mut self, samples: i32) -> Result<(), String> {
+        if !audio::is_available() {
+            return Err("No audio device is open".to_string());
+        }
+        let channels = sdl2::mixer::allocate_channels(-1);
+        sdl2::mixer::Music::halt();
+        sdl2::mixer::Channel::all().halt();
+        sdl2::mixer::close_audio();
+        sdl2::mixer::open_audio(audio::SAMPLE_RATE, AUDIO_S16LSB, DEFAULT_CHANNELS, samples)?;
+        sdl2::mixer::allocate_channels(channels);
+
+        self.reset();
+        self.register();
+        Ok(())
+    }
+
+    // Underruns seen since the last call.
+    pub(crate) fn new_underruns(&mut self) -> u64 {
+        let total = self.state.underruns.load(Ordering::Relaxed);
+        let new = total.saturating_sub(self.reported);
+        self.reported = total;
+        new
+    }
+
+    fn register(&self) {
+        let data = Arc::into_raw(self.state.clone()) as *mut c_void;
+        let result = unsafe {
+            Mix_RegisterEffect(POST_MIX, Some(monitor_callback), Some(monitor_done), data)
+        };
+        if result == 0 {
+            unsafe { drop(Arc::from_raw(data as *const MonitorState)) };
+            log::warn!("Could not register audio monitor: {}", sdl2::get_error());
+        }
+    }
+}
+
+unsafe extern "C" fn monitor_callback(
+    _channel: i32,
+    _stream: *mut c_void,
+    length: i32,
+    data: *mut c_void,
+) {
+    let state = &*(data as *const MonitorState);
+    // Post-mix buffers are signed 16 bit, interleaved across the output channels.
+    let samples = length.max(0) as u64 / (2 * DEFAULT_CHANNELS as u64);
+    state.samples.store(samples, Ordering::Relaxed);
+
+    let now = state.started.elapsed().as_micros() as u64;
+    let last = state.last.swap(now, Ordering::Relaxed);
+    state.callbacks.fetch_add(1, Ordering::Relaxed);
+
+    if last == 0 {
+        return;
+    }
+
+    let gap = now.saturating_sub(last);
+    state.worst.fetch_max(gap, Ordering::Relaxed);
+    let expected = samples * 1_000_000 / audio::SAMPLE_RATE as u64;
+    if gap > expected * 2 {
+        state.underruns.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe extern "C" fn monitor_done(_channel: i32, data: *mut c_void) {
+    drop(Arc::from_raw(data as *const MonitorState));
+}
+
+impl Default for AudioMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,102 @@
+use stagehand::loading::Ticket;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl Anchor {
+    // Top left corner of a `size` box placed against this anchor inside `bounds`.
+    pub fn place(&self, bounds: (u32, u32), size: (u32, u32), offset: (i32, i32)) -> (i32, i32) {
+        let (bw, bh) = (bounds.0 as i32, bounds.1 as i32);
+        let (w, h) = (size.0 as i32, size.1 as i32);
+        let (x, y) = match self {
+            Anchor::TopLeft => (offset.0, offset.1),
+            Anchor::TopRight => (bw - w - offset.0, offset.1),
+            Anchor::BottomLeft => (offset.0, bh - h - offset.1),
+            Anchor::BottomRight => (bw - w - offset.0, bh - h - offset.1),
+            Anchor::Center => ((bw - w) / 2 + offset.0, (bh - h) / 2 + offset.1),
+        };
+        (x, y)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Watermark {
+    pub texture: Ticket,
+    pub anchor: Anchor,
+    pub offset: (i32, i32),
+    pub scale: f32,
+    pub opacity: u8,
+    pub visible: bool,
+}
+
+impl Watermark {
+    pub fn new(texture: Ticket, anchor: Anchor) -> Self {
+        Watermark {
+            texture,
+            anchor,
+            offset: (8, 8),
+            scale: 1.0,
+            opacity: 255,
+            visible: true,
+        }
+    }
+}
+
+pub struct Overlays {
+    pub enabled: bool,
+    watermarks: Vec<Option<Watermark>>,
+}
+
+impl Overlays {
+    pub fn new() -> Self {
+        Overlays {
+            enabled: true,
+            watermarks: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, watermark: Watermark) -> usize {
+        self.watermarks.push(Some(watermark));
+        self.watermarks.len() - 1
+    }
+
+    // Indices of other watermarks stay valid after a removal.
+    pub fn remove(&mut self, index: usize) -> Option<Watermark> {
+        self.watermarks.get_mut(index).and_then(|w| w.take())
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Watermark> {
+        self.watermarks.get_mut(index).and_then(|w| w.as_mut())
+    }
+
+    pub fn set_visible(&mut self, index: usize, visible: bool) {
+        if let Some(w) = self.get_mut(index) {
+            w.visible = visible;
+        }
+    }
+
+    pub fn visible(&self) -> Vec<Watermark> {
+        match self.enabled {
+            true => self
+                .watermarks
+                .iter()
+                .flatten()
+                .filter(|w| w.visible)
+                .copied()
+                .collect(),
+            false => Vec::new(),
+        }
+    }
+}
+
+impl Default for Overlays {
+    fn default() -> Self {
+        Self::new()
+    }
+}
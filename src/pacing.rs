@@ -0,0 +1,59 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacingMode {
+    // Updates per second regardless of the display.
+    Fixed(u32),
+    // The refresh rate of the display the window is on, divided by `divisor`, so a 144Hz
+    // monitor can run at 144 or 72 and a 60Hz one at 60 or 30.
+    Display { divisor: u32 },
+}
+
+// The update rate is only followed by FrameStepper with `follow_pacing` set. stagehand's
+// `gameloop` takes its rate as an argument and never reads this, so games on that loop
+// have to pass `target_rate()` themselves. The frame cap in FrameSkip does use the budget.
+pub struct FramePacing {
+    pub mode: PacingMode,
+    // Used while the display doesn't report a rate.
+    pub fallback: u32,
+    refresh: Option<u32>,
+}
+
+impl FramePacing {
+    pub fn new() -> Self {
+        FramePacing {
+            mode: PacingMode::Fixed(60),
+            fallback: 60,
+            refresh: None,
+        }
+    }
+
+    pub fn refresh_rate(&self) -> Option<u32> {
+        self.refresh
+    }
+
+    pub fn target_rate(&self) -> u32 {
+        match self.mode {
+            PacingMode::Fixed(rate) => rate.max(1),
+            PacingMode::Display { divisor } => {
+                (self.refresh.unwrap_or(self.fallback) / divisor.max(1)).max(1)
+            }
+        }
+    }
+
+    pub fn frame_budget(&self) -> f64 {
+        1000.0 / self.target_rate() as f64
+    }
+
+    // Returns true when the rate changed, for example after the window moved displays.
+    pub(crate) fn set_refresh_rate(&mut self, refresh: Option<u32>) -> bool {
+        let refresh = refresh.filter(|r| *r > 0);
+        let changed = refresh != self.refresh;
+        self.refresh = refresh;
+        changed
+    }
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
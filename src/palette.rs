@@ -0,0 +1,47 @@
+use sdl2::{pixels::Color, pixels::PixelFormatEnum, surface::Surface};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    pub swaps: Vec<(Color, Color)>,
+    // Per channel difference still treated as a match, for art with slight compression noise.
+    pub tolerance: u8,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn swap(mut self, from: Color, to: Color) -> Self {
+        self.swaps.push((from, to));
+        self
+    }
+
+    fn replacement(&self, r: u8, g: u8, b: u8) -> Option<Color> {
+        self.swaps
+            .iter()
+            .find(|(from, _)| {
+                from.r.abs_diff(r) <= self.tolerance
+                    && from.g.abs_diff(g) <= self.tolerance
+                    && from.b.abs_diff(b) <= self.tolerance
+            })
+            .map(|(_, to)| *to)
+    }
+}
+
+// Alpha of the source pixel is kept, so outlines and soft edges survive the swap.
+pub fn recolor_surface(surface: &Surface, palette: &Palette) -> Result<Surface<'static>, String> {
+    let mut converted = surface.convert_format(PixelFormatEnum::RGBA32)?;
+
+    converted.with_lock_mut(|pixels| {
+        for pixel in pixels.chunks_mut(4) {
+            if let Some(c) = palette.replacement(pixel[0], pixel[1], pixel[2]) {
+                pixel[0] = c.r;
+                pixel[1] = c.g;
+                pixel[2] = c.b;
+            }
+        }
+    });
+
+    Ok(converted)
+}
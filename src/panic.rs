@@ -0,0 +1,99 @@
+use std::{
+    ffi::CString,
+    panic,
+    sync::atomic::{AtomicU32, Ordering},
+    thread,
+};
+
+use sdl2::sys::{
+    mixer::{Mix_HaltChannel, Mix_HaltMusic},
+    SDL_GetWindowFromID, SDL_MessageBoxFlags, SDL_SetRelativeMouseMode, SDL_SetWindowFullscreen,
+    SDL_SetWindowGrab, SDL_ShowCursor, SDL_ShowSimpleMessageBox, SDL_bool, SDL_ENABLE,
+};
+
+// The hook can't hold the window, so it finds it again by id. SDL is only touched for
+// panics on the thread that installed it; other threads, which may be caught with
+// `catch_unwind` or a `JoinHandle`, only get the previous hook.
+static WINDOW_ID: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PanicOptions {
+    // Shown before aborting, None skips the message box.
+    pub message_title: Option<String>,
+    // Aborts once the hook is done instead of unwinding, so a half torn down app can't
+    // keep the display captured. Only panics on the thread that installed the hook abort.
+    pub abort: bool,
+}
+
+impl PanicOptions {
+    pub fn new() -> Self {
+        PanicOptions {
+            message_title: Some("Unexpected error".to_string()),
+            abort: false,
+        }
+    }
+}
+
+impl Default for PanicOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn install(window_id: u32, options: PanicOptions) {
+    WINDOW_ID.store(window_id, Ordering::Relaxed);
+    let main = thread::current().id();
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        // The default hook still prints the message and backtrace.
+        previous(info);
+        if thread::current().id() != main {
+            return;
+        }
+
+        let window = unsafe { SDL_GetWindowFromID(WINDOW_ID.load(Ordering::Relaxed)) };
+        unsafe {
+            if !window.is_null() {
+                SDL_SetWindowFullscreen(window, 0);
+                SDL_SetWindowGrab(window, SDL_bool::SDL_FALSE);
+            }
+            SDL_SetRelativeMouseMode(SDL_bool::SDL_FALSE);
+            SDL_ShowCursor(SDL_ENABLE as i32);
+            // Audio is only halted; closing it would free the device under chunks and
+            // music the storage still owns.
+            Mix_HaltMusic();
+            Mix_HaltChannel(-1);
+        }
+
+        if let Some(title) = &options.message_title {
+            let text = match info.payload().downcast_ref::<&str>() {
+                Some(s) => s.to_string(),
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(s) => s.clone(),
+                    None => "The game stopped unexpectedly.".to_string(),
+                },
+            };
+            let text = match info.location() {
+                Some(l) => format!("{}\n\n{}:{}", text, l.file(), l.line()),
+                None => text,
+            };
+
+            // Interior nul bytes would fail the conversion, so they're dropped.
+            let title = CString::new(title.replace('\0', "")).unwrap_or_default();
+            let text = CString::new(text.replace('\0', "")).unwrap_or_default();
+            unsafe {
+                SDL_ShowSimpleMessageBox(
+                    SDL_MessageBoxFlags::SDL_MESSAGEBOX_ERROR as u32,
+                    title.as_ptr(),
+                    text.as_ptr(),
+                    window,
+                );
+            }
+        }
+
+        if options.abort {
+            std::process::abort();
+        }
+    }));
+}
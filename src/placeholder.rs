@@ -0,0 +1,31 @@
+use sdl2::{pixels::Color, pixels::PixelFormatEnum, rect::Rect, surface::Surface};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MissingAssets {
+    pub textures: u64,
+    pub fonts: u64,
+    pub sounds: u64,
+    pub music: u64,
+}
+
+pub const PLACEHOLDER_SIZE: u32 = 32;
+
+pub fn checkerboard_surface() -> Result<Surface<'static>, String> {
+    let mut surface = Surface::new(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, PixelFormatEnum::RGBA32)?;
+    let cell = PLACEHOLDER_SIZE / 4;
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let color = match (x + y) % 2 {
+                0 => Color::RGB(255, 0, 255),
+                _ => Color::RGB(0, 0, 0),
+            };
+            surface.fill_rect(
+                Rect::new((x * cell) as i32, (y * cell) as i32, cell, cell),
+                color,
+            )?;
+        }
+    }
+
+    Ok(surface)
+}
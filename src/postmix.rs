@@ -0,0 +1,182 @@
+use std::{
+    os::raw::{c_int, c_void},
+    slice,
+    sync::{Arc, Mutex},
+};
+
+use log::warn;
+use sdl2::{
+    mixer::{AUDIO_S16LSB, DEFAULT_CHANNELS},
+    sys::mixer::Mix_SetPostMix,
+};
+
+use crate::{
+    audio,
+    visualizer::{Analyzer, AudioLevels},
+};
+
+// Runs on the audio thread with the final mix, signed 16 bit and interleaved across the
+// output channels. Samples can be changed in place, for a limiter or similar, and anything
+// returned reaches scenes on the next update. Keep it short: the device waits on it.
+pub type PostMixCallback = Box<dyn FnMut(&mut [i16]) -> Option<Vec<f32>> + Send>;
+
+struct PostMixShared {
+    callback: Mutex<Option<PostMixCallback>>,
+    results: Mutex<Option<Vec<f32>>>,
+    analyzer: Mutex<Option<Analyzer>>,
+    // Written in place by the audio thread, the flag says there's something new.
+    levels: Mutex<(AudioLevels, bool)>,
+}
+
+// Custom processing of the final mix, after every channel and post-mix effect.
+pub struct PostMix {
+    shared: Arc<PostMixShared>,
+    registered: bool,
+    results: Option<Vec<f32>>,
+    fresh: bool,
+    levels: AudioLevels,
+}
+
+impl PostMix {
+    pub fn new() -> Self {
+        PostMix {
+            shared: Arc::new(PostMixShared {
+                callback: Mutex::new(None),
+                results: Mutex::new(None),
+                analyzer: Mutex::new(None),
+                levels: Mutex::new((AudioLevels::default(), false)),
+            }),
+            registered: false,
+            results: None,
+            fresh: false,
+            levels: AudioLevels::default(),
+        }
+    }
+
+    pub fn set_callback(&mut self, callback: Option<PostMixCallback>) {
+        if let Ok(mut c) = self.shared.callback.lock() {
+            *c = callback;
+        }
+        self.register();
+    }
+
+    fn register(&mut self) {
+        if !self.registered {
+            let data = Arc::into_raw(self.shared.clone()) as *mut c_void;
+            unsafe { Mix_SetPostMix(Some(post_mix_callback), data) };
+            self.registered = true;
+        }
+    }
+
+    // Points a reopened device back at the data from the first registration, which is
+    // still alive.
+    pub(crate) fn reapply(&mut self) {
+        if self.registered {
+            let data = Arc::as_ptr(&self.shared) as *mut c_void;
+            unsafe { Mix_SetPostMix(Some(post_mix_callback), data) };
+        }
+    }
+
+    // Latest results the callback returned.
+    pub fn results(&self) -> Option<&[f32]> {
+        self.results.as_deref()
+    }
+
+    // True only on the update the results arrived.
+    pub fn has_new_results(&self) -> bool {
+        self.fresh
+    }
+
+    // The visualizer measures the mix before the callback changes it.
+    // The mixer may have opened the device at another rate or channel count than asked for.
+    pub(crate) fn set_analysis(&mut self, bands: Option<usize>) {
+        let (rate, channels) = match sdl2::mixer::query_spec() {
+            Ok((_, format, _)) if format != AUDIO_S16LSB => {
+                warn!(
+                    "Audio visualizer needs 16 bit samples, the device has {:#x}",
+                    format
+                );
+                return;
+            }
+            Ok((rate, _, channels)) => (rate, channels.max(1) as usize),
+            Err(_) => (audio::SAMPLE_RATE, DEFAULT_CHANNELS as usize),
+        };
+
+        if let Ok(mut a) = self.shared.analyzer.lock() {
+            *a = bands.map(|b| Analyzer::new(b, rate, channels));
+        }
+        if let Ok(mut l) = self.shared.levels.lock() {
+            l.0.bands = Vec::with_capacity(bands.unwrap_or(0));
+            l.1 = false;
+        }
+        if bands.is_some() {
+            self.register();
+        }
+    }
+
+    // Copied out into a buffer kept here, so neither side allocates once the bands are sized.
+    pub(crate) fn levels(&mut self) -> Option<&AudioLevels> {
+        let mut shared = self.shared.levels.lock().ok()?;
+        if !shared.1 {
+            return None;
+        }
+        shared.1 = false;
+        self.levels.clone_from(&shared.0);
+        Some(&self.levels)
+    }
+
+    pub(crate) fn collect(&mut self) {
+        let results = self.shared.results.lock().ok().and_then(|mut r| r.take());
+        self.fresh = results.is_some();
+        if results.is_some() {
+            self.results = results;
+        }
+    }
+}
+
+impl Drop for PostMix {
+    fn drop(&mut self) {
+        if self.registered {
+            // SDL_mixer holds the audio lock while swapping, so the callback is done with
+            // the data once this returns.
+            unsafe {
+                Mix_SetPostMix(None, std::ptr::null_mut());
+                drop(Arc::from_raw(Arc::as_ptr(&self.shared)));
+            }
+        }
+    }
+}
+
+impl Default for PostMix {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe extern "C" fn post_mix_callback(data: *mut c_void, stream: *mut u8, length: c_int) {
+    let shared = &*(data as *const PostMixShared);
+    let samples = slice::from_raw_parts_mut(stream as *mut i16, length.max(0) as usize / 2);
+
+    // Skipped rather than waited on while the main thread holds either lock.
+    if let Ok(mut analyzer) = shared.analyzer.try_lock() {
+        if let (Some(analyzer), Ok(mut levels)) = (analyzer.as_mut(), shared.levels.try_lock()) {
+            analyzer.analyze(samples, &mut levels.0);
+            levels.1 = true;
+        }
+    }
+
+    let results = match shared.callback.try_lock() {
+        Ok(mut callback) => match callback.as_mut() {
+            Some(callback) => callback(samples),
+            None => None,
+        },
+        // Being replaced, this buffer goes through untouched.
+        Err(_) => None,
+    };
+
+    if let Some(results) = results {
+        if let Ok(mut r) = shared.results.lock() {
+            *r = Some(results);
+        }
+    }
+}
@@ -0,0 +1,613 @@
+use std::{cell::RefCell, collections::HashMap, ops::Range, rc::Rc};
+
+use log::{error, warn};
+use sdl2::{
+    pixels::Color,
+    rect::{FRect, Rect},
+    render::Texture,
+    sys::SDL_RenderGeometry,
+};
+use stagehand::{
+    draw::{Draw, DrawBatch, DrawType},
+    loading::{ResourceError, Ticket},
+};
+
+use crate::{
+    anchor::ScreenAnchor,
+    config::ScaleQuality,
+    coords::YAxis,
+    custom::{CustomDraw, CustomDrawContext},
+    filtering,
+    framedump::{self, DumpedDraw},
+    keyed::KeyedDraw,
+    loading::AssetKind,
+    repeated::{self, DrawRepeated},
+    richtext::{self, TextSpan},
+    trail::TrailConfig,
+    SDLApp,
+};
+
+pub(crate) struct PreparedDraw<'t> {
+    texture: Rc<RefCell<Texture<'t>>>,
+    // Where the texture sits when it was packed into an atlas page.
+    region: Option<Rect>,
+    batch: usize,
+    index: usize,
+    tint: Option<Color>,
+    scale: (f32, f32),
+    anchor: Option<ScreenAnchor>,
+    filter: Option<ScaleQuality>,
+}
+
+pub(crate) struct PreparedBatch {
+    batch: usize,
+    trail: Option<TrailConfig>,
+    draws: Range<usize>,
+}
+
+// Growth of the reused frame command buffers only. Sorting, text rendering and the rest of
+// a frame still allocate, so this doesn't show a frame is free of heap allocations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameAllocations {
+    pub draw_capacity: usize,
+    pub batch_capacity: usize,
+    // Times a command buffer had to grow.
+    pub last_frame: usize,
+    pub total: u64,
+}
+
+// Flat list of everything a frame draws, with tickets already resolved to textures. Kept
+// between frames so its buffers are reused.
+pub(crate) struct FrameCommands<'t> {
+    batches: Vec<PreparedBatch>,
+    draws: Vec<PreparedDraw<'t>>,
+    textures: HashMap<Ticket, (Rc<RefCell<Texture<'t>>>, Option<Rect>)>,
+    allocations: FrameAllocations,
+}
+
+impl<'t> FrameCommands<'t> {
+    pub(crate) fn new() -> Self {
+        FrameCommands {
+            batches: Vec::new(),
+            draws: Vec::new(),
+            textures: HashMap::new(),
+            allocations: FrameAllocations::default(),
+        }
+    }
+
+    pub(crate) fn reserve(&mut self, draws: usize, batches: usize) {
+        self.draws.reserve(draws.saturating_sub(self.draws.len()));
+        self.batches
+            .reserve(batches.saturating_sub(self.batches.len()));
+        self.textures
+            .reserve(draws.saturating_sub(self.textures.len()));
+    }
+
+    pub(crate) fn allocations(&self) -> FrameAllocations {
+        FrameAllocations {
+            draw_capacity: self.draws.capacity(),
+            batch_capacity: self.batches.capacity(),
+            ..self.allocations
+        }
+    }
+
+    pub(crate) fn draw_count(&self) -> usize {
+        self.draws.len()
+    }
+
+    fn push_draw(&mut self, draw: PreparedDraw<'t>) {
+        if self.draws.len() == self.draws.capacity() {
+            self.allocations.last_frame += 1;
+            self.allocations.total += 1;
+        }
+        self.draws.push(draw);
+    }
+
+    fn push_batch(&mut self, batch: PreparedBatch) {
+        if self.batches.len() == self.batches.capacity() {
+            self.allocations.last_frame += 1;
+            self.allocations.total += 1;
+        }
+        self.batches.push(batch);
+    }
+
+    // Capacity is kept, so the next frame reuses the same buffers.
+    pub(crate) fn clear(&mut self) {
+        self.batches.clear();
+        self.draws.clear();
+        self.textures.clear();
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.allocations.last_frame = 0;
+    }
+}
+
+impl<'t> Default for FrameCommands<'t> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
+    // Both passes run on the main thread, one after the other; this is not a worker-thread
+    // pipeline. Resolving tickets hands out `Rc` textures and SDL's renderer belongs to the
+    // thread that created it, so nothing here can move to another thread. The split only
+    // leaves submission with nothing to do besides copying.
+    pub(crate) fn prepare_frame(
+        &mut self,
+        batches: &[DrawBatch<Draw, ()>],
+        commands: &mut FrameCommands<'a>,
+    ) {
+        let control = self.batch_control.clone();
+        let control = control.borrow();
+        let mut order = control.resolve(batches.len());
+        control.sort_batches(&mut order, batches);
+        let mut sorted = Vec::new();
+        let tints = self.tints.clone();
+        let trails = self.trails.clone();
+        let scales = self.scales.clone();
+        let anchors = self.anchors.clone();
+        let filters = self.filters.clone();
+        let (tints, trails, scales) = (tints.borrow(), trails.borrow(), scales.borrow());
+        let (anchors, filters) = (anchors.borrow(), filters.borrow());
+        commands.begin();
+        self.text_cache.begin_frame();
+        let dumping = self.frame_dump.borrow().is_capturing();
+
+        for batch_index in order {
+            let start = commands.draws.len();
+            let tint = tints.batch(batch_index);
+
+            let instructions = &batches[batch_index].instructions;
+            let is_sorted = control.sort_draws(batch_index, instructions, &mut sorted);
+
+            for i in 0..instructions.len() {
+                let index = match is_sorted {
+                    true => sorted[i],
+                    false => i,
+                };
+                let draw = &instructions[index];
+                let resolved = self.resolve_draw(draw, &mut commands.textures);
+                if dumping {
+                    self.dump_draw(batch_index, index, draw, resolved.as_ref().err().copied());
+                }
+                let (texture, region) = match resolved {
+                    Ok(t) => t,
+                    Err(_) => continue,
+                };
+
+                commands.push_draw(PreparedDraw {
+                    texture,
+                    region,
+                    batch: batch_index,
+                    index,
+                    tint,
+                    scale: scales.get(batch_index, index),
+                    anchor: anchors.get(batch_index, index),
+                    filter: match filters.is_empty() {
+                        true => None,
+                        false => filters.get(draw.ticket, batch_index),
+                    },
+                });
+            }
+
+            commands.push_batch(PreparedBatch {
+                batch: batch_index,
+                trail: trails.batch(batch_index),
+                draws: start..commands.draws.len(),
+            });
+        }
+    }
+
+    pub(crate) fn submit_frame(
+        &mut self,
+        batches: &[DrawBatch<Draw, ()>],
+        commands: &FrameCommands<'a>,
+    ) {
+        let repeated = self.repeated.borrow_mut().take();
+        let custom = self.custom_draws.borrow_mut().take();
+        let keyed = self.keyed.borrow_mut().take_draws();
+        for prepared in commands.batches.iter() {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!(
+                "batch",
+                index = prepared.batch,
+                draws = prepared.draws.len()
+            )
+            .entered();
+            let trailing = match prepared.trail {
+                Some(t) => self.begin_trail(prepared.batch, t),
+                None => false,
+            };
+
+            for draw in commands.draws[prepared.draws.clone()].iter() {
+                let instruction = &batches[draw.batch].instructions[draw.index];
+                let data = &instruction.data;
+                if self.frame_dump.borrow().is_capturing() {
+                    self.frame_dump.borrow_mut().select(draw.batch, draw.index);
+                }
+                let previous = draw
+                    .filter
+                    .and_then(|f| filtering::set_scale_mode(&draw.texture.borrow(), f));
+                self.render_texture(
+                    draw.texture.clone(),
+                    draw.region,
+                    data,
+                    draw.tint,
+                    draw.scale,
+                    draw.anchor,
+                );
+                if let Some(previous) = previous {
+                    filtering::set_scale_mode(&draw.texture.borrow(), previous);
+                }
+            }
+
+            if trailing {
+                self.end_trail(prepared.batch);
+            }
+
+            for draw in repeated
+                .iter()
+                .filter(|r| r.after_batch == Some(prepared.batch))
+            {
+                self.render_repeated(draw);
+            }
+            for draw in custom
+                .iter()
+                .filter(|c| c.after_batch == Some(prepared.batch))
+            {
+                self.render_custom(draw);
+            }
+            for draw in keyed
+                .iter()
+                .filter(|k| k.after_batch() == Some(prepared.batch))
+            {
+                self.render_keyed(draw);
+            }
+        }
+
+        for draw in repeated.iter().filter(|r| r.after_batch.is_none()) {
+            self.render_repeated(draw);
+        }
+        for draw in custom.iter().filter(|c| c.after_batch.is_none()) {
+            self.render_custom(draw);
+        }
+        for draw in keyed.iter().filter(|k| k.after_batch().is_none()) {
+            self.render_keyed(draw);
+        }
+
+        self.evict_idle_trails();
+    }
+
+    fn render_keyed(&mut self, draw: &KeyedDraw) {
+        let (kind, key) = match draw {
+            KeyedDraw::Texture(key, ..) => (AssetKind::Texture, key),
+            KeyedDraw::Text(font, ..) => (AssetKind::Font, font),
+        };
+        let ticket = self
+            .keyed
+            .borrow_mut()
+            .resolve(&self.storage.borrow(), kind, key);
+        let ticket = match ticket {
+            Some(t) => t,
+            None => return,
+        };
+
+        match draw {
+            KeyedDraw::Texture(_, data, _) => {
+                let texture = self.storage.borrow().textures.get_by_ticket(ticket);
+                match texture {
+                    Ok(t) => self.render_texture(t, None, data, None, (1.0, 1.0), None),
+                    Err(e) => ResourceError::log_failure(e),
+                }
+            }
+            KeyedDraw::Text(_, text, color, data, _) => {
+                if let Some(t) = self.cached_text(ticket, text, *color) {
+                    self.render_texture(t, None, data, None, (1.0, 1.0), None);
+                }
+            }
+        }
+    }
+
+    fn render_custom(&mut self, draw: &CustomDraw) {
+        let mut callback = match self.custom_draws.borrow_mut().take_callback(&draw.kind) {
+            Some(c) => c,
+            None => return,
+        };
+
+        let coordinates = self.coordinates.clone();
+        let coordinates = coordinates.borrow();
+        let mut context = CustomDrawContext {
+            canvas: &mut self.canvas,
+            coordinates: &coordinates,
+            offset: (self.draw_offset.0 as f32, self.draw_offset.1 as f32),
+        };
+        callback(&mut context, draw.payload.as_ref());
+
+        self.custom_draws
+            .borrow_mut()
+            .restore_callback(&draw.kind, callback);
+    }
+
+    fn render_repeated(&mut self, draw: &DrawRepeated) {
+        let atlas = self.storage.borrow().atlas_region(draw.ticket);
+        let (texture, region) = match atlas {
+            Some((page, rect)) => (page, Some(rect)),
+            None => match self.storage.borrow().textures.get_by_ticket(draw.ticket) {
+                Ok(t) => (t, None),
+                Err(e) => {
+                    ResourceError::log_failure(e);
+                    self.missing.textures += 1;
+                    return;
+                }
+            },
+        };
+
+        let tex = texture.borrow();
+        let query = tex.query();
+        let page = (query.width.max(1) as f32, query.height.max(1) as f32);
+        let region = region.unwrap_or_else(|| Rect::new(0, 0, query.width, query.height));
+        let (sx, sy, sw, sh) = match &draw.source {
+            Some(r) => (
+                region.x() as f32 + r.x,
+                region.y() as f32 + r.y,
+                r.width,
+                r.height,
+            ),
+            None => (
+                region.x() as f32,
+                region.y() as f32,
+                region.width() as f32,
+                region.height() as f32,
+            ),
+        };
+        let uv = (
+            sx / page.0,
+            sy / page.1,
+            (sx + sw) / page.0,
+            (sy + sh) / page.1,
+        );
+
+        let coordinates = self.coordinates.clone();
+        let coordinates = coordinates.borrow();
+        let world = coordinates.pixels_per_unit.is_some();
+        let (w, h) = draw.size.unwrap_or((sw, sh));
+        let (w, h) = match world {
+            true => (w * coordinates.unit_scale(), h * coordinates.unit_scale()),
+            false => (w, h),
+        };
+        let y_up = coordinates.y_axis == YAxis::Up;
+        let offset = (self.draw_offset.0 as f32, self.draw_offset.1 as f32);
+        let color = draw.tint.unwrap_or(Color::RGBA(255, 255, 255, 255));
+
+        let mut vertices = Vec::with_capacity(draw.positions.len() * 4);
+        let mut indices = Vec::with_capacity(draw.positions.len() * 6);
+        for &(x, y) in draw.positions.iter() {
+            let (x, y) = match world {
+                true => coordinates.world_to_logical(x, y),
+                false => (x, y),
+            };
+            // Y-up positions are bottom-left corners.
+            let y = match y_up {
+                true => coordinates.flip_y(y) - h,
+                false => y,
+            };
+            let dest = FRect::new(x + offset.0, y + offset.1, w, h);
+            if self.culling && !self.is_visible(&dest, 0.0, (0.0, 0.0)) {
+                self.culled += 1;
+                continue;
+            }
+            repeated::push_quad(&mut vertices, &mut indices, dest, uv, color);
+        }
+        if vertices.is_empty() {
+            return;
+        }
+
+        let result = unsafe {
+            SDL_RenderGeometry(
+                self.canvas.raw(),
+                tex.raw(),
+                vertices.as_ptr(),
+                vertices.len() as i32,
+                indices.as_ptr(),
+                indices.len() as i32,
+            )
+        };
+        if result != 0 {
+            warn!("Repeated draw failed: {}", sdl2::get_error());
+        }
+        // Keeps the texture borrowed until SDL is done with it.
+        drop(tex);
+    }
+
+    fn dump_draw(&self, batch: usize, index: usize, draw: &Draw, dropped: Option<&'static str>) {
+        let storage = self.storage.borrow();
+        let (kind, text) = match &draw.draw_type {
+            DrawType::Texture => (AssetKind::Texture, None),
+            DrawType::Text(s, _) => (AssetKind::Font, Some(s.clone())),
+        };
+
+        self.frame_dump.borrow_mut().push(DumpedDraw {
+            batch,
+            index,
+            key: storage.ticket_key(kind, draw.ticket).map(|k| k.to_string()),
+            text,
+            data: framedump::data_json(&draw.data),
+            dropped,
+            result: None,
+        });
+    }
+
+    // Sizes the draw buffers up front so the first frames don't grow them.
+    pub fn reserve_draws(&mut self, draws: usize, batches: usize) {
+        self.commands.reserve(draws, batches);
+    }
+
+    pub fn frame_allocations(&self) -> FrameAllocations {
+        self.commands.allocations()
+    }
+
+    // Errors say why the draw is dropped, for frame dumps.
+    fn resolve_draw(
+        &mut self,
+        draw: &Draw,
+        cache: &mut HashMap<Ticket, (Rc<RefCell<Texture<'a>>>, Option<Rect>)>,
+    ) -> Result<(Rc<RefCell<Texture<'a>>>, Option<Rect>), &'static str> {
+        match &draw.draw_type {
+            DrawType::Texture => {
+                if let Some(t) = cache.get(&draw.ticket) {
+                    return Ok(t.clone());
+                }
+
+                if let Some((page, rect)) = self.storage.borrow().atlas_region(draw.ticket) {
+                    cache.insert(draw.ticket, (page.clone(), Some(rect)));
+                    return Ok((page, Some(rect)));
+                }
+
+                let texture = self.storage.borrow().textures.get_by_ticket(draw.ticket);
+                match texture {
+                    Ok(t) => {
+                        cache.insert(draw.ticket, (t.clone(), None));
+                        Ok((t, None))
+                    }
+                    Err(e) => {
+                        ResourceError::log_failure(e);
+                        self.missing.textures += 1;
+                        self.placeholder_texture()
+                            .map(|t| (t, None))
+                            .ok_or("unresolved ticket and no placeholder texture")
+                    }
+                }
+            }
+            DrawType::Text(s, c) => {
+                let color = crate::to_color(c);
+                let visible = match self.reveals.borrow().visible(draw.ticket, s) {
+                    Some(0) => return Err("reveal at 0 characters"),
+                    v => v,
+                };
+
+                let visible = match visible {
+                    Some(v) => v,
+                    None => {
+                        return self
+                            .cached_text(draw.ticket, s, color)
+                            .map(|t| (t, None))
+                            .ok_or("text could not be rendered");
+                    }
+                };
+
+                let cached = self
+                    .reveal_cache
+                    .get(&draw.ticket)
+                    .and_then(|t| t.get(s.as_str()));
+                if let Some((count, cached, texture)) = cached {
+                    if *count == visible && *cached == color {
+                        return Ok((texture.clone(), None));
+                    }
+                }
+
+                let texture = self
+                    .render_text(draw.ticket, s, color, Some(visible))
+                    .ok_or("text could not be rendered")?;
+                self.reveal_cache
+                    .entry(draw.ticket)
+                    .or_default()
+                    .insert(s.clone(), (visible, color, texture.clone()));
+                Ok((texture, None))
+            }
+        }
+    }
+
+    fn cached_text(
+        &mut self,
+        ticket: Ticket,
+        text: &str,
+        color: Color,
+    ) -> Option<Rc<RefCell<Texture<'a>>>> {
+        if let Some(t) = self.text_cache.get(ticket, text, color) {
+            return Some(t);
+        }
+        let texture = self.render_text(ticket, text, color, None)?;
+        // Stand ins for a missing font aren't kept, the font may still load.
+        if self.storage.borrow().fonts.get_by_ticket(ticket).is_ok() {
+            self.text_cache
+                .insert(ticket, text, color, texture.clone(), false);
+        }
+        Some(texture)
+    }
+
+    // `limit` cuts the text after that many visible characters, markup not counted.
+    pub(crate) fn render_text(
+        &mut self,
+        ticket: Ticket,
+        text: &str,
+        color: Color,
+        limit: Option<usize>,
+    ) -> Option<Rc<RefCell<Texture<'a>>>> {
+        let mut spans = match self.rich_text && richtext::has_markup(text) {
+            true => richtext::parse(text, color),
+            false => vec![TextSpan {
+                text: text.to_string(),
+                color,
+                bold: false,
+            }],
+        };
+        if let Some(limit) = limit {
+            richtext::truncate(&mut spans, limit);
+        }
+
+        let font = self.storage.borrow().fonts.get_by_ticket(ticket);
+        let font = match font {
+            Ok(f) => f,
+            Err(e) => {
+                ResourceError::log_failure(e);
+                self.missing.fonts += 1;
+                let plain: String = spans.iter().map(|s| s.text.as_str()).collect();
+                return match self.fallback_text_texture(&plain, color) {
+                    Ok(t) => Some(Rc::new(RefCell::new(t))),
+                    Err(_) => self.placeholder_texture(),
+                };
+            }
+        };
+
+        let mut glyphs = self.glyphs.borrow_mut();
+        if glyphs.check_on_render {
+            for span in spans.iter() {
+                glyphs.check(ticket, &font.borrow(), &span.text);
+            }
+        }
+        drop(glyphs);
+
+        let tabular = self.tabular_fonts.contains(&ticket) && richtext::has_digits(text);
+        let mode = self.text_mode(ticket);
+        let surface = match spans.as_slice() {
+            [] => return None,
+            [span] if !span.bold && !tabular => mode.render(&font.borrow(), &span.text, span.color),
+            _ => richtext::render_spans(&mut font.borrow_mut(), &spans, tabular, mode),
+        };
+        let surface = match surface {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Error rendering font, using the built in font: {}", e);
+                let plain: String = spans.iter().map(|s| s.text.as_str()).collect();
+                return self
+                    .fallback_text_texture(&plain, color)
+                    .ok()
+                    .map(|t| Rc::new(RefCell::new(t)));
+            }
+        };
+
+        match self
+            .texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())
+        {
+            Ok(t) => Some(Rc::new(RefCell::new(t))),
+            Err(e) => {
+                error!("Error transferring text surface to texture: {}", e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use sdl2::{
+    controller::Button,
+    event::Event,
+    keyboard::{Keycode, Scancode},
+    mouse::MouseButton,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputSampling {
+    // Digital bindings read what is held when input is polled.
+    State,
+    // A press since the last poll also counts, even when it was released again before the
+    // poll, so taps shorter than a frame aren't lost at low frame rates.
+    Events,
+}
+
+// Key and button presses queued between updates, for `InputSampling::Events`. Polls in
+// frames that run no update keep adding to the queue.
+pub struct PressQueue {
+    pub sampling: InputSampling,
+    keys: HashSet<Scancode>,
+    keycodes: HashSet<Keycode>,
+    mouse: HashSet<MouseButton>,
+    buttons: HashSet<(u32, Button)>,
+    consumed: bool,
+}
+
+impl PressQueue {
+    pub fn new() -> Self {
+        PressQueue {
+            sampling: InputSampling::State,
+            keys: HashSet::new(),
+            keycodes: HashSet::new(),
+            mouse: HashSet::new(),
+            buttons: HashSet::new(),
+            consumed: true,
+        }
+    }
+
+    pub(crate) fn begin(&mut self) {
+        if !self.consumed {
+            return;
+        }
+        self.keys.clear();
+        self.keycodes.clear();
+        self.mouse.clear();
+        self.buttons.clear();
+        self.consumed = false;
+    }
+
+    pub(crate) fn consume(&mut self) {
+        self.consumed = true;
+    }
+
+    pub(crate) fn record(&mut self, event: &Event) {
+        if self.sampling == InputSampling::State {
+            return;
+        }
+
+        match event {
+            Event::KeyDown {
+                scancode,
+                keycode,
+                repeat: false,
+                ..
+            } => {
+                if let Some(s) = scancode {
+                    self.keys.insert(*s);
+                }
+                if let Some(k) = keycode {
+                    self.keycodes.insert(*k);
+                }
+            }
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                self.mouse.insert(*mouse_btn);
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.buttons.insert((*which, *button));
+            }
+            _ => (),
+        }
+    }
+
+    pub(crate) fn key(&self, key: Scancode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    pub(crate) fn keycode(&self, key: Keycode) -> bool {
+        self.keycodes.contains(&key)
+    }
+
+    pub(crate) fn mouse(&self, button: MouseButton) -> bool {
+        self.mouse.contains(&button)
+    }
+
+    pub(crate) fn button(&self, which: u32, button: Button) -> bool {
+        self.buttons.contains(&(which, button))
+    }
+}
+
+impl Default for PressQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use sdl2::TimerSubsystem;
+use stagehand::loading::Ticket;
+
+pub struct Profiler {
+    pub overlay: bool,
+    pub font: Option<Ticket>,
+
+    timer: TimerSubsystem,
+    open: HashMap<String, u64>,
+    current: Vec<(String, f64)>,
+    last: Vec<(String, f64)>,
+}
+
+impl Profiler {
+    pub fn new(timer: TimerSubsystem) -> Self {
+        Profiler {
+            overlay: false,
+            font: None,
+
+            timer,
+            open: HashMap::new(),
+            current: Vec::new(),
+            last: Vec::new(),
+        }
+    }
+
+    pub fn begin(&mut self, name: &str) {
+        self.open
+            .insert(name.to_string(), self.timer.performance_counter());
+    }
+
+    pub fn end(&mut self, name: &str) {
+        let start = match self.open.remove(name) {
+            Some(s) => s,
+            None => return,
+        };
+
+        let elapsed = self.timer.performance_counter().saturating_sub(start) as f64 * 1000.0
+            / self.timer.performance_frequency() as f64;
+
+        match self.current.iter_mut().find(|(n, _)| n == name) {
+            Some((_, total)) => *total += elapsed,
+            None => self.current.push((name.to_string(), elapsed)),
+        }
+    }
+
+    pub fn scope<T, F: FnOnce() -> T>(&mut self, name: &str, f: F) -> T {
+        self.begin(name);
+        let result = f();
+        self.end(name);
+        result
+    }
+
+    // Milliseconds per scope for the last completed frame, in first-seen order.
+    pub fn last_frame(&self) -> &[(String, f64)] {
+        &self.last
+    }
+
+    pub(crate) fn next_frame(&mut self) {
+        self.last = std::mem::take(&mut self.current);
+    }
+}
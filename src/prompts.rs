@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use sdl2::{
+    controller::{Axis, GameController},
+    rect::Rect,
+    sys::{SDL_GameControllerFromInstanceID, SDL_GameControllerGetType, SDL_GameControllerType},
+};
+use stagehand::loading::Ticket;
+
+use crate::input::{SDLCommand, SDLGamepadFeature};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PromptFamily {
+    Xbox,
+    PlayStation,
+    Switch,
+    KeyboardMouse,
+}
+
+impl PromptFamily {
+    pub fn from_controller(controller: &GameController) -> Self {
+        let kind = unsafe {
+            let raw = SDL_GameControllerFromInstanceID(controller.instance_id() as i32);
+            match raw.is_null() {
+                true => SDL_GameControllerType::SDL_CONTROLLER_TYPE_UNKNOWN,
+                false => SDL_GameControllerGetType(raw),
+            }
+        };
+
+        match kind {
+            SDL_GameControllerType::SDL_CONTROLLER_TYPE_PS3
+            | SDL_GameControllerType::SDL_CONTROLLER_TYPE_PS4
+            | SDL_GameControllerType::SDL_CONTROLLER_TYPE_PS5 => PromptFamily::PlayStation,
+            SDL_GameControllerType::SDL_CONTROLLER_TYPE_NINTENDO_SWITCH_PRO => PromptFamily::Switch,
+            _ => {
+                // Joy-Cons report their own types on newer SDL versions only.
+                let name = controller.name().to_lowercase();
+                match name.contains("nintendo") || name.contains("joy-con") {
+                    true => PromptFamily::Switch,
+                    false => PromptFamily::Xbox,
+                }
+            }
+        }
+    }
+
+    // File name prefix in a prompt pack.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            PromptFamily::Xbox => "xbox",
+            PromptFamily::PlayStation => "ps",
+            PromptFamily::Switch => "switch",
+            PromptFamily::KeyboardMouse => "kbm",
+        }
+    }
+
+    // Splits a pack file stem like `ps_cross` into its family and input name.
+    pub fn from_name(name: &str) -> Option<(Self, &str)> {
+        let (prefix, input) = name.split_once('_')?;
+        [
+            PromptFamily::Xbox,
+            PromptFamily::PlayStation,
+            PromptFamily::Switch,
+            PromptFamily::KeyboardMouse,
+        ]
+        .into_iter()
+        .find(|f| f.prefix().eq_ignore_ascii_case(prefix))
+        .map(|f| (f, input))
+    }
+
+    fn is_gamepad(&self) -> bool {
+        *self != PromptFamily::KeyboardMouse
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PromptIcon {
+    pub ticket: Ticket,
+    // Set for icons cut from a shared sheet.
+    pub source: Option<Rect>,
+}
+
+// Icons are named `<family>_<input>`, for example `xbox_a`, `ps_dpup`, `kbm_space` or
+// `kbm_mouse_left`. Inputs use SDL's names: controller buttons and axes as SDL spells
+// them in mappings, keys by their scancode name in lower case with spaces as underscores.
+pub struct PromptPack {
+    icons: HashMap<(PromptFamily, String), PromptIcon>,
+    // Gamepad families missing an icon borrow it from this one.
+    pub fallback: PromptFamily,
+}
+
+impl PromptPack {
+    pub fn new() -> Self {
+        PromptPack {
+            icons: HashMap::new(),
+            fallback: PromptFamily::Xbox,
+        }
+    }
+
+    pub fn insert(&mut self, family: PromptFamily, input: &str, icon: PromptIcon) {
+        self.icons.insert((family, input.to_lowercase()), icon);
+    }
+
+    // Takes a file stem like `ps_cross`; returns false when the prefix isn't a family.
+    pub fn insert_named(&mut self, name: &str, icon: PromptIcon) -> bool {
+        match PromptFamily::from_name(name) {
+            Some((family, input)) => {
+                self.insert(family, input, icon);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.icons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.icons.is_empty()
+    }
+
+    pub fn icon(&self, family: PromptFamily, input: &str) -> Option<PromptIcon> {
+        let input = input.to_lowercase();
+        self.icons
+            .get(&(family, input.clone()))
+            .copied()
+            .or_else(|| match family.is_gamepad() && family != self.fallback {
+                true => self.icons.get(&(self.fallback, input)).copied(),
+                false => None,
+            })
+    }
+
+    // Icon for the first input of a command that belongs to `family`'s kind of device.
+    pub fn prompt(&self, command: &SDLCommand, family: PromptFamily) -> Option<PromptIcon> {
+        let input = input_name(command, family.is_gamepad())?;
+        self.icon(family, &input)
+    }
+
+    // First command among an action's bindings with an icon for `family`.
+    pub fn prompt_any(&self, commands: &[SDLCommand], family: PromptFamily) -> Option<PromptIcon> {
+        commands.iter().find_map(|c| self.prompt(c, family))
+    }
+}
+
+impl Default for PromptPack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn input_name(command: &SDLCommand, gamepad: bool) -> Option<String> {
+    match (command, gamepad) {
+        (SDLCommand::Key(chord), false) => chord.items().first().map(|k| key_name(k.name())),
+        (SDLCommand::KeyCode(chord, _), false) => {
+            chord.items().first().map(|k| key_name(&k.name()))
+        }
+        (SDLCommand::MouseButton(chord), false) => chord
+            .items()
+            .first()
+            .map(|b| format!("mouse_{:?}", b).to_lowercase()),
+        (SDLCommand::MultiClick(button, _), false) => {
+            Some(format!("mouse_{:?}", button).to_lowercase())
+        }
+        (SDLCommand::MouseWheel, false) => Some("mouse_wheel".to_string()),
+        (SDLCommand::Gamepad(feature, _), true) => match feature {
+            SDLGamepadFeature::Button(chord) => chord.items().first().map(|b| b.string()),
+            SDLGamepadFeature::Axis(axis) => Some(axis.string()),
+            SDLGamepadFeature::Stick(Axis::RightX, _) => Some("rightstick".to_string()),
+            SDLGamepadFeature::Stick(..) => Some("leftstick".to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn key_name(name: &str) -> String {
+    name.to_lowercase().replace(' ', "_")
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+// splitmix64, small and fully reproducible from its single word of state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RngStream {
+    state: u64,
+}
+
+impl RngStream {
+    pub fn new(seed: u64) -> Self {
+        RngStream { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // Inclusive of `min`, exclusive of `max`. An empty range, `max <= min`, gives `min`.
+    pub fn range(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+        // Spans up to the whole i64 range fit in a u64.
+        let span = max.abs_diff(min);
+        min.wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+// Public so it can go into save files; `streams` holds each key's current state, sorted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RandomSnapshot {
+    pub seed: u64,
+    pub streams: Vec<(String, u64)>,
+}
+
+pub struct RandomService {
+    seed: u64,
+    streams: HashMap<String, RngStream>,
+}
+
+impl RandomService {
+    pub fn new(seed: u64) -> Self {
+        RandomService {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    // Each key gets its own sequence so one scene drawing more numbers doesn't shift another's.
+    pub fn stream(&mut self, key: &str) -> &mut RngStream {
+        let seed = self.seed ^ fnv1a(key);
+        self.streams
+            .entry(key.to_string())
+            .or_insert_with(|| RngStream::new(seed))
+    }
+
+    pub fn snapshot(&self) -> RandomSnapshot {
+        let mut streams: Vec<_> = self
+            .streams
+            .iter()
+            .map(|(k, s)| (k.clone(), s.state))
+            .collect();
+        streams.sort();
+
+        RandomSnapshot {
+            seed: self.seed,
+            streams,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &RandomSnapshot) {
+        self.seed = snapshot.seed;
+        self.streams = snapshot
+            .streams
+            .iter()
+            .map(|(k, state)| (k.clone(), RngStream::new(*state)))
+            .collect();
+    }
+}
+
+impl Default for RandomService {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+// Stable across runs and platforms, unlike the std hasher.
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
@@ -0,0 +1,256 @@
+use log::{error, warn};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::{self, JoinHandle},
+};
+
+// Frames waiting for the encoder. When it falls this far behind, new frames are dropped
+// and counted rather than queued without limit.
+const QUEUE_FRAMES: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordFormat {
+    Frames,
+    #[cfg(feature = "gif")]
+    Gif,
+}
+
+struct RecordedFrame {
+    width: u32,
+    height: u32,
+    ticks: u64,
+    pixels: Vec<u8>,
+}
+
+pub struct Recorder {
+    sender: Option<SyncSender<RecordedFrame>>,
+    worker: Option<JoinHandle<Result<(), String>>>,
+    // Encoders still writing out recordings that were stopped, joined once they finish.
+    finishing: Vec<JoinHandle<Result<(), String>>>,
+    every: u32,
+    counter: u32,
+    dropped: u64,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder {
+            sender: None,
+            worker: None,
+            finishing: Vec::new(),
+            every: 1,
+            counter: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    // True while a stopped recording is still being written out.
+    pub fn is_finishing(&self) -> bool {
+        !self.finishing.is_empty()
+    }
+
+    // Frames skipped since the last start because the encoder queue was full.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn start(&mut self, path: PathBuf, format: RecordFormat, every: u32) -> Result<(), String> {
+        self.stop();
+
+        // The encoder runs on its own thread, which emscripten builds without pthreads lack.
+        if cfg!(target_os = "emscripten") {
+            return Err("Recording is not supported in the browser".to_string());
+        }
+
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_FRAMES);
+        let worker = match format {
+            RecordFormat::Frames => {
+                fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+                thread::spawn(move || write_frames(path, receiver))
+            }
+            #[cfg(feature = "gif")]
+            RecordFormat::Gif => {
+                let file = File::create(&path).map_err(|e| e.to_string())?;
+                thread::spawn(move || write_gif(file, receiver))
+            }
+        };
+
+        self.sender = Some(sender);
+        self.worker = Some(worker);
+        self.every = every.max(1);
+        self.counter = 0;
+        self.dropped = 0;
+
+        Ok(())
+    }
+
+    // Closes the queue and leaves the encoder to finish in the background.
+    pub fn stop(&mut self) {
+        self.sender = None;
+        self.finishing.extend(self.worker.take());
+    }
+
+    pub(crate) fn poll(&mut self) {
+        let (done, running): (Vec<_>, Vec<_>) =
+            self.finishing.drain(..).partition(|w| w.is_finished());
+        self.finishing = running;
+
+        for worker in done {
+            finish(worker);
+        }
+    }
+
+    pub(crate) fn wants_frame(&mut self) -> bool {
+        if self.sender.is_none() {
+            return false;
+        }
+
+        let wanted = self.counter % self.every == 0;
+        self.counter = self.counter.wrapping_add(1);
+        wanted
+    }
+
+    // Pixels are tightly packed RGB24.
+    pub(crate) fn push(&mut self, width: u32, height: u32, ticks: u64, pixels: Vec<u8>) {
+        let sender = match &self.sender {
+            Some(s) => s,
+            None => return,
+        };
+
+        let frame = RecordedFrame {
+            width,
+            height,
+            ticks,
+            pixels,
+        };
+
+        match sender.try_send(frame) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Recording encoder stopped unexpectedly, ending recording");
+                self.stop();
+            }
+        }
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Waits for encoders so recordings aren't cut off on exit.
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.stop();
+
+        for worker in self.finishing.drain(..) {
+            finish(worker);
+        }
+    }
+}
+
+fn finish(worker: JoinHandle<Result<(), String>>) {
+    match worker.join() {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => error!("Recording failed: {}", e),
+        Err(_) => error!("Recording encoder thread panicked"),
+    }
+}
+
+fn write_frames(path: PathBuf, receiver: Receiver<RecordedFrame>) -> Result<(), String> {
+    for (index, frame) in receiver.iter().enumerate() {
+        let file = path.join(format!("frame_{:06}.ppm", index));
+        let result = File::create(&file).and_then(|f| {
+            let mut writer = BufWriter::new(f);
+            write!(writer, "P6\n{} {}\n255\n", frame.width, frame.height)?;
+            writer.write_all(&frame.pixels)?;
+            writer.flush()
+        });
+
+        if let Err(e) = result {
+            error!("Could not write recorded frame {}: {}", file.display(), e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "gif")]
+fn write_gif(file: File, receiver: Receiver<RecordedFrame>) -> Result<(), String> {
+    let mut file = Some(file);
+    let mut encoder = None;
+    let mut pending: Option<RecordedFrame> = None;
+
+    // A GIF has one canvas size, so frames captured after a window resize are skipped.
+    let mut size = None;
+    let mut warned = false;
+    let frames = receiver.iter().filter(|f| {
+        let (width, height) = *size.get_or_insert((f.width, f.height));
+        if (width, height) == (f.width, f.height) {
+            return true;
+        }
+
+        if !warned {
+            warn!(
+                "Recorded frame size changed from {}x{} to {}x{}, skipping resized frames",
+                width, height, f.width, f.height
+            );
+            warned = true;
+        }
+        false
+    });
+
+    for frame in frames.map(Some).chain(std::iter::once(None)) {
+        if let Some(previous) = pending.take() {
+            let (width, height) = match (
+                u16::try_from(previous.width),
+                u16::try_from(previous.height),
+            ) {
+                (Ok(w), Ok(h)) => (w, h),
+                _ => {
+                    return Err(format!(
+                        "{}x{} is too large for a GIF",
+                        previous.width, previous.height
+                    ))
+                }
+            };
+
+            if let Some(file) = file.take() {
+                let mut e = gif::Encoder::new(BufWriter::new(file), width, height, &[])
+                    .map_err(|e| format!("Could not start GIF encoder: {}", e))?;
+                if let Err(e) = e.set_repeat(gif::Repeat::Infinite) {
+                    warn!("Could not set GIF repeat: {}", e);
+                }
+                encoder = Some(e);
+            }
+
+            let delay = match &frame {
+                Some(f) => f.ticks.saturating_sub(previous.ticks),
+                None => 16,
+            };
+
+            let mut image = gif::Frame::from_rgb_speed(width, height, &previous.pixels, 10);
+            image.delay = (delay / 10).max(1) as u16;
+
+            if let Some(encoder) = &mut encoder {
+                encoder
+                    .write_frame(&image)
+                    .map_err(|e| format!("Could not encode GIF frame: {}", e))?;
+            }
+        }
+
+        pending = frame;
+    }
+
+    Ok(())
+}
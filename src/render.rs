@@ -0,0 +1,206 @@
+use sdl2::{
+    pixels::Color,
+    pixels::PixelFormatEnum,
+    rect::Rect,
+    render::{Canvas, Texture},
+    sys::{SDL_GetRenderTarget, SDL_RenderFlush, SDL_SetRenderTarget, SDL_WindowFlags},
+    video::{VideoSubsystem, Window},
+};
+use std::ptr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionMode {
+    Integer,
+    SubPixel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AngleUnit {
+    Degrees,
+    Radians,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PivotMode {
+    Normalized,
+    Absolute,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RotationConfig {
+    pub unit: AngleUnit,
+    pub pivot: PivotMode,
+}
+
+impl Default for RotationConfig {
+    fn default() -> Self {
+        RotationConfig {
+            unit: AngleUnit::Degrees,
+            pivot: PivotMode::Normalized,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClearMode {
+    Full(Color),
+    Region(Rect, Color),
+    Skip,
+}
+
+impl Default for ClearMode {
+    fn default() -> Self {
+        ClearMode::Full(Color::RGB(55, 55, 55))
+    }
+}
+
+impl ClearMode {
+    pub(crate) fn apply(&self, canvas: &mut Canvas<Window>) -> Result<(), String> {
+        match self {
+            ClearMode::Full(color) => {
+                canvas.set_draw_color(*color);
+                canvas.clear();
+                Ok(())
+            }
+            ClearMode::Region(rect, color) => {
+                let previous = canvas.blend_mode();
+                canvas.set_blend_mode(sdl2::render::BlendMode::None);
+                canvas.set_draw_color(*color);
+                let result = canvas.fill_rect(*rect);
+                canvas.set_blend_mode(previous);
+                result
+            }
+            ClearMode::Skip => Ok(()),
+        }
+    }
+}
+
+pub struct FrameContext<'f, 't> {
+    pub canvas: &'f mut Canvas<Window>,
+    pub frame: &'f mut Texture<'t>,
+}
+
+impl<'f, 't> FrameContext<'f, 't> {
+    pub fn video(&self) -> &VideoSubsystem {
+        self.canvas.window().subsystem()
+    }
+
+    pub fn gl_available(&self) -> bool {
+        self.canvas.window().window_flags() & SDL_WindowFlags::SDL_WINDOW_OPENGL as u32 != 0
+    }
+
+    // Must be called before issuing raw GL commands so SDL's queued draws land first.
+    pub fn flush(&mut self) {
+        unsafe {
+            SDL_RenderFlush(self.canvas.raw());
+        }
+    }
+
+    pub fn copy_frame(&mut self) -> Result<(), String> {
+        self.canvas.copy(self.frame, None, None)
+    }
+}
+
+pub trait PostProcess {
+    fn process(&mut self, context: &mut FrameContext);
+}
+
+impl<F> PostProcess for F
+where
+    F: FnMut(&mut FrameContext),
+{
+    fn process(&mut self, context: &mut FrameContext) {
+        self(context)
+    }
+}
+
+// Binding a target resets the renderer scale, so a downscaled frame target needs it again.
+pub(crate) fn bind_frame(
+    canvas: &mut Canvas<Window>,
+    frame: Option<&Texture>,
+    scale: Option<(f32, f32)>,
+) -> Result<(), String> {
+    set_target(canvas, frame)?;
+    if let (Some(_), Some((x, y))) = (frame, scale) {
+        canvas.set_scale(x, y)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn set_target(
+    canvas: &mut Canvas<Window>,
+    target: Option<&Texture>,
+) -> Result<(), String> {
+    let raw = match target {
+        Some(t) => t.raw(),
+        None => ptr::null_mut(),
+    };
+
+    if unsafe { SDL_SetRenderTarget(canvas.raw(), raw) } != 0 {
+        return Err(sdl2::get_error());
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PixelBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl PixelBuffer {
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let i = ((y * self.width + x) * 4) as usize;
+        Some(Color::RGBA(
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ))
+    }
+}
+
+pub fn read_canvas(canvas: &Canvas<Window>, rect: Option<Rect>) -> Result<PixelBuffer, String> {
+    let (width, height) = match rect {
+        Some(r) => (r.width(), r.height()),
+        None => canvas.output_size()?,
+    };
+
+    let pixels = canvas.read_pixels(rect, PixelFormatEnum::RGBA32)?;
+    Ok(PixelBuffer {
+        width,
+        height,
+        pixels,
+    })
+}
+
+// Only works on textures created with target access.
+pub fn read_texture(
+    canvas: &mut Canvas<Window>,
+    texture: &Texture,
+    rect: Option<Rect>,
+) -> Result<PixelBuffer, String> {
+    let previous = unsafe { SDL_GetRenderTarget(canvas.raw()) };
+
+    if unsafe { SDL_SetRenderTarget(canvas.raw(), texture.raw()) } != 0 {
+        return Err(sdl2::get_error());
+    }
+
+    let query = texture.query();
+    let result = match rect {
+        Some(r) => read_canvas(canvas, Some(r)),
+        None => read_canvas(canvas, Some(Rect::new(0, 0, query.width, query.height))),
+    };
+
+    unsafe {
+        SDL_SetRenderTarget(canvas.raw(), previous);
+    }
+
+    result
+}
@@ -0,0 +1,102 @@
+use sdl2::{
+    pixels::Color,
+    rect::FRect,
+    sys::{SDL_Color, SDL_FPoint, SDL_Vertex},
+};
+use stagehand::{draw::DrawRect, loading::Ticket};
+
+// One texture at many positions, for starfields, particles and bullet patterns. The
+// texture is resolved and borrowed once and every copy goes to the renderer in a single
+// geometry call, instead of paying per instruction for thousands of identical sprites.
+// Copies can't rotate or flip.
+pub struct DrawRepeated {
+    pub ticket: Ticket,
+    pub source: Option<DrawRect>,
+    // Size of each copy, the source or texture size when None.
+    pub size: Option<(f32, f32)>,
+    // Top-left corners in the same coordinates as regular draws.
+    pub positions: Vec<(f32, f32)>,
+    pub tint: Option<Color>,
+    // Drawn right after this batch, or above every batch when None. Skipped along with a
+    // hidden batch.
+    pub after_batch: Option<usize>,
+}
+
+impl DrawRepeated {
+    pub fn new(ticket: Ticket, positions: Vec<(f32, f32)>) -> Self {
+        DrawRepeated {
+            ticket,
+            source: None,
+            size: None,
+            positions,
+            tint: None,
+            after_batch: None,
+        }
+    }
+}
+
+// Queued for the next frame only, like keyed draws.
+pub struct RepeatedDraws {
+    draws: Vec<DrawRepeated>,
+}
+
+impl RepeatedDraws {
+    pub fn new() -> Self {
+        RepeatedDraws { draws: Vec::new() }
+    }
+
+    pub fn draw(&mut self, draw: DrawRepeated) {
+        self.draws.push(draw);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<DrawRepeated> {
+        std::mem::take(&mut self.draws)
+    }
+}
+
+impl Default for RepeatedDraws {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Two triangles covering `dest`, with texture coordinates `uv` as (left, top, right,
+// bottom) in 0..1.
+pub(crate) fn push_quad(
+    vertices: &mut Vec<SDL_Vertex>,
+    indices: &mut Vec<i32>,
+    dest: FRect,
+    uv: (f32, f32, f32, f32),
+    color: Color,
+) {
+    let base = vertices.len() as i32;
+    let color = SDL_Color {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+        a: color.a,
+    };
+    let corners = [
+        (dest.x(), dest.y(), uv.0, uv.1),
+        (dest.x() + dest.width(), dest.y(), uv.2, uv.1),
+        (
+            dest.x() + dest.width(),
+            dest.y() + dest.height(),
+            uv.2,
+            uv.3,
+        ),
+        (dest.x(), dest.y() + dest.height(), uv.0, uv.3),
+    ];
+    for (x, y, u, v) in corners {
+        vertices.push(SDL_Vertex {
+            position: SDL_FPoint { x, y },
+            color,
+            tex_coord: SDL_FPoint { x: u, y: v },
+        });
+    }
+    indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use sdl2::{
+    render::{Texture, TextureCreator},
+    video::WindowContext,
+};
+use stagehand::loading::Ticket;
+
+use crate::loading::{self, SDLStorage};
+
+// Textures belong to the creator that made them, so drawing a stored texture to a second
+// window needs a copy uploaded through that window's creator.
+pub struct TextureResidency<'r> {
+    creators: HashMap<u32, &'r TextureCreator<WindowContext>>,
+    copies: HashMap<(u32, Ticket), Texture<'r>>,
+}
+
+impl<'r> TextureResidency<'r> {
+    pub fn new() -> Self {
+        TextureResidency {
+            creators: HashMap::new(),
+            copies: HashMap::new(),
+        }
+    }
+
+    pub fn attach(&mut self, window_id: u32, creator: &'r TextureCreator<WindowContext>) {
+        self.detach(window_id);
+        self.creators.insert(window_id, creator);
+    }
+
+    pub fn detach(&mut self, window_id: u32) {
+        self.creators.remove(&window_id);
+        self.copies.retain(|(window, _), _| *window != window_id);
+    }
+
+    pub fn is_resident(&self, window_id: u32, ticket: Ticket) -> bool {
+        self.copies.contains_key(&(window_id, ticket))
+    }
+
+    // Uploads the texture for the window on first use and reuses the copy afterwards.
+    pub fn resident(
+        &mut self,
+        window_id: u32,
+        ticket: Ticket,
+        storage: &SDLStorage,
+    ) -> Result<&mut Texture<'r>, String> {
+        if !self.copies.contains_key(&(window_id, ticket)) {
+            let creator = self
+                .creators
+                .get(&window_id)
+                .ok_or_else(|| format!("Window {} has no attached texture creator", window_id))?;
+            let path = storage
+                .texture_source(ticket)
+                .ok_or_else(|| "Texture has no file source to re-upload from".to_string())?;
+            let texture = loading::load_texture_file(creator, &path)?;
+            self.copies.insert((window_id, ticket), texture);
+        }
+
+        self.copies
+            .get_mut(&(window_id, ticket))
+            .ok_or_else(|| "Texture copy missing".to_string())
+    }
+
+    // Drops every window's copy so the next draw re-uploads from the source.
+    pub fn invalidate(&mut self, ticket: Ticket) {
+        self.copies.retain(|(_, t), _| *t != ticket);
+    }
+
+    pub fn clear(&mut self) {
+        self.copies.clear();
+    }
+}
+
+impl<'r> Default for TextureResidency<'r> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use log::warn;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControllerPolicy {
+    First,
+    Strongest,
+    MostRecent,
+}
+
+// What a binding to a specific controller does while no pad is attached at that index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingController {
+    Inactive,
+    // Reads any attached controller through `policy` instead, so single-player games with
+    // pad-numbered bindings still work with whichever pad is plugged in.
+    AnyController,
+}
+
+pub struct ControllerResolution {
+    pub policy: ControllerPolicy,
+    pub missing: MissingController,
+    last_used: Option<usize>,
+    sources: HashMap<(usize, usize), usize>,
+    warned: RefCell<HashSet<usize>>,
+}
+
+impl ControllerResolution {
+    pub fn new() -> Self {
+        ControllerResolution {
+            policy: ControllerPolicy::First,
+            missing: MissingController::Inactive,
+            last_used: None,
+            sources: HashMap::new(),
+            warned: RefCell::new(HashSet::new()),
+        }
+    }
+
+    pub fn last_used(&self) -> Option<usize> {
+        self.last_used
+    }
+
+    // The controller that produced the action during the last input poll.
+    pub fn controller_for(&self, user_index: usize, action_index: usize) -> Option<usize> {
+        self.sources.get(&(user_index, action_index)).copied()
+    }
+
+    pub(crate) fn used(&mut self, controller: usize) {
+        self.last_used = Some(controller);
+    }
+
+    // Slots that are filled again get warned about afresh if they go missing later.
+    pub(crate) fn begin<T>(&mut self, controllers: &[Option<T>]) {
+        self.warned
+            .get_mut()
+            .retain(|i| controllers.get(*i).map_or(true, Option::is_none));
+    }
+
+    // Reported once for as long as the slot stays empty.
+    pub(crate) fn missing_index(&self, index: usize) {
+        if self.warned.borrow_mut().insert(index) {
+            warn!(
+                "Input is bound to controller {} but no controller is connected there",
+                index
+            );
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        controller: Option<usize>,
+    ) {
+        match controller {
+            Some(c) => self.sources.insert((user_index, action_index), c),
+            None => self.sources.remove(&(user_index, action_index)),
+        };
+    }
+}
+
+impl Default for ControllerResolution {
+    fn default() -> Self {
+        Self::new()
+    }
+}
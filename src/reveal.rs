@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use sdl2::pixels::Color;
+use stagehand::loading::Ticket;
+
+use crate::richtext;
+
+struct Reveal {
+    rate: f64,
+    elapsed: f64,
+    // Visible characters once done, which leaves out markup when rich text is on.
+    total: usize,
+    skipped: bool,
+}
+
+// Typewriter reveals for text draws. A registered font ticket and string pair draws only
+// the characters reached so far, advancing with each update's delta, and the partial
+// texture is only rebuilt when another character appears.
+pub struct TextReveals {
+    reveals: HashMap<Ticket, HashMap<String, Reveal>>,
+    markup: bool,
+}
+
+impl TextReveals {
+    pub fn new() -> Self {
+        TextReveals {
+            reveals: HashMap::new(),
+            markup: false,
+        }
+    }
+
+    // Restarts the reveal when the same text is started again.
+    pub fn start(&mut self, font: Ticket, text: &str, chars_per_second: f64) {
+        let total = visible_length(text, self.markup);
+        self.reveals.entry(font).or_default().insert(
+            text.to_string(),
+            Reveal {
+                rate: chars_per_second.max(0.0),
+                elapsed: 0.0,
+                total,
+                skipped: false,
+            },
+        );
+    }
+
+    pub fn skip(&mut self, font: Ticket, text: &str) {
+        if let Some(reveal) = self.reveals.get_mut(&font).and_then(|r| r.get_mut(text)) {
+            reveal.skipped = true;
+        }
+    }
+
+    pub fn remove(&mut self, font: Ticket, text: &str) {
+        if let Some(reveals) = self.reveals.get_mut(&font) {
+            reveals.remove(text);
+            if reveals.is_empty() {
+                self.reveals.remove(&font);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.reveals.clear();
+    }
+
+    // Characters shown right now, or None when the text isn't being revealed.
+    pub fn visible(&self, font: Ticket, text: &str) -> Option<usize> {
+        let reveal = self.reveals.get(&font)?.get(text)?;
+        if reveal.skipped {
+            return Some(reveal.total);
+        }
+        Some(((reveal.elapsed * reveal.rate) as usize).min(reveal.total))
+    }
+
+    pub fn is_complete(&self, font: Ticket, text: &str) -> bool {
+        match self.reveals.get(&font).and_then(|r| r.get(text)) {
+            Some(reveal) => self.visible(font, text) >= Some(reveal.total),
+            None => true,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.reveals.is_empty()
+    }
+
+    pub(crate) fn advance(&mut self, delta: f64) {
+        for reveal in self.reveals.values_mut().flat_map(|r| r.values_mut()) {
+            reveal.elapsed += delta;
+        }
+    }
+
+    // Follows the app's rich text setting, so markup is only left out of the count when
+    // it won't be drawn.
+    pub(crate) fn set_markup(&mut self, markup: bool) {
+        self.markup = markup;
+        for (text, reveal) in self.reveals.values_mut().flat_map(|r| r.iter_mut()) {
+            reveal.total = visible_length(text, markup);
+        }
+    }
+}
+
+impl Default for TextReveals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn visible_length(text: &str, markup: bool) -> usize {
+    match markup && richtext::has_markup(text) {
+        true => richtext::parse(text, Color::RGB(255, 255, 255))
+            .iter()
+            .map(|s| s.text.chars().count())
+            .sum(),
+        false => text.chars().count(),
+    }
+}
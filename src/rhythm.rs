@@ -0,0 +1,93 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tempo {
+    pub bpm: f64,
+    pub offset: f64,
+    pub beats_per_bar: u32,
+}
+
+pub struct BeatClock {
+    pub tempo: Option<Tempo>,
+    beat: Option<f64>,
+    crossed: Vec<u64>,
+    // Accessibility latency in seconds, taken off the beat when judging input.
+    latency: f64,
+}
+
+impl BeatClock {
+    pub fn new() -> Self {
+        BeatClock {
+            tempo: None,
+            beat: None,
+            crossed: Vec::new(),
+            latency: 0.0,
+        }
+    }
+
+    pub fn beat(&self) -> Option<f64> {
+        self.beat
+    }
+
+    pub fn bar(&self) -> Option<u64> {
+        let tempo = self.tempo?;
+        Some(self.beat?.max(0.0) as u64 / tempo.beats_per_bar.max(1) as u64)
+    }
+
+    // 0.0 exactly on the beat, approaching 1.0 right before the next one.
+    pub fn phase(&self) -> Option<f64> {
+        self.beat.map(|b| b - b.floor())
+    }
+
+    pub fn crossed_beats(&self) -> &[u64] {
+        &self.crossed
+    }
+
+    pub fn on_beat(&self) -> bool {
+        !self.crossed.is_empty()
+    }
+
+    // Distance in seconds to the nearest beat, for judging player input timing. The beat
+    // is taken as the player perceived it, late by the accessibility latency.
+    pub fn offset_from_beat(&self) -> Option<f64> {
+        let tempo = self.tempo?;
+        let seconds_per_beat = 60.0 / tempo.bpm;
+        let beat = self.beat? - self.latency / seconds_per_beat;
+        let phase = beat - beat.floor();
+        Some(match phase < 0.5 {
+            true => phase * seconds_per_beat,
+            false => (phase - 1.0) * seconds_per_beat,
+        })
+    }
+
+    pub(crate) fn update(&mut self, position: Option<f64>, latency: u64) {
+        self.crossed.clear();
+        self.latency = latency as f64 / 1000.0;
+
+        let (tempo, position) = match (self.tempo, position) {
+            (Some(t), Some(p)) if t.bpm > 0.0 => (t, p),
+            _ => {
+                self.beat = None;
+                return;
+            }
+        };
+
+        let beat = (position - tempo.offset) * tempo.bpm / 60.0;
+        if let Some(previous) = self.beat {
+            if beat > previous {
+                let first = previous.floor() as i64 + 1;
+                for b in first..=beat.floor() as i64 {
+                    if b >= 0 {
+                        self.crossed.push(b as u64);
+                    }
+                }
+            }
+        }
+
+        self.beat = Some(beat);
+    }
+}
+
+impl Default for BeatClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
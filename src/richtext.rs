@@ -0,0 +1,265 @@
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    render::BlendMode,
+    surface::Surface,
+    ttf::{Font, FontStyle},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    pub color: Color,
+    pub bold: bool,
+}
+
+// How SDL_ttf rasterizes text. Solid is fastest and unsmoothed, shaded antialiases against
+// a fixed background, which keeps small text crisp and suits text that changes every frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextRenderMode {
+    Solid,
+    Shaded(Color),
+    #[default]
+    Blended,
+}
+
+impl TextRenderMode {
+    pub fn render(
+        &self,
+        font: &Font,
+        text: &str,
+        color: Color,
+    ) -> Result<Surface<'static>, String> {
+        let partial = font.render(text);
+        let surface = match self {
+            TextRenderMode::Solid => partial.solid(color),
+            TextRenderMode::Shaded(background) => partial.shaded(color, *background),
+            TextRenderMode::Blended => partial.blended(color),
+        };
+        surface.map_err(|e| e.to_string())
+    }
+}
+
+// Markup is `[color=#rrggbb]...[/color]`, `[color=name]...[/color]` and `[b]...[/b]`, with
+// `[[` for a literal bracket. Tags nest; unknown tags are kept as text.
+pub fn has_markup(text: &str) -> bool {
+    text.contains('[')
+}
+
+pub fn parse(text: &str, base: Color) -> Vec<TextSpan> {
+    let mut spans: Vec<TextSpan> = Vec::new();
+    let mut colors = vec![base];
+    let mut bold = 0u32;
+    let mut current = String::new();
+    let mut rest = text;
+
+    let flush = |spans: &mut Vec<TextSpan>, current: &mut String, color: Color, bold: bool| {
+        if current.is_empty() {
+            return;
+        }
+        match spans.last_mut() {
+            Some(last) if last.color == color && last.bold == bold => last.text.push_str(current),
+            _ => spans.push(TextSpan {
+                text: current.clone(),
+                color,
+                bold,
+            }),
+        }
+        current.clear();
+    };
+
+    while let Some(open) = rest.find('[') {
+        current.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        if let Some(after) = rest.strip_prefix("[[") {
+            current.push('[');
+            rest = after;
+            continue;
+        }
+
+        let close = match rest.find(']') {
+            Some(c) => c,
+            None => break,
+        };
+        let tag = &rest[1..close];
+
+        let color = *colors.last().unwrap_or(&base);
+        let handled = if tag == "b" {
+            flush(&mut spans, &mut current, color, bold > 0);
+            bold += 1;
+            true
+        } else if tag == "/b" {
+            flush(&mut spans, &mut current, color, bold > 0);
+            bold = bold.saturating_sub(1);
+            true
+        } else if tag == "/color" {
+            flush(&mut spans, &mut current, color, bold > 0);
+            if colors.len() > 1 {
+                colors.pop();
+            }
+            true
+        } else if let Some(c) = tag.strip_prefix("color=").and_then(parse_color) {
+            flush(&mut spans, &mut current, color, bold > 0);
+            colors.push(c);
+            true
+        } else {
+            false
+        };
+
+        match handled {
+            true => rest = &rest[close + 1..],
+            false => {
+                current.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    current.push_str(rest);
+    let color = *colors.last().unwrap_or(&base);
+    flush(&mut spans, &mut current, color, bold > 0);
+    spans
+}
+
+// Keeps the first `count` characters across the spans.
+pub fn truncate(spans: &mut Vec<TextSpan>, count: usize) {
+    let mut remaining = count;
+    spans.retain_mut(|span| {
+        if remaining == 0 {
+            return false;
+        }
+        let length = span.text.chars().count();
+        if length > remaining {
+            span.text = span.text.chars().take(remaining).collect();
+        }
+        remaining -= length.min(remaining);
+        !span.text.is_empty()
+    });
+}
+
+pub fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Color::RGB(channel(0)?, channel(2)?, channel(4)?)),
+            8 => Some(Color::RGBA(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                channel(6)?,
+            )),
+            _ => None,
+        };
+    }
+
+    match value {
+        "white" => Some(Color::RGB(255, 255, 255)),
+        "black" => Some(Color::RGB(0, 0, 0)),
+        "gray" | "grey" => Some(Color::RGB(128, 128, 128)),
+        "red" => Some(Color::RGB(230, 60, 60)),
+        "green" => Some(Color::RGB(80, 200, 90)),
+        "blue" => Some(Color::RGB(80, 130, 240)),
+        "yellow" => Some(Color::RGB(240, 210, 60)),
+        "orange" => Some(Color::RGB(240, 140, 40)),
+        _ => None,
+    }
+}
+
+// Lays the spans out on one line in a single surface, so the whole string stays one draw.
+// With `tabular`, every digit sits centered in a cell as wide as the widest digit, so
+// counters don't shift sideways as their value changes.
+pub fn render_spans(
+    font: &mut Font,
+    spans: &[TextSpan],
+    tabular: bool,
+    mode: TextRenderMode,
+) -> Result<Surface<'static>, String> {
+    let style = font.get_style();
+    let mut pieces = Vec::new();
+    for span in spans.iter() {
+        font.set_style(match span.bold {
+            true => style | FontStyle::BOLD,
+            false => style,
+        });
+        let cell = match tabular {
+            true => digit_advance(font),
+            false => 0,
+        };
+
+        for segment in segments(&span.text, tabular) {
+            let digit = tabular && segment.bytes().all(|b| b.is_ascii_digit());
+            let surface = mode.render(font, segment, span.color).map(|s| {
+                let width = match digit {
+                    true => s.width().max(cell),
+                    false => s.width(),
+                };
+                (s, width)
+            });
+            pieces.push(surface);
+        }
+    }
+    font.set_style(style);
+
+    let mut surfaces = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        surfaces.push(piece?);
+    }
+
+    let width: u32 = surfaces.iter().map(|(_, w)| *w).sum();
+    let height = surfaces
+        .iter()
+        .map(|(s, _)| s.height())
+        .max()
+        .unwrap_or(font.height() as u32);
+
+    let mut target = Surface::new(width.max(1), height.max(1), PixelFormatEnum::RGBA32)?;
+    target.fill_rect(None, Color::RGBA(0, 0, 0, 0))?;
+
+    let mut x = 0;
+    for (surface, cell) in surfaces.iter_mut() {
+        // Copy alpha as is instead of blending against the transparent target.
+        surface.set_blend_mode(BlendMode::None)?;
+        let offset = (*cell - surface.width()) as i32 / 2;
+        let dest = Rect::new(x + offset, 0, surface.width(), surface.height());
+        surface.blit(None, &mut target, dest)?;
+        x += *cell as i32;
+    }
+
+    Ok(target)
+}
+
+pub fn has_digits(text: &str) -> bool {
+    text.bytes().any(|b| b.is_ascii_digit())
+}
+
+fn digit_advance(font: &Font) -> u32 {
+    ('0'..='9')
+        .filter_map(|c| font.find_glyph_metrics(c))
+        .map(|m| m.advance.max(0) as u32)
+        .max()
+        .unwrap_or(0)
+}
+
+// Runs of text, with every ASCII digit split out on its own when `digits` is set.
+fn segments(text: &str, digits: bool) -> Vec<&str> {
+    if !digits {
+        return vec![text];
+    }
+
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if c.is_ascii_digit() {
+            if start < i {
+                segments.push(&text[start..i]);
+            }
+            segments.push(&text[i..i + 1]);
+            start = i + 1;
+        }
+    }
+    if start < text.len() {
+        segments.push(&text[start..]);
+    }
+    segments
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RumbleSegment {
+    // Motor strengths from 0.0 to 1.0.
+    pub low: f32,
+    pub high: f32,
+    // Milliseconds.
+    pub duration: u64,
+    // Slides from the previous segment's strengths instead of jumping to these.
+    pub ramp: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RumblePattern {
+    segments: Vec<RumbleSegment>,
+    looping: bool,
+}
+
+impl RumblePattern {
+    pub fn new() -> Self {
+        RumblePattern {
+            segments: Vec::new(),
+            looping: false,
+        }
+    }
+
+    pub fn hold(mut self, low: f32, high: f32, duration: u64) -> Self {
+        self.segments.push(RumbleSegment {
+            low,
+            high,
+            duration,
+            ramp: false,
+        });
+        self
+    }
+
+    pub fn ramp(mut self, low: f32, high: f32, duration: u64) -> Self {
+        self.segments.push(RumbleSegment {
+            low,
+            high,
+            duration,
+            ramp: true,
+        });
+        self
+    }
+
+    pub fn pause(self, duration: u64) -> Self {
+        self.hold(0.0, 0.0, duration)
+    }
+
+    // Repeats until stopped by its handle.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn heartbeat() -> Self {
+        RumblePattern::new()
+            .hold(0.8, 0.2, 90)
+            .pause(110)
+            .hold(0.5, 0.1, 90)
+            .pause(700)
+            .looping()
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.segments.iter().map(|s| s.duration).sum()
+    }
+
+    // Strengths `elapsed` milliseconds in, None once a pattern that doesn't loop is over.
+    pub fn at(&self, elapsed: u64) -> Option<(f32, f32)> {
+        let total = self.duration();
+        if total == 0 {
+            return None;
+        }
+        let mut t = match (self.looping, elapsed >= total) {
+            (_, false) => elapsed,
+            (true, true) => elapsed % total,
+            (false, true) => return None,
+        };
+
+        // A looping ramp at the start slides from where the pattern ended.
+        let mut previous = self
+            .segments
+            .last()
+            .filter(|_| self.looping)
+            .map_or((0.0, 0.0), |s| (s.low, s.high));
+        for segment in self.segments.iter() {
+            if t < segment.duration {
+                if !segment.ramp {
+                    return Some((segment.low, segment.high));
+                }
+                let f = t as f32 / segment.duration as f32;
+                return Some((
+                    previous.0 + (segment.low - previous.0) * f,
+                    previous.1 + (segment.high - previous.1) * f,
+                ));
+            }
+            t -= segment.duration;
+            previous = (segment.low, segment.high);
+        }
+        Some(previous)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RumbleHandle(u64);
+
+struct PlayingPattern {
+    controller: usize,
+    pattern: RumblePattern,
+    start: Option<u64>,
+}
+
+// Rumble patterns advanced by the backend each update. Patterns on the same controller
+// combine by taking the strongest of each motor.
+pub struct Rumble {
+    pub enabled: bool,
+    // Scales every pattern, for players who want it softer.
+    pub strength: f32,
+    playing: HashMap<RumbleHandle, PlayingPattern>,
+    next: u64,
+    sent: HashMap<usize, (u16, u16, u64)>,
+}
+
+impl Rumble {
+    pub fn new() -> Self {
+        Rumble {
+            enabled: true,
+            strength: 1.0,
+            playing: HashMap::new(),
+            next: 0,
+            sent: HashMap::new(),
+        }
+    }
+
+    // Controllers are by player slot, see `SDLApp::controller_capabilities`. Starts on the
+    // next update.
+    pub fn play(&mut self, controller: usize, pattern: RumblePattern) -> RumbleHandle {
+        let handle = RumbleHandle(self.next);
+        self.next += 1;
+        self.playing.insert(
+            handle,
+            PlayingPattern {
+                controller,
+                pattern,
+                start: None,
+            },
+        );
+        handle
+    }
+
+    pub fn stop(&mut self, handle: RumbleHandle) {
+        self.playing.remove(&handle);
+    }
+
+    pub fn stop_all(&mut self) {
+        self.playing.clear();
+    }
+
+    pub fn is_playing(&self, handle: RumbleHandle) -> bool {
+        self.playing.contains_key(&handle)
+    }
+
+    // Motor values per controller that need sending: ones that changed, and steady ones
+    // again before the previous rumble call runs out after `refresh` milliseconds.
+    pub(crate) fn advance(&mut self, now: u64, refresh: u64) -> Vec<(usize, u16, u16)> {
+        let mut levels: HashMap<usize, (f32, f32)> = HashMap::new();
+        self.playing.retain(|_, p| {
+            let start = *p.start.get_or_insert(now);
+            match p.pattern.at(now - start) {
+                Some((low, high)) => {
+                    let level = levels.entry(p.controller).or_insert((0.0, 0.0));
+                    *level = (level.0.max(low), level.1.max(high));
+                    true
+                }
+                None => false,
+            }
+        });
+
+        let scale = match self.enabled {
+            true => self.strength.clamp(0.0, 1.0) * u16::MAX as f32,
+            false => 0.0,
+        };
+        // Controllers that went quiet get one last call to stop their motors.
+        for controller in self.sent.keys() {
+            levels.entry(*controller).or_insert((0.0, 0.0));
+        }
+
+        let mut send = Vec::new();
+        for (controller, (low, high)) in levels {
+            let (low, high) = (
+                (low.clamp(0.0, 1.0) * scale) as u16,
+                (high.clamp(0.0, 1.0) * scale) as u16,
+            );
+            let due = match self.sent.get(&controller) {
+                Some((l, h, at)) => (*l, *h) != (low, high) || now - at >= refresh / 2,
+                None => (low, high) != (0, 0),
+            };
+            if !due {
+                continue;
+            }
+
+            match (low, high) {
+                (0, 0) => self.sent.remove(&controller),
+                _ => self.sent.insert(controller, (low, high, now)),
+            };
+            send.push((controller, low, high));
+        }
+        send
+    }
+
+    // A controller that reconnects into the same slot starts quiet.
+    pub(crate) fn forget(&mut self, controller: usize) {
+        self.playing.retain(|_, p| p.controller != controller);
+        self.sent.remove(&controller);
+    }
+}
+
+impl Default for Rumble {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+// Scales for draws by batch slot, the instruction's index within its batch in the order
+// the stage returned them, so two draws of the same texture can differ.
+pub struct DrawScales {
+    draws: HashMap<(usize, usize), (f32, f32)>,
+    batches: HashMap<usize, (f32, f32)>,
+    pub global: (f32, f32),
+}
+
+impl DrawScales {
+    pub fn new() -> Self {
+        DrawScales {
+            draws: HashMap::new(),
+            batches: HashMap::new(),
+            global: (1.0, 1.0),
+        }
+    }
+
+    // Only applies to location destinations, explicit rects are drawn as given.
+    pub fn set(&mut self, batch_index: usize, index: usize, scale: Option<(f32, f32)>) {
+        match scale {
+            Some(s) => self.draws.insert((batch_index, index), s),
+            None => self.draws.remove(&(batch_index, index)),
+        };
+    }
+
+    pub fn set_batch(&mut self, batch_index: usize, scale: Option<(f32, f32)>) {
+        match scale {
+            Some(s) => self.batches.insert(batch_index, s),
+            None => self.batches.remove(&batch_index),
+        };
+    }
+
+    // A draw's own scale wins over its batch's.
+    pub fn get(&self, batch_index: usize, index: usize) -> (f32, f32) {
+        let (sx, sy) = self
+            .draws
+            .get(&(batch_index, index))
+            .or_else(|| self.batches.get(&batch_index))
+            .copied()
+            .unwrap_or((1.0, 1.0));
+        (sx * self.global.0, sy * self.global.1)
+    }
+
+    pub fn clear(&mut self) {
+        self.draws.clear();
+        self.batches.clear();
+    }
+}
+
+impl Default for DrawScales {
+    fn default() -> Self {
+        Self::new()
+    }
+}
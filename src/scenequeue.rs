@@ -0,0 +1,39 @@
+pub(crate) enum SceneRequest {
+    SetActive(String, bool),
+    Remove(String),
+}
+
+// Activation changes and removals asked for while scenes update, applied through SDLApp
+// once the stage update is over. Scenes switch each other through this rather than the
+// stage directly, so `scene_keys` and `is_scene_active` always match what the stage has.
+// Share it with scenes through their update content.
+pub struct SceneQueue {
+    pub(crate) requests: Vec<SceneRequest>,
+}
+
+impl SceneQueue {
+    pub fn new() -> Self {
+        SceneQueue {
+            requests: Vec::new(),
+        }
+    }
+
+    pub fn set_active(&mut self, key: &str, active: bool) {
+        self.requests
+            .push(SceneRequest::SetActive(key.to_string(), active));
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.requests.push(SceneRequest::Remove(key.to_string()));
+    }
+
+    pub fn pending(&self) -> usize {
+        self.requests.len()
+    }
+}
+
+impl Default for SceneQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
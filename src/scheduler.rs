@@ -0,0 +1,127 @@
+use stagehand::utility::UpdateInstruction;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TaskId(u64);
+
+enum Payload<Message> {
+    Instruction(UpdateInstruction),
+    // Repeating messages carry how to copy themselves for each delivery.
+    Message(Message, Option<fn(&Message) -> Message>),
+}
+
+struct Task<Message> {
+    id: TaskId,
+    payload: Payload<Message>,
+    due: Option<u64>,
+    delay: u64,
+    repeat: bool,
+}
+
+// Instructions run like ones returned from a scene update. Messages go to the message
+// inbox, where scenes read them on the update after they are due.
+pub struct Scheduler<Message> {
+    tasks: Vec<Task<Message>>,
+    next_id: u64,
+}
+
+impl<Message> Scheduler<Message> {
+    pub fn new() -> Self {
+        Scheduler {
+            tasks: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn after(&mut self, delay: u64, instruction: UpdateInstruction) -> TaskId {
+        self.push(delay, Payload::Instruction(instruction), false)
+    }
+
+    pub fn every(&mut self, interval: u64, instruction: UpdateInstruction) -> TaskId {
+        self.push(interval.max(1), Payload::Instruction(instruction), true)
+    }
+
+    pub fn message_after(&mut self, delay: u64, message: Message) -> TaskId {
+        self.push(delay, Payload::Message(message, None), false)
+    }
+
+    pub fn message_every(&mut self, interval: u64, message: Message) -> TaskId
+    where
+        Message: Clone,
+    {
+        let payload = Payload::Message(message, Some(Message::clone as fn(&Message) -> Message));
+        self.push(interval.max(1), payload, true)
+    }
+
+    pub fn cancel(&mut self, id: TaskId) -> bool {
+        let count = self.tasks.len();
+        self.tasks.retain(|t| t.id != id);
+        self.tasks.len() != count
+    }
+
+    pub fn clear(&mut self) {
+        self.tasks.clear();
+    }
+
+    pub fn is_scheduled(&self, id: TaskId) -> bool {
+        self.tasks.iter().any(|t| t.id == id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    fn push(&mut self, delay: u64, payload: Payload<Message>, repeat: bool) -> TaskId {
+        let id = TaskId(self.next_id);
+        self.next_id += 1;
+
+        self.tasks.push(Task {
+            id,
+            payload,
+            due: None,
+            delay,
+            repeat,
+        });
+
+        id
+    }
+
+    // Tasks are timed from the first update after they were scheduled.
+    pub(crate) fn run_due<F, G>(&mut self, now: u64, mut run: F, mut deliver: G)
+    where
+        F: FnMut(&UpdateInstruction),
+        G: FnMut(Message),
+    {
+        let tasks = std::mem::take(&mut self.tasks);
+        for mut task in tasks {
+            let due = *task.due.get_or_insert(now + task.delay);
+            if due > now {
+                self.tasks.push(task);
+                continue;
+            }
+
+            match task.payload {
+                Payload::Instruction(ref instruction) => run(instruction),
+                Payload::Message(ref message, Some(copy)) => deliver(copy(message)),
+                Payload::Message(message, None) => {
+                    deliver(message);
+                    continue;
+                }
+            }
+
+            if task.repeat {
+                task.due = Some(due + task.delay * ((now - due) / task.delay + 1));
+                self.tasks.push(task);
+            }
+        }
+    }
+}
+
+impl<Message> Default for Scheduler<Message> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,509 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::warn;
+use sdl2::{
+    controller::{Axis, Button},
+    keyboard::{Keycode, Mod, Scancode},
+    mouse::MouseButton,
+};
+
+use crate::{
+    audio::Bus,
+    input::{Chord, SDLCommand, SDLGamepadFeature},
+};
+
+// Stands in for the commands of an action the player unbound.
+const UNBOUND: &str = "-";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FullscreenSetting {
+    Windowed,
+    Desktop,
+    Exclusive {
+        width: u32,
+        height: u32,
+        refresh_rate: u32,
+    },
+}
+
+// Per-user settings most games offer in an options menu: the window, bus levels and
+// rebound actions, plus whatever else the game stores under its own keys. Persisted with
+// `SDLApp::persist_settings`, which puts everything back the way the player left it.
+pub struct Settings {
+    // Size of the window when not fullscreen.
+    pub window_size: Option<(u32, u32)>,
+    pub fullscreen: FullscreenSetting,
+    levels: HashMap<Bus, f32>,
+    bindings: HashMap<(usize, usize), Vec<SDLCommand>>,
+    pub(crate) defaults: HashMap<(usize, usize), Vec<SDLCommand>>,
+    values: HashMap<String, String>,
+    path: Option<PathBuf>,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings {
+            window_size: None,
+            fullscreen: FullscreenSetting::Windowed,
+            levels: HashMap::new(),
+            bindings: HashMap::new(),
+            defaults: HashMap::new(),
+            values: HashMap::new(),
+            path: None,
+        }
+    }
+
+    pub fn level(&self, bus: Bus) -> Option<f32> {
+        self.levels.get(&bus).copied()
+    }
+
+    pub fn set_level(&mut self, bus: Bus, level: f32) {
+        self.levels.insert(bus, level.clamp(0.0, 1.0));
+    }
+
+    // Commands the player bound to an action in place of the ones the game set up.
+    pub fn binding(&self, user_index: usize, action_index: usize) -> Option<&[SDLCommand]> {
+        self.bindings
+            .get(&(user_index, action_index))
+            .map(|b| &b[..])
+    }
+
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    // Tabs, line breaks and backslashes are escaped when saved.
+    pub fn set_value(&mut self, key: &str, value: Option<&str>) {
+        match value {
+            Some(v) => self.values.insert(key.to_string(), v.to_string()),
+            None => self.values.remove(key),
+        };
+    }
+
+    pub(crate) fn set_binding(
+        &mut self,
+        user_index: usize,
+        action_index: usize,
+        commands: Option<Vec<SDLCommand>>,
+    ) {
+        match commands {
+            Some(c) => self.bindings.insert((user_index, action_index), c),
+            None => self.bindings.remove(&(user_index, action_index)),
+        };
+    }
+
+    pub(crate) fn bindings(&self) -> impl Iterator<Item = (&(usize, usize), &Vec<SDLCommand>)> {
+        self.bindings.iter()
+    }
+
+    // Reads what was saved at `path`, if anything, and saves there from then on.
+    pub fn persist_to(&mut self, path: &Path) -> Result<(), String> {
+        self.path = Some(path.to_path_buf());
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.as_slice() {
+                ["window", w, h] => {
+                    if let (Ok(w), Ok(h)) = (w.parse(), h.parse()) {
+                        self.window_size = Some((w, h));
+                    }
+                }
+                ["fullscreen", "windowed"] => self.fullscreen = FullscreenSetting::Windowed,
+                ["fullscreen", "desktop"] => self.fullscreen = FullscreenSetting::Desktop,
+                ["fullscreen", "exclusive", w, h, r] => {
+                    if let (Ok(width), Ok(height), Ok(refresh_rate)) =
+                        (w.parse(), h.parse(), r.parse())
+                    {
+                        self.fullscreen = FullscreenSetting::Exclusive {
+                            width,
+                            height,
+                            refresh_rate,
+                        };
+                    }
+                }
+                ["level", bus, level] => {
+                    let bus = match *bus {
+                        "music" => Bus::Music,
+                        "sounds" => Bus::Sounds,
+                        _ => continue,
+                    };
+                    if let Ok(level) = level.parse() {
+                        self.set_level(bus, level);
+                    }
+                }
+                ["binding", user, action, commands @ ..] => {
+                    let (user, action) = match (user.parse(), action.parse()) {
+                        (Ok(u), Ok(a)) => (u, a),
+                        _ => continue,
+                    };
+                    // An unbound action is saved as a lone `-`. A binding that doesn't read
+                    // back whole is dropped, leaving the default.
+                    if let [UNBOUND] | [""] = commands {
+                        self.bindings.insert((user, action), Vec::new());
+                        continue;
+                    }
+                    match commands.iter().map(|c| command_from_str(c)).collect() {
+                        Some(commands) => {
+                            self.bindings.insert((user, action), commands);
+                        }
+                        None => warn!("Ignoring unreadable binding for action {}", action),
+                    }
+                }
+                ["value", key, value] => {
+                    self.values.insert(unescape(key), unescape(value));
+                }
+                _ => continue,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+
+        let mut text = String::new();
+        if let Some((w, h)) = self.window_size {
+            let _ = writeln!(text, "window\t{}\t{}", w, h);
+        }
+        let _ = match self.fullscreen {
+            FullscreenSetting::Windowed => writeln!(text, "fullscreen\twindowed"),
+            FullscreenSetting::Desktop => writeln!(text, "fullscreen\tdesktop"),
+            FullscreenSetting::Exclusive {
+                width,
+                height,
+                refresh_rate,
+            } => writeln!(
+                text,
+                "fullscreen\texclusive\t{}\t{}\t{}",
+                width, height, refresh_rate
+            ),
+        };
+        for (bus, name) in [(Bus::Music, "music"), (Bus::Sounds, "sounds")] {
+            if let Some(level) = self.level(bus) {
+                let _ = writeln!(text, "level\t{}\t{}", name, level);
+            }
+        }
+
+        let mut bindings: Vec<_> = self.bindings.iter().collect();
+        bindings.sort_by_key(|(k, _)| **k);
+        for ((user, action), commands) in bindings {
+            let encoded: Option<Vec<String>> = commands.iter().map(command_to_string).collect();
+            match encoded {
+                Some(c) if c.is_empty() => {
+                    let _ = writeln!(text, "binding\t{}\t{}\t{}", user, action, UNBOUND);
+                }
+                Some(c) => {
+                    let _ = writeln!(text, "binding\t{}\t{}\t{}", user, action, c.join("\t"));
+                }
+                None => warn!("Action {} has commands that can't be saved", action),
+            }
+        }
+
+        let mut values: Vec<_> = self.values.iter().collect();
+        values.sort();
+        for (key, value) in values {
+            let _ = writeln!(text, "value\t{}\t{}", escape(key), escape(value));
+        }
+
+        // Written beside the old file and renamed over it, so a crash mid-save can't
+        // leave the player with half their settings.
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, text).map_err(|e| e.to_string())?;
+        fs::rename(&temporary, path).map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn save_logged(&self) {
+        if let Err(e) = self.save() {
+            warn!("Could not save settings: {}", e);
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Keys are stored as SDL's numeric codes, since several key names contain commas.
+// Digital axes and sticks wrap each command they nest in parentheses.
+fn command_to_string(command: &SDLCommand) -> Option<String> {
+    let text = match command {
+        SDLCommand::Key(chord) => format!("key:{}", chord_to_string(chord, |k| *k as i32)),
+        SDLCommand::KeyCode(chord, mods) => format!(
+            "keycode:{}:{}",
+            chord_to_string(chord, |k| *k as i32),
+            mods.bits()
+        ),
+        SDLCommand::MouseButton(chord) => {
+            format!("mouse:{}", chord_to_string(chord, |b| *b as u8))
+        }
+        SDLCommand::MultiClick(button, clicks) => format!("clicks:{}:{}", *button as u8, clicks),
+        SDLCommand::MousePosition => "position".to_string(),
+        SDLCommand::MouseWheel => "wheel".to_string(),
+        SDLCommand::Gamepad(feature, index) => {
+            let feature = match feature {
+                SDLGamepadFeature::Button(chord) => {
+                    format!("button:{}", chord_to_string(chord, |b| b.string()))
+                }
+                SDLGamepadFeature::Axis(axis) => format!("axis:{}", axis.string()),
+                SDLGamepadFeature::Stick(x, y) => format!("stick:{},{}", x.string(), y.string()),
+                SDLGamepadFeature::Gyro { scale } => format!("gyro:{}", scale),
+                SDLGamepadFeature::Accelerometer { scale } => format!("accel:{}", scale),
+                SDLGamepadFeature::Touchpad(pad) => format!("touchpad:{}", pad),
+            };
+            let index = index.map_or("-".to_string(), |i| i.to_string());
+            format!("pad:{}:{}", index, feature)
+        }
+        SDLCommand::Touch(finger) => format!("touch:{}", finger),
+        SDLCommand::DigitalAxis { negative, positive } => format!(
+            "daxis:({})({})",
+            command_to_string(negative)?,
+            command_to_string(positive)?
+        ),
+        SDLCommand::DigitalStick {
+            up,
+            down,
+            left,
+            right,
+            normalize,
+        } => format!(
+            "dstick:{}:({})({})({})({})",
+            *normalize as u8,
+            command_to_string(up)?,
+            command_to_string(down)?,
+            command_to_string(left)?,
+            command_to_string(right)?
+        ),
+    };
+    Some(text)
+}
+
+fn command_from_str(text: &str) -> Option<SDLCommand> {
+    let (kind, rest) = text.split_once(':').unwrap_or((text, ""));
+    let command = match kind {
+        "key" => SDLCommand::Key(chord_from_str(rest, |k| {
+            Scancode::from_i32(k.parse().ok()?)
+        })?),
+        "keycode" => {
+            let (chord, mods) = rest.rsplit_once(':')?;
+            SDLCommand::KeyCode(
+                chord_from_str(chord, |k| Keycode::from_i32(k.parse().ok()?))?,
+                Mod::from_bits(mods.parse().ok()?)?,
+            )
+        }
+        "mouse" => SDLCommand::MouseButton(chord_from_str(rest, mouse_button)?),
+        "clicks" => {
+            let (button, clicks) = rest.split_once(':')?;
+            SDLCommand::MultiClick(mouse_button(button)?, clicks.parse().ok()?)
+        }
+        "position" => SDLCommand::MousePosition,
+        "wheel" => SDLCommand::MouseWheel,
+        "pad" => {
+            let (index, feature) = rest.split_once(':')?;
+            let index = match index {
+                "-" => None,
+                i => Some(i.parse().ok()?),
+            };
+            let (feature, value) = feature.split_once(':')?;
+            let feature = match feature {
+                "button" => SDLGamepadFeature::Button(chord_from_str(value, Button::from_string)?),
+                "axis" => SDLGamepadFeature::Axis(Axis::from_string(value)?),
+                "stick" => {
+                    let (x, y) = value.split_once(',')?;
+                    SDLGamepadFeature::Stick(Axis::from_string(x)?, Axis::from_string(y)?)
+                }
+                "gyro" => SDLGamepadFeature::Gyro {
+                    scale: value.parse().ok()?,
+                },
+                "accel" => SDLGamepadFeature::Accelerometer {
+                    scale: value.parse().ok()?,
+                },
+                "touchpad" => SDLGamepadFeature::Touchpad(value.parse().ok()?),
+                _ => return None,
+            };
+            SDLCommand::Gamepad(feature, index)
+        }
+        "touch" => SDLCommand::Touch(rest.parse().ok()?),
+        "daxis" => {
+            let [negative, positive]: [SDLCommand; 2] = nested_commands(rest)?.try_into().ok()?;
+            SDLCommand::DigitalAxis {
+                negative: Box::new(negative),
+                positive: Box::new(positive),
+            }
+        }
+        "dstick" => {
+            let (normalize, commands) = rest.split_once(':')?;
+            let [up, down, left, right]: [SDLCommand; 4] =
+                nested_commands(commands)?.try_into().ok()?;
+            SDLCommand::DigitalStick {
+                up: Box::new(up),
+                down: Box::new(down),
+                left: Box::new(left),
+                right: Box::new(right),
+                normalize: match normalize {
+                    "0" => false,
+                    "1" => true,
+                    _ => return None,
+                },
+            }
+        }
+        _ => return None,
+    };
+    Some(command)
+}
+
+// Reads a run of parenthesized commands, which may nest further.
+fn nested_commands(text: &str) -> Option<Vec<SDLCommand>> {
+    let mut commands = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => {
+                if depth == 0 {
+                    start = i + 1;
+                }
+                depth += 1;
+            }
+            ')' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    commands.push(command_from_str(&text[start..i])?);
+                }
+            }
+            _ if depth == 0 => return None,
+            _ => {}
+        }
+    }
+    match depth {
+        0 => Some(commands),
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn chord_to_string<T, S: ToString>(chord: &Chord<T>, item: impl Fn(&T) -> S) -> String {
+    let items: Vec<String> = chord.items().iter().map(|i| item(i).to_string()).collect();
+    let kind = match chord {
+        Chord::All(_) => "all",
+        Chord::Any(_) => "any",
+    };
+    format!("{}:{}", kind, items.join(","))
+}
+
+fn chord_from_str<T>(text: &str, item: impl Fn(&str) -> Option<T>) -> Option<Chord<T>> {
+    let (kind, items) = text.split_once(':')?;
+    let items = match items {
+        "" => Vec::new(),
+        items => items.split(',').map(item).collect::<Option<Vec<T>>>()?,
+    };
+    match kind {
+        "all" => Some(Chord::All(items)),
+        "any" => Some(Chord::Any(items)),
+        _ => None,
+    }
+}
+
+fn mouse_button(text: &str) -> Option<MouseButton> {
+    match MouseButton::from_ll(text.parse().ok()?) {
+        MouseButton::Unknown => None,
+        button => Some(button),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let path = std::env::temp_dir().join(format!("settings-{}.txt", std::process::id()));
+        let stick = SDLCommand::DigitalStick {
+            up: Box::new(SDLCommand::key(Scancode::W)),
+            down: Box::new(SDLCommand::key(Scancode::S)),
+            left: Box::new(SDLCommand::DigitalAxis {
+                negative: Box::new(SDLCommand::key(Scancode::A)),
+                positive: Box::new(SDLCommand::MouseButton(Chord::Any(vec![
+                    MouseButton::Left,
+                    MouseButton::Right,
+                ]))),
+            }),
+            right: Box::new(SDLCommand::Gamepad(
+                SDLGamepadFeature::Button(Chord::All(vec![Button::A, Button::B])),
+                Some(1),
+            )),
+            normalize: true,
+        };
+
+        let mut settings = Settings::new();
+        settings.persist_to(&path).unwrap();
+        settings.window_size = Some((1280, 720));
+        settings.set_level(Bus::Music, 0.5);
+        settings.set_binding(0, 3, Some(vec![stick.clone(), SDLCommand::MouseWheel]));
+        settings.set_binding(1, 2, Some(Vec::new()));
+        settings.set_value("name\twith tab", Some("line\nbreak \\ and \\t"));
+        settings.save().unwrap();
+
+        let mut loaded = Settings::new();
+        loaded.persist_to(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.window_size, Some((1280, 720)));
+        assert_eq!(loaded.level(Bus::Music), Some(0.5));
+        assert_eq!(
+            loaded.binding(0, 3),
+            Some(&[stick, SDLCommand::MouseWheel][..])
+        );
+        assert_eq!(loaded.binding(1, 2), Some(&[][..]));
+        assert_eq!(
+            loaded.value("name\twith tab"),
+            Some("line\nbreak \\ and \\t")
+        );
+    }
+}
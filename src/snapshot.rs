@@ -0,0 +1,132 @@
+use sdl2::{
+    controller::{Button, GameController},
+    keyboard::{KeyboardState, Mod, Scancode},
+    mouse::{MouseButton, MouseState},
+};
+
+use crate::{calibration::AXES, input::translate_axis};
+
+pub const BUTTONS: [Button; 21] = [
+    Button::A,
+    Button::B,
+    Button::X,
+    Button::Y,
+    Button::Back,
+    Button::Guide,
+    Button::Start,
+    Button::LeftStick,
+    Button::RightStick,
+    Button::LeftShoulder,
+    Button::RightShoulder,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+    Button::Misc1,
+    Button::Paddle1,
+    Button::Paddle2,
+    Button::Paddle3,
+    Button::Paddle4,
+    Button::Touchpad,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControllerSnapshot {
+    pub instance_id: u32,
+    // In `calibration::AXES` order, -1.0 to 1.0 without deadzones or calibration.
+    pub axes: [f32; 6],
+    pub buttons: Vec<Button>,
+}
+
+impl ControllerSnapshot {
+    pub fn button(&self, button: Button) -> bool {
+        self.buttons.contains(&button)
+    }
+}
+
+// Raw device state as of the last input poll, for scenes that want more than named
+// actions. Positions are window pixels; `pointer` is the same point in the space hit
+// regions use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputSnapshot {
+    pub keys: Vec<Scancode>,
+    pub modifiers: Mod,
+    pub mouse: (i32, i32),
+    pub pointer: (f32, f32),
+    pub mouse_buttons: Vec<MouseButton>,
+    pub wheel: (f32, f32),
+    // By controller slot, `None` where nothing is connected.
+    pub controllers: Vec<Option<ControllerSnapshot>>,
+}
+
+impl InputSnapshot {
+    pub fn new() -> Self {
+        InputSnapshot {
+            keys: Vec::new(),
+            modifiers: Mod::NOMOD,
+            mouse: (0, 0),
+            pointer: (0.0, 0.0),
+            mouse_buttons: Vec::new(),
+            wheel: (0.0, 0.0),
+            controllers: Vec::new(),
+        }
+    }
+
+    pub fn key(&self, key: Scancode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    pub fn mouse_button(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    pub fn controller(&self, index: usize) -> Option<&ControllerSnapshot> {
+        self.controllers.get(index).and_then(Option::as_ref)
+    }
+
+    // Buffers are refilled in place so taking a snapshot every frame doesn't allocate.
+    pub(crate) fn capture(
+        &mut self,
+        keys: &KeyboardState,
+        modifiers: Mod,
+        mouse: &MouseState,
+        controllers: &[Option<GameController>],
+    ) {
+        self.keys.clear();
+        self.keys.extend(keys.pressed_scancodes());
+        self.modifiers = modifiers;
+        self.mouse = (mouse.x(), mouse.y());
+        self.mouse_buttons.clear();
+        self.mouse_buttons.extend(mouse.pressed_mouse_buttons());
+
+        self.controllers.resize_with(controllers.len(), || None);
+        for (snapshot, controller) in self.controllers.iter_mut().zip(controllers) {
+            let controller = match controller {
+                Some(c) => c,
+                None => {
+                    *snapshot = None;
+                    continue;
+                }
+            };
+            let snapshot = snapshot.get_or_insert_with(|| ControllerSnapshot {
+                instance_id: 0,
+                axes: [0.0; 6],
+                buttons: Vec::new(),
+            });
+            snapshot.instance_id = controller.instance_id();
+            for (value, axis) in snapshot.axes.iter_mut().zip(AXES) {
+                *value = translate_axis(controller.axis(axis));
+            }
+            snapshot.buttons.clear();
+            snapshot
+                .buttons
+                .extend(BUTTONS.iter().copied().filter(|b| controller.button(*b)));
+        }
+    }
+}
+
+impl Default for InputSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use sdl2::mixer::Channel;
+use stagehand::loading::Ticket;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct MusicSnapshot {
+    pub ticket: Ticket,
+    pub loops: i32,
+    pub volume: f32,
+    // Seconds in, None for formats that can't report or seek.
+    pub position: Option<f64>,
+}
+
+// Everything needed to bring the soundscape back after another scene took over the audio,
+// like a cutscene.
+#[derive(Clone, PartialEq)]
+pub struct AudioSnapshot {
+    pub music: Option<MusicSnapshot>,
+    pub music_bus: f32,
+    pub sounds_bus: f32,
+    // Looping sounds and their volumes.
+    pub loops: Vec<(Ticket, f32)>,
+}
+
+pub(crate) enum SoundscapeRequest {
+    PlayLoop(Ticket, f32),
+    StopLoop(Ticket),
+    StopLoops,
+    Save(String),
+    Restore(String),
+}
+
+// Looping sounds, like ambience, and named audio snapshots. Requests apply after the stage
+// update, so scenes can save before switching and restore when they come back.
+pub struct Soundscape {
+    pub(crate) requests: Vec<SoundscapeRequest>,
+    pub(crate) loops: Vec<(Ticket, f32, Channel)>,
+    pub(crate) saved: HashMap<String, AudioSnapshot>,
+}
+
+impl Soundscape {
+    pub fn new() -> Self {
+        Soundscape {
+            requests: Vec::new(),
+            loops: Vec::new(),
+            saved: HashMap::new(),
+        }
+    }
+
+    pub fn play_loop(&mut self, ticket: Ticket, volume: f32) {
+        self.requests
+            .push(SoundscapeRequest::PlayLoop(ticket, volume));
+    }
+
+    pub fn stop_loop(&mut self, ticket: Ticket) {
+        self.requests.push(SoundscapeRequest::StopLoop(ticket));
+    }
+
+    pub fn stop_loops(&mut self) {
+        self.requests.push(SoundscapeRequest::StopLoops);
+    }
+
+    pub fn save(&mut self, name: &str) {
+        self.requests
+            .push(SoundscapeRequest::Save(name.to_string()));
+    }
+
+    // Keeps the snapshot, so the same soundscape can be restored again.
+    pub fn restore(&mut self, name: &str) {
+        self.requests
+            .push(SoundscapeRequest::Restore(name.to_string()));
+    }
+
+    pub fn snapshot(&self, name: &str) -> Option<&AudioSnapshot> {
+        self.saved.get(name)
+    }
+
+    pub fn forget(&mut self, name: &str) -> Option<AudioSnapshot> {
+        self.saved.remove(name)
+    }
+
+    pub fn is_looping(&self, ticket: Ticket) -> bool {
+        self.loops.iter().any(|(t, ..)| *t == ticket)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.requests.clear();
+        self.loops.clear();
+        self.saved.clear();
+    }
+
+    // Loops stop when something else takes their channel.
+    pub(crate) fn prune(&mut self) {
+        self.loops.retain(|(.., c)| c.is_playing());
+    }
+}
+
+impl Default for Soundscape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
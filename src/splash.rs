@@ -0,0 +1,140 @@
+use std::{
+    f32::consts::TAU,
+    time::{Duration, Instant},
+};
+
+use sdl2::{
+    event::Event,
+    pixels::Color,
+    rect::Rect,
+    render::{BlendMode, Canvas, Texture},
+    video::Window,
+    Sdl,
+};
+
+const SPINNER_DOTS: usize = 8;
+
+// Keeps the window alive during long synchronous loads before the app exists: every tick
+// pumps events so the platform doesn't flag the window as not responding, and redraws the
+// splash image with a spinner. Call `tick` between loads, for example from the progress
+// callback of `SDLStorage::load_all_with`.
+pub struct SplashScreen {
+    pub background: Color,
+    pub spinner_color: Color,
+    // Shortest time between redraws, so fast loads aren't slowed down by presenting.
+    pub min_interval: Duration,
+    started: Instant,
+    last_draw: Option<Instant>,
+}
+
+impl SplashScreen {
+    pub fn new() -> Self {
+        SplashScreen {
+            background: Color::RGB(0, 0, 0),
+            spinner_color: Color::RGB(230, 230, 230),
+            min_interval: Duration::from_millis(16),
+            started: Instant::now(),
+            last_draw: None,
+        }
+    }
+
+    // Returns false once the user has asked to quit, so loading can stop early.
+    pub fn tick(
+        &mut self,
+        sdl: &Sdl,
+        canvas: &mut Canvas<Window>,
+        image: Option<&Texture>,
+        progress: Option<f32>,
+    ) -> Result<bool, String> {
+        let mut events = sdl.event_pump()?;
+        if events.poll_iter().any(|e| matches!(e, Event::Quit { .. })) {
+            return Ok(false);
+        }
+
+        let now = Instant::now();
+        if let Some(last) = self.last_draw {
+            if now.duration_since(last) < self.min_interval {
+                return Ok(true);
+            }
+        }
+        self.last_draw = Some(now);
+
+        self.draw(canvas, image, progress)?;
+        Ok(true)
+    }
+
+    fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        image: Option<&Texture>,
+        progress: Option<f32>,
+    ) -> Result<(), String> {
+        let (w, h) = canvas.output_size()?;
+        canvas.set_draw_color(self.background);
+        canvas.clear();
+
+        // The image fits in the middle 60% of the window without stretching.
+        if let Some(image) = image {
+            let query = image.query();
+            let fit = (w as f32 * 0.6 / query.width.max(1) as f32)
+                .min(h as f32 * 0.6 / query.height.max(1) as f32)
+                .min(1.0);
+            let (iw, ih) = (
+                (query.width as f32 * fit) as u32,
+                (query.height as f32 * fit) as u32,
+            );
+            let dst = Rect::new(
+                (w as i32 - iw as i32) / 2,
+                (h as i32 - ih as i32) / 2,
+                iw,
+                ih,
+            );
+            canvas.copy(image, None, dst)?;
+        }
+
+        canvas.set_blend_mode(BlendMode::Blend);
+        let center = (w as f32 / 2.0, h as f32 * 0.85);
+        let radius = (h as f32 * 0.03).max(8.0);
+        let dot = (radius / 3.0).max(2.0) as u32;
+        let lead = (self.started.elapsed().as_secs_f32() * SPINNER_DOTS as f32) as usize;
+        for i in 0..SPINNER_DOTS {
+            let angle = i as f32 / SPINNER_DOTS as f32 * TAU;
+            // Dots fade out behind the leading one.
+            let age = (lead + SPINNER_DOTS - i) % SPINNER_DOTS;
+            let alpha = 255 - (age * 255 / SPINNER_DOTS) as u8;
+            let (x, y) = (
+                center.0 + angle.cos() * radius,
+                center.1 + angle.sin() * radius,
+            );
+            let c = self.spinner_color;
+            canvas.set_draw_color(Color::RGBA(c.r, c.g, c.b, alpha));
+            canvas.fill_rect(Rect::new(
+                x as i32 - dot as i32 / 2,
+                y as i32 - dot as i32 / 2,
+                dot,
+                dot,
+            ))?;
+        }
+
+        if let Some(progress) = progress {
+            let bar = (w as f32 * 0.4) as u32;
+            let x = (w - bar) as i32 / 2;
+            let y = (center.1 + radius * 2.0) as i32;
+            canvas.set_draw_color(self.spinner_color);
+            canvas.draw_rect(Rect::new(x, y, bar, 6))?;
+            let filled = (bar as f32 * progress.clamp(0.0, 1.0)) as u32;
+            if filled > 0 {
+                canvas.fill_rect(Rect::new(x, y, filled, 6))?;
+            }
+        }
+
+        canvas.present();
+        Ok(())
+    }
+}
+
+impl Default for SplashScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
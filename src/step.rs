@@ -0,0 +1,117 @@
+use stagehand::app::App;
+
+use crate::SDLApp;
+
+// Drives the app one frame at a time for hosts that own the loop, like the browser.
+pub struct FrameStepper {
+    // Milliseconds of game time per update.
+    pub timestep: f64,
+    // Value passed to update; defaults to the timestep in seconds.
+    pub delta: f64,
+    // Updates allowed per step before dropping time, so a stalled tab doesn't spiral.
+    pub max_updates: u32,
+    // Takes the update rate from the app's FramePacing on every step.
+    pub follow_pacing: bool,
+    accumulator: f64,
+    last: Option<f64>,
+}
+
+impl FrameStepper {
+    pub fn new(updates_per_second: u32) -> Self {
+        let timestep = 1000.0 / updates_per_second.max(1) as f64;
+        FrameStepper {
+            timestep,
+            delta: timestep / 1000.0,
+            max_updates: 5,
+            follow_pacing: false,
+            accumulator: 0.0,
+            last: None,
+        }
+    }
+
+    pub fn paced() -> Self {
+        FrameStepper {
+            follow_pacing: true,
+            ..Self::new(60)
+        }
+    }
+
+    // The accumulated time keeps its fraction of a step, so a rate change mid-game
+    // neither drops nor doubles an update.
+    pub fn set_rate(&mut self, updates_per_second: u32) {
+        let timestep = 1000.0 / updates_per_second.max(1) as f64;
+        if timestep == self.timestep {
+            return;
+        }
+
+        self.accumulator = self.accumulator / self.timestep * timestep;
+        self.timestep = timestep;
+        self.delta = timestep / 1000.0;
+    }
+}
+
+impl<'a, 'b, 'c, IContent, UContent, Message> SDLApp<'a, 'b, 'c, IContent, UContent, Message> {
+    // Returns false once the app asked to quit.
+    pub fn step(&mut self, stepper: &mut FrameStepper) -> Result<bool, String> {
+        let now = self.precise_ticks();
+        let elapsed = now - stepper.last.unwrap_or(now);
+        stepper.last = Some(now);
+
+        // Events are polled once a step. Updates that need finer timing than the frame
+        // read the timestamps in the input timeline.
+        if !self.processed_events()? {
+            return Ok(false);
+        }
+
+        if stepper.follow_pacing {
+            stepper.set_rate(self.pacing.borrow().target_rate());
+        }
+
+        stepper.accumulator += elapsed;
+        let mut updates = 0;
+        while stepper.accumulator >= stepper.timestep {
+            if updates == stepper.max_updates {
+                stepper.accumulator = 0.0;
+                break;
+            }
+            self.update(stepper.delta);
+            stepper.accumulator -= stepper.timestep;
+            updates += 1;
+        }
+
+        self.draw(stepper.accumulator / stepper.timestep, now as u64);
+        Ok(true)
+    }
+}
+
+#[cfg(target_os = "emscripten")]
+pub mod emscripten {
+    use std::os::raw::{c_int, c_void};
+
+    extern "C" {
+        fn emscripten_set_main_loop_arg(
+            func: extern "C" fn(*mut c_void),
+            arg: *mut c_void,
+            fps: c_int,
+            simulate_infinite_loop: c_int,
+        );
+        fn emscripten_cancel_main_loop();
+    }
+
+    extern "C" fn trampoline<F: FnMut() -> bool>(arg: *mut c_void) {
+        let callback = unsafe { &mut *(arg as *mut F) };
+        if !callback() {
+            unsafe { emscripten_cancel_main_loop() };
+        }
+    }
+
+    // Never returns. Emscripten leaves the caller's stack in place, so the callback may
+    // borrow locals from it such as the loaders the app was built from.
+    pub fn set_main_loop<F: FnMut() -> bool>(callback: F) -> ! {
+        let callback = Box::into_raw(Box::new(callback));
+        unsafe {
+            emscripten_set_main_loop_arg(trampoline::<F>, callback as *mut c_void, 0, 1);
+        }
+        unreachable!()
+    }
+}
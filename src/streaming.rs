@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::loading::{AssetSource, SDLStorage};
+
+pub type Region = (i32, i32);
+
+// Loads the asset groups of map regions around the view as it moves and releases the
+// ones left behind, spending at most `budget` milliseconds a frame on loading so crossing
+// into a new area doesn't stall. Regions are cells of a grid in world units.
+pub struct RegionStreaming<'c> {
+    pub region_size: (f32, f32),
+    // Regions this many cells around the view's region are kept loaded.
+    pub radius: i32,
+    pub budget: f64,
+    // Streams around this point instead of the view center when set.
+    pub focus: Option<(f32, f32)>,
+    pub enabled: bool,
+    regions: HashMap<Region, Vec<AssetSource<'c>>>,
+    loaded: HashSet<Region>,
+    // Region and how many of its assets are in so far.
+    queue: VecDeque<(Region, usize)>,
+    center: Option<Region>,
+    loaded_this_frame: Vec<Region>,
+    released_this_frame: Vec<Region>,
+}
+
+impl<'c> RegionStreaming<'c> {
+    pub fn new() -> Self {
+        RegionStreaming {
+            region_size: (1024.0, 1024.0),
+            radius: 1,
+            budget: 4.0,
+            focus: None,
+            enabled: true,
+            regions: HashMap::new(),
+            loaded: HashSet::new(),
+            queue: VecDeque::new(),
+            center: None,
+            loaded_this_frame: Vec::new(),
+            released_this_frame: Vec::new(),
+        }
+    }
+
+    // Stops streaming for good, for shutdown.
+    pub(crate) fn clear(&mut self) {
+        self.enabled = false;
+        self.queue.clear();
+        self.loaded.clear();
+    }
+
+    pub fn register(&mut self, region: Region, assets: Vec<AssetSource<'c>>) {
+        self.regions.insert(region, assets);
+        // Picked up again on the next update if it's in range.
+        self.center = None;
+    }
+
+    pub fn region_at(&self, x: f32, y: f32) -> Region {
+        (
+            (x / self.region_size.0.max(f32::EPSILON)).floor() as i32,
+            (y / self.region_size.1.max(f32::EPSILON)).floor() as i32,
+        )
+    }
+
+    // Storage group holding the region's assets.
+    pub fn group_name(region: Region) -> String {
+        format!("region:{},{}", region.0, region.1)
+    }
+
+    pub fn is_loaded(&self, region: Region) -> bool {
+        self.loaded.contains(&region)
+    }
+
+    pub fn center(&self) -> Option<Region> {
+        self.center
+    }
+
+    // Assets still waiting for a turn.
+    pub fn pending_assets(&self) -> usize {
+        self.queue
+            .iter()
+            .map(|(r, done)| {
+                self.regions
+                    .get(r)
+                    .map_or(0, |a| a.len().saturating_sub(*done))
+            })
+            .sum()
+    }
+
+    // Share of the regions in range that are fully loaded, 1.0 once streaming caught up.
+    pub fn progress(&self) -> f32 {
+        let queued = self.queue.len();
+        let total = self.loaded.len() + queued;
+        match total {
+            0 => 1.0,
+            _ => self.loaded.len() as f32 / total as f32,
+        }
+    }
+
+    pub fn loaded_this_frame(&self) -> &[Region] {
+        &self.loaded_this_frame
+    }
+
+    pub fn released_this_frame(&self) -> &[Region] {
+        &self.released_this_frame
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        view_center: (f32, f32),
+        storage: &mut SDLStorage<'_, '_, 'c>,
+        now: impl Fn() -> f64,
+    ) {
+        self.loaded_this_frame.clear();
+        self.released_this_frame.clear();
+        if !self.enabled {
+            return;
+        }
+
+        let (x, y) = self.focus.unwrap_or(view_center);
+        let center = self.region_at(x, y);
+        if self.center != Some(center) {
+            self.center = Some(center);
+            self.retarget(center, storage);
+        }
+
+        let start = now();
+        while let Some((region, done)) = self.queue.front_mut() {
+            if now() - start >= self.budget {
+                break;
+            }
+
+            let region = *region;
+            let assets = match self.regions.get(&region) {
+                Some(a) => a,
+                None => {
+                    self.queue.pop_front();
+                    continue;
+                }
+            };
+
+            if let Some(asset) = assets.get(*done) {
+                storage.load_group(&Self::group_name(region), std::slice::from_ref(asset), true);
+                *done += 1;
+            }
+            if *done >= assets.len() {
+                self.queue.pop_front();
+                self.loaded.insert(region);
+                self.loaded_this_frame.push(region);
+            }
+        }
+    }
+
+    fn retarget(&mut self, center: Region, storage: &mut SDLStorage<'_, '_, 'c>) {
+        let radius = self.radius.max(0);
+        let in_range =
+            |r: &Region| (r.0 - center.0).abs() <= radius && (r.1 - center.1).abs() <= radius;
+
+        let leaving: Vec<Region> = self
+            .loaded
+            .iter()
+            .filter(|r| !in_range(r))
+            .copied()
+            .collect();
+        for region in leaving {
+            storage.release_group(&Self::group_name(region));
+            self.loaded.remove(&region);
+            self.released_this_frame.push(region);
+        }
+
+        // Half loaded regions that fell out of range are dropped too.
+        let mut abandoned = Vec::new();
+        self.queue.retain(|(r, done)| match in_range(r) {
+            true => true,
+            false => {
+                if *done > 0 {
+                    abandoned.push(*r);
+                }
+                false
+            }
+        });
+        for region in abandoned {
+            storage.release_group(&Self::group_name(region));
+        }
+
+        // Nearest regions first.
+        let mut wanted: Vec<Region> = self
+            .regions
+            .keys()
+            .filter(|r| in_range(r))
+            .filter(|r| !self.loaded.contains(r) && !self.queue.iter().any(|(q, _)| q == *r))
+            .copied()
+            .collect();
+        wanted.sort_by_key(|r| (r.0 - center.0).abs().max((r.1 - center.1).abs()));
+        self.queue.extend(wanted.into_iter().map(|r| (r, 0)));
+    }
+}
+
+impl<'c> Default for RegionStreaming<'c> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
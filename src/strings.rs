@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+pub struct StringTable<'a> {
+    entries: HashMap<String, String>,
+    lookup: Option<Box<dyn Fn(&str) -> Option<String> + 'a>>,
+}
+
+impl<'a> StringTable<'a> {
+    pub fn new() -> Self {
+        StringTable {
+            entries: HashMap::new(),
+            lookup: None,
+        }
+    }
+
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.entries.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn extend<I: IntoIterator<Item = (String, String)>>(&mut self, entries: I) {
+        self.entries.extend(entries);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // Table entries only; text from the lookup can't be listed.
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.entries.values().map(|v| v.as_str())
+    }
+
+    // Consulted before the table entries.
+    pub fn set_lookup(&mut self, lookup: Option<Box<dyn Fn(&str) -> Option<String> + 'a>>) {
+        self.lookup = lookup;
+    }
+
+    // Unknown keys are returned unchanged so untranslated text still shows up.
+    pub fn get(&self, key: &str) -> String {
+        if let Some(lookup) = &self.lookup {
+            if let Some(value) = lookup(key) {
+                return value;
+            }
+        }
+
+        match self.entries.get(key) {
+            Some(v) => v.clone(),
+            None => key.to_string(),
+        }
+    }
+}
+
+impl<'a> Default for StringTable<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,224 @@
+use std::{ffi::CStr, os::raw::c_void};
+
+use sdl2::sys::{SDL_GetPowerInfo, SDL_GetPreferredLocales, SDL_PowerState, SDL_free};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerState {
+    Unknown,
+    OnBattery,
+    NoBattery,
+    Charging,
+    Charged,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PowerInfo {
+    pub state: PowerState,
+    pub seconds: Option<i32>,
+    pub percent: Option<i32>,
+}
+
+pub fn power_info() -> PowerInfo {
+    let (mut seconds, mut percent) = (-1, -1);
+    let state = unsafe { SDL_GetPowerInfo(&mut seconds, &mut percent) };
+
+    PowerInfo {
+        state: match state {
+            SDL_PowerState::SDL_POWERSTATE_ON_BATTERY => PowerState::OnBattery,
+            SDL_PowerState::SDL_POWERSTATE_NO_BATTERY => PowerState::NoBattery,
+            SDL_PowerState::SDL_POWERSTATE_CHARGING => PowerState::Charging,
+            SDL_PowerState::SDL_POWERSTATE_CHARGED => PowerState::Charged,
+            _ => PowerState::Unknown,
+        },
+        seconds: if seconds >= 0 { Some(seconds) } else { None },
+        percent: if percent >= 0 { Some(percent) } else { None },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locale {
+    // ISO 639 code like "en" or "pt".
+    pub language: String,
+    // ISO 3166 code like "US" or "BR", if the user picked one.
+    pub country: Option<String>,
+}
+
+// Most preferred first. Empty when the platform doesn't say.
+pub fn preferred_locales() -> Vec<Locale> {
+    let mut locales = Vec::new();
+    unsafe {
+        let list = SDL_GetPreferredLocales();
+        if list.is_null() {
+            return locales;
+        }
+
+        let mut entry = list;
+        while !(*entry).language.is_null() {
+            let country = match (*entry).country.is_null() {
+                true => None,
+                false => Some(
+                    CStr::from_ptr((*entry).country)
+                        .to_string_lossy()
+                        .into_owned(),
+                ),
+            };
+            locales.push(Locale {
+                language: CStr::from_ptr((*entry).language)
+                    .to_string_lossy()
+                    .into_owned(),
+                country,
+            });
+            entry = entry.add(1);
+        }
+        SDL_free(list as *mut c_void);
+    }
+    locales
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemTheme {
+    Unknown,
+    Light,
+    Dark,
+}
+
+// SDL2 has no theme query, so this only knows about an explicit dark variant in the
+// environment. A theme without one could still be dark, so that stays Unknown.
+pub fn system_theme() -> SystemTheme {
+    match std::env::var("GTK_THEME") {
+        Ok(theme) if theme.to_lowercase().ends_with(":dark") => SystemTheme::Dark,
+        _ => SystemTheme::Unknown,
+    }
+}
+
+pub struct SystemStatus {
+    pub low_battery_percent: i32,
+    pub poll_interval: u64,
+
+    power: PowerInfo,
+    low_battery: bool,
+    became_low: bool,
+    last_poll: Option<u64>,
+
+    locales: Vec<Locale>,
+    locale_pending: bool,
+    locale_changed: bool,
+    theme: SystemTheme,
+    theme_changed: bool,
+
+    screensaver: Option<bool>,
+}
+
+impl SystemStatus {
+    pub fn new() -> Self {
+        SystemStatus {
+            low_battery_percent: 15,
+            poll_interval: 5000,
+
+            power: PowerInfo {
+                state: PowerState::Unknown,
+                seconds: None,
+                percent: None,
+            },
+            low_battery: false,
+            became_low: false,
+            last_poll: None,
+
+            locales: Vec::new(),
+            locale_pending: true,
+            locale_changed: false,
+            theme: SystemTheme::Unknown,
+            theme_changed: false,
+
+            screensaver: None,
+        }
+    }
+
+    pub fn power(&self) -> PowerInfo {
+        self.power
+    }
+
+    pub fn is_low_battery(&self) -> bool {
+        self.low_battery
+    }
+
+    // True only on the update where the battery first dropped below the threshold.
+    pub fn became_low_battery(&self) -> bool {
+        self.became_low
+    }
+
+    pub fn locales(&self) -> &[Locale] {
+        &self.locales
+    }
+
+    pub fn locale(&self) -> Option<&Locale> {
+        self.locales.first()
+    }
+
+    // True only on the update after the user changed their language settings.
+    pub fn locale_changed(&self) -> bool {
+        self.locale_changed
+    }
+
+    pub fn theme(&self) -> SystemTheme {
+        self.theme
+    }
+
+    // True only on the update where a theme change was noticed, which can lag by up to
+    // `poll_interval`.
+    pub fn theme_changed(&self) -> bool {
+        self.theme_changed
+    }
+
+    pub fn set_screensaver(&mut self, enabled: bool) {
+        self.screensaver = Some(enabled);
+    }
+
+    pub(crate) fn take_screensaver_request(&mut self) -> Option<bool> {
+        self.screensaver.take()
+    }
+
+    pub(crate) fn locale_event(&mut self) {
+        self.locale_pending = true;
+    }
+
+    pub(crate) fn poll(&mut self, now: u64) {
+        self.became_low = false;
+        self.locale_changed = false;
+        self.theme_changed = false;
+
+        if self.locale_pending {
+            self.locale_pending = false;
+            let locales = preferred_locales();
+            // The first read at startup isn't a change.
+            self.locale_changed = self.last_poll.is_some() && locales != self.locales;
+            self.locales = locales;
+        }
+
+        if let Some(last) = self.last_poll {
+            if now.saturating_sub(last) < self.poll_interval {
+                return;
+            }
+        }
+        let first = self.last_poll.replace(now).is_none();
+        self.power = power_info();
+
+        let low = self.power.state == PowerState::OnBattery
+            && self
+                .power
+                .percent
+                .map_or(false, |p| p <= self.low_battery_percent);
+        self.became_low = low && !self.low_battery;
+        self.low_battery = low;
+
+        let theme = system_theme();
+        self.theme_changed = !first && self.theme != theme;
+        self.theme = theme;
+    }
+}
+
+impl Default for SystemStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
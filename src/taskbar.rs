@@ -0,0 +1,110 @@
+use sdl2::{surface::Surface, sys::SDL_FlashOperation};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attention {
+    // One flash of the taskbar entry.
+    Briefly,
+    // Keeps flashing until the window gets focus.
+    UntilFocused,
+    Cancel,
+}
+
+impl Attention {
+    pub(crate) fn operation(self) -> SDL_FlashOperation {
+        match self {
+            Attention::Briefly => SDL_FlashOperation::SDL_FLASH_BRIEFLY,
+            Attention::UntilFocused => SDL_FlashOperation::SDL_FLASH_UNTIL_FOCUSED,
+            Attention::Cancel => SDL_FlashOperation::SDL_FLASH_CANCEL,
+        }
+    }
+}
+
+// Signals for a window that isn't being watched: an animated icon and progress shown in
+// the title, for long turns or downloads. SDL2 has no taskbar progress bar, so progress
+// goes in front of the window title, which is what the taskbar entry shows.
+pub struct Taskbar {
+    frames: Vec<Surface<'static>>,
+    interval: u64,
+    frame: usize,
+    last_frame: u64,
+    icon_dirty: bool,
+    progress: Option<f32>,
+    title: Option<String>,
+    title_dirty: bool,
+}
+
+impl Taskbar {
+    pub fn new() -> Self {
+        Taskbar {
+            frames: Vec::new(),
+            interval: 250,
+            frame: 0,
+            last_frame: 0,
+            icon_dirty: false,
+            progress: None,
+            title: None,
+            title_dirty: false,
+        }
+    }
+
+    // One frame sets a still icon. The frames cycle every `interval` milliseconds.
+    pub fn set_icon_frames(&mut self, frames: Vec<Surface<'static>>, interval: u64) {
+        self.frames = frames;
+        self.interval = interval.max(1);
+        self.frame = 0;
+        self.icon_dirty = true;
+    }
+
+    // From 0.0 to 1.0, None clears it.
+    pub fn set_progress(&mut self, progress: Option<f32>) {
+        let progress = progress.map(|p| p.clamp(0.0, 1.0));
+        // Only whole percents change the title.
+        let percent = |p: Option<f32>| p.map(|p| (p * 100.0) as u32);
+        if percent(progress) != percent(self.progress) {
+            self.title_dirty = true;
+        }
+        self.progress = progress;
+    }
+
+    pub fn progress(&self) -> Option<f32> {
+        self.progress
+    }
+
+    // Icon to set, if the animation moved on.
+    pub(crate) fn advance(&mut self, now: u64) -> Option<&Surface<'static>> {
+        if self.frames.len() > 1 && now.saturating_sub(self.last_frame) >= self.interval {
+            self.frame = (self.frame + 1) % self.frames.len();
+            self.icon_dirty = true;
+        }
+        if !self.icon_dirty {
+            return None;
+        }
+
+        self.icon_dirty = false;
+        self.last_frame = now;
+        self.frames.get(self.frame)
+    }
+
+    // Title to set, if the progress changed. `current` is remembered as the base title
+    // the first time progress is shown.
+    pub(crate) fn title(&mut self, current: &str) -> Option<String> {
+        if !self.title_dirty {
+            return None;
+        }
+        self.title_dirty = false;
+
+        match self.progress {
+            Some(p) => {
+                let base = self.title.get_or_insert_with(|| current.to_string());
+                Some(format!("{}% - {}", (p * 100.0) as u32, base))
+            }
+            None => self.title.take(),
+        }
+    }
+}
+
+impl Default for Taskbar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,144 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use sdl2::{pixels::Color, render::Texture};
+use stagehand::loading::Ticket;
+
+type TextKey = (Ticket, String, (u8, u8, u8, u8));
+
+struct CachedText<'a> {
+    texture: Rc<RefCell<Texture<'a>>>,
+    // None for warmed text that hasn't been drawn yet, which is kept however long the
+    // loading screen takes.
+    last_used: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextCacheStats {
+    pub entries: usize,
+    // Text draws in the last frame that reused a texture, and ones that had to render.
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl fmt::Display for TextCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} cached texts, {} hits, {} misses",
+            self.entries, self.hits, self.misses
+        )
+    }
+}
+
+// Rendered text by font, string and color, so static labels don't go through a surface and
+// a texture upload every frame. Text that isn't drawn for `max_idle` frames is dropped.
+pub struct TextCache<'a> {
+    pub max_idle: u64,
+    entries: HashMap<TextKey, CachedText<'a>>,
+    frame: u64,
+    hits: usize,
+    misses: usize,
+    last: TextCacheStats,
+}
+
+impl<'a> TextCache<'a> {
+    pub fn new() -> Self {
+        TextCache {
+            max_idle: 120,
+            entries: HashMap::new(),
+            frame: 0,
+            hits: 0,
+            misses: 0,
+            last: TextCacheStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> TextCacheStats {
+        self.last
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn contains(&self, font: Ticket, text: &str, color: Color) -> bool {
+        self.entries
+            .contains_key(&(font, text.to_string(), color.rgba()))
+    }
+
+    // Hits and misses so far this frame.
+    pub(crate) fn counts(&self) -> (usize, usize) {
+        (self.hits, self.misses)
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.last = TextCacheStats {
+            entries: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+        };
+        self.hits = 0;
+        self.misses = 0;
+        self.frame += 1;
+
+        let (frame, max_idle) = (self.frame, self.max_idle);
+        self.entries
+            .retain(|_, e| e.last_used.map_or(true, |used| frame - used <= max_idle));
+    }
+
+    pub(crate) fn get(
+        &mut self,
+        font: Ticket,
+        text: &str,
+        color: Color,
+    ) -> Option<Rc<RefCell<Texture<'a>>>> {
+        let key = (font, text.to_string(), color.rgba());
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.last_used = Some(self.frame);
+                self.hits += 1;
+                Some(entry.texture.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        font: Ticket,
+        text: &str,
+        color: Color,
+        texture: Rc<RefCell<Texture<'a>>>,
+        warmed: bool,
+    ) {
+        let last_used = match warmed {
+            true => None,
+            false => Some(self.frame),
+        };
+        self.entries.insert(
+            (font, text.to_string(), color.rgba()),
+            CachedText { texture, last_used },
+        );
+    }
+
+    pub(crate) fn remove_font(&mut self, font: Ticket) {
+        self.entries.retain(|(f, ..), _| *f != font);
+    }
+}
+
+impl<'a> Default for TextCache<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,76 @@
+use sdl2::{rect::FPoint, render::Canvas, video::Window};
+
+use crate::coords::CoordinateSpace;
+
+// How wide a line or outline is drawn. The width is in draw units, so world units that
+// follow the camera zoom when `pixels_per_unit` is set, and logical pixels otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineThickness {
+    pub width: f32,
+    // Off for overlays and debug lines, which keep their width whatever the camera does.
+    pub follows_zoom: bool,
+    // Fewest output pixels a line covers, so it doesn't vanish when zoomed out or when the
+    // canvas is scaled down.
+    pub min_pixels: f32,
+}
+
+impl LineThickness {
+    pub fn new(width: f32) -> Self {
+        LineThickness {
+            width,
+            follows_zoom: true,
+            min_pixels: 1.0,
+        }
+    }
+
+    pub fn fixed(width: f32) -> Self {
+        LineThickness {
+            follows_zoom: false,
+            ..Self::new(width)
+        }
+    }
+
+    // Width in canvas pixels, before the renderer scales the canvas to the window.
+    pub fn canvas_width(&self, coordinates: &CoordinateSpace) -> f32 {
+        let width = match (self.follows_zoom, coordinates.pixels_per_unit) {
+            (true, Some(_)) => self.width * coordinates.unit_scale(),
+            _ => self.width,
+        };
+        let (sx, sy) = coordinates.render_scale();
+        width.max(self.min_pixels / sx.min(sy).max(f32::EPSILON))
+    }
+}
+
+impl Default for LineThickness {
+    fn default() -> Self {
+        Self::fixed(1.0)
+    }
+}
+
+// SDL only draws hairlines, so wider ones are made of parallel strokes a pixel apart.
+pub(crate) fn draw_lines(
+    canvas: &mut Canvas<Window>,
+    points: &[FPoint],
+    width: f32,
+) -> Result<(), String> {
+    if width <= 1.0 {
+        return canvas.draw_flines(points);
+    }
+
+    let strokes = width.ceil() as usize;
+    let step = width / strokes as f32;
+    for pair in points.windows(2) {
+        let (dx, dy) = (pair[1].x() - pair[0].x(), pair[1].y() - pair[0].y());
+        let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        let normal = (-dy / length, dx / length);
+        for i in 0..strokes {
+            let offset = (i as f32 + 0.5) * step - width / 2.0;
+            let (ox, oy) = (normal.0 * offset, normal.1 * offset);
+            canvas.draw_fline(
+                FPoint::new(pair[0].x() + ox, pair[0].y() + oy),
+                FPoint::new(pair[1].x() + ox, pair[1].y() + oy),
+            )?;
+        }
+    }
+    Ok(())
+}
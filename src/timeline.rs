@@ -0,0 +1,185 @@
+use sdl2::{controller::Button, event::Event, keyboard::Scancode, mouse::MouseButton};
+use stagehand::input::InputMap;
+
+use crate::input::{SDLCommand, SDLGamepadFeature};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimedInput {
+    Key(Scancode),
+    MouseButton(MouseButton),
+    // Controller index in the connected list.
+    Button(usize, Button),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimedEvent {
+    // SDL ticks when the platform reported it, ahead of the poll that handled it, less the
+    // accessibility latency.
+    pub timestamp: u64,
+    pub input: TimedInput,
+    pub pressed: bool,
+    // (user, action) pairs with a binding on this input, chords included.
+    pub actions: Vec<(usize, usize)>,
+}
+
+// Every press and release since the last update with the time it happened, for judging
+// input between frames, like hits in a rhythm game. Events polled in frames without an
+// update are kept for the next one.
+pub struct InputTimeline {
+    pub enabled: bool,
+    events: Vec<TimedEvent>,
+    polled_at: u64,
+    latency: u64,
+    consumed: bool,
+}
+
+impl InputTimeline {
+    pub fn new() -> Self {
+        InputTimeline {
+            enabled: false,
+            events: Vec::new(),
+            polled_at: 0,
+            latency: 0,
+            consumed: true,
+        }
+    }
+
+    pub fn events(&self) -> &[TimedEvent] {
+        &self.events
+    }
+
+    pub fn for_action(
+        &self,
+        user_index: usize,
+        action_index: usize,
+    ) -> impl Iterator<Item = &TimedEvent> {
+        self.events
+            .iter()
+            .filter(move |e| e.actions.contains(&(user_index, action_index)))
+    }
+
+    // First press of the action since the last update.
+    pub fn pressed_at(&self, user_index: usize, action_index: usize) -> Option<u64> {
+        self.for_action(user_index, action_index)
+            .find(|e| e.pressed)
+            .map(|e| e.timestamp)
+    }
+
+    // Ticks when input was last polled, so `polled_at - timestamp` is how late an event
+    // was seen.
+    pub fn polled_at(&self) -> u64 {
+        self.polled_at
+    }
+
+    pub(crate) fn begin(&mut self, now: u64, latency: u64) {
+        if self.consumed {
+            self.events.clear();
+            self.consumed = false;
+        }
+        self.polled_at = now;
+        self.latency = latency;
+    }
+
+    pub(crate) fn consume(&mut self) {
+        self.consumed = true;
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        event: &Event,
+        controller: Option<usize>,
+        input: &InputMap<SDLCommand>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        let (timestamp, timed, pressed) = match *event {
+            Event::KeyDown {
+                timestamp,
+                scancode: Some(s),
+                repeat: false,
+                ..
+            } => (timestamp, TimedInput::Key(s), true),
+            Event::KeyUp {
+                timestamp,
+                scancode: Some(s),
+                ..
+            } => (timestamp, TimedInput::Key(s), false),
+            Event::MouseButtonDown {
+                timestamp,
+                mouse_btn,
+                ..
+            } => (timestamp, TimedInput::MouseButton(mouse_btn), true),
+            Event::MouseButtonUp {
+                timestamp,
+                mouse_btn,
+                ..
+            } => (timestamp, TimedInput::MouseButton(mouse_btn), false),
+            Event::ControllerButtonDown {
+                timestamp, button, ..
+            } => match controller {
+                Some(c) => (timestamp, TimedInput::Button(c, button), true),
+                None => return,
+            },
+            Event::ControllerButtonUp {
+                timestamp, button, ..
+            } => match controller {
+                Some(c) => (timestamp, TimedInput::Button(c, button), false),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let actions = input
+            .commands
+            .iter()
+            .filter(|c| c.commands.iter().any(|command| binds(command, timed)))
+            .map(|c| (c.user_index, c.action_index))
+            .collect();
+        self.events.push(TimedEvent {
+            timestamp: (timestamp as u64).saturating_sub(self.latency),
+            input: timed,
+            pressed,
+            actions,
+        });
+    }
+}
+
+impl Default for InputTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn binds(command: &SDLCommand, input: TimedInput) -> bool {
+    match (command, input) {
+        (SDLCommand::Key(chord), TimedInput::Key(key)) => chord.items().contains(&key),
+        (SDLCommand::KeyCode(chord, _), TimedInput::Key(key)) => chord
+            .items()
+            .iter()
+            .any(|k| Scancode::from_keycode(*k) == Some(key)),
+        (SDLCommand::MouseButton(chord), TimedInput::MouseButton(button)) => {
+            chord.items().contains(&button)
+        }
+        (SDLCommand::MultiClick(b, _), TimedInput::MouseButton(button)) => *b == button,
+        (
+            SDLCommand::Gamepad(SDLGamepadFeature::Button(chord), index),
+            TimedInput::Button(controller, button),
+        ) => index.map_or(true, |i| i == controller) && chord.items().contains(&button),
+        (SDLCommand::DigitalAxis { negative, positive }, _) => {
+            binds(negative, input) || binds(positive, input)
+        }
+        (
+            SDLCommand::DigitalStick {
+                up,
+                down,
+                left,
+                right,
+                ..
+            },
+            _,
+        ) => [up, down, left, right].iter().any(|c| binds(c, input)),
+        _ => false,
+    }
+}
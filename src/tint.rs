@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use sdl2::pixels::Color;
+
+pub struct Tints {
+    batches: HashMap<usize, Color>,
+    pub global: Option<Color>,
+}
+
+impl Tints {
+    pub fn new() -> Self {
+        Tints {
+            batches: HashMap::new(),
+            global: None,
+        }
+    }
+
+    // Batches are indexed in the order the stage returns them for a frame.
+    pub fn set_batch(&mut self, batch_index: usize, tint: Option<Color>) {
+        match tint {
+            Some(c) => self.batches.insert(batch_index, c),
+            None => self.batches.remove(&batch_index),
+        };
+    }
+
+    pub fn batch(&self, batch_index: usize) -> Option<Color> {
+        self.batches.get(&batch_index).copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.batches.clear();
+        self.global = None;
+    }
+}
+
+impl Default for Tints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use stagehand::{draw::DrawRect, input::ActionState, input::ActionType, loading::Ticket};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VirtualControlKind {
+    Button,
+    Stick { radius: f32 },
+}
+
+pub struct VirtualControl {
+    pub kind: VirtualControlKind,
+    pub rect: DrawRect,
+    pub texture: Ticket,
+    pub pressed_texture: Option<Ticket>,
+    pub knob_texture: Option<Ticket>,
+}
+
+pub struct VirtualGamepad {
+    pub enabled: bool,
+    pub opacity: u8,
+    controls: Vec<VirtualControl>,
+    fingers: HashMap<i64, usize>,
+    sticks: HashMap<usize, (f32, f32)>,
+}
+
+impl VirtualGamepad {
+    pub fn new() -> Self {
+        VirtualGamepad {
+            enabled: false,
+            opacity: 160,
+            controls: Vec::new(),
+            fingers: HashMap::new(),
+            sticks: HashMap::new(),
+        }
+    }
+
+    // Returns the index used to bind the control with `SDLCommand::Touch`.
+    pub fn add(&mut self, control: VirtualControl) -> usize {
+        self.controls.push(control);
+        self.controls.len() - 1
+    }
+
+    pub fn controls(&self) -> &[VirtualControl] {
+        &self.controls
+    }
+
+    pub fn is_pressed(&self, control: usize) -> bool {
+        self.fingers.values().any(|c| *c == control)
+    }
+
+    pub fn stick(&self, control: usize) -> Option<(f32, f32)> {
+        self.sticks.get(&control).copied()
+    }
+
+    pub fn state(&self, control: usize) -> Option<ActionType> {
+        match self.controls.get(control)?.kind {
+            VirtualControlKind::Button => match self.is_pressed(control) {
+                true => Some(ActionType::Digital(ActionState::Down)),
+                false => None,
+            },
+            VirtualControlKind::Stick { .. } => self
+                .stick(control)
+                .map(|(x, y)| ActionType::Analog { x, y }),
+        }
+    }
+
+    pub(crate) fn touch_down(&mut self, finger: i64, x: f32, y: f32) {
+        if !self.enabled {
+            return;
+        }
+
+        let hit = self.controls.iter().rposition(|c| {
+            let r = &c.rect;
+            x >= r.x && y >= r.y && x < r.x + r.width && y < r.y + r.height
+        });
+
+        if let Some(control) = hit {
+            self.fingers.insert(finger, control);
+            self.touch_move(finger, x, y);
+        }
+    }
+
+    // Sticks keep tracking a finger that has slid outside their rect.
+    pub(crate) fn touch_move(&mut self, finger: i64, x: f32, y: f32) {
+        let control = match self.fingers.get(&finger) {
+            Some(c) => *c,
+            None => return,
+        };
+
+        if let VirtualControlKind::Stick { radius } = self.controls[control].kind {
+            let r = &self.controls[control].rect;
+            let dx = (x - (r.x + r.width / 2.0)) / radius.max(1.0);
+            let dy = (y - (r.y + r.height / 2.0)) / radius.max(1.0);
+            let length = (dx * dx + dy * dy).sqrt();
+            let (dx, dy) = match length > 1.0 {
+                true => (dx / length, dy / length),
+                false => (dx, dy),
+            };
+            self.sticks.insert(control, (dx, dy));
+        }
+    }
+
+    pub(crate) fn touch_up(&mut self, finger: i64) {
+        if let Some(control) = self.fingers.remove(&finger) {
+            if !self.is_pressed(control) {
+                self.sticks.remove(&control);
+            }
+        }
+    }
+}
+
+impl Default for VirtualGamepad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
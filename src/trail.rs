@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrailConfig {
+    // Alpha kept from the previous frame's trail, 0 disables the afterimage.
+    pub persistence: u8,
+}
+
+pub struct Trails {
+    // Frames a batch can go without a trail before its feedback buffers are freed.
+    pub idle_frames: u32,
+    batches: HashMap<usize, TrailConfig>,
+}
+
+impl Trails {
+    pub fn new() -> Self {
+        Trails {
+            idle_frames: 60,
+            batches: HashMap::new(),
+        }
+    }
+
+    // Batches are indexed in the order the stage returns them for a frame.
+    pub fn set_batch(&mut self, batch_index: usize, trail: Option<TrailConfig>) {
+        match trail {
+            Some(t) => self.batches.insert(batch_index, t),
+            None => self.batches.remove(&batch_index),
+        };
+    }
+
+    pub fn batch(&self, batch_index: usize) -> Option<TrailConfig> {
+        self.batches.get(&batch_index).copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.batches.clear();
+    }
+}
+
+impl Default for Trails {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,161 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TweenId(u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+    BackOut,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => match t < 0.5 {
+                true => 2.0 * t * t,
+                false => -1.0 + (4.0 - 2.0 * t) * t,
+            },
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => match t < 0.5 {
+                true => 4.0 * t * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            },
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::BackOut => {
+                let c = 1.70158;
+                1.0 + (c + 1.0) * (t - 1.0).powi(3) + c * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+struct Tween {
+    from: f32,
+    to: f32,
+    duration: u64,
+    elapsed: f64,
+    easing: Easing,
+    looping: bool,
+}
+
+pub struct Tweens {
+    pub paused: bool,
+    pub time_scale: f64,
+    tweens: HashMap<TweenId, Tween>,
+    next_id: u64,
+    last_tick: Option<u64>,
+}
+
+impl Tweens {
+    pub fn new() -> Self {
+        Tweens {
+            paused: false,
+            time_scale: 1.0,
+            tweens: HashMap::new(),
+            next_id: 0,
+            last_tick: None,
+        }
+    }
+
+    // Duration is in milliseconds of scaled time.
+    pub fn create(&mut self, from: f32, to: f32, duration: u64, easing: Easing) -> TweenId {
+        self.push(from, to, duration, easing, false)
+    }
+
+    pub fn create_looping(&mut self, from: f32, to: f32, duration: u64, easing: Easing) -> TweenId {
+        self.push(from, to, duration, easing, true)
+    }
+
+    pub fn value(&self, id: TweenId) -> Option<f32> {
+        self.tweens.get(&id).map(|t| {
+            let progress = match t.duration {
+                0 => 1.0,
+                d => (t.elapsed / d as f64) as f32,
+            };
+            t.from + (t.to - t.from) * t.easing.apply(progress)
+        })
+    }
+
+    pub fn is_finished(&self, id: TweenId) -> bool {
+        self.tweens
+            .get(&id)
+            .map_or(true, |t| !t.looping && t.elapsed >= t.duration as f64)
+    }
+
+    // Finished tweens keep reporting their end value until removed.
+    pub fn remove(&mut self, id: TweenId) -> bool {
+        self.tweens.remove(&id).is_some()
+    }
+
+    pub fn remove_finished(&mut self) {
+        self.tweens
+            .retain(|_, t| t.looping || t.elapsed < t.duration as f64);
+    }
+
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+
+    fn push(
+        &mut self,
+        from: f32,
+        to: f32,
+        duration: u64,
+        easing: Easing,
+        looping: bool,
+    ) -> TweenId {
+        let id = TweenId(self.next_id);
+        self.next_id += 1;
+
+        self.tweens.insert(
+            id,
+            Tween {
+                from,
+                to,
+                duration,
+                elapsed: 0.0,
+                easing,
+                looping,
+            },
+        );
+
+        id
+    }
+
+    pub(crate) fn advance(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.last_tick.unwrap_or(now));
+        self.last_tick = Some(now);
+        if self.paused {
+            return;
+        }
+
+        let step = elapsed as f64 * self.time_scale.max(0.0);
+        for tween in self.tweens.values_mut() {
+            tween.elapsed += step;
+            if tween.looping && tween.duration > 0 {
+                tween.elapsed %= tween.duration as f64;
+            } else {
+                tween.elapsed = tween.elapsed.min(tween.duration as f64);
+            }
+        }
+    }
+}
+
+impl Default for Tweens {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,209 @@
+use std::f32::consts::PI;
+
+const WINDOW: usize = 1024;
+const LOWEST: f32 = 40.0;
+const HIGHEST: f32 = 16000.0;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct AudioLevels {
+    pub(crate) amplitude: f32,
+    pub(crate) peak: f32,
+    pub(crate) bands: Vec<f32>,
+}
+
+// Audio thread side: keeps the last WINDOW mono samples and measures them after every
+// buffer.
+pub(crate) struct Analyzer {
+    channels: usize,
+    history: Vec<f32>,
+    next: usize,
+    window: Vec<f32>,
+    real: Vec<f32>,
+    imag: Vec<f32>,
+    // Bin ranges for each band, log spaced so bass isn't squeezed into one band.
+    ranges: Vec<(usize, usize)>,
+}
+
+impl Analyzer {
+    pub(crate) fn new(bands: usize, rate: i32, channels: usize) -> Self {
+        let bands = bands.max(1);
+        let bin = rate as f32 / WINDOW as f32;
+        let ratio = HIGHEST / LOWEST;
+        let ranges = (0..bands)
+            .map(|b| {
+                let low = LOWEST * ratio.powf(b as f32 / bands as f32);
+                let high = LOWEST * ratio.powf((b + 1) as f32 / bands as f32);
+                let from = ((low / bin) as usize).clamp(1, WINDOW / 2 - 1);
+                let to = ((high / bin) as usize).clamp(from + 1, WINDOW / 2);
+                (from, to)
+            })
+            .collect();
+
+        Analyzer {
+            channels: channels.max(1),
+            history: vec![0.0; WINDOW],
+            next: 0,
+            window: (0..WINDOW)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (WINDOW - 1) as f32).cos())
+                .collect(),
+            real: vec![0.0; WINDOW],
+            imag: vec![0.0; WINDOW],
+            ranges,
+        }
+    }
+
+    pub(crate) fn analyze(&mut self, samples: &[i16], levels: &mut AudioLevels) {
+        let channels = self.channels;
+        let mut sum = 0.0;
+        let mut peak = 0.0f32;
+        let mut frames = 0;
+        for frame in samples.chunks_exact(channels) {
+            let mono =
+                frame.iter().map(|s| *s as f32).sum::<f32>() / (channels as f32 * i16::MAX as f32);
+            sum += mono * mono;
+            peak = peak.max(mono.abs());
+            frames += 1;
+
+            self.history[self.next] = mono;
+            self.next = (self.next + 1) % WINDOW;
+        }
+
+        levels.amplitude = (sum / frames.max(1) as f32).sqrt();
+        levels.peak = peak;
+        self.bands(&mut levels.bands);
+    }
+
+    // `out` keeps its capacity, so this doesn't allocate after the first buffer.
+    fn bands(&mut self, out: &mut Vec<f32>) {
+        for i in 0..WINDOW {
+            self.real[i] = self.history[(self.next + i) % WINDOW] * self.window[i];
+            self.imag[i] = 0.0;
+        }
+        fft(&mut self.real, &mut self.imag);
+
+        // A full scale sine comes out around WINDOW / 4 after the Hann window.
+        let scale = 4.0 / WINDOW as f32;
+        out.clear();
+        out.extend(self.ranges.iter().map(|&(from, to)| {
+            let strongest = (from..to)
+                .map(|i| (self.real[i] * self.real[i] + self.imag[i] * self.imag[i]).sqrt())
+                .fold(0.0, f32::max);
+            (strongest * scale).min(1.0)
+        }));
+    }
+}
+
+// In place radix-2, the length must be a power of two.
+fn fft(real: &mut [f32], imag: &mut [f32]) {
+    let n = real.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imag.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= n {
+        let angle = -2.0 * PI / length as f32;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + length / 2);
+                let tr = real[b] * cos - imag[b] * sin;
+                let ti = real[b] * sin + imag[b] * cos;
+                real[b] = real[a] - tr;
+                imag[b] = imag[a] - ti;
+                real[a] += tr;
+                imag[a] += ti;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+// Loudness and coarse spectrum of the final mix for music-reactive visuals. Levels go
+// from 0.0 to 1.0 and fall off by `decay` per second, so short peaks stay visible for a
+// few frames instead of flickering.
+pub struct AudioVisualizer {
+    // Fraction of the level lost per second once the sound drops.
+    pub decay: f32,
+    bands: usize,
+    enabled: bool,
+    amplitude: f32,
+    peak: f32,
+    levels: Vec<f32>,
+}
+
+impl AudioVisualizer {
+    pub fn new() -> Self {
+        AudioVisualizer {
+            decay: 0.9,
+            bands: 16,
+            enabled: false,
+            amplitude: 0.0,
+            peak: 0.0,
+            levels: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn band_count(&self) -> usize {
+        self.bands
+    }
+
+    // RMS of the latest buffer.
+    pub fn amplitude(&self) -> f32 {
+        self.amplitude
+    }
+
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    // From the lowest frequencies to the highest.
+    pub fn bands(&self) -> &[f32] {
+        &self.levels
+    }
+
+    pub fn band(&self, index: usize) -> f32 {
+        self.levels.get(index).copied().unwrap_or(0.0)
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool, bands: usize) {
+        self.enabled = enabled;
+        self.bands = bands.max(1);
+        self.levels = vec![0.0; self.bands];
+        self.amplitude = 0.0;
+        self.peak = 0.0;
+    }
+
+    pub(crate) fn update(&mut self, levels: Option<&AudioLevels>, delta: f64) {
+        let keep = (1.0 - self.decay.clamp(0.0, 1.0)).powf(delta as f32);
+        let fall = |old: f32, new: f32| new.max(old * keep);
+
+        let silence = AudioLevels::default();
+        let levels = levels.unwrap_or(&silence);
+        self.amplitude = fall(self.amplitude, levels.amplitude);
+        self.peak = fall(self.peak, levels.peak);
+        for (i, level) in self.levels.iter_mut().enumerate() {
+            *level = fall(*level, levels.bands.get(i).copied().unwrap_or(0.0));
+        }
+    }
+}
+
+impl Default for AudioVisualizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,76 @@
+use sdl2::{pixels::Color, rect::FRect};
+
+use crate::thickness::LineThickness;
+
+pub(crate) struct DrawShape {
+    pub(crate) dest: FRect,
+    pub(crate) angle: f64,
+    // Rotation pivot relative to the top-left of `dest`.
+    pub(crate) pivot: (f32, f32),
+    pub(crate) culled: bool,
+}
+
+// Outlines every draw of the frame on top of it: the destination rect turned by its
+// rotation, a dot at the draw's position and a cross at the rotation pivot.
+pub struct DrawBounds {
+    pub enabled: bool,
+    pub color: Color,
+    // Draws culled as off screen, left out when None.
+    pub culled_color: Option<Color>,
+    pub origin_color: Color,
+    pub pivot_color: Color,
+    // Outlines keep this width at any zoom unless it's set to follow the camera.
+    pub thickness: LineThickness,
+    pub(crate) shapes: Vec<DrawShape>,
+}
+
+impl DrawBounds {
+    pub fn new() -> Self {
+        DrawBounds {
+            enabled: false,
+            color: Color::RGB(0, 255, 128),
+            culled_color: None,
+            origin_color: Color::RGB(255, 255, 0),
+            pivot_color: Color::RGB(255, 64, 64),
+            thickness: LineThickness::fixed(1.0),
+            shapes: Vec::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub(crate) fn record(&mut self, dest: FRect, angle: f64, pivot: (f32, f32), culled: bool) {
+        self.shapes.push(DrawShape {
+            dest,
+            angle,
+            pivot,
+            culled,
+        });
+    }
+}
+
+impl Default for DrawBounds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Corners clockwise from the top-left, turned like SDL turns the texture.
+pub(crate) fn corners(shape: &DrawShape) -> [(f32, f32); 4] {
+    let d = &shape.dest;
+    let (px, py) = (d.x() + shape.pivot.0, d.y() + shape.pivot.1);
+    let (sin, cos) = (shape.angle.to_radians() as f32).sin_cos();
+    let turn = |x: f32, y: f32| {
+        let (dx, dy) = (x - px, y - py);
+        (px + dx * cos - dy * sin, py + dx * sin + dy * cos)
+    };
+
+    [
+        turn(d.x(), d.y()),
+        turn(d.x() + d.width(), d.y()),
+        turn(d.x() + d.width(), d.y() + d.height()),
+        turn(d.x(), d.y() + d.height()),
+    ]
+}
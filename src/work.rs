@@ -0,0 +1,109 @@
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WorkId(u64);
+
+// One chunk of work per call; return true once there is nothing left to do.
+pub trait IncrementalWork {
+    fn step(&mut self) -> bool;
+}
+
+impl<F> IncrementalWork for F
+where
+    F: FnMut() -> bool,
+{
+    fn step(&mut self) -> bool {
+        self()
+    }
+}
+
+pub struct WorkQueue<'w> {
+    // Milliseconds per frame the queue may use.
+    pub budget: f64,
+    jobs: VecDeque<(WorkId, Box<dyn IncrementalWork + 'w>)>,
+    // Jobs taken out of the queue while it runs them.
+    running: HashSet<WorkId>,
+    next_id: u64,
+}
+
+impl<'w> WorkQueue<'w> {
+    pub fn new() -> Self {
+        WorkQueue {
+            budget: 2.0,
+            jobs: VecDeque::new(),
+            running: HashSet::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn push(&mut self, work: Box<dyn IncrementalWork + 'w>) -> WorkId {
+        let id = WorkId(self.next_id);
+        self.next_id += 1;
+        self.jobs.push_back((id, work));
+        id
+    }
+
+    pub fn cancel(&mut self, id: WorkId) -> bool {
+        let count = self.jobs.len();
+        self.jobs.retain(|(j, _)| *j != id);
+        self.jobs.len() != count || self.running.remove(&id)
+    }
+
+    pub fn is_pending(&self, id: WorkId) -> bool {
+        self.jobs.iter().any(|(j, _)| *j == id) || self.running.contains(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len() + self.running.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Jobs take turns a step at a time; at least one step runs even over budget so work
+    // always makes progress. The jobs are taken out of the queue while they run, so a job
+    // can push or cancel work through the shared queue.
+    pub(crate) fn run<F>(queue: &RefCell<Self>, now: F)
+    where
+        F: Fn() -> f64,
+    {
+        let (mut jobs, budget) = {
+            let mut queue = queue.borrow_mut();
+            let jobs = std::mem::take(&mut queue.jobs);
+            queue.running = jobs.iter().map(|(id, _)| *id).collect();
+            (jobs, queue.budget)
+        };
+
+        let start = now();
+        while let Some((id, mut job)) = jobs.pop_front() {
+            let done = job.step();
+            let mut queue = queue.borrow_mut();
+            if done {
+                queue.running.remove(&id);
+            } else if queue.running.contains(&id) {
+                jobs.push_back((id, job));
+            }
+            drop(queue);
+            if now() - start >= budget {
+                break;
+            }
+        }
+
+        // Left over jobs go ahead of anything pushed while running, minus any cancelled.
+        let mut queue = queue.borrow_mut();
+        let running = std::mem::take(&mut queue.running);
+        jobs.retain(|(id, _)| running.contains(id));
+        jobs.append(&mut queue.jobs);
+        queue.jobs = jobs;
+    }
+}
+
+impl<'w> Default for WorkQueue<'w> {
+    fn default() -> Self {
+        Self::new()
+    }
+}